@@ -0,0 +1,21 @@
+CREATE TABLE booking_series(
+    id BLOB NOT NULL PRIMARY KEY,
+    unit_id VARCHAR(20) NOT NULL,
+    holder_id BLOB NOT NULL,
+    customer_id BLOB NOT NULL,
+    interval_weeks INTEGER NOT NULL,
+    created_at DATETIME NOT NULL,
+    canceled_at DATETIME,
+    FOREIGN KEY(unit_id) REFERENCES unit(id),
+    FOREIGN KEY(holder_id) REFERENCES user(id),
+    FOREIGN KEY(customer_id) REFERENCES identity(id)
+);
+CREATE INDEX idx_booking_series_customer_id ON booking_series(customer_id);
+
+CREATE TABLE booking_series_occurrence(
+    booking_id BLOB NOT NULL PRIMARY KEY,
+    series_id BLOB NOT NULL,
+    FOREIGN KEY(booking_id) REFERENCES booking(id),
+    FOREIGN KEY(series_id) REFERENCES booking_series(id)
+);
+CREATE INDEX idx_booking_series_occurrence_series_id ON booking_series_occurrence(series_id);
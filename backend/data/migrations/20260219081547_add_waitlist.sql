@@ -0,0 +1,20 @@
+CREATE TABLE waitlist_entry(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    unit_id VARCHAR(20) NOT NULL,
+    user_id BLOB NOT NULL,
+    customer_id BLOB NOT NULL,
+    time_from DATETIME NOT NULL,
+    time_to DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    notified_at DATETIME,
+    adhoc_reservation_id INTEGER,
+    deleted_at DATETIME,
+    FOREIGN KEY(unit_id) REFERENCES unit(id),
+    FOREIGN KEY(user_id) REFERENCES user(id),
+    FOREIGN KEY(customer_id) REFERENCES identity(id),
+    FOREIGN KEY(adhoc_reservation_id) REFERENCES adhoc_reservation(id)
+);
+CREATE INDEX idx_waitlist_entry_unit_id ON waitlist_entry(unit_id);
+CREATE INDEX idx_waitlist_entry_user_id ON waitlist_entry(user_id);
+CREATE INDEX idx_waitlist_entry_time_from ON waitlist_entry(time_from);
+CREATE INDEX idx_waitlist_entry_time_to ON waitlist_entry(time_to);
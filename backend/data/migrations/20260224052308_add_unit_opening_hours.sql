@@ -0,0 +1,23 @@
+CREATE TABLE unit_opening_hours(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    unit_id VARCHAR(20) NOT NULL,
+    -- Minutes elapsed since Monday 00:00 UTC
+    start_minute INTEGER NOT NULL,
+    duration_minutes INTEGER NOT NULL,
+    FOREIGN KEY(unit_id) REFERENCES unit(id)
+);
+CREATE INDEX idx_unit_opening_hours_unit_id ON unit_opening_hours(unit_id);
+
+CREATE TABLE unit_closure(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    unit_id VARCHAR(20) NOT NULL,
+    time_from DATETIME NOT NULL,
+    time_to DATETIME NOT NULL,
+    reason TEXT,
+    created_at DATETIME NOT NULL,
+    deleted_at DATETIME,
+    FOREIGN KEY(unit_id) REFERENCES unit(id)
+);
+CREATE INDEX idx_unit_closure_unit_id ON unit_closure(unit_id);
+CREATE INDEX idx_unit_closure_time_from ON unit_closure(time_from);
+CREATE INDEX idx_unit_closure_time_to ON unit_closure(time_to);
@@ -0,0 +1,45 @@
+CREATE TABLE coupon(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    code VARCHAR(40) NOT NULL,
+    name TEXT NOT NULL,
+    discount_type VARCHAR(20) NOT NULL,
+    -- Percent for 'percentage', amount in won for 'fixed'
+    discount_value INTEGER NOT NULL,
+    valid_from DATETIME,
+    valid_to DATETIME,
+    max_redemptions INTEGER,
+    max_redemptions_per_user INTEGER,
+    first_booking_only BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME NOT NULL,
+    deleted_at DATETIME
+);
+-- Codes of deleted coupons may be reused
+CREATE UNIQUE INDEX idx_coupon_code ON coupon(code COLLATE NOCASE) WHERE deleted_at IS NULL;
+
+-- Units the coupon is restricted to. No rows means every unit.
+CREATE TABLE coupon_unit(
+    coupon_id INTEGER NOT NULL,
+    unit_id VARCHAR(20) NOT NULL,
+    PRIMARY KEY(coupon_id, unit_id),
+    FOREIGN KEY(coupon_id) REFERENCES coupon(id),
+    FOREIGN KEY(unit_id) REFERENCES unit(id)
+);
+
+CREATE TABLE coupon_redemption(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    coupon_id INTEGER NOT NULL,
+    user_id BLOB NOT NULL,
+    -- Left empty until the Toss payment is confirmed
+    product_id BLOB UNIQUE,
+    toss_payments_transaction_id BLOB UNIQUE,
+    original_price INTEGER NOT NULL,
+    discount_price INTEGER NOT NULL,
+    created_at DATETIME NOT NULL,
+    canceled_at DATETIME,
+    FOREIGN KEY(coupon_id) REFERENCES coupon(id),
+    FOREIGN KEY(user_id) REFERENCES user(id),
+    FOREIGN KEY(product_id) REFERENCES product(id),
+    FOREIGN KEY(toss_payments_transaction_id) REFERENCES toss_payments_transaction(id)
+);
+CREATE INDEX idx_coupon_redemption_coupon_id ON coupon_redemption(coupon_id);
+CREATE INDEX idx_coupon_redemption_user_id ON coupon_redemption(user_id);
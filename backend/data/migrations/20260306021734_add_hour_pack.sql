@@ -0,0 +1,33 @@
+CREATE TABLE hour_pack(
+    id BLOB NOT NULL PRIMARY KEY,
+    holder_id BLOB NOT NULL,
+    -- Identity whose balance is credited once the pack is paid
+    customer_id BLOB NOT NULL,
+    name TEXT NOT NULL,
+    minutes INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    created_at DATETIME NOT NULL,
+    confirmed_at DATETIME,
+    canceled_at DATETIME,
+    FOREIGN KEY(id) REFERENCES product(id),
+    FOREIGN KEY(holder_id) REFERENCES user(id),
+    FOREIGN KEY(customer_id) REFERENCES identity(id)
+);
+CREATE INDEX idx_hour_pack_holder_id ON hour_pack(holder_id);
+CREATE INDEX idx_hour_pack_customer_id ON hour_pack(customer_id);
+
+-- Positive minutes are credits, negative minutes are debits
+CREATE TABLE hour_balance_entry(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    identity_id BLOB NOT NULL,
+    minutes INTEGER NOT NULL,
+    hour_pack_id BLOB,
+    booking_id BLOB,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(identity_id) REFERENCES identity(id),
+    FOREIGN KEY(hour_pack_id) REFERENCES hour_pack(id),
+    FOREIGN KEY(booking_id) REFERENCES booking(id)
+);
+CREATE INDEX idx_hour_balance_entry_identity_id ON hour_balance_entry(identity_id);
+CREATE INDEX idx_hour_balance_entry_hour_pack_id ON hour_balance_entry(hour_pack_id);
+CREATE INDEX idx_hour_balance_entry_booking_id ON hour_balance_entry(booking_id);
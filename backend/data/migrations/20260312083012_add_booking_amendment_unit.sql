@@ -0,0 +1,2 @@
+ALTER TABLE booking_amendment ADD COLUMN original_unit_id VARCHAR(20) REFERENCES unit(id);
+ALTER TABLE booking_amendment ADD COLUMN desired_unit_id VARCHAR(20) REFERENCES unit(id);
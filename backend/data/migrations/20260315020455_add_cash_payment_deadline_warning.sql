@@ -0,0 +1,5 @@
+CREATE TABLE cash_payment_deadline_warning(
+    product_id BLOB NOT NULL PRIMARY KEY,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(product_id) REFERENCES product(id)
+);
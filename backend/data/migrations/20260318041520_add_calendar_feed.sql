@@ -0,0 +1,6 @@
+CREATE TABLE calendar_feed(
+    identity_id BLOB NOT NULL PRIMARY KEY,
+    token BLOB NOT NULL UNIQUE,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(identity_id) REFERENCES identity(id)
+);
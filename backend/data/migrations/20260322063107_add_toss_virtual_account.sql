@@ -0,0 +1,6 @@
+ALTER TABLE toss_payments_transaction ADD COLUMN virtual_account_bank_code TEXT;
+ALTER TABLE toss_payments_transaction ADD COLUMN virtual_account_number TEXT;
+ALTER TABLE toss_payments_transaction ADD COLUMN virtual_account_due_at DATETIME;
+ALTER TABLE toss_payments_transaction ADD COLUMN virtual_account_secret TEXT;
+
+CREATE INDEX idx_toss_payments_transaction_virtual_account_due_at ON toss_payments_transaction(virtual_account_due_at);
@@ -0,0 +1,26 @@
+CREATE TABLE message_outbox(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    channel TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    next_attempt_at DATETIME NOT NULL,
+    delivered_at DATETIME,
+    failed_at DATETIME,
+    last_error TEXT
+);
+
+CREATE INDEX idx_message_outbox_next_attempt_at ON message_outbox(next_attempt_at);
+CREATE INDEX idx_message_outbox_failed_at ON message_outbox(failed_at);
+
+CREATE TABLE message_delivery_log(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    message_id INTEGER NOT NULL,
+    attempted_at DATETIME NOT NULL,
+    error TEXT,
+    FOREIGN KEY(message_id) REFERENCES message_outbox(id)
+);
+
+CREATE INDEX idx_message_delivery_log_message_id ON message_delivery_log(message_id);
@@ -0,0 +1,11 @@
+CREATE TABLE user_email(
+    user_id BLOB NOT NULL PRIMARY KEY,
+    email VARCHAR(254) NOT NULL,
+    verification_token BLOB,
+    verification_expires_at DATETIME,
+    verified_at DATETIME,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES user(id)
+);
+
+CREATE UNIQUE INDEX idx_user_email_verification_token ON user_email(verification_token);
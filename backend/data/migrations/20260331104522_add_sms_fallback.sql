@@ -0,0 +1,10 @@
+CREATE TABLE user_phone_number(
+    user_id BLOB NOT NULL PRIMARY KEY,
+    phone_number VARCHAR(20) NOT NULL,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES user(id)
+);
+
+ALTER TABLE message_outbox ADD COLUMN fallback_of INTEGER REFERENCES message_outbox(id);
+
+CREATE INDEX idx_message_outbox_fallback_of ON message_outbox(fallback_of);
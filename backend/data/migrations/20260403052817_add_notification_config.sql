@@ -0,0 +1,7 @@
+CREATE TABLE notification_config(
+    user_id BLOB NOT NULL PRIMARY KEY,
+    data JSONB NOT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES user(id)
+);
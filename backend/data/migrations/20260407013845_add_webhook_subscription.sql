@@ -0,0 +1,37 @@
+CREATE TABLE webhook_subscription(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url TEXT NOT NULL,
+    -- Key to sign the payloads with HMAC-SHA256
+    secret TEXT NOT NULL,
+    event_types JSONB NOT NULL,
+    created_at DATETIME NOT NULL,
+    deleted_at DATETIME
+);
+
+CREATE TABLE webhook_delivery(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    subscription_id INTEGER NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    next_attempt_at DATETIME NOT NULL,
+    delivered_at DATETIME,
+    failed_at DATETIME,
+    last_error TEXT,
+    FOREIGN KEY(subscription_id) REFERENCES webhook_subscription(id)
+);
+
+CREATE INDEX idx_webhook_delivery_subscription_id ON webhook_delivery(subscription_id);
+CREATE INDEX idx_webhook_delivery_next_attempt_at ON webhook_delivery(next_attempt_at);
+
+CREATE TABLE webhook_delivery_log(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    delivery_id INTEGER NOT NULL,
+    attempted_at DATETIME NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    FOREIGN KEY(delivery_id) REFERENCES webhook_delivery(id)
+);
+
+CREATE INDEX idx_webhook_delivery_log_delivery_id ON webhook_delivery_log(delivery_id);
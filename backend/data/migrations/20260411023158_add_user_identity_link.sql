@@ -0,0 +1,18 @@
+CREATE TABLE user_identity_link(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id BLOB NOT NULL,
+    provider VARCHAR(20) NOT NULL,
+    foreign_id VARCHAR(40) NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(user_id) REFERENCES user(id),
+    UNIQUE(provider, foreign_id)
+);
+CREATE INDEX idx_user_identity_link_user_id ON user_identity_link(user_id);
+
+INSERT INTO user_identity_link(user_id, provider, foreign_id, created_at)
+SELECT id, provider, foreign_id, created_at FROM user WHERE provider <> 'handle';
+
+-- Handle identities are keyed by the handle of their plain credential
+INSERT INTO user_identity_link(user_id, provider, foreign_id, created_at)
+SELECT u.id, 'handle', upc.handle, u.created_at
+FROM user_plain_credential "upc" JOIN user "u" ON u.id = upc.user_id;
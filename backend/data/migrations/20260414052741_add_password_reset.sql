@@ -0,0 +1,19 @@
+CREATE TABLE password_reset_token(
+    token BLOB NOT NULL PRIMARY KEY,
+    user_id BLOB NOT NULL,
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME,
+    FOREIGN KEY(user_id) REFERENCES user(id)
+);
+CREATE INDEX idx_password_reset_token_user_id ON password_reset_token(user_id);
+
+-- Recent attempts of credential operations, keyed by handle, user or client address
+CREATE TABLE credential_attempt(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    kind VARCHAR(30) NOT NULL,
+    key TEXT NOT NULL,
+    attempted_at DATETIME NOT NULL
+);
+CREATE INDEX idx_credential_attempt_kind_key ON credential_attempt(kind, key);
+CREATE INDEX idx_credential_attempt_attempted_at ON credential_attempt(attempted_at);
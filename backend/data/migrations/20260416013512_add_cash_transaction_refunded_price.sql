@@ -0,0 +1,3 @@
+-- Refunds already settled by the administrator; refund_price stays cumulative
+ALTER TABLE cash_transaction ADD COLUMN refunded_price INTEGER;
+UPDATE cash_transaction SET refunded_price = refund_price WHERE refunded_at IS NOT NULL;
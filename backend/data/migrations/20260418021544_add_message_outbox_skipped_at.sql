@@ -0,0 +1 @@
+ALTER TABLE message_outbox ADD COLUMN skipped_at DATETIME;
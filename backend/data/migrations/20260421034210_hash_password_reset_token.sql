@@ -0,0 +1,14 @@
+-- Reset tokens are stored as their SHA-256 digest. Outstanding tokens are short-lived, so they are
+-- dropped rather than migrated.
+DROP INDEX idx_password_reset_token_user_id;
+DROP TABLE password_reset_token;
+
+CREATE TABLE password_reset_token(
+    token_hash BLOB NOT NULL PRIMARY KEY,
+    user_id BLOB NOT NULL,
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME,
+    FOREIGN KEY(user_id) REFERENCES user(id)
+);
+CREATE INDEX idx_password_reset_token_user_id ON password_reset_token(user_id);
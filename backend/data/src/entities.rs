@@ -1,17 +1,23 @@
 mod booking;
 mod identity;
+mod messaging;
 mod payment;
 mod prefs;
 mod unit;
+mod webhook;
 
 pub use booking::{
-    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingAmendment, OccupiedSlot,
-    Product, ProductDiscriminator, TelemetryFile,
+    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingAmendment, BookingSeries,
+    OccupiedSlot, Product, ProductDiscriminator, TelemetryFile, WaitlistEntry,
 };
 pub use identity::{
-    Group, GroupAssociation, Identity, IdentityDiscriminator, User, UserCashPaymentInformation,
-    UserPlainCredential,
+    CalendarFeed, Group, GroupAssociation, Identity, IdentityDiscriminator, User,
+    UserCashPaymentInformation, UserEmail, UserIdentityLink, UserPhoneNumber, UserPlainCredential,
 };
-pub use payment::{CashTransaction, TossPaymentsTransaction};
-pub use prefs::MixerConfig;
-pub use unit::{Space, Unit};
+pub use messaging::{MessageDeliveryLog, OutboxMessage};
+pub use payment::{
+    CashTransaction, Coupon, CouponRedemption, HourBalanceEntry, HourPack, TossPaymentsTransaction,
+};
+pub use prefs::{MixerConfig, NotificationConfig};
+pub use unit::{Space, Unit, UnitClosure, UnitOpeningHours};
+pub use webhook::{WebhookDelivery, WebhookDeliveryLog, WebhookSubscription};
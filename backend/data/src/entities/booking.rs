@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use dxe_types::{
-    AdhocParkingId, AdhocReservationId, BookingAmendmentId, BookingId, SpaceId, TelemetryType,
-    UnitId,
+    AdhocParkingId, AdhocReservationId, BookingAmendmentId, BookingId, BookingSeriesId, IdentityId,
+    SpaceId, TelemetryType, UnitId, UserId, WaitlistEntryId,
 };
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
-use crate::entities::{Identity, User};
+use crate::entities::{HourPack, Identity, User};
 
 #[derive(Debug, Clone)]
 pub struct OccupiedSlot {
@@ -13,9 +14,10 @@ pub struct OccupiedSlot {
     pub time_from: DateTime<Utc>,
     pub time_to: DateTime<Utc>,
     pub confirmed: bool,
+    pub closed: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Booking {
     pub id: BookingId,
     pub unit_id: UnitId,
@@ -32,8 +34,10 @@ pub struct Booking {
 pub struct BookingAmendment {
     pub id: BookingAmendmentId,
     pub booking_id: BookingId,
+    pub original_unit_id: Option<UnitId>,
     pub original_time_from: DateTime<Utc>,
     pub original_time_to: DateTime<Utc>,
+    pub desired_unit_id: Option<UnitId>,
     pub desired_time_from: DateTime<Utc>,
     pub desired_time_to: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
@@ -41,17 +45,30 @@ pub struct BookingAmendment {
     pub canceled_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, FromRow)]
+pub struct BookingSeries {
+    pub id: BookingSeriesId,
+    pub unit_id: UnitId,
+    pub holder_id: UserId,
+    pub customer_id: IdentityId,
+    pub interval_weeks: i64,
+    pub created_at: DateTime<Utc>,
+    pub canceled_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Clone, Debug, sqlx::Type)]
 #[sqlx(rename_all = "snake_case")]
 pub enum ProductDiscriminator {
     Booking,
     BookingAmendment,
+    HourPack,
 }
 
 #[derive(Debug, Clone)]
 pub enum Product {
     Booking(Box<Booking>),
     Amendment(BookingAmendment),
+    HourPack(HourPack),
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -66,7 +83,7 @@ pub struct AdhocReservation {
     pub remark: Option<String>,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Deserialize, Serialize)]
 pub struct AudioRecording {
     pub booking_id: BookingId,
     pub url: String,
@@ -91,3 +108,17 @@ pub struct AdhocParking {
     pub license_plate_number: String,
     pub created_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WaitlistEntry {
+    pub id: WaitlistEntryId,
+    pub unit_id: UnitId,
+    pub user_id: UserId,
+    pub customer_id: IdentityId,
+    pub time_from: DateTime<Utc>,
+    pub time_to: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub notified_at: Option<DateTime<Utc>>,
+    pub adhoc_reservation_id: Option<AdhocReservationId>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
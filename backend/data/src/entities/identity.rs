@@ -1,5 +1,9 @@
 use chrono::{DateTime, Utc};
-use dxe_types::{GroupId, IdentityId, IdentityProvider, UserId};
+use dxe_types::{
+    CalendarFeedToken, EmailVerificationToken, GroupId, IdentityId, IdentityProvider, UserId,
+    UserIdentityLinkId,
+};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 #[derive(Clone, Debug, sqlx::Type)]
@@ -9,7 +13,7 @@ pub enum IdentityDiscriminator {
     Group,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Deserialize, Serialize)]
 pub struct User {
     pub id: UserId,
     pub provider: IdentityProvider,
@@ -20,7 +24,7 @@ pub struct User {
     pub license_plate_number: Option<String>,
 }
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Deserialize, Serialize)]
 pub struct Group {
     pub id: GroupId,
     pub name: String,
@@ -30,7 +34,7 @@ pub struct Group {
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Identity {
     User(User),
     Group(Group),
@@ -66,9 +70,43 @@ pub struct UserCashPaymentInformation {
     pub refund_account: Option<String>,
 }
 
+#[derive(Debug, Clone, FromRow)]
+pub struct UserEmail {
+    pub user_id: UserId,
+    pub email: String,
+    pub verification_token: Option<EmailVerificationToken>,
+    pub verification_expires_at: Option<DateTime<Utc>>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UserPhoneNumber {
+    pub user_id: UserId,
+    pub phone_number: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Sign-in method attached to a user. A user may have several, one per provider account.
+#[derive(Debug, Clone, FromRow)]
+pub struct UserIdentityLink {
+    pub id: UserIdentityLinkId,
+    pub user_id: UserId,
+    pub provider: IdentityProvider,
+    pub foreign_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, FromRow)]
 pub struct UserPlainCredential {
     pub user_id: UserId,
     pub handle: String,
     pub argon2_password: String,
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct CalendarFeed {
+    pub identity_id: IdentityId,
+    pub token: CalendarFeedToken,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{MessagingChannel, OutboxMessageId};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxMessage {
+    pub id: OutboxMessageId,
+    pub channel: MessagingChannel,
+    pub recipient: String,
+    pub event_type: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    /// Set when the message is no longer relevant, e.g. a reminder for a booking since moved.
+    pub skipped_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub fallback_of: Option<OutboxMessageId>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct MessageDeliveryLog {
+    pub id: i64,
+    pub message_id: OutboxMessageId,
+    pub attempted_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
@@ -1,5 +1,8 @@
 use chrono::{DateTime, Utc};
-use dxe_types::{AdhocReservationId, ForeignPaymentId, ProductId, UserId};
+use dxe_types::{
+    AdhocReservationId, BookingId, CouponDiscountType, CouponId, ForeignPaymentId, HourPackId,
+    IdentityId, ProductId, UserId,
+};
 use sqlx::FromRow;
 
 #[derive(Debug, Clone, FromRow)]
@@ -12,6 +15,7 @@ pub struct CashTransaction {
     pub refund_price: Option<i64>,
     pub refund_account: Option<String>,
     pub refunded_at: Option<DateTime<Utc>>,
+    pub refunded_price: Option<i64>,
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -26,4 +30,60 @@ pub struct TossPaymentsTransaction {
     pub confirmed_at: Option<DateTime<Utc>>,
     pub refund_price: Option<i64>,
     pub refunded_at: Option<DateTime<Utc>>,
+    pub virtual_account_bank_code: Option<String>,
+    pub virtual_account_number: Option<String>,
+    pub virtual_account_due_at: Option<DateTime<Utc>>,
+    pub virtual_account_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Coupon {
+    pub id: CouponId,
+    pub code: String,
+    pub name: String,
+    pub discount_type: CouponDiscountType,
+    pub discount_value: i64,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub max_redemptions: Option<i64>,
+    pub max_redemptions_per_user: Option<i64>,
+    pub first_booking_only: bool,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct CouponRedemption {
+    pub id: i64,
+    pub coupon_id: CouponId,
+    pub user_id: UserId,
+    pub product_id: Option<ProductId>,
+    pub toss_payments_transaction_id: Option<ForeignPaymentId>,
+    pub original_price: i64,
+    pub discount_price: i64,
+    pub created_at: DateTime<Utc>,
+    pub canceled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct HourPack {
+    pub id: HourPackId,
+    pub holder_id: UserId,
+    pub customer_id: IdentityId,
+    pub name: String,
+    pub minutes: i64,
+    pub price: i64,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub canceled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct HourBalanceEntry {
+    pub id: i64,
+    pub identity_id: IdentityId,
+    pub minutes: i64,
+    pub hour_pack_id: Option<HourPackId>,
+    pub booking_id: Option<BookingId>,
+    pub created_at: DateTime<Utc>,
 }
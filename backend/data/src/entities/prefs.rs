@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
-use dxe_types::entities::MixerPreferences;
-use dxe_types::{IdentityId, UnitId};
+use dxe_types::entities::{MixerPreferences, NotificationPreferences};
+use dxe_types::{IdentityId, UnitId, UserId};
 use sqlx::FromRow;
 use sqlx::types::Json;
 
@@ -12,3 +12,11 @@ pub struct MixerConfig {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct NotificationConfig {
+    pub user_id: UserId,
+    pub data: Json<NotificationPreferences>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,4 +1,5 @@
-use dxe_types::{SpaceId, UnitId};
+use chrono::{DateTime, Utc};
+use dxe_types::{SpaceId, UnitClosureId, UnitId};
 use sqlx::FromRow;
 
 #[derive(Debug, Clone, FromRow)]
@@ -13,3 +14,21 @@ pub struct Unit {
     pub space_id: SpaceId,
     pub enabled: bool,
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UnitOpeningHours {
+    pub unit_id: UnitId,
+    pub start_minute: i64,
+    pub duration_minutes: i64,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UnitClosure {
+    pub id: UnitClosureId,
+    pub unit_id: UnitId,
+    pub time_from: DateTime<Utc>,
+    pub time_to: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,39 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use dxe_types::{WebhookDeliveryId, WebhookEventType, WebhookSubscriptionId};
+use sqlx::FromRow;
+use sqlx::types::Json;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookSubscription {
+    pub id: WebhookSubscriptionId,
+    pub url: String,
+    pub secret: String,
+    pub event_types: Json<HashSet<WebhookEventType>>,
+    pub created_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDelivery {
+    pub id: WebhookDeliveryId,
+    pub subscription_id: WebhookSubscriptionId,
+    pub event_type: WebhookEventType,
+    pub payload: String,
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDeliveryLog {
+    pub id: i64,
+    pub delivery_id: WebhookDeliveryId,
+    pub attempted_at: DateTime<Utc>,
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+}
@@ -14,6 +14,8 @@ pub enum Error {
     InvalidTimeRange,
     #[error("Specified time range is already occupied")]
     TimeRangeOccupied,
+    #[error("Identity is already linked to a user")]
+    IdentityAlreadyLinked,
     #[error("Error querying database: {0}")]
     Sqlx(#[from] sqlx::Error),
 }
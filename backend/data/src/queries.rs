@@ -1,6 +1,13 @@
 pub mod booking;
+pub mod calendar_feed;
+pub mod coupon;
+pub mod credential;
+pub mod hour_pack;
 pub mod identity;
+pub mod messaging;
 pub mod payment;
 pub mod prefs;
 pub mod unit;
 pub mod user;
+pub mod waitlist;
+pub mod webhook;
@@ -1,17 +1,17 @@
 use chrono::{DateTime, Utc};
 use dxe_types::{
-    AdhocParkingId, AdhocReservationId, BookingAmendmentId, BookingId, GroupId, IdentityId,
-    IdentityProvider, ProductId, SpaceId, TelemetryType, UnitId, UserId,
+    AdhocParkingId, AdhocReservationId, BookingAmendmentId, BookingId, BookingSeriesId, GroupId,
+    HourPackId, IdentityId, IdentityProvider, ProductId, SpaceId, TelemetryType, UnitId, UserId,
 };
 use sqlx::SqliteConnection;
 
 use crate::Error;
 use crate::entities::{
-    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingAmendment, CashTransaction,
-    Group, Identity, IdentityDiscriminator, OccupiedSlot, Product, ProductDiscriminator,
-    TelemetryFile, User,
+    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingAmendment, BookingSeries,
+    CashTransaction, Group, HourPack, Identity, IdentityDiscriminator, OccupiedSlot, Product,
+    ProductDiscriminator, TelemetryFile, User,
 };
-use crate::queries::unit::is_unit_enabled;
+use crate::queries::unit::{get_closed_slots, is_unit_enabled};
 use crate::utils::is_in_effect;
 
 const MAX_ARBITRARY_DATETIME_RANGE: DateTime<Utc> = DateTime::from_timestamp_nanos(i64::MAX);
@@ -143,7 +143,7 @@ pub async fn is_booking_available(
         .cloned()
         .unwrap_or(AdhocReservationId::nil());
 
-    Ok(sqlx::query!(
+    let count = sqlx::query!(
         r#"
             SELECT
                 (SELECT COUNT(*)
@@ -161,7 +161,20 @@ pub async fn is_booking_available(
                     MAX(time_from, ?1) < MIN(time_to, ?2) AND
                     (deleted_at IS NULL OR deleted_at > ?4) AND
                     unit_id = ?3 AND
-                    id != ?6)
+                    id != ?6) +
+                (SELECT COUNT(*)
+                FROM booking_amendment "ba"
+                JOIN booking "b" ON b.id = ba.booking_id
+                JOIN cash_transaction "c" ON c.product_id = ba.id
+                WHERE
+                    ba.desired_time_to >= ?1 AND
+                    MAX(ba.desired_time_from, ?1) < MIN(ba.desired_time_to, ?2) AND
+                    (ba.confirmed_at IS NULL OR ba.confirmed_at > ?4) AND
+                    (ba.canceled_at IS NULL OR ba.canceled_at > ?4) AND
+                    (b.canceled_at IS NULL OR b.canceled_at > ?4) AND
+                    (c.confirmed_at IS NULL OR c.confirmed_at > ?4) AND
+                    COALESCE(ba.desired_unit_id, b.unit_id) = ?3 AND
+                    ba.booking_id != ?5)
                 AS "count"
         "#,
         range_from,
@@ -173,8 +186,17 @@ pub async fn is_booking_available(
     )
     .fetch_one(&mut *connection)
     .await?
-    .count
-        == 0)
+    .count;
+
+    if count > 0 {
+        return Ok(false);
+    }
+
+    Ok(
+        get_closed_slots(&mut *connection, now, unit_id, range_from, range_to)
+            .await?
+            .is_empty(),
+    )
 }
 
 pub async fn get_occupied_slots(
@@ -225,6 +247,7 @@ pub async fn get_occupied_slots(
             time_from: booking.time_from,
             time_to: booking.time_to,
             confirmed: booking.confirmed_at.map(|v| &v < now).unwrap_or(false),
+            closed: false,
         })
     }
 
@@ -256,9 +279,53 @@ pub async fn get_occupied_slots(
             time_from: reservation.time_from,
             time_to: reservation.time_to,
             confirmed: true,
+            closed: false,
         });
     }
 
+    // Rescheduled bookings awaiting the deposit for the price difference hold the desired range
+    let amendments = sqlx::query!(
+        r#"
+        SELECT
+            cu.name OR cg.name AS "name: String",
+            ba.desired_time_from AS "time_from: DateTime<Utc>",
+            ba.desired_time_to AS "time_to: DateTime<Utc>"
+        FROM booking_amendment "ba"
+        JOIN booking "b" ON b.id = ba.booking_id
+        JOIN cash_transaction "c" ON c.product_id = ba.id
+        JOIN identity "ci" ON b.customer_id = ci.id
+        LEFT OUTER JOIN user "cu" ON ci.discriminator = 'user' AND ci.id = cu.id
+        LEFT OUTER JOIN "group" "cg" ON ci.discriminator = 'group' AND ci.id = cg.id
+        WHERE
+            ba.desired_time_to >= ?1 AND ba.desired_time_from < ?2 AND
+            COALESCE(ba.desired_unit_id, b.unit_id) = ?3 AND
+            (ba.confirmed_at IS NULL OR ba.confirmed_at > ?4) AND
+            (ba.canceled_at IS NULL OR ba.canceled_at > ?4) AND
+            (b.canceled_at IS NULL OR b.canceled_at > ?4) AND
+            (c.confirmed_at IS NULL OR c.confirmed_at > ?4) AND
+            ba.booking_id != ?5
+        "#,
+        range_from,
+        range_to,
+        unit_id,
+        now,
+        exclude_booking_id,
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    for amendment in amendments {
+        records.push(OccupiedSlot {
+            name: amendment.name.unwrap_or_default(),
+            time_from: amendment.time_from,
+            time_to: amendment.time_to,
+            confirmed: false,
+            closed: false,
+        });
+    }
+
+    records.extend(get_closed_slots(&mut *connection, now, unit_id, range_from, range_to).await?);
+
     Ok(records)
 }
 
@@ -300,13 +367,24 @@ pub async fn get_product(
             b.canceled_at AS "b_canceled_at: DateTime<Utc>",
             ba.id AS "ba_id: Option<BookingAmendmentId>",
             ba.booking_id AS "ba_booking_id: Option<BookingId>",
+            ba.original_unit_id AS "ba_original_unit_id: UnitId",
             ba.original_time_from AS "ba_original_time_from: Option<DateTime<Utc>>",
             ba.original_time_to AS "ba_original_time_to: Option<DateTime<Utc>>",
+            ba.desired_unit_id AS "ba_desired_unit_id: UnitId",
             ba.desired_time_from AS "ba_desired_time_from: Option<DateTime<Utc>>",
             ba.desired_time_to AS "ba_desired_time_to: Option<DateTime<Utc>>",
             ba.created_at AS "ba_created_at: Option<DateTime<Utc>>",
             ba.confirmed_at AS "ba_confirmed_at: DateTime<Utc>",
-            ba.canceled_at AS "ba_canceled_at: DateTime<Utc>"
+            ba.canceled_at AS "ba_canceled_at: DateTime<Utc>",
+            hp.id AS "hp_id: Option<HourPackId>",
+            hp.holder_id AS "hp_holder_id: Option<UserId>",
+            hp.customer_id AS "hp_customer_id: Option<IdentityId>",
+            hp.name AS "hp_name: Option<String>",
+            hp.minutes AS "hp_minutes: Option<i64>",
+            hp.price AS "hp_price: Option<i64>",
+            hp.created_at AS "hp_created_at: Option<DateTime<Utc>>",
+            hp.confirmed_at AS "hp_confirmed_at: DateTime<Utc>",
+            hp.canceled_at AS "hp_canceled_at: DateTime<Utc>"
         FROM product "p"
         LEFT OUTER JOIN booking "b" ON
             b.id = p.id AND
@@ -314,6 +392,9 @@ pub async fn get_product(
         LEFT OUTER JOIN booking_amendment "ba" ON
             ba.id = p.id AND
             p.discriminator = 'booking_amendment'
+        LEFT OUTER JOIN hour_pack "hp" ON
+            hp.id = p.id AND
+            p.discriminator = 'hour_pack'
         LEFT OUTER JOIN user "hu" ON b.holder_id = hu.id
         LEFT OUTER JOIN identity "ci" ON b.customer_id = ci.id
         LEFT OUTER JOIN user "cu" ON
@@ -395,12 +476,14 @@ pub async fn get_product(
                 booking_id: result
                     .ba_booking_id
                     .ok_or(Error::MissingField("ba_booking_id"))?,
+                original_unit_id: result.ba_original_unit_id,
                 original_time_from: result
                     .ba_original_time_from
                     .ok_or(Error::MissingField("ba_original_time_from"))?,
                 original_time_to: result
                     .ba_original_time_to
                     .ok_or(Error::MissingField("ba_original_time_to"))?,
+                desired_unit_id: result.ba_desired_unit_id,
                 desired_time_from: result
                     .ba_desired_time_from
                     .ok_or(Error::MissingField("ba_desired_time_from"))?,
@@ -413,6 +496,23 @@ pub async fn get_product(
                 confirmed_at: result.ba_confirmed_at,
                 canceled_at: result.ba_canceled_at,
             }),
+            ProductDiscriminator::HourPack => Product::HourPack(HourPack {
+                id: result.hp_id.ok_or(Error::MissingField("hp_id"))?,
+                holder_id: result
+                    .hp_holder_id
+                    .ok_or(Error::MissingField("hp_holder_id"))?,
+                customer_id: result
+                    .hp_customer_id
+                    .ok_or(Error::MissingField("hp_customer_id"))?,
+                name: result.hp_name.ok_or(Error::MissingField("hp_name"))?,
+                minutes: result.hp_minutes.ok_or(Error::MissingField("hp_minutes"))?,
+                price: result.hp_price.ok_or(Error::MissingField("hp_price"))?,
+                created_at: result
+                    .hp_created_at
+                    .ok_or(Error::MissingField("hp_created_at"))?,
+                confirmed_at: result.hp_confirmed_at,
+                canceled_at: result.hp_canceled_at,
+            }),
         })
     } else {
         None
@@ -978,6 +1078,7 @@ pub async fn get_bookings_with_pending_cash_payment(
     sqlx::query!(
         r#"
         SELECT
+            ctx.product_id AS "ctx_product_id: ProductId",
             b.id AS "b_id: BookingId",
             b.unit_id AS "b_unit_id: UnitId",
             hu.id AS "hu_id: UserId",
@@ -1013,16 +1114,21 @@ pub async fn get_bookings_with_pending_cash_payment(
             ctx.confirmed_at AS "ctx_confirmed_at: DateTime<Utc>",
             ctx.refund_account AS "ctx_refund_account: String",
             ctx.refund_price AS "ctx_refund_price: i64",
-            ctx.refunded_at AS "ctx_refunded_at: DateTime<Utc>"
+            ctx.refunded_at AS "ctx_refunded_at: DateTime<Utc>",
+            ctx.refunded_price AS "ctx_refunded_price: i64"
         FROM booking "b"
         JOIN user "hu" ON b.holder_id = hu.id
         JOIN identity "ci" ON b.customer_id = ci.id
         JOIN product "p" ON b.id = p.id AND p.discriminator = 'booking'
-        JOIN cash_transaction "ctx" ON ctx.product_id = p.id
+        JOIN cash_transaction "ctx" ON
+            ctx.product_id = p.id OR
+            ctx.product_id IN (
+                SELECT id FROM booking_amendment
+                WHERE booking_id = b.id AND (canceled_at IS NULL OR canceled_at > ?1))
         LEFT OUTER JOIN user "cu" ON ci.discriminator = 'user' AND ci.id = cu.id
         LEFT OUTER JOIN "group" "cg" ON ci.discriminator = 'group' AND ci.id = cg.id
         WHERE
-            (b.confirmed_at IS NULL OR b.confirmed_at >= ?1) AND
+            (ctx.product_id != b.id OR b.confirmed_at IS NULL OR b.confirmed_at >= ?1) AND
             (ctx.confirmed_at IS NULL OR ctx.confirmed_at >= ?1) AND
             b.time_to >= ?2 AND b.time_from < ?3
         ORDER BY b.created_at DESC
@@ -1090,7 +1196,7 @@ pub async fn get_bookings_with_pending_cash_payment(
                 canceled_at: v.b_canceled_at,
             },
             CashTransaction {
-                product_id: v.b_id.into(),
+                product_id: v.ctx_product_id,
                 created_at: v.ctx_created_at,
                 depositor_name: v.ctx_depsitor_name,
                 price: v.ctx_price,
@@ -1098,12 +1204,72 @@ pub async fn get_bookings_with_pending_cash_payment(
                 refund_account: v.ctx_refund_account,
                 refund_price: v.ctx_refund_price,
                 refunded_at: v.ctx_refunded_at,
+                refunded_price: v.ctx_refunded_price,
             },
         ))
     })
     .collect::<Result<Vec<_>, Error>>()
 }
 
+pub async fn get_booking_ids_with_unpaid_cash_transaction(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<BookingId>, Error> {
+    Ok(sqlx::query!(
+        r#"
+        SELECT b.id AS "id: BookingId"
+        FROM booking "b"
+        JOIN cash_transaction "ctx" ON b.id = ctx.product_id
+        WHERE
+            b.time_to > ?1 AND
+            (b.confirmed_at IS NULL OR b.confirmed_at > ?1) AND
+            (b.canceled_at IS NULL OR b.canceled_at > ?1) AND
+            (ctx.confirmed_at IS NULL OR ctx.confirmed_at > ?1)
+        ORDER BY b.created_at ASC
+        "#,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    .into_iter()
+    .map(|v| v.id)
+    .collect())
+}
+
+/// Booking amendments whose price difference is still to be paid in cash.
+pub async fn get_booking_amendments_with_unpaid_cash_transaction(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<BookingAmendment>, Error> {
+    Ok(sqlx::query_as!(
+        BookingAmendment,
+        r#"
+        SELECT
+            ba.id AS "id: _",
+            ba.booking_id AS "booking_id: _",
+            ba.original_unit_id AS "original_unit_id: _",
+            ba.original_time_from AS "original_time_from: _",
+            ba.original_time_to AS "original_time_to: _",
+            ba.desired_unit_id AS "desired_unit_id: _",
+            ba.desired_time_from AS "desired_time_from: _",
+            ba.desired_time_to AS "desired_time_to: _",
+            ba.created_at AS "created_at: _",
+            ba.confirmed_at AS "confirmed_at: _",
+            ba.canceled_at AS "canceled_at: _"
+        FROM booking_amendment "ba"
+        JOIN cash_transaction "ctx" ON ba.id = ctx.product_id
+        WHERE
+            (ba.confirmed_at IS NULL OR ba.confirmed_at > ?1) AND
+            (ba.canceled_at IS NULL OR ba.canceled_at > ?1) AND
+            (ctx.confirmed_at IS NULL OR ctx.confirmed_at > ?1)
+        ORDER BY ba.created_at ASC
+        "#,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
 pub async fn get_bookings_with_pending_cash_refunds(
     connection: &mut SqliteConnection,
     date_from: &DateTime<Utc>,
@@ -1149,7 +1315,8 @@ pub async fn get_bookings_with_pending_cash_refunds(
             ctx.confirmed_at AS "ctx_confirmed_at: DateTime<Utc>",
             ctx.refund_account AS "ctx_refund_account: String",
             ctx.refund_price AS "ctx_refund_price: i64",
-            ctx.refunded_at AS "ctx_refunded_at: DateTime<Utc>"
+            ctx.refunded_at AS "ctx_refunded_at: DateTime<Utc>",
+            ctx.refunded_price AS "ctx_refunded_price: i64"
         FROM booking "b"
         JOIN user "hu" ON b.holder_id = hu.id
         JOIN identity "ci" ON b.customer_id = ci.id
@@ -1226,6 +1393,7 @@ pub async fn get_bookings_with_pending_cash_refunds(
                 refund_account: v.ctx_refund_account,
                 refund_price: v.ctx_refund_price,
                 refunded_at: v.ctx_refunded_at,
+                refunded_price: v.ctx_refunded_price,
             },
         ))
     })
@@ -1461,6 +1629,62 @@ pub async fn update_booking_time(
     }
 }
 
+/// Moves the booking to another time range, possibly on another unit.
+pub async fn reschedule_booking(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    booking_id: &BookingId,
+    unit_id: &UnitId,
+    time_from: &DateTime<Utc>,
+    time_to: &DateTime<Utc>,
+) -> Result<bool, Error> {
+    let booking = get_booking(&mut *connection, booking_id)
+        .await?
+        .ok_or(Error::BookingNotFound)?;
+
+    if &booking.unit_id == unit_id {
+        return update_booking_time(connection, now, booking_id, time_from, time_to).await;
+    }
+
+    if is_unit_enabled(&mut *connection, unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    if !is_booking_available(
+        &mut *connection,
+        now,
+        unit_id,
+        time_from,
+        time_to,
+        Some(booking_id),
+        None,
+    )
+    .await?
+    {
+        return Err(Error::TimeRangeOccupied);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE booking
+        SET
+            unit_id=?1,
+            time_from=?2,
+            time_to=?3
+        WHERE
+            id=?4
+        "#,
+        unit_id,
+        time_from,
+        time_to,
+        booking_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn confirm_booking(
     connection: &mut SqliteConnection,
     booking_id: &BookingId,
@@ -1511,8 +1735,10 @@ pub async fn get_booking_amendment(
         SELECT
             id AS "id: _",
             booking_id AS "booking_id: _",
+            original_unit_id AS "original_unit_id: _",
             original_time_from AS "original_time_from: _",
             original_time_to AS "original_time_to: _",
+            desired_unit_id AS "desired_unit_id: _",
             desired_time_from AS "desired_time_from: _",
             desired_time_to AS "desired_time_to: _",
             created_at AS "created_at: _",
@@ -1533,6 +1759,7 @@ pub async fn create_booking_amendment(
     connection: &mut SqliteConnection,
     now: &DateTime<Utc>,
     booking_id: &BookingId,
+    desired_unit_id: &UnitId,
     desired_time_from: &DateTime<Utc>,
     desired_time_to: &DateTime<Utc>,
     is_confirmed: bool,
@@ -1541,14 +1768,14 @@ pub async fn create_booking_amendment(
         .await?
         .ok_or(Error::BookingNotFound)?;
 
-    if is_unit_enabled(connection, &booking.unit_id).await? != Some(true) {
+    if is_unit_enabled(connection, desired_unit_id).await? != Some(true) {
         return Err(Error::UnitNotFound);
     }
 
     if !is_booking_available(
         connection,
         now,
-        &booking.unit_id,
+        desired_unit_id,
         desired_time_from,
         desired_time_to,
         Some(booking_id),
@@ -1579,18 +1806,22 @@ pub async fn create_booking_amendment(
         INSERT INTO booking_amendment(
             id,
             booking_id,
+            original_unit_id,
             original_time_from,
             original_time_to,
+            desired_unit_id,
             desired_time_from,
             desired_time_to,
             created_at,
             confirmed_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
         "#,
         booking_amendment_id,
         booking_id,
+        booking.unit_id,
         booking.time_from,
         booking.time_to,
+        desired_unit_id,
         desired_time_from,
         desired_time_to,
         now,
@@ -1621,17 +1852,24 @@ pub async fn confirm_booking_amendment(
         .await?
         .ok_or(Error::BookingNotFound)?;
 
-    if booking.time_from != booking_amendment.desired_time_from
+    let desired_unit_id = booking_amendment
+        .desired_unit_id
+        .unwrap_or_else(|| booking.unit_id.clone());
+
+    if booking.unit_id != desired_unit_id
+        || booking.time_from != booking_amendment.desired_time_from
         || booking.time_to != booking_amendment.desired_time_to
     {
         let _ = sqlx::query!(
             r#"
             UPDATE booking
             SET
-                time_from=?1,
-                time_to=?2
-            WHERE id=?3
+                unit_id=?1,
+                time_from=?2,
+                time_to=?3
+            WHERE id=?4
             "#,
+            desired_unit_id,
             booking_amendment.desired_time_from,
             booking_amendment.desired_time_to,
             booking_amendment.booking_id
@@ -1678,6 +1916,227 @@ pub async fn cancel_booking_amendment(
     Ok(result.rows_affected() > 0)
 }
 
+/// Cancels the amendments of the booking which are still awaiting payment, of any payment type.
+pub async fn cancel_pending_booking_amendments(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    booking_id: &BookingId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE booking_amendment
+        SET canceled_at=?1
+        WHERE
+            booking_id=?2 AND
+            (confirmed_at IS NULL OR confirmed_at > ?1) AND
+            (canceled_at IS NULL OR canceled_at > ?1)
+        "#,
+        now,
+        booking_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_booking_series(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    user_id: &UserId,
+    customer_id: &IdentityId,
+    interval_weeks: i64,
+) -> Result<BookingSeriesId, Error> {
+    if is_unit_enabled(connection, unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    let series_id = BookingSeriesId::generate();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO booking_series(id, unit_id, holder_id, customer_id, interval_weeks, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        "#,
+        series_id,
+        unit_id,
+        user_id,
+        customer_id,
+        interval_weeks,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(series_id)
+}
+
+pub async fn add_booking_to_series(
+    connection: &mut SqliteConnection,
+    series_id: &BookingSeriesId,
+    booking_id: &BookingId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO booking_series_occurrence(booking_id, series_id)
+        VALUES (?1, ?2)
+        "#,
+        booking_id,
+        series_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_booking_series_with_user_id(
+    connection: &mut SqliteConnection,
+    series_id: &BookingSeriesId,
+    user_id: &UserId,
+) -> Result<Option<BookingSeries>, Error> {
+    Ok(sqlx::query_as!(
+        BookingSeries,
+        r#"
+        SELECT
+            s.id AS "id: _",
+            s.unit_id AS "unit_id: _",
+            s.holder_id AS "holder_id: _",
+            s.customer_id AS "customer_id: _",
+            s.interval_weeks,
+            s.created_at AS "created_at: _",
+            s.canceled_at AS "canceled_at: _"
+        FROM booking_series "s"
+        WHERE
+            s.id = ?1 AND
+            (s.customer_id = ?2 OR EXISTS (SELECT user_id FROM group_association "ga" WHERE ga.group_id = s.customer_id AND ga.user_id = ?2))
+        "#,
+        series_id,
+        user_id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_bookings_by_series_id(
+    connection: &mut SqliteConnection,
+    series_id: &BookingSeriesId,
+) -> Result<Vec<Booking>, Error> {
+    sqlx::query!(
+        r#"
+        SELECT
+            b.id AS "b_id: BookingId",
+            b.unit_id AS "b_unit_id: UnitId",
+            hu.id AS "hu_id: UserId",
+            hu.provider AS "hu_provider: IdentityProvider",
+            hu.foreign_id AS "hu_foreign_id",
+            hu.name AS "hu_name",
+            hu.created_at AS "hu_created_at: DateTime<Utc>",
+            hu.deactivated_at AS "hu_deactivated_at: DateTime<Utc>",
+            hu.license_plate_number AS "hu_license_plate_number",
+            ci.discriminator AS "ci_discriminator: IdentityDiscriminator",
+            cu.id AS "cu_id: Option<UserId>",
+            cu.provider AS "cu_provider: Option<IdentityProvider>",
+            cu.foreign_id AS "cu_foreign_id: Option<String>",
+            cu.name AS "cu_name: Option<String>",
+            cu.created_at AS "cu_created_at: Option<DateTime<Utc>>",
+            cu.deactivated_at AS "cu_deactivated_at: DateTime<Utc>",
+            cu.license_plate_number AS "cu_license_plate_number",
+            cg.id AS "cg_id: Option<GroupId>",
+            cg.name AS "cg_name: Option<String>",
+            cg.owner_id AS "cg_owner_id: Option<UserId>",
+            cg.is_open AS "cg_is_open: Option<bool>",
+            cg.created_at AS "cg_created_at: Option<DateTime<Utc>>",
+            cg.deleted_at AS "cg_deleted_at: DateTime<Utc>",
+            b.time_from AS "b_time_from: DateTime<Utc>",
+            b.time_to AS "b_time_to: DateTime<Utc>",
+            b.created_at AS "b_created_at: DateTime<Utc>",
+            b.confirmed_at AS "b_confirmed_at: DateTime<Utc>",
+            b.canceled_at AS "b_canceled_at: DateTime<Utc>"
+        FROM booking_series_occurrence "so"
+        JOIN booking "b" ON so.booking_id = b.id
+        JOIN user "hu" ON b.holder_id = hu.id
+        JOIN identity "ci" ON b.customer_id = ci.id
+        LEFT OUTER JOIN user "cu" ON ci.discriminator = 'user' AND ci.id = cu.id
+        LEFT OUTER JOIN "group" "cg" ON ci.discriminator = 'group' AND ci.id = cg.id
+        WHERE
+            so.series_id = ?1
+        ORDER BY b.time_from ASC
+        "#,
+        series_id,
+    )
+    .fetch_all(&mut *connection)
+    .await?
+    .into_iter()
+    .map(|v| {
+        Ok(Booking {
+            id: v.b_id,
+            unit_id: v.b_unit_id,
+            holder: User {
+                id: v.hu_id,
+                provider: v.hu_provider,
+                foreign_id: v.hu_foreign_id,
+                name: v.hu_name,
+                created_at: v.hu_created_at,
+                deactivated_at: v.hu_deactivated_at,
+                license_plate_number: v.hu_license_plate_number,
+            },
+            customer: match v.ci_discriminator {
+                IdentityDiscriminator::User => Identity::User(User {
+                    id: v.cu_id.ok_or(Error::MissingField("cu_id"))?,
+                    provider: v.cu_provider.ok_or(Error::MissingField("cu_provider"))?,
+                    foreign_id: v
+                        .cu_foreign_id
+                        .ok_or(Error::MissingField("cu_foreign_id"))?,
+                    name: v.cu_name.ok_or(Error::MissingField("cu_name"))?,
+                    created_at: v
+                        .cu_created_at
+                        .ok_or(Error::MissingField("cu_created_at"))?,
+                    deactivated_at: v.cu_deactivated_at,
+                    license_plate_number: v.cu_license_plate_number,
+                }),
+                IdentityDiscriminator::Group => Identity::Group(Group {
+                    id: v.cg_id.ok_or(Error::MissingField("cg_id"))?,
+                    name: v.cg_name.ok_or(Error::MissingField("cg_name"))?,
+                    owner_id: v.cg_owner_id.ok_or(Error::MissingField("cg_owner_id"))?,
+                    is_open: v.cg_is_open.ok_or(Error::MissingField("cg_is_open"))?,
+                    created_at: v
+                        .cg_created_at
+                        .ok_or(Error::MissingField("cg_created_at"))?,
+                    deleted_at: v.cg_deleted_at,
+                }),
+            },
+            time_from: v.b_time_from,
+            time_to: v.b_time_to,
+            created_at: v.b_created_at,
+            confirmed_at: v.b_confirmed_at,
+            canceled_at: v.b_canceled_at,
+        })
+    })
+    .collect::<Result<Vec<_>, Error>>()
+}
+
+pub async fn cancel_booking_series(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    series_id: &BookingSeriesId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE booking_series
+        SET canceled_at = ?2
+        WHERE id = ?1 AND canceled_at IS NULL
+        "#,
+        series_id,
+        now
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn create_adhoc_reservation(
     connection: &mut SqliteConnection,
     now: &DateTime<Utc>,
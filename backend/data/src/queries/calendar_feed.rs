@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{CalendarFeedToken, IdentityId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+use crate::entities::CalendarFeed;
+
+pub async fn get_calendar_feed(
+    connection: &mut SqliteConnection,
+    identity_id: &IdentityId,
+) -> Result<Option<CalendarFeed>, Error> {
+    Ok(sqlx::query_as!(
+        CalendarFeed,
+        r#"
+        SELECT
+            identity_id AS "identity_id: _",
+            token AS "token: _",
+            created_at AS "created_at: _"
+        FROM calendar_feed
+        WHERE identity_id = ?1
+        "#,
+        identity_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_calendar_feed_by_token(
+    connection: &mut SqliteConnection,
+    token: &CalendarFeedToken,
+) -> Result<Option<CalendarFeed>, Error> {
+    Ok(sqlx::query_as!(
+        CalendarFeed,
+        r#"
+        SELECT
+            identity_id AS "identity_id: _",
+            token AS "token: _",
+            created_at AS "created_at: _"
+        FROM calendar_feed
+        WHERE token = ?1
+        "#,
+        token
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+/// Issues a new token for the identity's feed, invalidating the previous one if any.
+pub async fn create_or_rotate_calendar_feed(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    identity_id: &IdentityId,
+) -> Result<CalendarFeedToken, Error> {
+    let token = CalendarFeedToken::generate();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO calendar_feed(identity_id, token, created_at)
+        VALUES(?1, ?2, ?3)
+        ON CONFLICT(identity_id)
+        DO UPDATE SET
+            token = excluded.token,
+            created_at = excluded.created_at
+        "#,
+        identity_id,
+        token,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(token)
+}
@@ -0,0 +1,417 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{CouponDiscountType, CouponId, ForeignPaymentId, ProductId, UnitId, UserId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+use crate::entities::{Coupon, CouponRedemption};
+
+pub async fn create_coupon(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    code: &str,
+    name: &str,
+    discount_type: CouponDiscountType,
+    discount_value: i64,
+    valid_from: &Option<DateTime<Utc>>,
+    valid_to: &Option<DateTime<Utc>>,
+    max_redemptions: Option<i64>,
+    max_redemptions_per_user: Option<i64>,
+    first_booking_only: bool,
+    unit_ids: &[UnitId],
+) -> Result<CouponId, Error> {
+    if let (Some(valid_from), Some(valid_to)) = (valid_from, valid_to)
+        && valid_from >= valid_to
+    {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO coupon(
+            code, name, discount_type, discount_value, valid_from, valid_to,
+            max_redemptions, max_redemptions_per_user, first_booking_only, created_at
+        )
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        RETURNING id AS "id: CouponId"
+        "#,
+        code,
+        name,
+        discount_type,
+        discount_value,
+        valid_from,
+        valid_to,
+        max_redemptions,
+        max_redemptions_per_user,
+        first_booking_only,
+        now,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    for unit_id in unit_ids {
+        sqlx::query!(
+            r#"
+            INSERT INTO coupon_unit(coupon_id, unit_id)
+            VALUES(?1, ?2)
+            "#,
+            result.id,
+            unit_id,
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(result.id)
+}
+
+pub async fn get_coupon(
+    connection: &mut SqliteConnection,
+    id: &CouponId,
+) -> Result<Option<Coupon>, Error> {
+    Ok(sqlx::query_as!(
+        Coupon,
+        r#"
+        SELECT
+            id AS "id: _",
+            code,
+            name,
+            discount_type AS "discount_type: _",
+            discount_value,
+            valid_from AS "valid_from: _",
+            valid_to AS "valid_to: _",
+            max_redemptions,
+            max_redemptions_per_user,
+            first_booking_only,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM coupon
+        WHERE id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_coupon_by_code(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    code: &str,
+) -> Result<Option<Coupon>, Error> {
+    Ok(sqlx::query_as!(
+        Coupon,
+        r#"
+        SELECT
+            id AS "id: _",
+            code,
+            name,
+            discount_type AS "discount_type: _",
+            discount_value,
+            valid_from AS "valid_from: _",
+            valid_to AS "valid_to: _",
+            max_redemptions,
+            max_redemptions_per_user,
+            first_booking_only,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM coupon
+        WHERE
+            code = ?1 COLLATE NOCASE AND
+            (deleted_at IS NULL OR deleted_at > ?2)
+        "#,
+        code,
+        now,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_coupons(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<Coupon>, Error> {
+    Ok(sqlx::query_as!(
+        Coupon,
+        r#"
+        SELECT
+            id AS "id: _",
+            code,
+            name,
+            discount_type AS "discount_type: _",
+            discount_value,
+            valid_from AS "valid_from: _",
+            valid_to AS "valid_to: _",
+            max_redemptions,
+            max_redemptions_per_user,
+            first_booking_only,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM coupon
+        WHERE deleted_at IS NULL OR deleted_at > ?1
+        ORDER BY created_at DESC
+        "#,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn get_coupon_unit_ids(
+    connection: &mut SqliteConnection,
+    coupon_id: &CouponId,
+) -> Result<Vec<UnitId>, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT unit_id AS "unit_id: UnitId"
+        FROM coupon_unit
+        WHERE coupon_id = ?1
+        "#,
+        coupon_id,
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    Ok(result.into_iter().map(|v| v.unit_id).collect())
+}
+
+pub async fn delete_coupon(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &CouponId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE coupon
+        SET deleted_at = ?1
+        WHERE id = ?2 AND deleted_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Counts redemptions which have not been canceled. Redemptions pending on a Toss payment are
+/// counted while the slot is held for the payment, so that concurrent payments cannot exceed the
+/// limits.
+pub async fn count_coupon_redemptions(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    coupon_id: &CouponId,
+    user_id: Option<&UserId>,
+) -> Result<i64, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count"
+        FROM coupon_redemption
+        WHERE
+            coupon_id = ?1 AND
+            (?2 IS NULL OR user_id = ?2) AND
+            (canceled_at IS NULL OR canceled_at > ?3) AND
+            (
+                product_id IS NOT NULL OR
+                EXISTS(
+                    SELECT 1
+                    FROM toss_payments_transaction "t"
+                    JOIN adhoc_reservation "a" ON t.temporary_reservation_id = a.id
+                    WHERE
+                        t.id = coupon_redemption.toss_payments_transaction_id AND
+                        (a.deleted_at IS NULL OR a.deleted_at > ?3)
+                )
+            )
+        "#,
+        coupon_id,
+        user_id,
+        now,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.count)
+}
+
+pub async fn has_booking_history(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count"
+        FROM booking
+        WHERE
+            holder_id = ?1 AND
+            (canceled_at IS NULL OR canceled_at > ?2)
+        "#,
+        user_id,
+        now,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.count > 0)
+}
+
+pub async fn create_coupon_redemption(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    coupon_id: &CouponId,
+    user_id: &UserId,
+    product_id: Option<&ProductId>,
+    toss_payments_transaction_id: Option<&ForeignPaymentId>,
+    original_price: i64,
+    discount_price: i64,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO coupon_redemption(
+            coupon_id, user_id, product_id, toss_payments_transaction_id,
+            original_price, discount_price, created_at
+        )
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        coupon_id,
+        user_id,
+        product_id,
+        toss_payments_transaction_id,
+        original_price,
+        discount_price,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_coupon_redemption_by_product_id(
+    connection: &mut SqliteConnection,
+    product_id: &ProductId,
+) -> Result<Option<CouponRedemption>, Error> {
+    Ok(sqlx::query_as!(
+        CouponRedemption,
+        r#"
+        SELECT
+            id,
+            coupon_id AS "coupon_id: _",
+            user_id AS "user_id: _",
+            product_id AS "product_id: _",
+            toss_payments_transaction_id AS "toss_payments_transaction_id: _",
+            original_price,
+            discount_price,
+            created_at AS "created_at: _",
+            canceled_at AS "canceled_at: _"
+        FROM coupon_redemption
+        WHERE product_id = ?1
+        "#,
+        product_id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+/// Whether the redemption pending on the Toss payment still fits in the limits of its coupon,
+/// counting only the redemptions which have already been attached to a product. Passes when no
+/// coupon has been applied to the payment.
+pub async fn is_pending_coupon_redemption_available(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_payments_transaction_id: &ForeignPaymentId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT
+            c.max_redemptions,
+            c.max_redemptions_per_user,
+            (SELECT COUNT(*)
+            FROM coupon_redemption "o"
+            WHERE
+                o.coupon_id = r.coupon_id AND
+                o.id != r.id AND
+                o.product_id IS NOT NULL AND
+                (o.canceled_at IS NULL OR o.canceled_at > ?2)) AS "redemptions!: i64",
+            (SELECT COUNT(*)
+            FROM coupon_redemption "o"
+            WHERE
+                o.coupon_id = r.coupon_id AND
+                o.user_id = r.user_id AND
+                o.id != r.id AND
+                o.product_id IS NOT NULL AND
+                (o.canceled_at IS NULL OR o.canceled_at > ?2)) AS "user_redemptions!: i64"
+        FROM coupon_redemption "r"
+        JOIN coupon "c" ON r.coupon_id = c.id
+        WHERE r.toss_payments_transaction_id = ?1 AND r.product_id IS NULL
+        "#,
+        toss_payments_transaction_id,
+        now,
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    Ok(result.is_none_or(|v| {
+        v.max_redemptions.is_none_or(|max| v.redemptions < max)
+            && v.max_redemptions_per_user
+                .is_none_or(|max| v.user_redemptions < max)
+    }))
+}
+
+/// Attaches a redemption made during Toss payment initiation to the confirmed product.
+pub async fn attach_coupon_redemption(
+    connection: &mut SqliteConnection,
+    toss_payments_transaction_id: &ForeignPaymentId,
+    product_id: &ProductId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE coupon_redemption
+        SET product_id = ?1
+        WHERE toss_payments_transaction_id = ?2 AND product_id IS NULL
+        "#,
+        product_id,
+        toss_payments_transaction_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_pending_coupon_redemption(
+    connection: &mut SqliteConnection,
+    toss_payments_transaction_id: &ForeignPaymentId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM coupon_redemption
+        WHERE toss_payments_transaction_id = ?1 AND product_id IS NULL
+        "#,
+        toss_payments_transaction_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn cancel_coupon_redemption(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    product_id: &ProductId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE coupon_redemption
+        SET canceled_at = ?1
+        WHERE product_id = ?2 AND canceled_at IS NULL
+        "#,
+        now,
+        product_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
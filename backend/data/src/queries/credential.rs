@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{CredentialAttemptKind, UserId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+
+/// Stores a reset token by its digest, so that the stored value can't be used as a reset link.
+pub async fn create_password_reset_token(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+    token_hash: &[u8],
+    expires_at: &DateTime<Utc>,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO password_reset_token(token_hash, user_id, created_at, expires_at)
+        VALUES(?1, ?2, ?3, ?4)
+        "#,
+        token_hash,
+        user_id,
+        now,
+        expires_at,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks the token with the digest used and returns the user it was issued to, if it is still
+/// valid. Other outstanding tokens of the user are invalidated along with it.
+pub async fn consume_password_reset_token(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    token_hash: &[u8],
+) -> Result<Option<UserId>, Error> {
+    let Some(user_id) = sqlx::query_scalar!(
+        r#"
+        UPDATE password_reset_token
+        SET used_at=?1
+        WHERE token_hash=?2 AND used_at IS NULL AND expires_at > ?1
+        RETURNING user_id AS "user_id: UserId"
+        "#,
+        now,
+        token_hash,
+    )
+    .fetch_optional(&mut *connection)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE password_reset_token
+        SET used_at=?1
+        WHERE user_id=?2 AND used_at IS NULL
+        "#,
+        now,
+        user_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(Some(user_id))
+}
+
+/// Records the attempt unless the key already has `max_attempts` attempts since `since`, returning
+/// whether it was recorded. Checked within the insert so that concurrent attempts can't overrun
+/// the limit.
+pub async fn create_credential_attempt_within_limit(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    kind: CredentialAttemptKind,
+    key: &str,
+    since: &DateTime<Utc>,
+    max_attempts: i64,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO credential_attempt(kind, key, attempted_at)
+        SELECT ?1, ?2, ?3
+        WHERE (
+            SELECT COUNT(*)
+            FROM credential_attempt
+            WHERE kind=?1 AND key=?2 AND attempted_at > ?4
+        ) < ?5
+        "#,
+        kind,
+        key,
+        now,
+        since,
+        max_attempts,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_credential_attempts(
+    connection: &mut SqliteConnection,
+    kind: CredentialAttemptKind,
+    key: &str,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM credential_attempt
+        WHERE kind=?1 AND key=?2
+        "#,
+        kind,
+        key,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_expired_credential_attempts(
+    connection: &mut SqliteConnection,
+    before: &DateTime<Utc>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM credential_attempt
+        WHERE attempted_at <= ?1
+        "#,
+        before,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
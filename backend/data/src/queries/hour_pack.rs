@@ -0,0 +1,258 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{BookingId, HourPackId, IdentityId, ProductId, UserId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+use crate::entities::{HourBalanceEntry, HourPack};
+
+pub async fn create_hour_pack(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    holder_id: &UserId,
+    customer_id: &IdentityId,
+    name: &str,
+    minutes: i64,
+    price: i64,
+) -> Result<HourPackId, Error> {
+    let hour_pack_id = HourPackId::generate();
+    let product_id = ProductId::from(hour_pack_id);
+
+    sqlx::query!(
+        r#"
+        INSERT INTO product(id, discriminator)
+        VALUES(?1, 'hour_pack')
+        "#,
+        product_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO hour_pack(id, holder_id, customer_id, name, minutes, price, created_at)
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        hour_pack_id,
+        holder_id,
+        customer_id,
+        name,
+        minutes,
+        price,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(hour_pack_id)
+}
+
+pub async fn get_hour_pack(
+    connection: &mut SqliteConnection,
+    id: &HourPackId,
+) -> Result<Option<HourPack>, Error> {
+    Ok(sqlx::query_as!(
+        HourPack,
+        r#"
+        SELECT
+            id AS "id: _",
+            holder_id AS "holder_id: _",
+            customer_id AS "customer_id: _",
+            name,
+            minutes,
+            price,
+            created_at AS "created_at: _",
+            confirmed_at AS "confirmed_at: _",
+            canceled_at AS "canceled_at: _"
+        FROM hour_pack
+        WHERE id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_hour_packs_by_holder_id(
+    connection: &mut SqliteConnection,
+    holder_id: &UserId,
+) -> Result<Vec<HourPack>, Error> {
+    Ok(sqlx::query_as!(
+        HourPack,
+        r#"
+        SELECT
+            id AS "id: _",
+            holder_id AS "holder_id: _",
+            customer_id AS "customer_id: _",
+            name,
+            minutes,
+            price,
+            created_at AS "created_at: _",
+            confirmed_at AS "confirmed_at: _",
+            canceled_at AS "canceled_at: _"
+        FROM hour_pack
+        WHERE holder_id = ?1
+        ORDER BY created_at DESC
+        "#,
+        holder_id
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn get_pending_hour_packs(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<HourPack>, Error> {
+    Ok(sqlx::query_as!(
+        HourPack,
+        r#"
+        SELECT
+            id AS "id: _",
+            holder_id AS "holder_id: _",
+            customer_id AS "customer_id: _",
+            name,
+            minutes,
+            price,
+            created_at AS "created_at: _",
+            confirmed_at AS "confirmed_at: _",
+            canceled_at AS "canceled_at: _"
+        FROM hour_pack
+        WHERE
+            (confirmed_at IS NULL OR confirmed_at > ?1) AND
+            (canceled_at IS NULL OR canceled_at > ?1)
+        ORDER BY created_at ASC
+        "#,
+        now
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn confirm_hour_pack(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &HourPackId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE hour_pack
+        SET confirmed_at = ?1
+        WHERE id = ?2 AND confirmed_at IS NULL AND canceled_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn cancel_hour_pack(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &HourPackId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE hour_pack
+        SET canceled_at = ?1
+        WHERE id = ?2 AND canceled_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_hour_balance(
+    connection: &mut SqliteConnection,
+    identity_id: &IdentityId,
+) -> Result<i64, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COALESCE(SUM(minutes), 0) AS "minutes!: i64"
+        FROM hour_balance_entry
+        WHERE identity_id = ?1
+        "#,
+        identity_id,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.minutes)
+}
+
+pub async fn get_hour_balance_entries(
+    connection: &mut SqliteConnection,
+    identity_id: &IdentityId,
+) -> Result<Vec<HourBalanceEntry>, Error> {
+    Ok(sqlx::query_as!(
+        HourBalanceEntry,
+        r#"
+        SELECT
+            id,
+            identity_id AS "identity_id: _",
+            minutes,
+            hour_pack_id AS "hour_pack_id: _",
+            booking_id AS "booking_id: _",
+            created_at AS "created_at: _"
+        FROM hour_balance_entry
+        WHERE identity_id = ?1
+        ORDER BY created_at DESC, id DESC
+        "#,
+        identity_id
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Minutes debited for the booking and credited back on cancellation, if the booking was paid
+/// with hour balance.
+pub async fn get_hour_balance_usage_by_booking_id(
+    connection: &mut SqliteConnection,
+    booking_id: &BookingId,
+) -> Result<Option<(i64, i64)>, Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT
+            -SUM(CASE WHEN minutes < 0 THEN minutes ELSE 0 END) AS "debited: i64",
+            SUM(CASE WHEN minutes > 0 THEN minutes ELSE 0 END) AS "credited: i64"
+        FROM hour_balance_entry
+        WHERE booking_id = ?1
+        "#,
+        booking_id,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.debited.zip(result.credited))
+}
+
+pub async fn create_hour_balance_entry(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    identity_id: &IdentityId,
+    minutes: i64,
+    hour_pack_id: Option<&HourPackId>,
+    booking_id: Option<&BookingId>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO hour_balance_entry(identity_id, minutes, hour_pack_id, booking_id, created_at)
+        VALUES(?1, ?2, ?3, ?4, ?5)
+        "#,
+        identity_id,
+        minutes,
+        hour_pack_id,
+        booking_id,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -35,7 +35,7 @@ pub async fn get_identity(
         WHERE
             i.id = ?1 AND
             (g.deleted_at IS NULL OR g.deleted_at < ?2) AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?2)
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?2)
         "#,
         identity_id,
         now
@@ -128,7 +128,7 @@ pub async fn get_group_with_members(
         WHERE
             g.id = ?1 AND
             (g.deleted_at IS NULL OR g.deleted_at < ?2) AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?2)
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?2)
         "#,
         group_id,
         now
@@ -220,7 +220,7 @@ pub async fn get_all_groups_associated_with_members(
         JOIN user "u" ON ga.user_id = u.id
         WHERE
             (g.deleted_at IS NULL OR g.deleted_at < ?1) AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?1)
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?1)
         "#,
         now,
     )
@@ -283,7 +283,7 @@ pub async fn get_groups_associated_with_members(
         JOIN user "u" ON ga.user_id = u.id
         WHERE
             (g.deleted_at IS NULL OR g.deleted_at < ?2) AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?2) AND
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?2) AND
             EXISTS (
                 SELECT
                     user_id
@@ -594,7 +594,7 @@ pub async fn get_user_plain_credential_with_user_id(
             user_plain_credential "upc" ON u.id = upc.user_id
         WHERE
             u.id = ?1 AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?2)
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?2)
         "#,
         user_id,
         now
@@ -644,7 +644,7 @@ pub async fn get_user_plain_credential_with_handle(
             user_plain_credential "upc" ON u.id = upc.user_id
         WHERE
             upc.handle = ?1 AND
-            (u.deactivated_at IS NULL OR u.deactivated_at < ?2)
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?2)
         "#,
         handle,
         now
@@ -670,3 +670,44 @@ pub async fn get_user_plain_credential_with_handle(
         )
     }))
 }
+
+pub async fn create_user_plain_credential(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+    handle: &str,
+    argon2_password: &str,
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_plain_credential(user_id, handle, argon2_password)
+        VALUES(?1, ?2, ?3)
+        "#,
+        user_id,
+        handle,
+        argon2_password,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn update_user_plain_credential_password(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+    argon2_password: &str,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_plain_credential
+        SET argon2_password=?2
+        WHERE user_id=?1
+        "#,
+        user_id,
+        argon2_password,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
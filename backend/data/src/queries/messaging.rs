@@ -0,0 +1,395 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{BookingId, MessagingChannel, OutboxMessageId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+use crate::entities::{MessageDeliveryLog, OutboxMessage};
+
+pub async fn create_outbox_message(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    channel: MessagingChannel,
+    recipient: &str,
+    event_type: &str,
+    payload: &str,
+    fallback_of: Option<&OutboxMessageId>,
+    scheduled_at: Option<&DateTime<Utc>>,
+) -> Result<OutboxMessageId, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO message_outbox(channel, recipient, event_type, payload, created_at, next_attempt_at, fallback_of)
+        VALUES(?1, ?2, ?3, ?4, ?5, COALESCE(?7, ?5), ?6)
+        "#,
+        channel,
+        recipient,
+        event_type,
+        payload,
+        now,
+        fallback_of,
+        scheduled_at,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(OutboxMessageId::from(result.last_insert_rowid()))
+}
+
+pub async fn get_outbox_message(
+    connection: &mut SqliteConnection,
+    id: &OutboxMessageId,
+) -> Result<Option<OutboxMessage>, Error> {
+    Ok(sqlx::query_as!(
+        OutboxMessage,
+        r#"
+        SELECT
+            id AS "id!: _",
+            channel AS "channel: _",
+            recipient,
+            event_type,
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            skipped_at AS "skipped_at: _",
+            last_error,
+            fallback_of AS "fallback_of: _"
+        FROM message_outbox
+        WHERE id = ?1
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+/// Messages which are neither delivered nor given up on, and due for the next attempt.
+pub async fn get_due_outbox_messages(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<OutboxMessage>, Error> {
+    Ok(sqlx::query_as!(
+        OutboxMessage,
+        r#"
+        SELECT
+            id AS "id!: _",
+            channel AS "channel: _",
+            recipient,
+            event_type,
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            skipped_at AS "skipped_at: _",
+            last_error,
+            fallback_of AS "fallback_of: _"
+        FROM message_outbox
+        WHERE
+            delivered_at IS NULL AND
+            failed_at IS NULL AND
+            skipped_at IS NULL AND
+            next_attempt_at <= ?1
+        ORDER BY next_attempt_at ASC, id ASC
+        LIMIT ?2
+        "#,
+        now,
+        limit,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn get_failed_outbox_messages(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<OutboxMessage>, Error> {
+    Ok(sqlx::query_as!(
+        OutboxMessage,
+        r#"
+        SELECT
+            id AS "id!: _",
+            channel AS "channel: _",
+            recipient,
+            event_type,
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            skipped_at AS "skipped_at: _",
+            last_error,
+            fallback_of AS "fallback_of: _"
+        FROM message_outbox
+        WHERE failed_at IS NOT NULL
+        ORDER BY failed_at DESC
+        "#,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Messages sent through another channel in place of the given one.
+pub async fn get_outbox_message_fallbacks(
+    connection: &mut SqliteConnection,
+    id: &OutboxMessageId,
+) -> Result<Vec<OutboxMessage>, Error> {
+    Ok(sqlx::query_as!(
+        OutboxMessage,
+        r#"
+        SELECT
+            id AS "id!: _",
+            channel AS "channel: _",
+            recipient,
+            event_type,
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            skipped_at AS "skipped_at: _",
+            last_error,
+            fallback_of AS "fallback_of: _"
+        FROM message_outbox
+        WHERE fallback_of = ?1
+        ORDER BY id ASC
+        "#,
+        id,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn mark_outbox_message_delivered(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &OutboxMessageId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET attempts = attempts + 1, delivered_at = ?1, last_error = NULL
+        WHERE id = ?2 AND delivered_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records a failed attempt, scheduling the next one. Messages without the next attempt are given
+/// up on.
+pub async fn mark_outbox_message_attempt_failed(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &OutboxMessageId,
+    error: &str,
+    next_attempt_at: Option<&DateTime<Utc>>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET
+            attempts = attempts + 1,
+            last_error = ?1,
+            next_attempt_at = COALESCE(?2, next_attempt_at),
+            failed_at = CASE WHEN ?2 IS NULL THEN ?3 ELSE NULL END
+        WHERE id = ?4 AND delivered_at IS NULL
+        "#,
+        error,
+        next_attempt_at,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drops the message which is no longer relevant, without counting it as failed.
+pub async fn mark_outbox_message_skipped(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &OutboxMessageId,
+    reason: &str,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET skipped_at = ?1, last_error = ?2
+        WHERE id = ?3 AND delivered_at IS NULL AND failed_at IS NULL AND skipped_at IS NULL
+        "#,
+        now,
+        reason,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drops the messages of the event scheduled for the recipient, which haven't been attempted yet.
+pub async fn skip_scheduled_outbox_messages(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    channel: MessagingChannel,
+    recipient: &str,
+    event_type: &str,
+    reason: &str,
+) -> Result<u64, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET skipped_at = ?1, last_error = ?2
+        WHERE
+            channel = ?3 AND
+            recipient = ?4 AND
+            event_type = ?5 AND
+            attempts = 0 AND
+            next_attempt_at > ?1 AND
+            delivered_at IS NULL AND
+            failed_at IS NULL AND
+            skipped_at IS NULL
+        "#,
+        now,
+        reason,
+        channel,
+        recipient,
+        event_type,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Whether the message of the event for the booking has been queued for the recipient, unless it
+/// has been given up on or dropped since.
+pub async fn has_booking_outbox_message(
+    connection: &mut SqliteConnection,
+    channel: MessagingChannel,
+    recipient: &str,
+    event_type: &str,
+    booking_id: &BookingId,
+) -> Result<bool, Error> {
+    // The payload has the ID as text, unlike the tables
+    let booking_id = booking_id.to_string();
+    let result = sqlx::query!(
+        r#"
+        SELECT id
+        FROM message_outbox
+        WHERE
+            channel = ?1 AND
+            recipient = ?2 AND
+            event_type = ?3 AND
+            json_extract(payload, '$.booking.id') = ?4 AND
+            failed_at IS NULL AND
+            skipped_at IS NULL
+        LIMIT 1
+        "#,
+        channel,
+        recipient,
+        event_type,
+        booking_id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    Ok(result.is_some())
+}
+
+/// Gives up on the message which has been accepted, but later reported as undelivered.
+pub async fn mark_outbox_message_bounced(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &OutboxMessageId,
+    error: &str,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET delivered_at = NULL, failed_at = ?1, last_error = ?2
+        WHERE id = ?3 AND delivered_at IS NOT NULL
+        "#,
+        now,
+        error,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Puts the message which has been given up on back in the queue with fresh attempts.
+pub async fn requeue_outbox_message(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &OutboxMessageId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE message_outbox
+        SET attempts = 0, next_attempt_at = ?1, failed_at = NULL
+        WHERE id = ?2 AND failed_at IS NOT NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_message_delivery_log(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    message_id: &OutboxMessageId,
+    error: Option<&str>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO message_delivery_log(message_id, attempted_at, error)
+        VALUES(?1, ?2, ?3)
+        "#,
+        message_id,
+        now,
+        error,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_message_delivery_logs(
+    connection: &mut SqliteConnection,
+    message_id: &OutboxMessageId,
+) -> Result<Vec<MessageDeliveryLog>, Error> {
+    Ok(sqlx::query_as!(
+        MessageDeliveryLog,
+        r#"
+        SELECT
+            id AS "id!",
+            message_id AS "message_id: _",
+            attempted_at AS "attempted_at: _",
+            error
+        FROM message_delivery_log
+        WHERE message_id = ?1
+        ORDER BY attempted_at ASC, id ASC
+        "#,
+        message_id,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
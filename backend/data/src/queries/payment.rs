@@ -43,7 +43,8 @@ pub async fn get_cash_transaction(
             confirmed_at AS "confirmed_at: _",
             refund_price AS "refund_price: _",
             refund_account AS "refund_account: _",
-            refunded_at AS "refunded_at: _"
+            refunded_at AS "refunded_at: _",
+            refunded_price AS "refunded_price: _"
         FROM cash_transaction
         WHERE product_id=?1
         "#,
@@ -53,6 +54,73 @@ pub async fn get_cash_transaction(
     .await?)
 }
 
+pub async fn get_cash_transactions_by_booking_amendments(
+    connection: &mut SqliteConnection,
+    booking_id: &BookingId,
+) -> Result<Vec<CashTransaction>, Error> {
+    Ok(sqlx::query_as!(
+        CashTransaction,
+        r#"
+        SELECT
+            c.product_id AS "product_id: _",
+            c.depositor_name AS "depositor_name: _",
+            c.price AS "price: _",
+            c.created_at AS "created_at: _",
+            c.confirmed_at AS "confirmed_at: _",
+            c.refund_price AS "refund_price: _",
+            c.refund_account AS "refund_account: _",
+            c.refunded_at AS "refunded_at: _",
+            c.refunded_price AS "refunded_price: _"
+        FROM
+            cash_transaction "c"
+        JOIN booking_amendment "ba" ON
+            ba.id = c.product_id
+        WHERE
+            ba.booking_id = ?1
+        ORDER BY c.created_at ASC
+        "#,
+        booking_id
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Unpaid cash transactions whose booking, booking amendment or hour pack is still active.
+pub async fn get_pending_cash_transactions(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<CashTransaction>, Error> {
+    Ok(sqlx::query_as!(
+        CashTransaction,
+        r#"
+        SELECT
+            c.product_id AS "product_id: _",
+            c.depositor_name AS "depositor_name: _",
+            c.price AS "price: _",
+            c.created_at AS "created_at: _",
+            c.confirmed_at AS "confirmed_at: _",
+            c.refund_price AS "refund_price: _",
+            c.refund_account AS "refund_account: _",
+            c.refunded_at AS "refunded_at: _",
+            c.refunded_price AS "refunded_price: _"
+        FROM
+            cash_transaction "c"
+        LEFT OUTER JOIN booking "b" ON b.id = c.product_id
+        LEFT OUTER JOIN booking_amendment "ba" ON ba.id = c.product_id
+        LEFT OUTER JOIN hour_pack "hp" ON hp.id = c.product_id
+        WHERE
+            (c.confirmed_at IS NULL OR c.confirmed_at > ?1) AND
+            (b.canceled_at IS NULL OR b.canceled_at > ?1) AND
+            (ba.canceled_at IS NULL OR ba.canceled_at > ?1) AND
+            (hp.canceled_at IS NULL OR hp.canceled_at > ?1)
+        ORDER BY c.created_at ASC
+        "#,
+        now
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
 pub async fn confirm_cash_payment(
     connection: &mut SqliteConnection,
     now: &DateTime<Utc>,
@@ -82,8 +150,8 @@ pub async fn update_cash_refund_information(
     let result = sqlx::query!(
         r#"
         UPDATE cash_transaction
-        SET refund_price=?1, refund_account=?2
-        WHERE product_id=?3 AND refunded_at IS NULL
+        SET refund_price=?1, refund_account=?2, refunded_at=NULL
+        WHERE product_id=?3 AND ?1 >= COALESCE(refunded_price, 0)
         "#,
         refund_price,
         refund_account,
@@ -103,7 +171,7 @@ pub async fn refund_cash_payment(
     let result = sqlx::query!(
         r#"
         UPDATE cash_transaction
-        SET refunded_at=?1
+        SET refunded_at=?1, refunded_price=refund_price
         WHERE product_id=?2 AND refunded_at IS NULL
         "#,
         now,
@@ -115,6 +183,47 @@ pub async fn refund_cash_payment(
     Ok(result.rows_affected() > 0)
 }
 
+pub async fn update_cash_transaction_price(
+    connection: &mut SqliteConnection,
+    product_id: &ProductId,
+    price: i64,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE cash_transaction
+        SET price=?1
+        WHERE product_id=?2 AND confirmed_at IS NULL
+        "#,
+        price,
+        product_id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Marks that the customers have been warned about the payment deadline. Returns `false` if they
+/// already have been.
+pub async fn create_cash_payment_deadline_warning(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    product_id: &ProductId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO cash_payment_deadline_warning(product_id, created_at)
+        VALUES(?1, ?2)
+        "#,
+        product_id,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn create_toss_payments_transaction(
     connection: &mut SqliteConnection,
     now: &DateTime<Utc>,
@@ -157,7 +266,11 @@ pub async fn get_toss_payments_transaction_by_id(
             created_at AS "created_at: _",
             confirmed_at AS "confirmed_at: _",
             refund_price,
-            refunded_at AS "refunded_at: _"
+            refunded_at AS "refunded_at: _",
+            virtual_account_bank_code,
+            virtual_account_number,
+            virtual_account_due_at AS "virtual_account_due_at: _",
+            virtual_account_secret
         FROM
             toss_payments_transaction
         WHERE
@@ -187,7 +300,11 @@ pub async fn get_toss_payments_transactions_by_booking_amentments(
             t.created_at AS "created_at: _",
             t.confirmed_at AS "confirmed_at: _",
             t.refund_price AS "refund_price",
-            t.refunded_at AS "refunded_at: _"
+            t.refunded_at AS "refunded_at: _",
+            t.virtual_account_bank_code,
+            t.virtual_account_number,
+            t.virtual_account_due_at AS "virtual_account_due_at: _",
+            t.virtual_account_secret
         FROM
             toss_payments_transaction "t"
         JOIN product "p" ON
@@ -224,7 +341,11 @@ pub async fn get_toss_payments_transaction_by_temporary_reservation_id(
             created_at AS "created_at: _",
             confirmed_at AS "confirmed_at: _",
             refund_price,
-            refunded_at AS "refunded_at: _"
+            refunded_at AS "refunded_at: _",
+            virtual_account_bank_code,
+            virtual_account_number,
+            virtual_account_due_at AS "virtual_account_due_at: _",
+            virtual_account_secret
         FROM
             toss_payments_transaction
         WHERE
@@ -253,7 +374,11 @@ pub async fn get_toss_payments_transaction_by_product_id(
             created_at AS "created_at: _",
             confirmed_at AS "confirmed_at: _",
             refund_price,
-            refunded_at AS "refunded_at: _"
+            refunded_at AS "refunded_at: _",
+            virtual_account_bank_code,
+            virtual_account_number,
+            virtual_account_due_at AS "virtual_account_due_at: _",
+            virtual_account_secret
         FROM
             toss_payments_transaction
         WHERE
@@ -265,6 +390,115 @@ pub async fn get_toss_payments_transaction_by_product_id(
     .await?)
 }
 
+pub async fn get_toss_payments_transactions_created_since(
+    connection: &mut SqliteConnection,
+    since: &DateTime<Utc>,
+) -> Result<Vec<TossPaymentsTransaction>, Error> {
+    Ok(sqlx::query_as!(
+        TossPaymentsTransaction,
+        r#"
+        SELECT
+            id AS "id: _",
+            user_id AS "user_id: _",
+            temporary_reservation_id AS "temporary_reservation_id: _",
+            product_id AS "product_id: _",
+            price,
+            payment_key,
+            created_at AS "created_at: _",
+            confirmed_at AS "confirmed_at: _",
+            refund_price,
+            refunded_at AS "refunded_at: _",
+            virtual_account_bank_code,
+            virtual_account_number,
+            virtual_account_due_at AS "virtual_account_due_at: _",
+            virtual_account_secret
+        FROM
+            toss_payments_transaction
+        WHERE
+            created_at >= ?1
+        ORDER BY created_at ASC
+        "#,
+        since,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Virtual accounts issued for orders which have neither been paid nor closed by their due date.
+pub async fn get_overdue_toss_virtual_accounts(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<TossPaymentsTransaction>, Error> {
+    Ok(sqlx::query_as!(
+        TossPaymentsTransaction,
+        r#"
+        SELECT
+            id AS "id: _",
+            user_id AS "user_id: _",
+            temporary_reservation_id AS "temporary_reservation_id: _",
+            product_id AS "product_id: _",
+            price,
+            payment_key,
+            created_at AS "created_at: _",
+            confirmed_at AS "confirmed_at: _",
+            refund_price,
+            refunded_at AS "refunded_at: _",
+            virtual_account_bank_code,
+            virtual_account_number,
+            virtual_account_due_at AS "virtual_account_due_at: _",
+            virtual_account_secret
+        FROM
+            toss_payments_transaction
+        WHERE
+            virtual_account_due_at <= ?1 AND
+            confirmed_at IS NULL AND
+            refunded_at IS NULL
+        ORDER BY virtual_account_due_at ASC
+        "#,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Attaches the product to the order while the issued virtual account waits for the deposit.
+/// The transaction gets confirmed when the deposit arrives.
+pub async fn issue_toss_payments_virtual_account(
+    connection: &mut SqliteConnection,
+    id: &ForeignPaymentId,
+    product_id: &ProductId,
+    payment_key: &str,
+    bank_code: &str,
+    account_number: &str,
+    due_at: &DateTime<Utc>,
+    secret: Option<&str>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE toss_payments_transaction
+        SET
+            product_id=?1,
+            payment_key=?2,
+            virtual_account_bank_code=?3,
+            virtual_account_number=?4,
+            virtual_account_due_at=?5,
+            virtual_account_secret=?6
+        WHERE id=?7 AND confirmed_at IS NULL
+        "#,
+        product_id,
+        payment_key,
+        bank_code,
+        account_number,
+        due_at,
+        secret,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn confirm_toss_payments_transaction(
     connection: &mut SqliteConnection,
     now: &DateTime<Utc>,
@@ -310,3 +544,23 @@ pub async fn refund_toss_payments(
 
     Ok(result.rows_affected() > 0)
 }
+
+pub async fn update_toss_payments_transaction_price(
+    connection: &mut SqliteConnection,
+    id: &ForeignPaymentId,
+    price: i64,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE toss_payments_transaction
+        SET price=?1
+        WHERE id=?2 AND confirmed_at IS NULL
+        "#,
+        price,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
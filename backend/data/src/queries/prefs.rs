@@ -1,11 +1,11 @@
 use chrono::{DateTime, Utc};
-use dxe_types::entities::MixerPreferences;
-use dxe_types::{IdentityId, UnitId};
+use dxe_types::entities::{MixerPreferences, NotificationPreferences};
+use dxe_types::{IdentityId, UnitId, UserId};
 use sqlx::SqliteConnection;
 use sqlx::types::Json;
 
 use crate::Error;
-use crate::entities::MixerConfig;
+use crate::entities::{MixerConfig, NotificationConfig};
 
 pub async fn create_or_update_mixer_config(
     connection: &mut SqliteConnection,
@@ -61,3 +61,52 @@ pub async fn get_mixer_config(
     .fetch_optional(&mut *connection)
     .await?)
 }
+
+pub async fn create_or_update_notification_config(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+    data: &NotificationPreferences,
+) -> Result<bool, Error> {
+    let data = Json(data);
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO notification_config(user_id, data, created_at, updated_at)
+        VALUES($1, $2, $3, $3)
+        ON CONFLICT(user_id)
+        DO UPDATE SET
+            data = excluded.data,
+            updated_at = excluded.updated_at
+        "#,
+        user_id,
+        data,
+        now
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_notification_config(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+) -> Result<Option<NotificationConfig>, Error> {
+    Ok(sqlx::query_as!(
+        NotificationConfig,
+        r#"
+        SELECT
+            user_id AS "user_id: UserId",
+            data AS "data: Json<NotificationPreferences>",
+            created_at AS "created_at: DateTime<Utc>",
+            updated_at AS "updated_at: DateTime<Utc>"
+        FROM
+            notification_config
+        WHERE
+            user_id = ?1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
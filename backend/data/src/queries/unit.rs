@@ -1,8 +1,11 @@
-use dxe_types::{SpaceId, UnitId};
+use chrono::{DateTime, Datelike, NaiveTime, TimeDelta, Utc};
+use dxe_types::{SpaceId, UnitClosureId, UnitId};
 use sqlx::SqliteConnection;
 
 use crate::Error;
-use crate::entities::{Space, Unit};
+use crate::entities::{OccupiedSlot, Space, Unit, UnitClosure, UnitOpeningHours};
+
+const MINUTES_PER_WEEK: i64 = 7 * 24 * 60;
 
 pub async fn get_space_by_unit_id(
     connection: &mut SqliteConnection,
@@ -63,3 +66,340 @@ pub async fn is_unit_enabled(
 
     Ok(result.map(|v| v.enabled))
 }
+
+pub async fn replace_unit_opening_hours(
+    connection: &mut SqliteConnection,
+    unit_id: &UnitId,
+    windows: &[(i64, i64)],
+) -> Result<(), Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM unit_opening_hours
+        WHERE unit_id = ?1
+        "#,
+        unit_id
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    for (start_minute, duration_minutes) in windows {
+        let start_minute = start_minute.rem_euclid(MINUTES_PER_WEEK);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO unit_opening_hours(unit_id, start_minute, duration_minutes)
+            VALUES(?1, ?2, ?3)
+            "#,
+            unit_id,
+            start_minute,
+            duration_minutes,
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_unit_opening_hours(
+    connection: &mut SqliteConnection,
+    unit_id: &UnitId,
+) -> Result<Vec<UnitOpeningHours>, Error> {
+    Ok(sqlx::query_as!(
+        UnitOpeningHours,
+        r#"
+        SELECT
+            unit_id AS "unit_id: _",
+            start_minute,
+            duration_minutes
+        FROM unit_opening_hours
+        WHERE unit_id = ?1
+        ORDER BY start_minute
+        "#,
+        unit_id
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn create_unit_closure(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    time_from: &DateTime<Utc>,
+    time_to: &DateTime<Utc>,
+    reason: &Option<String>,
+) -> Result<UnitClosureId, Error> {
+    if is_unit_enabled(connection, unit_id).await?.is_none() {
+        return Err(Error::UnitNotFound);
+    }
+
+    if time_from >= time_to {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO unit_closure(unit_id, time_from, time_to, reason, created_at)
+        VALUES(?1, ?2, ?3, ?4, ?5)
+        RETURNING id AS "id: UnitClosureId"
+        "#,
+        unit_id,
+        time_from,
+        time_to,
+        reason,
+        now,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.id)
+}
+
+pub async fn get_unit_closure(
+    connection: &mut SqliteConnection,
+    id: &UnitClosureId,
+) -> Result<Option<UnitClosure>, Error> {
+    Ok(sqlx::query_as!(
+        UnitClosure,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            reason,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM unit_closure
+        WHERE id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_unit_closures(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    range_from: &DateTime<Utc>,
+    range_to: &DateTime<Utc>,
+) -> Result<Vec<UnitClosure>, Error> {
+    Ok(sqlx::query_as!(
+        UnitClosure,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            reason,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM unit_closure
+        WHERE
+            time_to > ?1 AND time_from < ?2 AND
+            unit_id = ?3 AND
+            (deleted_at IS NULL OR deleted_at > ?4)
+        ORDER BY time_from
+        "#,
+        range_from,
+        range_to,
+        unit_id,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn get_unit_closures_by_unit_id(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    range_from: Option<DateTime<Utc>>,
+) -> Result<Vec<UnitClosure>, Error> {
+    let range_from = range_from.unwrap_or_default();
+
+    Ok(sqlx::query_as!(
+        UnitClosure,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            reason,
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM unit_closure
+        WHERE
+            unit_id = ?1 AND
+            time_to > ?2 AND
+            (deleted_at IS NULL OR deleted_at > ?3)
+        ORDER BY time_from
+        "#,
+        unit_id,
+        range_from,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn delete_unit_closure(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &UnitClosureId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE unit_closure
+        SET deleted_at = ?1
+        WHERE id = ?2 AND deleted_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Time ranges within the given range where the unit is not open for booking, either because
+/// they fall outside of weekly opening hours or because of a dated closure.
+pub async fn get_closed_slots(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    range_from: &DateTime<Utc>,
+    range_to: &DateTime<Utc>,
+) -> Result<Vec<OccupiedSlot>, Error> {
+    let opening_hours = get_unit_opening_hours(&mut *connection, unit_id).await?;
+
+    let mut slots = off_hours(&opening_hours, *range_from, *range_to)
+        .into_iter()
+        .map(|(time_from, time_to)| OccupiedSlot {
+            name: String::new(),
+            time_from,
+            time_to,
+            confirmed: true,
+            closed: true,
+        })
+        .collect::<Vec<_>>();
+
+    for closure in get_unit_closures(&mut *connection, now, unit_id, range_from, range_to).await? {
+        slots.push(OccupiedSlot {
+            name: closure.reason.unwrap_or_default(),
+            time_from: closure.time_from,
+            time_to: closure.time_to,
+            confirmed: true,
+            closed: true,
+        });
+    }
+
+    Ok(slots)
+}
+
+fn off_hours(
+    opening_hours: &[UnitOpeningHours],
+    range_from: DateTime<Utc>,
+    range_to: DateTime<Utc>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    if opening_hours.is_empty() || range_from >= range_to {
+        return vec![];
+    }
+
+    // Expand weekly windows starting from the week before, as a window may span into the next
+    let first_week = (range_from.date_naive()
+        - TimeDelta::days(range_from.weekday().num_days_from_monday() as i64 + 7))
+    .and_time(NaiveTime::MIN)
+    .and_utc();
+
+    let mut open = vec![];
+    let mut week = first_week;
+    while week < range_to {
+        for window in opening_hours {
+            let start = week + TimeDelta::minutes(window.start_minute);
+            open.push((start, start + TimeDelta::minutes(window.duration_minutes)));
+        }
+        week += TimeDelta::weeks(1);
+    }
+    open.sort();
+
+    let mut closed = vec![];
+    let mut cursor = range_from;
+    for (start, end) in open {
+        if end <= cursor {
+            continue;
+        }
+        if start >= range_to {
+            break;
+        }
+        if start > cursor {
+            closed.push((cursor, start));
+        }
+        cursor = end;
+    }
+    if cursor < range_to {
+        closed.push((cursor, range_to));
+    }
+
+    closed
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use dxe_types::UnitId;
+
+    use super::{MINUTES_PER_WEEK, off_hours};
+    use crate::entities::UnitOpeningHours;
+
+    fn window(start_minute: i64, duration_minutes: i64) -> UnitOpeningHours {
+        UnitOpeningHours {
+            unit_id: UnitId::from("studio".to_owned()),
+            start_minute,
+            duration_minutes,
+        }
+    }
+
+    fn utc(day: u32, hour: u32) -> DateTime<Utc> {
+        // 2026-03-02 is a Monday
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn always_open_without_opening_hours() {
+        assert!(off_hours(&[], utc(2, 0), utc(9, 0)).is_empty());
+    }
+
+    #[test]
+    fn closed_outside_of_daily_window() {
+        // Monday 09:00 - 18:00
+        let opening_hours = [window(9 * 60, 9 * 60)];
+
+        assert_eq!(
+            off_hours(&opening_hours, utc(2, 0), utc(3, 0)),
+            vec![(utc(2, 0), utc(2, 9)), (utc(2, 18), utc(3, 0))]
+        );
+        assert!(off_hours(&opening_hours, utc(2, 10), utc(2, 12)).is_empty());
+    }
+
+    #[test]
+    fn window_spanning_into_next_week() {
+        // Sunday 22:00 - Monday 02:00
+        let opening_hours = [window(MINUTES_PER_WEEK - 2 * 60, 4 * 60)];
+
+        assert_eq!(
+            off_hours(&opening_hours, utc(2, 0), utc(2, 6)),
+            vec![(utc(2, 2), utc(2, 6))]
+        );
+        assert_eq!(
+            off_hours(&opening_hours, utc(1, 20), utc(2, 1)),
+            vec![(utc(1, 20), utc(1, 22))]
+        );
+    }
+}
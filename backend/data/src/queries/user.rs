@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
-use dxe_types::{IdentityId, IdentityProvider, UserId};
+use dxe_types::{EmailVerificationToken, IdentityId, IdentityProvider, UserId, UserIdentityLinkId};
 use sqlx::{Executor, QueryBuilder, SqliteConnection};
 
 use crate::Error;
-use crate::entities::{IdentityDiscriminator, User, UserCashPaymentInformation};
+use crate::entities::{
+    IdentityDiscriminator, User, UserCashPaymentInformation, UserEmail, UserIdentityLink,
+    UserPhoneNumber,
+};
 
 pub async fn create_user(
     connection: &mut SqliteConnection,
@@ -42,6 +45,8 @@ pub async fn create_user(
     .execute(&mut *connection)
     .await?;
 
+    create_user_identity_link(&mut *connection, &now, &user_id, provider, foreign_id).await?;
+
     Ok(user_id)
 }
 
@@ -63,7 +68,7 @@ pub async fn get_user_by_id(
             license_plate_number
         FROM user
         WHERE id = ?1 AND
-            (deactivated_at IS NULL OR deactivated_at < ?2)
+            (deactivated_at IS NULL OR deactivated_at > ?2)
         "#,
         user_id,
         now
@@ -82,16 +87,17 @@ pub async fn get_user_by_foreign_id(
         User,
         r#"
         SELECT
-            id AS "id: _",
-            provider AS "provider: _",
-            foreign_id,
-            name,
-            created_at AS "created_at: _",
-            deactivated_at AS "deactivated_at: _",
-            license_plate_number
-        FROM user
-        WHERE provider = ?1 AND foreign_id = ?2 AND
-            (deactivated_at IS NULL OR deactivated_at < ?3)
+            u.id AS "id: _",
+            u.provider AS "provider: _",
+            u.foreign_id,
+            u.name,
+            u.created_at AS "created_at: _",
+            u.deactivated_at AS "deactivated_at: _",
+            u.license_plate_number
+        FROM user "u"
+        JOIN user_identity_link "uil" ON uil.user_id = u.id
+        WHERE uil.provider = ?1 AND uil.foreign_id = ?2 AND
+            (u.deactivated_at IS NULL OR u.deactivated_at > ?3)
         "#,
         provider,
         foreign_id,
@@ -239,3 +245,553 @@ pub async fn update_user_cash_payment_refund_account(
 
     Ok(result.rows_affected() > 0)
 }
+
+pub async fn get_user_email(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+) -> Result<Option<UserEmail>, Error> {
+    Ok(sqlx::query_as!(
+        UserEmail,
+        r#"
+        SELECT
+            user_id AS "user_id: _",
+            email,
+            verification_token AS "verification_token: _",
+            verification_expires_at AS "verification_expires_at: _",
+            verified_at AS "verified_at: _",
+            updated_at AS "updated_at: _"
+        FROM user_email
+        WHERE user_id=?1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+/// Sets the email address of the user, which stays unverified until the token is used.
+pub async fn update_user_email(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+    email: &str,
+    verification_token: &EmailVerificationToken,
+    verification_expires_at: &DateTime<Utc>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_email(user_id, email, verification_token, verification_expires_at, updated_at)
+        VALUES(?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(user_id) DO UPDATE SET
+            email=?2,
+            verification_token=?3,
+            verification_expires_at=?4,
+            verified_at=NULL,
+            updated_at=?5
+        "#,
+        user_id,
+        email,
+        verification_token,
+        verification_expires_at,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_user_email(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM user_email
+        WHERE user_id=?1
+        "#,
+        user_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn verify_user_email(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    verification_token: &EmailVerificationToken,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_email
+        SET
+            verification_token=NULL,
+            verification_expires_at=NULL,
+            verified_at=?1,
+            updated_at=?1
+        WHERE verification_token=?2 AND verification_expires_at > ?1
+        "#,
+        now,
+        verification_token,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_user_phone_number(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+) -> Result<Option<UserPhoneNumber>, Error> {
+    Ok(sqlx::query_as!(
+        UserPhoneNumber,
+        r#"
+        SELECT
+            user_id AS "user_id: _",
+            phone_number,
+            updated_at AS "updated_at: _"
+        FROM user_phone_number
+        WHERE user_id=?1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn update_user_phone_number(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+    phone_number: &str,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_phone_number(user_id, phone_number, updated_at)
+        VALUES(?1, ?2, ?3)
+        ON CONFLICT(user_id) DO UPDATE SET
+            phone_number=?2,
+            updated_at=?3
+        "#,
+        user_id,
+        phone_number,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_user_identity_links(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+) -> Result<Vec<UserIdentityLink>, Error> {
+    Ok(sqlx::query_as!(
+        UserIdentityLink,
+        r#"
+        SELECT
+            id AS "id!: _",
+            user_id AS "user_id: _",
+            provider AS "provider: _",
+            foreign_id,
+            created_at AS "created_at: _"
+        FROM user_identity_link
+        WHERE user_id=?1
+        ORDER BY created_at ASC, id ASC
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn create_user_identity_link(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+    provider: IdentityProvider,
+    foreign_id: &str,
+) -> Result<UserIdentityLinkId, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_identity_link(user_id, provider, foreign_id, created_at)
+        VALUES(?1, ?2, ?3, ?4)
+        "#,
+        user_id,
+        provider,
+        foreign_id,
+        now,
+    )
+    .execute(&mut *connection)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(e) if e.is_unique_violation() => Error::IdentityAlreadyLinked,
+        e => Error::Sqlx(e),
+    })?;
+
+    Ok(result.last_insert_rowid().into())
+}
+
+/// Whether the sign-in method is linked to any user, including deactivated ones.
+pub async fn is_identity_linked(
+    connection: &mut SqliteConnection,
+    provider: IdentityProvider,
+    foreign_id: &str,
+) -> Result<bool, Error> {
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_identity_link WHERE provider=?1 AND foreign_id=?2
+        ) AS "linked: bool"
+        "#,
+        provider,
+        foreign_id,
+    )
+    .fetch_one(&mut *connection)
+    .await?)
+}
+
+/// Detaches a sign-in method from the user. Handle links take the plain credential with them.
+///
+/// When the detached method is the one the user row was registered with, the oldest remaining
+/// link takes its place so that `user.provider` and `user.foreign_id` keep pointing at a live
+/// identity.
+pub async fn delete_user_identity_link(
+    connection: &mut SqliteConnection,
+    user_id: &UserId,
+    link_id: UserIdentityLinkId,
+) -> Result<bool, Error> {
+    let Some(link) = sqlx::query_as!(
+        UserIdentityLink,
+        r#"
+        DELETE FROM user_identity_link
+        WHERE id=?1 AND user_id=?2
+        RETURNING
+            id AS "id!: _",
+            user_id AS "user_id: _",
+            provider AS "provider: _",
+            foreign_id,
+            created_at AS "created_at: _"
+        "#,
+        link_id,
+        user_id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    if link.provider == IdentityProvider::Handle {
+        sqlx::query!(
+            r#"
+            DELETE FROM user_plain_credential
+            WHERE user_id=?1 AND handle=?2
+            "#,
+            user_id,
+            link.foreign_id,
+        )
+        .execute(&mut *connection)
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE user
+        SET (provider, foreign_id) = (
+            SELECT provider, foreign_id
+            FROM user_identity_link
+            WHERE user_id=?1
+            ORDER BY created_at ASC, id ASC
+            LIMIT 1
+        )
+        WHERE id=?1 AND provider=?2 AND foreign_id=?3 AND
+            EXISTS(SELECT 1 FROM user_identity_link WHERE user_id=?1)
+        "#,
+        user_id,
+        link.provider,
+        link.foreign_id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(true)
+}
+
+/// Folds `source` into `target`: bookings, group memberships, payment records, sign-in methods
+/// and administrator rights are re-pointed to `target`, and `source` is deactivated.
+///
+/// Per-user settings (plain credential, email, phone number, notification preferences, cash
+/// payment information, mixer presets, calendar feed) are only carried over when `target` has
+/// none of its own. The payment method is chosen per booking, so there is no payment setting to
+/// reconcile.
+pub async fn merge_users(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    source: &UserId,
+    target: &UserId,
+) -> Result<(), Error> {
+    let source_identity: IdentityId = (*source).into();
+
+    // Bookings
+    sqlx::query!(
+        "UPDATE booking SET holder_id=?2 WHERE holder_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE booking SET customer_id=?2 WHERE customer_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE booking_series SET holder_id=?2 WHERE holder_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE booking_series SET customer_id=?2 WHERE customer_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE adhoc_reservation SET holder_id=?2 WHERE holder_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE adhoc_reservation SET customer_id=?2 WHERE customer_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE waitlist_entry SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE waitlist_entry SET customer_id=?2 WHERE customer_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE hour_pack SET holder_id=?2 WHERE holder_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE hour_pack SET customer_id=?2 WHERE customer_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE hour_balance_entry SET identity_id=?2 WHERE identity_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    // Group memberships
+    sqlx::query!(
+        r#"UPDATE "group" SET owner_id=?2 WHERE owner_id=?1"#,
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE group_association SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM group_association WHERE user_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+
+    // Payment records
+    sqlx::query!(
+        "UPDATE toss_payments_transaction SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE coupon_redemption SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE user_cash_payment_information SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM user_cash_payment_information WHERE user_id=?1",
+        source,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    // Sign-in methods
+    sqlx::query!(
+        r#"
+        DELETE FROM user_identity_link
+        WHERE user_id=?1 AND provider='handle' AND
+            EXISTS(SELECT 1 FROM user_plain_credential WHERE user_id=?2)
+        "#,
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        r#"
+        DELETE FROM user_plain_credential
+        WHERE user_id=?1 AND EXISTS(SELECT 1 FROM user_plain_credential WHERE user_id=?2)
+        "#,
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE user_plain_credential SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!(
+        "UPDATE user_identity_link SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    // Contacts and preferences
+    sqlx::query!(
+        "UPDATE OR IGNORE user_email SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM user_email WHERE user_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE user_phone_number SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM user_phone_number WHERE user_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE notification_config SET user_id=?2 WHERE user_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM notification_config WHERE user_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE mixer_config SET identity_id=?2 WHERE identity_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM mixer_config WHERE identity_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query!(
+        "UPDATE OR IGNORE calendar_feed SET identity_id=?2 WHERE identity_id=?1",
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM calendar_feed WHERE identity_id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+
+    // Pending resets would otherwise act on whichever credential `target` ends up with
+    sqlx::query!(
+        "DELETE FROM password_reset_token WHERE user_id=?1 AND used_at IS NULL",
+        source,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    // Privileges
+    sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO administrator(id)
+        SELECT ?2 WHERE EXISTS(SELECT 1 FROM administrator WHERE id=?1)
+        "#,
+        source,
+        target,
+    )
+    .execute(&mut *connection)
+    .await?;
+    sqlx::query!("DELETE FROM administrator WHERE id=?1", source)
+        .execute(&mut *connection)
+        .await?;
+
+    // The source row keeps its NOT NULL identity columns, so its foreign ID is replaced with a
+    // tombstone to let the identity register again once it is unlinked from the target.
+    let tombstone = format!("merged:{source_identity}");
+
+    sqlx::query!(
+        r#"
+        UPDATE user
+        SET foreign_id=?2, deactivated_at=?3
+        WHERE id=?1
+        "#,
+        source,
+        tombstone,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use chrono::{DateTime, Utc};
+use dxe_types::{AdhocReservationId, IdentityId, UnitId, UserId, WaitlistEntryId};
+use sqlx::SqliteConnection;
+
+use crate::Error;
+use crate::entities::WaitlistEntry;
+use crate::queries::unit::is_unit_enabled;
+
+pub async fn create_waitlist_entry(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    user_id: &UserId,
+    customer_id: &IdentityId,
+    time_from: &DateTime<Utc>,
+    time_to: &DateTime<Utc>,
+) -> Result<WaitlistEntryId, Error> {
+    if is_unit_enabled(connection, unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    if time_from >= time_to {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO waitlist_entry(unit_id, user_id, customer_id, time_from, time_to, created_at)
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6)
+        RETURNING id AS "id: WaitlistEntryId"
+        "#,
+        unit_id,
+        user_id,
+        customer_id,
+        time_from,
+        time_to,
+        now,
+    )
+    .fetch_one(&mut *connection)
+    .await?;
+
+    Ok(result.id)
+}
+
+pub async fn get_waitlist_entry(
+    connection: &mut SqliteConnection,
+    id: &WaitlistEntryId,
+) -> Result<Option<WaitlistEntry>, Error> {
+    Ok(sqlx::query_as!(
+        WaitlistEntry,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            user_id AS "user_id: _",
+            customer_id AS "customer_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            created_at AS "created_at: _",
+            notified_at AS "notified_at: _",
+            adhoc_reservation_id AS "adhoc_reservation_id: _",
+            deleted_at AS "deleted_at: _"
+        FROM waitlist_entry
+        WHERE id = ?1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_waitlist_entries_by_user_id(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    user_id: &UserId,
+) -> Result<Vec<WaitlistEntry>, Error> {
+    Ok(sqlx::query_as!(
+        WaitlistEntry,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            user_id AS "user_id: _",
+            customer_id AS "customer_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            created_at AS "created_at: _",
+            notified_at AS "notified_at: _",
+            adhoc_reservation_id AS "adhoc_reservation_id: _",
+            deleted_at AS "deleted_at: _"
+        FROM waitlist_entry
+        WHERE
+            user_id = ?1 AND
+            time_to > ?2 AND
+            (deleted_at IS NULL OR deleted_at > ?2)
+        ORDER BY time_from ASC
+        "#,
+        user_id,
+        now
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn get_pending_waitlist_entries(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    range_from: &DateTime<Utc>,
+    range_to: &DateTime<Utc>,
+) -> Result<Vec<WaitlistEntry>, Error> {
+    Ok(sqlx::query_as!(
+        WaitlistEntry,
+        r#"
+        SELECT
+            id AS "id: _",
+            unit_id AS "unit_id: _",
+            user_id AS "user_id: _",
+            customer_id AS "customer_id: _",
+            time_from AS "time_from: _",
+            time_to AS "time_to: _",
+            created_at AS "created_at: _",
+            notified_at AS "notified_at: _",
+            adhoc_reservation_id AS "adhoc_reservation_id: _",
+            deleted_at AS "deleted_at: _"
+        FROM waitlist_entry
+        WHERE
+            unit_id = ?1 AND
+            MAX(time_from, ?2) < MIN(time_to, ?3) AND
+            time_from > ?4 AND
+            notified_at IS NULL AND
+            (deleted_at IS NULL OR deleted_at > ?4)
+        ORDER BY created_at ASC
+        "#,
+        unit_id,
+        range_from,
+        range_to,
+        now
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Entries offered a priority hold which has since been claimed, declined or let lapse.
+pub async fn get_finished_waitlist_holds(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<WaitlistEntry>, Error> {
+    Ok(sqlx::query_as!(
+        WaitlistEntry,
+        r#"
+        SELECT
+            w.id AS "id: _",
+            w.unit_id AS "unit_id: _",
+            w.user_id AS "user_id: _",
+            w.customer_id AS "customer_id: _",
+            w.time_from AS "time_from: _",
+            w.time_to AS "time_to: _",
+            w.created_at AS "created_at: _",
+            w.notified_at AS "notified_at: _",
+            w.adhoc_reservation_id AS "adhoc_reservation_id: _",
+            w.deleted_at AS "deleted_at: _"
+        FROM waitlist_entry w
+        JOIN adhoc_reservation a ON a.id = w.adhoc_reservation_id
+        WHERE
+            w.notified_at IS NOT NULL AND
+            w.deleted_at IS NULL AND
+            a.deleted_at <= ?1
+        ORDER BY w.notified_at ASC
+        "#,
+        now
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn update_waitlist_entry_notified(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WaitlistEntryId,
+    adhoc_reservation_id: &AdhocReservationId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE waitlist_entry
+        SET
+            notified_at = ?1,
+            adhoc_reservation_id = ?2
+        WHERE id = ?3 AND notified_at IS NULL
+        "#,
+        now,
+        adhoc_reservation_id,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_waitlist_entry(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WaitlistEntryId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE waitlist_entry
+        SET deleted_at = ?1
+        WHERE id = ?2 AND deleted_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use dxe_types::{WebhookDeliveryId, WebhookEventType, WebhookSubscriptionId};
+use sqlx::SqliteConnection;
+use sqlx::types::Json;
+
+use crate::Error;
+use crate::entities::{WebhookDelivery, WebhookDeliveryLog, WebhookSubscription};
+
+pub async fn create_webhook_subscription(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    url: &str,
+    secret: &str,
+    event_types: &HashSet<WebhookEventType>,
+) -> Result<WebhookSubscriptionId, Error> {
+    let event_types = Json(event_types);
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO webhook_subscription(url, secret, event_types, created_at)
+        VALUES(?1, ?2, ?3, ?4)
+        "#,
+        url,
+        secret,
+        event_types,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(WebhookSubscriptionId::from(result.last_insert_rowid()))
+}
+
+pub async fn get_webhook_subscription(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WebhookSubscriptionId,
+) -> Result<Option<WebhookSubscription>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookSubscription,
+        r#"
+        SELECT
+            id AS "id!: _",
+            url,
+            secret,
+            event_types AS "event_types: Json<HashSet<WebhookEventType>>",
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM webhook_subscription
+        WHERE
+            id = ?1 AND
+            (deleted_at IS NULL OR deleted_at > ?2)
+        "#,
+        id,
+        now,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+pub async fn get_webhook_subscriptions(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+) -> Result<Vec<WebhookSubscription>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookSubscription,
+        r#"
+        SELECT
+            id AS "id!: _",
+            url,
+            secret,
+            event_types AS "event_types: Json<HashSet<WebhookEventType>>",
+            created_at AS "created_at: _",
+            deleted_at AS "deleted_at: _"
+        FROM webhook_subscription
+        WHERE deleted_at IS NULL OR deleted_at > ?1
+        ORDER BY created_at ASC, id ASC
+        "#,
+        now,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn update_webhook_subscription(
+    connection: &mut SqliteConnection,
+    id: &WebhookSubscriptionId,
+    url: &str,
+    secret: &str,
+    event_types: &HashSet<WebhookEventType>,
+) -> Result<bool, Error> {
+    let event_types = Json(event_types);
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_subscription
+        SET url = ?1, secret = ?2, event_types = ?3
+        WHERE id = ?4 AND deleted_at IS NULL
+        "#,
+        url,
+        secret,
+        event_types,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Deletes the subscription, giving up on its pending deliveries.
+pub async fn delete_webhook_subscription(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WebhookSubscriptionId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_subscription
+        SET deleted_at = ?1
+        WHERE id = ?2 AND deleted_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE webhook_delivery
+        SET failed_at = ?1, last_error = 'Subscription has been deleted'
+        WHERE subscription_id = ?2 AND delivered_at IS NULL AND failed_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_webhook_delivery(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    subscription_id: &WebhookSubscriptionId,
+    event_type: WebhookEventType,
+    payload: &str,
+) -> Result<WebhookDeliveryId, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO webhook_delivery(subscription_id, event_type, payload, created_at, next_attempt_at)
+        VALUES(?1, ?2, ?3, ?4, ?4)
+        "#,
+        subscription_id,
+        event_type,
+        payload,
+        now,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(WebhookDeliveryId::from(result.last_insert_rowid()))
+}
+
+pub async fn get_webhook_delivery(
+    connection: &mut SqliteConnection,
+    id: &WebhookDeliveryId,
+) -> Result<Option<WebhookDelivery>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookDelivery,
+        r#"
+        SELECT
+            id AS "id!: _",
+            subscription_id AS "subscription_id: _",
+            event_type AS "event_type: _",
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            last_error
+        FROM webhook_delivery
+        WHERE id = ?1
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *connection)
+    .await?)
+}
+
+/// Deliveries of the subscription, latest first.
+pub async fn get_webhook_deliveries(
+    connection: &mut SqliteConnection,
+    subscription_id: &WebhookSubscriptionId,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookDelivery,
+        r#"
+        SELECT
+            id AS "id!: _",
+            subscription_id AS "subscription_id: _",
+            event_type AS "event_type: _",
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            last_error
+        FROM webhook_delivery
+        WHERE subscription_id = ?1
+        ORDER BY created_at DESC, id DESC
+        LIMIT ?2
+        "#,
+        subscription_id,
+        limit,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+/// Deliveries which are neither delivered nor given up on, and due for the next attempt.
+pub async fn get_due_webhook_deliveries(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookDelivery,
+        r#"
+        SELECT
+            id AS "id!: _",
+            subscription_id AS "subscription_id: _",
+            event_type AS "event_type: _",
+            payload,
+            attempts,
+            created_at AS "created_at: _",
+            next_attempt_at AS "next_attempt_at: _",
+            delivered_at AS "delivered_at: _",
+            failed_at AS "failed_at: _",
+            last_error
+        FROM webhook_delivery
+        WHERE
+            delivered_at IS NULL AND
+            failed_at IS NULL AND
+            next_attempt_at <= ?1
+        ORDER BY next_attempt_at ASC, id ASC
+        LIMIT ?2
+        "#,
+        now,
+        limit,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
+
+pub async fn mark_webhook_delivery_delivered(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WebhookDeliveryId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_delivery
+        SET attempts = attempts + 1, delivered_at = ?1, last_error = NULL
+        WHERE id = ?2 AND delivered_at IS NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records a failed attempt, scheduling the next one. Deliveries without the next attempt are
+/// given up on.
+pub async fn mark_webhook_delivery_attempt_failed(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WebhookDeliveryId,
+    error: &str,
+    next_attempt_at: Option<&DateTime<Utc>>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_delivery
+        SET
+            attempts = attempts + 1,
+            last_error = ?1,
+            next_attempt_at = COALESCE(?2, next_attempt_at),
+            failed_at = CASE WHEN ?2 IS NULL THEN ?3 ELSE NULL END
+        WHERE id = ?4 AND delivered_at IS NULL
+        "#,
+        error,
+        next_attempt_at,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Puts the delivery which has been given up on back in the queue with fresh attempts.
+pub async fn requeue_webhook_delivery(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    id: &WebhookDeliveryId,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE webhook_delivery
+        SET attempts = 0, next_attempt_at = ?1, failed_at = NULL
+        WHERE id = ?2 AND failed_at IS NOT NULL
+        "#,
+        now,
+        id,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn create_webhook_delivery_log(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    delivery_id: &WebhookDeliveryId,
+    status_code: Option<i64>,
+    error: Option<&str>,
+) -> Result<bool, Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO webhook_delivery_log(delivery_id, attempted_at, status_code, error)
+        VALUES(?1, ?2, ?3, ?4)
+        "#,
+        delivery_id,
+        now,
+        status_code,
+        error,
+    )
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_webhook_delivery_logs(
+    connection: &mut SqliteConnection,
+    delivery_id: &WebhookDeliveryId,
+) -> Result<Vec<WebhookDeliveryLog>, Error> {
+    Ok(sqlx::query_as!(
+        WebhookDeliveryLog,
+        r#"
+        SELECT
+            id AS "id!",
+            delivery_id AS "delivery_id: _",
+            attempted_at AS "attempted_at: _",
+            status_code,
+            error
+        FROM webhook_delivery_log
+        WHERE delivery_id = ?1
+        ORDER BY attempted_at ASC, id ASC
+        "#,
+        delivery_id,
+    )
+    .fetch_all(&mut *connection)
+    .await?)
+}
@@ -6,19 +6,32 @@ use parking_lot::Mutex;
 use reqwest::{Client, StatusCode};
 
 use crate::biztalk::models::{
-    AlimTalkButtonAttachment, AllimTalkAttachment, GetTokenResponse, SendAlimTalkRequest,
-    SendAlimTalkResponse,
+    AlimTalkButtonAttachment, AlimTalkResult, AllimTalkAttachment, GetResultAllResponse,
+    GetTokenResponse, SendAlimTalkRequest, SendAlimTalkResponse, SendSmsRequest, SendSmsResponse,
 };
 
 const BIZTALK_RESPONSE_OK: &str = "1000";
 
+// Result codes of KakaoTalk for the recipients who can't receive AlimTalk: invalid phone number,
+// undeliverable (e.g. the channel is blocked), not a KakaoTalk user, AlimTalk turned off, and
+// KakaoTalk being too old.
+const RECIPIENT_FAILURE_CODES: &[&str] = &["3008", "3018", "3019", "3020", "3021"];
+
 const BIZTALK_GET_TOKEN_URL: &str = "https://www.biztalk-api.com/v2/auth/getToken";
 const BIZTALK_SEND_ALIMTALK_URL: &str = "https://www.biztalk-api.com/v2/kko/sendAlimTalk";
+const BIZTALK_GET_ALIMTALK_RESULTS_URL: &str = "https://www.biztalk-api.com/v2/kko/getResultAll";
+const BIZTALK_SEND_SMS_URL: &str = "https://www.biztalk-api.com/v2/sms/sendSms";
+const BIZTALK_SEND_LMS_URL: &str = "https://www.biztalk-api.com/v2/lms/sendLms";
+
+// Messages longer than this are sent as LMS, counting non-ASCII characters as two bytes as in
+// EUC-KR.
+const SMS_MAX_BYTES: usize = 90;
 
 pub trait BiztalkConfig {
     fn bs_id(&self) -> &str;
     fn password(&self) -> &str;
     fn sender_key(&self) -> &str;
+    fn sms_sender_number(&self) -> Option<&str>;
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +42,7 @@ pub struct BiztalkClient {
     bs_id: String,
     password: String,
     sender_key: String,
+    sms_sender_number: Option<String>,
 }
 
 impl BiztalkClient {
@@ -40,6 +54,7 @@ impl BiztalkClient {
             bs_id: config.bs_id().to_owned(),
             password: config.password().to_owned(),
             sender_key: config.sender_key().to_owned(),
+            sms_sender_number: config.sms_sender_number().map(ToOwned::to_owned),
         }
     }
 
@@ -62,14 +77,14 @@ impl BiztalkClient {
             *self.token.lock() = Some(token);
             Ok(())
         } else {
-            Err(Error::Biztalk(
+            Err(Error::Authentication(
                 payload.response_code,
                 payload.message.clone().unwrap_or_default(),
             ))
         }
     }
 
-    async fn post(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+    async fn execute(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
         if self.token.lock().is_none() {
             self.refresh_token().await?;
         }
@@ -86,8 +101,11 @@ impl BiztalkClient {
         }
     }
 
+    /// Sends the AlimTalk message, identified by `msg_idx` in the results if given. Being accepted
+    /// doesn't mean it's delivered, which is only known from [`Self::get_alimtalk_results`].
     pub async fn send_alimtalk(
         &self,
+        msg_idx: Option<&str>,
         recipient: &str,
         template_code: &str,
         message: String,
@@ -97,7 +115,9 @@ impl BiztalkClient {
             .client
             .post(BIZTALK_SEND_ALIMTALK_URL)
             .json(&SendAlimTalkRequest {
-                msg_idx: uuid::Uuid::new_v4().to_string(),
+                msg_idx: msg_idx
+                    .map(ToOwned::to_owned)
+                    .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
                 app_user_id: recipient.to_owned(),
                 country_code: "82".to_string(),
                 sender_key: self.sender_key.clone(),
@@ -110,11 +130,11 @@ impl BiztalkClient {
             });
 
         let result = {
-            let result = self.post(request.try_clone().unwrap()).await;
+            let result = self.execute(request.try_clone().unwrap()).await;
             if matches!(result, Err(Error::Unauthorized)) {
                 self.refresh_token().await?;
 
-                self.post(request).await
+                self.execute(request).await
             } else {
                 result
             }
@@ -128,6 +148,101 @@ impl BiztalkClient {
             Err(Error::Biztalk(response.response_code, response.message))
         }
     }
+
+    /// Results of the AlimTalk messages reported since the last call. Each result is only returned
+    /// once.
+    pub async fn get_alimtalk_results(&self) -> Result<Vec<AlimTalkResult>, Error> {
+        let request = self.client.get(BIZTALK_GET_ALIMTALK_RESULTS_URL);
+
+        let result = {
+            let result = self.execute(request.try_clone().unwrap()).await;
+            if matches!(result, Err(Error::Unauthorized)) {
+                self.refresh_token().await?;
+
+                self.execute(request).await
+            } else {
+                result
+            }
+        }?;
+
+        let response: GetResultAllResponse = result.json().await?;
+
+        if response.response_code == BIZTALK_RESPONSE_OK {
+            Ok(response.response)
+        } else {
+            Err(Error::Biztalk(
+                response.response_code,
+                response.message.unwrap_or_default(),
+            ))
+        }
+    }
+
+    pub fn is_sms_enabled(&self) -> bool {
+        self.sms_sender_number.is_some()
+    }
+
+    /// Sends the text message to the phone number, as LMS if it doesn't fit in a SMS.
+    pub async fn send_sms(
+        &self,
+        phone_number: &str,
+        subject: &str,
+        message: String,
+    ) -> Result<(), Error> {
+        let callback = self.sms_sender_number.clone().ok_or(Error::NoSmsSender)?;
+
+        let is_long = message
+            .chars()
+            .map(|v| if v.is_ascii() { 1 } else { 2 })
+            .sum::<usize>()
+            > SMS_MAX_BYTES;
+
+        let request = self
+            .client
+            .post(if is_long {
+                BIZTALK_SEND_LMS_URL
+            } else {
+                BIZTALK_SEND_SMS_URL
+            })
+            .json(&SendSmsRequest {
+                msg_idx: uuid::Uuid::new_v4().to_string(),
+                country_code: "82".to_string(),
+                recipient: phone_number.to_owned(),
+                callback,
+                subject: is_long.then(|| subject.to_owned()),
+                message,
+            });
+
+        let result = {
+            let result = self.execute(request.try_clone().unwrap()).await;
+            if matches!(result, Err(Error::Unauthorized)) {
+                self.refresh_token().await?;
+
+                self.execute(request).await
+            } else {
+                result
+            }
+        }?;
+
+        let response: SendSmsResponse = result.json().await?;
+
+        if response.response_code == BIZTALK_RESPONSE_OK {
+            Ok(())
+        } else {
+            Err(Error::Biztalk(response.response_code, response.message))
+        }
+    }
+}
+
+/// Whether the code means the recipient can't be reached through AlimTalk, so it's no use trying
+/// again.
+pub fn is_recipient_failure(code: &str) -> bool {
+    RECIPIENT_FAILURE_CODES.contains(&code)
+}
+
+impl AlimTalkResult {
+    pub fn is_recipient_failure(&self) -> bool {
+        is_recipient_failure(&self.result_code)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -140,6 +255,10 @@ pub enum Error {
     NoToken,
     #[error("Error from Biztalk: {1}")]
     Biztalk(String, String),
+    #[error("Error issuing Biztalk token: {1}")]
+    Authentication(String, String),
     #[error("Unauthorized")]
     Unauthorized,
+    #[error("Sender number for SMS is not configured.")]
+    NoSmsSender,
 }
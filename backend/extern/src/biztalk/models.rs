@@ -25,14 +25,14 @@ pub(super) enum SendMethod {
     Push,
 }
 
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, Serialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ButtonType {
     #[default]
     Wl,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AlimTalkButtonAttachment {
     pub name: String,
     pub r#type: ButtonType,
@@ -67,3 +67,42 @@ pub(super) struct SendAlimTalkResponse {
     #[serde(rename = "msg")]
     pub message: String,
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SendSmsRequest {
+    pub msg_idx: String,
+    pub country_code: String,
+    pub recipient: String,
+    pub callback: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SendSmsResponse {
+    pub response_code: String,
+    #[serde(rename = "msg")]
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GetResultAllResponse {
+    pub response_code: String,
+    #[serde(default)]
+    pub response: Vec<AlimTalkResult>,
+    #[serde(rename = "msg")]
+    pub message: Option<String>,
+}
+
+/// Outcome of the AlimTalk message accepted earlier, reported once KakaoTalk has tried to deliver
+/// it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlimTalkResult {
+    pub msg_idx: String,
+    pub result_code: String,
+}
@@ -15,6 +15,8 @@ pub enum AccountPropertyKey {
     Birthday,
     #[serde(rename = "kakao_account.gender")]
     Gender,
+    #[serde(rename = "kakao_account.phone_number")]
+    PhoneNumber,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
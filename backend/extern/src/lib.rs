@@ -10,5 +10,11 @@ pub mod itsokey;
 pub mod kakao;
 #[cfg(feature = "ntfy")]
 pub mod ntfy;
+#[cfg(feature = "oidc")]
+pub mod oidc;
+#[cfg(feature = "smtp")]
+pub mod smtp;
 #[cfg(feature = "toss-payments")]
 pub mod toss_payments;
+#[cfg(feature = "webhook")]
+pub mod webhook;
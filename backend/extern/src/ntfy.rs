@@ -1,4 +1,7 @@
 use reqwest::Client;
+use reqwest::header::HeaderValue;
+
+const DEFAULT_BASE_URL: &str = "https://ntfy.sh";
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Channel {
@@ -7,16 +10,45 @@ pub enum Channel {
     Minor,
 }
 
+impl Channel {
+    fn priority(&self) -> &'static str {
+        match self {
+            Self::Important => "high",
+            Self::General => "default",
+            Self::Minor => "low",
+        }
+    }
+
+    fn tags(&self) -> &'static str {
+        match self {
+            Self::Important => "rotating_light",
+            Self::General => "bell",
+            Self::Minor => "memo",
+        }
+    }
+}
+
 pub trait NtfyConfig {
+    /// Address of the ntfy server, or `https://ntfy.sh` if not given.
+    fn base_url(&self) -> Option<&url::Url>;
+
     fn access_token(&self) -> Option<&str>;
 
     fn channel(&self, channel: Channel) -> &str;
+
+    fn title(&self) -> Option<&str>;
+
+    /// URL opened when the notification is clicked.
+    fn click_url(&self) -> Option<&url::Url>;
 }
 
 #[derive(Clone, Debug)]
 pub struct NtfyClient {
     client: reqwest::Client,
+    base_url: String,
     token: Option<String>,
+    title: Option<String>,
+    click_url: Option<String>,
 
     channel_important: String,
     channel_general: String,
@@ -27,7 +59,13 @@ impl NtfyClient {
     pub fn new(config: &impl NtfyConfig) -> Self {
         Self {
             client: Client::new(),
+            base_url: config
+                .base_url()
+                .map(|v| v.as_str().trim_end_matches('/').to_owned())
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_owned()),
             token: config.access_token().map(ToOwned::to_owned),
+            title: config.title().map(ToOwned::to_owned),
+            click_url: config.click_url().map(ToString::to_string),
 
             channel_important: config.channel(Channel::Important).to_owned(),
             channel_general: config.channel(Channel::General).to_owned(),
@@ -36,7 +74,7 @@ impl NtfyClient {
     }
 
     pub async fn send(&self, channel: Channel, message: String) -> Result<(), Error> {
-        let channel = match channel {
+        let topic = match channel {
             Channel::Important => &self.channel_important,
             Channel::General => &self.channel_general,
             Channel::Minor => &self.channel_minor,
@@ -44,14 +82,26 @@ impl NtfyClient {
 
         let mut request = self
             .client
-            .post(format!("https://ntfy.sh/{channel}"))
+            .post(format!("{}/{topic}", self.base_url))
+            .header("Priority", channel.priority())
+            .header("Tags", channel.tags())
             .body(message);
 
         if let Some(token) = &self.token {
             request = request.header("Authorization", format!("Bearer {token}"));
         }
+        if let Some(title) = &self.title {
+            // Titles are sent as raw UTF-8 as ntfy accepts them, which `&str` headers don't allow.
+            request = request.header("Title", HeaderValue::from_bytes(title.as_bytes())?);
+        }
+        if let Some(click_url) = &self.click_url {
+            request = request.header("Click", click_url);
+        }
 
-        self.client.execute(request.build()?).await?;
+        self.client
+            .execute(request.build()?)
+            .await?
+            .error_for_status()?;
 
         Ok(())
     }
@@ -61,4 +111,6 @@ impl NtfyClient {
 pub enum Error {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+    #[error("Invalid header value: {0}")]
+    InvalidHeader(#[from] reqwest::header::InvalidHeaderValue),
 }
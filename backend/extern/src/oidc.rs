@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+pub trait OidcProviderConfig {
+    fn client_id(&self) -> &str;
+    fn client_secret(&self) -> &str;
+    fn authorization_endpoint(&self) -> &url::Url;
+    fn token_endpoint(&self) -> &url::Url;
+    fn userinfo_endpoint(&self) -> &url::Url;
+    fn scopes(&self) -> &[String];
+    fn claims(&self) -> &ClaimMapping;
+}
+
+pub trait BearerToken {
+    fn access_token(&self) -> &str;
+}
+
+/// Locations of the user attributes in the userinfo response.
+///
+/// Defaults follow the OpenID Connect standard claims. Providers that only speak OAuth2
+/// (e.g. Naver) can point these at their own fields, optionally nested under `root`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ClaimMapping {
+    pub root: Option<String>,
+    pub subject: String,
+    pub name: String,
+    pub email: String,
+    pub phone_number: String,
+}
+
+impl Default for ClaimMapping {
+    fn default() -> Self {
+        Self {
+            root: None,
+            subject: String::from("sub"),
+            name: String::from("name"),
+            email: String::from("email"),
+            phone_number: String::from("phone_number"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    OAuth(#[from] OAuthError),
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Claim `{0}` is missing from the userinfo response")]
+    MissingClaim(String),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthError {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+impl std::error::Error for OAuthError {}
+
+impl std::fmt::Display for OAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.error_description.as_deref().unwrap_or(&self.error)
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Response<T> {
+    Error(OAuthError),
+    Response(T),
+}
+
+impl<T> From<Response<T>> for Result<T, Error> {
+    fn from(value: Response<T>) -> Self {
+        match value {
+            Response::Response(value) => Ok(value),
+            Response::Error(e) => Err(e.into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OAuthTokenResponse {
+    pub token_type: String,
+    pub access_token: String,
+    pub id_token: Option<String>,
+    pub expires_in: Option<i64>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+}
+
+impl BearerToken for OAuthTokenResponse {
+    fn access_token(&self) -> &str {
+        &self.access_token
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UserInfo {
+    pub subject: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+}
+
+/// Authorization endpoint to send the user to, with the `S256` PKCE challenge of the verifier
+/// later passed to [`get_oauth_token`].
+pub fn authorization_url(
+    config: &impl OidcProviderConfig,
+    redirect_url: &str,
+    state: &str,
+    code_challenge: &str,
+) -> url::Url {
+    let mut url = config.authorization_endpoint().clone();
+
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", config.client_id())
+        .append_pair("redirect_uri", redirect_url)
+        .append_pair("scope", &config.scopes().join(" "))
+        .append_pair("state", state)
+        .append_pair("code_challenge", code_challenge)
+        .append_pair("code_challenge_method", "S256");
+
+    url
+}
+
+pub async fn get_oauth_token(
+    config: &impl OidcProviderConfig,
+    code: &str,
+    redirect_url: &str,
+    code_verifier: &str,
+) -> Result<OAuthTokenResponse, Error> {
+    let form = HashMap::from([
+        ("grant_type", "authorization_code"),
+        ("client_id", config.client_id()),
+        ("client_secret", config.client_secret()),
+        ("redirect_uri", redirect_url),
+        ("code", code),
+        ("code_verifier", code_verifier),
+    ]);
+
+    reqwest::Client::new()
+        .post(config.token_endpoint().clone())
+        .form(&form)
+        .send()
+        .await?
+        .json::<Response<OAuthTokenResponse>>()
+        .await?
+        .into()
+}
+
+pub async fn get_user_info(
+    config: &impl OidcProviderConfig,
+    bearer_token: &impl BearerToken,
+) -> Result<UserInfo, Error> {
+    let response: Result<serde_json::Value, Error> = reqwest::Client::new()
+        .get(config.userinfo_endpoint().clone())
+        .bearer_auth(bearer_token.access_token())
+        .send()
+        .await?
+        .json::<Response<serde_json::Value>>()
+        .await?
+        .into();
+    let response = response?;
+
+    let claims = config.claims();
+    let root = match &claims.root {
+        Some(root) => response
+            .get(root)
+            .ok_or_else(|| Error::MissingClaim(root.clone()))?,
+        None => &response,
+    };
+
+    let claim = |key: &str| match root.get(key)? {
+        serde_json::Value::String(v) => Some(v.clone()),
+        serde_json::Value::Number(v) => Some(v.to_string()),
+        _ => None,
+    };
+
+    Ok(UserInfo {
+        subject: claim(&claims.subject)
+            .ok_or_else(|| Error::MissingClaim(claims.subject.clone()))?,
+        name: claim(&claims.name),
+        email: claim(&claims.email),
+        phone_number: claim(&claims.phone_number),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::{
+        ClaimMapping, OAuthTokenResponse, OidcProviderConfig, get_oauth_token, get_user_info,
+    };
+
+    struct LocalOidcConfig {
+        authorization_endpoint: url::Url,
+        token_endpoint: url::Url,
+        userinfo_endpoint: url::Url,
+        scopes: Vec<String>,
+        claims: ClaimMapping,
+    }
+
+    impl LocalOidcConfig {
+        fn new(port: u16, claims: ClaimMapping) -> Self {
+            let url =
+                |path: &str| url::Url::parse(&format!("http://127.0.0.1:{port}{path}")).unwrap();
+
+            Self {
+                authorization_endpoint: url("/authorize"),
+                token_endpoint: url("/token"),
+                userinfo_endpoint: url("/userinfo"),
+                scopes: vec![String::from("openid")],
+                claims,
+            }
+        }
+    }
+
+    impl OidcProviderConfig for LocalOidcConfig {
+        fn client_id(&self) -> &str {
+            "client"
+        }
+
+        fn client_secret(&self) -> &str {
+            "secret"
+        }
+
+        fn authorization_endpoint(&self) -> &url::Url {
+            &self.authorization_endpoint
+        }
+
+        fn token_endpoint(&self) -> &url::Url {
+            &self.token_endpoint
+        }
+
+        fn userinfo_endpoint(&self) -> &url::Url {
+            &self.userinfo_endpoint
+        }
+
+        fn scopes(&self) -> &[String] {
+            &self.scopes
+        }
+
+        fn claims(&self) -> &ClaimMapping {
+            &self.claims
+        }
+    }
+
+    /// Answers one HTTP request per response in order and returns the requests it received,
+    /// each as its head followed by its body.
+    async fn serve(listener: TcpListener, responses: Vec<&'static str>) -> Vec<String> {
+        let mut received = vec![];
+
+        for response in responses {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut request = String::new();
+            let mut content_length = 0;
+
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+            request.push_str(&String::from_utf8(body).unwrap());
+            received.push(request);
+
+            writer
+                .write_all(
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                        response.len()
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+        }
+
+        received
+    }
+
+    #[tokio::test]
+    async fn exchanges_code_and_maps_nested_claims() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = LocalOidcConfig::new(
+            listener.local_addr().unwrap().port(),
+            ClaimMapping {
+                root: Some(String::from("response")),
+                subject: String::from("id"),
+                name: String::from("name"),
+                email: String::from("email"),
+                phone_number: String::from("mobile"),
+            },
+        );
+        let server = tokio::spawn(serve(
+            listener,
+            vec![
+                r#"{"token_type":"bearer","access_token":"token","expires_in":3600}"#,
+                r#"{"resultcode":"00","message":"success","response":{"id":"naver-1","name":"Customer","mobile":"010-1234-5678"}}"#,
+            ],
+        ));
+
+        let token = get_oauth_token(&config, "code", "https://example.com/callback", "verifier")
+            .await
+            .unwrap();
+        let user_info = get_user_info(&config, &token).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(token.access_token, "token");
+        assert!(received[0].starts_with("POST /token "));
+        for field in [
+            "grant_type=authorization_code",
+            "client_id=client",
+            "client_secret=secret",
+            "code=code",
+            "code_verifier=verifier",
+        ] {
+            assert!(received[0].contains(field));
+        }
+        assert!(received[1].starts_with("GET /userinfo "));
+        assert!(
+            received[1]
+                .to_lowercase()
+                .contains("authorization: bearer token")
+        );
+
+        assert_eq!(user_info.subject, "naver-1");
+        assert_eq!(user_info.name.as_deref(), Some("Customer"));
+        assert_eq!(user_info.email, None);
+        assert_eq!(user_info.phone_number.as_deref(), Some("010-1234-5678"));
+    }
+
+    #[tokio::test]
+    async fn maps_standard_claims() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = LocalOidcConfig::new(
+            listener.local_addr().unwrap().port(),
+            ClaimMapping::default(),
+        );
+        let server = tokio::spawn(serve(
+            listener,
+            vec![r#"{"sub":1234,"name":"Customer","email":"customer@example.com"}"#],
+        ));
+
+        let token: OAuthTokenResponse =
+            serde_json::from_str(r#"{"token_type":"Bearer","access_token":"token"}"#).unwrap();
+        let user_info = get_user_info(&config, &token).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(user_info.subject, "1234");
+        assert_eq!(user_info.email.as_deref(), Some("customer@example.com"));
+    }
+
+    #[tokio::test]
+    async fn returns_oauth_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = LocalOidcConfig::new(
+            listener.local_addr().unwrap().port(),
+            ClaimMapping::default(),
+        );
+        let server = tokio::spawn(serve(
+            listener,
+            vec![r#"{"error":"invalid_grant","error_description":"Code has expired"}"#],
+        ));
+
+        let result =
+            get_oauth_token(&config, "code", "https://example.com/callback", "verifier").await;
+        server.await.unwrap();
+
+        assert_eq!(result.unwrap_err().to_string(), "Code has expired");
+    }
+}
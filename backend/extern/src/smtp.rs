@@ -0,0 +1,201 @@
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Deserialize;
+
+#[derive(Debug, Copy, Clone, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection, for local SMTP servers used in development.
+    None,
+    #[default]
+    StartTls,
+    Tls,
+}
+
+pub trait SmtpConfig {
+    fn host(&self) -> &str;
+    fn port(&self) -> Option<u16>;
+    fn security(&self) -> SmtpSecurity;
+    fn username(&self) -> Option<&str>;
+    fn password(&self) -> Option<&str>;
+    fn sender(&self) -> &str;
+}
+
+#[derive(Clone)]
+pub struct SmtpClient {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    sender: Mailbox,
+}
+
+impl SmtpClient {
+    pub fn new(config: &impl SmtpConfig) -> Result<Self, Error> {
+        let mut builder = match config.security() {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(config.host())
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(config.host())?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(config.host())?,
+        };
+
+        if let Some(port) = config.port() {
+            builder = builder.port(port);
+        }
+        if let Some(username) = config.username() {
+            builder = builder.credentials(Credentials::new(
+                username.to_owned(),
+                config.password().unwrap_or_default().to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            sender: config.sender().parse()?,
+        })
+    }
+
+    pub async fn send(
+        &self,
+        recipient: &str,
+        subject: &str,
+        text: String,
+        html: String,
+    ) -> Result<(), Error> {
+        let message = Message::builder()
+            .from(self.sender.clone())
+            .to(recipient.parse()?)
+            .subject(subject)
+            .multipart(MultiPart::alternative_plain_html(text, html))?;
+
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
+
+pub fn is_valid_address(address: &str) -> bool {
+    address.parse::<Address>().is_ok()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+    #[error("Could not build message: {0}")]
+    Message(#[from] lettre::error::Error),
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use super::{SmtpClient, SmtpConfig, SmtpSecurity};
+
+    struct LocalSmtpConfig {
+        port: u16,
+    }
+
+    impl SmtpConfig for LocalSmtpConfig {
+        fn host(&self) -> &str {
+            "127.0.0.1"
+        }
+
+        fn port(&self) -> Option<u16> {
+            Some(self.port)
+        }
+
+        fn security(&self) -> SmtpSecurity {
+            SmtpSecurity::None
+        }
+
+        fn username(&self) -> Option<&str> {
+            None
+        }
+
+        fn password(&self) -> Option<&str> {
+            None
+        }
+
+        fn sender(&self) -> &str {
+            "Dreamhouse <noreply@example.com>"
+        }
+    }
+
+    /// Accepts a single SMTP session and returns the commands and the message it received.
+    async fn receive_message(listener: TcpListener) -> Vec<String> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut received = vec![];
+        let mut in_data = false;
+
+        writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+        while let Some(line) = lines.next_line().await.unwrap() {
+            received.push(line.clone());
+
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 OK\r\n"
+            } else if line.starts_with("EHLO") {
+                b"250 localhost\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 End data with <CR><LF>.<CR><LF>\r\n"
+            } else if line == "QUIT" {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                b"250 OK\r\n"
+            };
+
+            writer.write_all(reply).await.unwrap();
+        }
+
+        received
+    }
+
+    #[tokio::test]
+    async fn sends_message_over_smtp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = LocalSmtpConfig {
+            port: listener.local_addr().unwrap().port(),
+        };
+        let server = tokio::spawn(receive_message(listener));
+
+        let client = SmtpClient::new(&config).unwrap();
+        client
+            .send(
+                "customer@example.com",
+                "Booking confirmed",
+                "Plain text body".to_owned(),
+                "<p>HTML body</p>".to_owned(),
+            )
+            .await
+            .unwrap();
+        drop(client);
+
+        let received = server.await.unwrap();
+
+        assert!(received.contains(&"MAIL FROM:<noreply@example.com>".to_owned()));
+        assert!(received.contains(&"RCPT TO:<customer@example.com>".to_owned()));
+        assert!(received.contains(&"Subject: Booking confirmed".to_owned()));
+        assert!(received.iter().any(|v| v.contains("Plain text body")));
+        assert!(received.iter().any(|v| v.contains("<p>HTML body</p>")));
+    }
+
+    #[test]
+    fn validates_addresses() {
+        assert!(super::is_valid_address("customer@example.com"));
+        assert!(!super::is_valid_address("customer"));
+        assert!(!super::is_valid_address("customer@"));
+    }
+}
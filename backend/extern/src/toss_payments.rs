@@ -8,6 +8,7 @@ use reqwest::StatusCode;
 const TOSS_PAYMENTS_CONFIRM_URL: &str = "https://api.tosspayments.com/v1/payments/confirm";
 const TOSS_PAYMENTS_CANCEL_URL: &str =
     "https://api.tosspayments.com/v1/payments/{payment_key}/cancel";
+const TOSS_PAYMENTS_ORDER_URL: &str = "https://api.tosspayments.com/v1/payments/orders/{order_id}";
 
 pub trait TossPaymentsConfig {
     fn server_secret_key(&self) -> &str;
@@ -59,11 +60,36 @@ impl TossPaymentsClient {
         }
     }
 
+    pub async fn get_payment_by_order_id<T: Display>(
+        &self,
+        order_id: &T,
+    ) -> Result<types::Payment, Error> {
+        let url = TOSS_PAYMENTS_ORDER_URL.replace("{order_id}", &format!("{order_id}"));
+
+        let response = self
+            .client
+            .get(url)
+            .basic_auth::<String, String>(self.server_secret_key.clone(), None)
+            .send()
+            .await?;
+
+        let status = response.status();
+
+        if status == StatusCode::OK {
+            Ok(response.json().await?)
+        } else if let Ok(v) = response.json::<types::ErrorV1>().await {
+            Err(v.into())
+        } else {
+            Err(Error::RemoteStatus(status))
+        }
+    }
+
     pub async fn cancel_payment(
         &self,
         payment_key: &str,
         cancel_reason: &str,
         cancel_amount: Option<i64>,
+        refund_receive_account: Option<&types::RefundReceiveAccount>,
     ) -> Result<types::Payment, Error> {
         let url = TOSS_PAYMENTS_CANCEL_URL.replace("{payment_key}", payment_key);
 
@@ -76,7 +102,7 @@ impl TossPaymentsClient {
             } else {
                 None
             },
-            refund_receive_account: None,
+            refund_receive_account: refund_receive_account.cloned(),
         };
 
         let response = self
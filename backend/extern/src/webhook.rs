@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "X-Dxe-Signature-256";
+pub const TIMESTAMP_HEADER: &str = "X-Dxe-Timestamp";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    High,
+    Default,
+    Low,
+}
+
+/// Shape of the request body.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// `{"priority", "title", "message", "sent_at"}`
+    #[default]
+    Generic,
+    /// Incoming webhook of Slack, `{"text"}`.
+    Slack,
+    /// Webhook of Discord, `{"content"}`.
+    Discord,
+}
+
+pub trait WebhookConfig {
+    fn url(&self) -> &url::Url;
+
+    fn format(&self) -> PayloadFormat;
+
+    fn title(&self) -> Option<&str>;
+
+    /// Key to sign the body with, sent in `X-Dxe-Signature-256` as in [`sign`] along with the
+    /// `X-Dxe-Timestamp` it covers.
+    fn secret(&self) -> Option<&str>;
+}
+
+#[derive(Serialize)]
+struct GenericPayload<'a> {
+    priority: Priority,
+    title: Option<&'a str>,
+    message: &'a str,
+    sent_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct SlackPayload {
+    text: String,
+}
+
+#[derive(Serialize)]
+struct DiscordPayload {
+    content: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct WebhookClient {
+    client: Client,
+    url: url::Url,
+    format: PayloadFormat,
+    title: Option<String>,
+    secret: Option<String>,
+}
+
+impl WebhookClient {
+    pub fn new(config: &impl WebhookConfig) -> Self {
+        Self {
+            client: Client::new(),
+            url: config.url().clone(),
+            format: config.format(),
+            title: config.title().map(ToOwned::to_owned),
+            secret: config.secret().map(ToOwned::to_owned),
+        }
+    }
+
+    fn text(&self, priority: Priority, message: &str, bold: &str) -> String {
+        let mut text = String::new();
+        if priority == Priority::High {
+            text.push_str("🚨 ");
+        }
+        if let Some(title) = &self.title {
+            text.push_str(&format!("{bold}{title}{bold}\n"));
+        }
+        text.push_str(message);
+        text
+    }
+
+    fn body(&self, priority: Priority, message: &str) -> Result<Vec<u8>, Error> {
+        Ok(match self.format {
+            PayloadFormat::Generic => serde_json::to_vec(&GenericPayload {
+                priority,
+                title: self.title.as_deref(),
+                message,
+                sent_at: Utc::now(),
+            })?,
+            PayloadFormat::Slack => serde_json::to_vec(&SlackPayload {
+                text: self.text(priority, message, "*"),
+            })?,
+            PayloadFormat::Discord => serde_json::to_vec(&DiscordPayload {
+                content: self.text(priority, message, "**"),
+            })?,
+        })
+    }
+
+    pub async fn send(&self, priority: Priority, message: String) -> Result<(), Error> {
+        let body = self.body(priority, &message)?;
+
+        let status = post(
+            &self.client,
+            &self.url,
+            self.secret.as_deref(),
+            vec![],
+            body,
+        )
+        .await?;
+
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(Error::Status(status))
+        }
+    }
+}
+
+/// `sha256=<hex digest>` of `<timestamp>.<body>`, signed with HMAC-SHA256. Receivers should
+/// reject stale timestamps so that a captured delivery cannot be replayed.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+
+    let digest = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|v| format!("{v:02x}"))
+        .collect::<String>();
+
+    format!("sha256={digest}")
+}
+
+/// Posts the JSON body with its timestamp and signature if the secret is given, returning the
+/// status of the response.
+pub async fn post(
+    client: &Client,
+    url: &url::Url,
+    secret: Option<&str>,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+) -> Result<StatusCode, Error> {
+    let mut request = client
+        .post(url.clone())
+        .header("Content-Type", "application/json");
+
+    if let Some(secret) = secret {
+        let timestamp = Utc::now().timestamp();
+        request = request
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(secret, timestamp, &body));
+    }
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = client.execute(request.body(body).build()?).await?;
+
+    Ok(response.status())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Unexpected status: {0}")]
+    Status(StatusCode),
+    #[error("Could not serialize payload: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        let body = br#"{"event":"booking.canceled"}"#;
+
+        assert_eq!(
+            sign("secret", 1700000000, body),
+            "sha256=a37075cd3abbdfd2914ce9455c2df34dab81f7b4b9a4cf524150d7ecd83e937b"
+        );
+        assert_ne!(
+            sign("secret", 1700000000, body),
+            sign("secret", 1700000001, body)
+        );
+    }
+}
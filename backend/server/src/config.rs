@@ -1,10 +1,22 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    path::PathBuf,
+};
 
-use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
-use dxe_types::{SpaceId, UnitId};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Timelike, Utc, Weekday};
+use dxe_types::{IdentityProvider, SpaceId, UnitId};
 use serde::Deserialize;
 use serde_with::{DisplayFromStr, serde_as};
 
+use crate::services::messaging::templates::MessageTemplate;
+use crate::services::notification::Priority;
+use crate::utils::datetime::truncate_time_to_slot;
+
+mod pricing;
+
+pub use pricing::{PriceBreakdown, PriceItem, PricingRules};
+
 #[derive(Deserialize, Debug)]
 pub struct DatabaseConfig {
     pub url: url::Url,
@@ -26,9 +38,116 @@ impl dxe_extern::kakao::KakaoRestApiConfig for KakaoAuthConfig {
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct OidcProviderConfig {
+    /// Shown on the login page, e.g. "Google".
+    pub display_name: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub authorization_endpoint: url::Url,
+    pub token_endpoint: url::Url,
+    pub userinfo_endpoint: url::Url,
+    #[serde(default = "OidcProviderConfig::default_scopes")]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub claims: dxe_extern::oidc::ClaimMapping,
+}
+
+impl OidcProviderConfig {
+    fn default_scopes() -> Vec<String> {
+        vec![
+            String::from("openid"),
+            String::from("profile"),
+            String::from("email"),
+        ]
+    }
+}
+
+impl dxe_extern::oidc::OidcProviderConfig for OidcProviderConfig {
+    fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    fn client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    fn authorization_endpoint(&self) -> &url::Url {
+        &self.authorization_endpoint
+    }
+
+    fn token_endpoint(&self) -> &url::Url {
+        &self.token_endpoint
+    }
+
+    fn userinfo_endpoint(&self) -> &url::Url {
+        &self.userinfo_endpoint
+    }
+
+    fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    fn claims(&self) -> &dxe_extern::oidc::ClaimMapping {
+        &self.claims
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct OidcProvidersConfig(pub HashMap<IdentityProvider, OidcProviderConfig>);
+
+impl OidcProvidersConfig {
+    pub fn validate(&self) -> Result<(), Error> {
+        for provider in self.0.keys() {
+            if matches!(provider, IdentityProvider::Kakao | IdentityProvider::Handle) {
+                return Err(Error::InvalidOidcProvider(
+                    *provider,
+                    "provider is signed in without OIDC",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct AuthConfig {
     pub kakao: KakaoAuthConfig,
+    /// Generic OAuth2/OpenID Connect providers, keyed by the identity provider they sign users in as.
+    #[serde(default)]
+    pub oidc: OidcProvidersConfig,
+    #[serde(default)]
+    pub credential: CredentialConfig,
+}
+
+/// Self-service handle/password accounts.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct CredentialConfig {
+    pub password_reset_expires_minutes: i64,
+    /// Attempts allowed per handle, user or client address within the window, for each of
+    /// login, signup, password change and password reset. Logins and reset requests count per
+    /// handle and client address together, so that others can't lock the owner out of the account.
+    pub max_attempts: i64,
+    /// Logins allowed per client address within the window, across all handles.
+    pub max_login_attempts_per_address: i64,
+    pub attempt_window_minutes: i64,
+    /// Reverse proxies whose `X-Forwarded-For` is trusted. The address of the peer is used as the
+    /// client address otherwise.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for CredentialConfig {
+    fn default() -> Self {
+        Self {
+            password_reset_expires_minutes: 30,
+            max_attempts: 5,
+            max_login_attempts_per_address: 20,
+            attempt_window_minutes: 15,
+            trusted_proxies: Vec::new(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -59,25 +178,200 @@ impl TimeZoneConfig {
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct OpeningHours {
+    pub weekday: Weekday,
+    pub open: NaiveTime,
+    /// Closing time on or before the opening time means the unit closes on the next day.
+    pub close: NaiveTime,
+}
+
+impl OpeningHours {
+    /// Start (in minutes since Monday 00:00 UTC) and length of the weekly opening window.
+    pub fn to_utc_window(&self, timezone_config: &TimeZoneConfig) -> (i64, i64) {
+        let open_minute = (self.open.num_seconds_from_midnight() / 60) as i64;
+        let close_minute = (self.close.num_seconds_from_midnight() / 60) as i64;
+
+        let mut duration = close_minute - open_minute;
+        if duration <= 0 {
+            duration += 24 * 60;
+        }
+
+        let local_start = self.weekday.num_days_from_monday() as i64 * 24 * 60 + open_minute;
+        let offset = (timezone_config.timezone.local_minus_utc() / 60) as i64;
+
+        (local_start - offset, duration)
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct UnitBookingConfig {
     pub base_price: i64,
     pub price_per_hour: i64,
+    pub slot_minutes: Option<i64>,
+    pub min_booking_minutes: Option<i64>,
+    pub max_booking_minutes: Option<i64>,
+    #[serde(default)]
+    pub opening_hours: Vec<OpeningHours>,
+    #[serde(default)]
+    pub pricing: PricingRules,
 }
 
 impl UnitBookingConfig {
-    fn calculate_price(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
-        let delta = to - from;
-        let hours = delta.num_hours();
+    const DEFAULT_SLOT_MINUTES: i64 = 60;
+
+    pub fn slot_size(&self) -> TimeDelta {
+        // Guards against a zero slot, which would never advance when stepping through a booking
+        TimeDelta::minutes(
+            self.slot_minutes
+                .unwrap_or(Self::DEFAULT_SLOT_MINUTES)
+                .max(1),
+        )
+    }
+
+    pub fn min_length(&self) -> TimeDelta {
+        self.min_booking_minutes
+            .map(TimeDelta::minutes)
+            .unwrap_or_else(|| self.slot_size())
+    }
+}
 
-        self.base_price + self.price_per_hour * hours
+#[derive(Clone, Deserialize, Debug)]
+pub struct BookingSeriesConfig {
+    pub max_occurrences: i64,
+}
+
+impl Default for BookingSeriesConfig {
+    fn default() -> Self {
+        Self {
+            max_occurrences: 12,
+        }
     }
+}
 
-    fn calculate_additive_price(&self, num_hours: i64) -> i64 {
-        self.price_per_hour * num_hours
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct WaitlistConfig {
+    pub hold_minutes: i64,
+    /// How often lapsed priority holds are offered to the next waiter.
+    pub check_interval_minutes: i64,
+}
+
+impl Default for WaitlistConfig {
+    fn default() -> Self {
+        Self {
+            hold_minutes: 30,
+            check_interval_minutes: 1,
+        }
     }
 }
 
+/// Deadline for paying cash bookings, whichever of the two limits comes first. Unpaid bookings
+/// are canceled once it passes. Disabled unless either limit is set.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct PaymentDeadlineConfig {
+    pub hours_after_creation: Option<i64>,
+    pub hours_before_start: Option<i64>,
+    /// Minimum time given to pay, for bookings made shortly before they start.
+    pub grace_minutes: i64,
+    pub warning_hours: i64,
+    pub check_interval_minutes: i64,
+}
+
+impl Default for PaymentDeadlineConfig {
+    fn default() -> Self {
+        Self {
+            hours_after_creation: None,
+            hours_before_start: None,
+            grace_minutes: 30,
+            warning_hours: 3,
+            check_interval_minutes: 5,
+        }
+    }
+}
+
+impl PaymentDeadlineConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.hours_after_creation.is_some() || self.hours_before_start.is_some()
+    }
+
+    pub fn deadline(
+        &self,
+        created_at: DateTime<Utc>,
+        time_from: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let after_creation = self
+            .hours_after_creation
+            .map(|v| created_at + TimeDelta::hours(v));
+        let before_start = self
+            .hours_before_start
+            .map(|v| time_from - TimeDelta::hours(v));
+
+        let deadline = match (after_creation, before_start) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b)?,
+        };
+
+        Some(deadline.max(created_at + TimeDelta::minutes(self.grace_minutes)))
+    }
+}
+
+/// Public availability feed shown to customers who have not signed in.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct AvailabilityConfig {
+    pub horizon_days: i64,
+    pub cache_seconds: i64,
+}
+
+impl Default for AvailabilityConfig {
+    fn default() -> Self {
+        Self {
+            horizon_days: 14,
+            cache_seconds: 60,
+        }
+    }
+}
+
+/// Column mapping of a bank's transaction history export. Columns are looked up by their header.
+#[derive(Clone, Deserialize, Debug)]
+pub struct BankStatementFormat {
+    /// Rows preceding the header, such as the account summary some banks put on top.
+    #[serde(default)]
+    pub skip_rows: usize,
+    pub date_column: String,
+    /// `chrono` format of the date column, with or without the time part.
+    pub date_format: String,
+    pub depositor_column: String,
+    pub amount_column: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct BankStatementConfig {
+    pub formats: HashMap<String, BankStatementFormat>,
+    pub match_window_days: i64,
+}
+
+impl Default for BankStatementConfig {
+    fn default() -> Self {
+        Self {
+            formats: HashMap::new(),
+            match_window_days: 3,
+        }
+    }
+}
+
+/// Prepaid bundle of booking hours on sale, usable on any unit.
+#[derive(Clone, Deserialize, Debug)]
+pub struct HourPackOffer {
+    pub id: String,
+    pub name: String,
+    pub hours: i64,
+    pub price: i64,
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct BookingConfig {
     pub lookahead_days: i64,
@@ -85,6 +379,18 @@ pub struct BookingConfig {
     pub buffer_time: (TimeDelta, TimeDelta),
     pub refund_rates: Vec<(i64, i64)>,
     pub units: HashMap<UnitId, UnitBookingConfig>,
+    #[serde(default)]
+    pub series: BookingSeriesConfig,
+    #[serde(default)]
+    pub waitlist: WaitlistConfig,
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+    #[serde(default)]
+    pub hour_packs: Vec<HourPackOffer>,
+    #[serde(default)]
+    pub payment_deadline: PaymentDeadlineConfig,
+    #[serde(default)]
+    pub availability: AvailabilityConfig,
 }
 
 impl BookingConfig {
@@ -92,6 +398,55 @@ impl BookingConfig {
         self.refund_rates.sort_by(|a, b| b.0.cmp(&a.0));
     }
 
+    pub fn validate(&self) -> Result<(), Error> {
+        for (unit_id, unit_booking_config) in self.units.iter() {
+            if unit_booking_config.slot_minutes.is_some_and(|v| v <= 0) {
+                return Err(Error::InvalidUnitBookingConfig(
+                    unit_id.clone(),
+                    "slot_minutes must be positive",
+                ));
+            }
+
+            let min_length = unit_booking_config.min_length();
+            let max_length = self.max_booking_length(unit_id).unwrap_or_default();
+            if min_length <= TimeDelta::zero() || max_length < min_length {
+                return Err(Error::InvalidUnitBookingConfig(
+                    unit_id.clone(),
+                    "booking length limits must be positive and in order",
+                ));
+            }
+        }
+
+        let mut offer_ids = HashSet::new();
+        for offer in self.hour_packs.iter() {
+            if offer.hours <= 0 || offer.price <= 0 {
+                return Err(Error::InvalidHourPackOffer(
+                    offer.id.clone(),
+                    "hours and price must be positive",
+                ));
+            }
+            if !offer_ids.insert(offer.id.as_str()) {
+                return Err(Error::InvalidHourPackOffer(
+                    offer.id.clone(),
+                    "id must be unique",
+                ));
+            }
+        }
+
+        if self.payment_deadline.check_interval_minutes <= 0 {
+            return Err(Error::InvalidPaymentDeadlineConfig(
+                "check_interval_minutes must be positive",
+            ));
+        }
+        if self.payment_deadline.grace_minutes < 0 || self.payment_deadline.warning_hours < 0 {
+            return Err(Error::InvalidPaymentDeadlineConfig(
+                "grace_minutes and warning_hours must not be negative",
+            ));
+        }
+
+        Ok(())
+    }
+
     pub fn is_in_buffer(
         &self,
         now: &DateTime<Utc>,
@@ -104,25 +459,92 @@ impl BookingConfig {
         now >= &lower && now < &upper
     }
 
-    pub fn calculate_additive_price(&self, unit_id: &UnitId, hours: i64) -> Result<i64, ()> {
+    pub fn max_booking_length(&self, unit_id: &UnitId) -> Result<TimeDelta, ()> {
+        let Some(unit_booking_config) = self.units.get(unit_id) else {
+            return Err(());
+        };
+
+        Ok(unit_booking_config
+            .max_booking_minutes
+            .map(TimeDelta::minutes)
+            .unwrap_or(TimeDelta::hours(self.max_booking_hours)))
+    }
+
+    /// Aligns the start of a booking to the slot grid of the unit, in local time.
+    pub fn truncate_time(
+        &self,
+        unit_id: &UnitId,
+        time_from: DateTime<FixedOffset>,
+    ) -> Result<DateTime<Utc>, ()> {
+        let Some(unit_booking_config) = self.units.get(unit_id) else {
+            return Err(());
+        };
+
+        Ok(truncate_time_to_slot(time_from, unit_booking_config.slot_size()).to_utc())
+    }
+
+    /// Whether the length is a whole number of slots, without checking the bounds.
+    pub fn is_slot_aligned(&self, unit_id: &UnitId, length: TimeDelta) -> Result<bool, ()> {
+        let Some(unit_booking_config) = self.units.get(unit_id) else {
+            return Err(());
+        };
+
+        let slot_minutes = unit_booking_config.slot_size().num_minutes();
+
+        Ok(length > TimeDelta::zero() && length.num_minutes() % slot_minutes == 0)
+    }
+
+    pub fn is_valid_length(&self, unit_id: &UnitId, length: TimeDelta) -> Result<bool, ()> {
+        let Some(unit_booking_config) = self.units.get(unit_id) else {
+            return Err(());
+        };
+
+        Ok(self.is_slot_aligned(unit_id, length)?
+            && length >= unit_booking_config.min_length()
+            && length <= self.max_booking_length(unit_id)?)
+    }
+
+    /// Price for extending a booking by the given range, without the base price.
+    pub fn calculate_additive_price(
+        &self,
+        timezone_config: &TimeZoneConfig,
+        unit_id: &UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+    ) -> Result<PriceBreakdown, ()> {
         let Some(unit_booking_config) = self.units.get(unit_id) else {
             return Err(());
         };
 
-        Ok(unit_booking_config.calculate_additive_price(hours))
+        Ok(unit_booking_config.pricing.calculate(
+            unit_booking_config,
+            &self.holidays,
+            timezone_config,
+            time_from,
+            time_to,
+            true,
+        ))
     }
 
     pub fn calculate_price(
         &self,
+        timezone_config: &TimeZoneConfig,
         unit_id: &UnitId,
         time_from: DateTime<Utc>,
         time_to: DateTime<Utc>,
-    ) -> Result<i64, ()> {
+    ) -> Result<PriceBreakdown, ()> {
         let Some(unit_booking_config) = self.units.get(unit_id) else {
             return Err(());
         };
 
-        Ok(unit_booking_config.calculate_price(time_from, time_to))
+        Ok(unit_booking_config.pricing.calculate(
+            unit_booking_config,
+            &self.holidays,
+            timezone_config,
+            time_from,
+            time_to,
+            false,
+        ))
     }
 
     pub fn calculate_refund_price(
@@ -202,21 +624,28 @@ pub struct DoorLockConfig {
     pub itsokey: Option<ItsokeyConfig>,
 }
 
-#[derive(Clone, Deserialize, Debug, Default)]
-#[serde(rename_all = "snake_case")]
+#[derive(Clone, Deserialize, Debug)]
+#[serde(rename_all = "snake_case", tag = "backend")]
 pub enum NotificationBackend {
-    #[default]
     Noop,
-    Ntfy,
+    Ntfy(NtfyConfig),
+    Webhook(WebhookConfig),
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct NtfyConfig {
+    base_url: Option<url::Url>,
     token: Option<String>,
     channels: HashMap<String, String>,
+    title: Option<String>,
+    click_url: Option<url::Url>,
 }
 
 impl dxe_extern::ntfy::NtfyConfig for NtfyConfig {
+    fn base_url(&self) -> Option<&url::Url> {
+        self.base_url.as_ref()
+    }
+
     fn access_token(&self) -> Option<&str> {
         self.token.as_deref()
     }
@@ -228,12 +657,102 @@ impl dxe_extern::ntfy::NtfyConfig for NtfyConfig {
             dxe_extern::ntfy::Channel::Minor => self.channels.get("minor").unwrap(),
         }
     }
+
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn click_url(&self) -> Option<&url::Url> {
+        self.click_url.as_ref()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebhookConfig {
+    url: url::Url,
+    #[serde(default)]
+    format: dxe_extern::webhook::PayloadFormat,
+    title: Option<String>,
+    secret: Option<String>,
 }
 
+impl dxe_extern::webhook::WebhookConfig for WebhookConfig {
+    fn url(&self) -> &url::Url {
+        &self.url
+    }
+
+    fn format(&self) -> dxe_extern::webhook::PayloadFormat {
+        self.format
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn secret(&self) -> Option<&str> {
+        self.secret.as_deref()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct NotificationBackendConfig {
+    #[serde(flatten)]
+    pub backend: NotificationBackend,
+    /// Notifications less important than this are not sent to the backend. All of them are sent if
+    /// not given.
+    pub min_priority: Option<Priority>,
+}
+
+/// Notifications are sent to all of the backends, or logged if there's none.
+///
+/// The single backend of the former `backend = "ntfy"` and `[notifications.ntfy]` keys is still
+/// read as one of the backends, so that existing configs keep sending notifications.
 #[derive(Default, Deserialize, Clone, Debug)]
+#[serde(try_from = "RawNotificationConfig")]
 pub struct NotificationConfig {
-    pub backend: NotificationBackend,
-    pub ntfy: Option<NtfyConfig>,
+    pub backends: Vec<NotificationBackendConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum LegacyNotificationBackend {
+    Noop,
+    Ntfy,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct RawNotificationConfig {
+    #[serde(default)]
+    backends: Vec<NotificationBackendConfig>,
+    backend: Option<LegacyNotificationBackend>,
+    ntfy: Option<NtfyConfig>,
+}
+
+impl TryFrom<RawNotificationConfig> for NotificationConfig {
+    type Error = &'static str;
+
+    fn try_from(value: RawNotificationConfig) -> Result<Self, Self::Error> {
+        let mut backends = value.backends;
+
+        match (value.backend, value.ntfy) {
+            (Some(LegacyNotificationBackend::Ntfy), Some(ntfy)) => {
+                backends.push(NotificationBackendConfig {
+                    backend: NotificationBackend::Ntfy(ntfy),
+                    min_priority: None,
+                });
+            }
+            (Some(LegacyNotificationBackend::Ntfy), None) => {
+                return Err("`backend = \"ntfy\"` requires the `ntfy` table");
+            }
+            (None | Some(LegacyNotificationBackend::Noop), Some(_)) => {
+                return Err("`ntfy` table is only read with `backend = \"ntfy\"`, use `backends`");
+            }
+            (None | Some(LegacyNotificationBackend::Noop), None) => {}
+        }
+
+        Ok(Self { backends })
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -241,6 +760,29 @@ pub struct BiztalkConfig {
     bs_id: String,
     password: String,
     sender_key: String,
+    /// Registered sender number for SMS/LMS, sent when AlimTalk is rejected.
+    #[serde(default)]
+    sms_sender_number: Option<String>,
+    /// Template codes registered on Biztalk, if different from the default ones.
+    #[serde(default)]
+    template_codes: HashMap<MessageTemplate, String>,
+    /// How often the delivery results of AlimTalk messages are fetched, to send the undelivered
+    /// ones as SMS.
+    #[serde(default = "BiztalkConfig::default_result_poll_interval_seconds")]
+    pub result_poll_interval_seconds: u64,
+}
+
+impl BiztalkConfig {
+    fn default_result_poll_interval_seconds() -> u64 {
+        60
+    }
+
+    pub fn template_code(&self, template: MessageTemplate) -> &str {
+        self.template_codes
+            .get(&template)
+            .map(String::as_str)
+            .unwrap_or(template.default_biztalk_code())
+    }
 }
 
 impl dxe_extern::biztalk::BiztalkConfig for BiztalkConfig {
@@ -255,11 +797,130 @@ impl dxe_extern::biztalk::BiztalkConfig for BiztalkConfig {
     fn sender_key(&self) -> &str {
         &self.sender_key
     }
+
+    fn sms_sender_number(&self) -> Option<&str> {
+        self.sms_sender_number.as_deref()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct SmtpConfig {
+    host: String,
+    port: Option<u16>,
+    #[serde(default)]
+    security: dxe_extern::smtp::SmtpSecurity,
+    username: Option<String>,
+    password: Option<String>,
+    sender: String,
+}
+
+impl dxe_extern::smtp::SmtpConfig for SmtpConfig {
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    fn security(&self) -> dxe_extern::smtp::SmtpSecurity {
+        self.security
+    }
+
+    fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+
+    fn sender(&self) -> &str {
+        &self.sender
+    }
+}
+
+/// Delivery of the messages queued in the outbox, retried with exponential backoff.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct MessageOutboxConfig {
+    pub poll_interval_seconds: u64,
+    pub batch_size: i64,
+    /// Attempts after which the message is given up on, until resent by admins.
+    pub max_attempts: i64,
+    pub initial_backoff_seconds: i64,
+    pub max_backoff_seconds: i64,
+}
+
+impl MessageOutboxConfig {
+    pub fn backoff(&self, attempts: i64) -> TimeDelta {
+        let exponent = (attempts - 1).clamp(0, 30) as u32;
+        let seconds = self
+            .initial_backoff_seconds
+            .saturating_mul(2i64.saturating_pow(exponent));
+
+        TimeDelta::seconds(seconds.min(self.max_backoff_seconds))
+    }
+}
+
+impl Default for MessageOutboxConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_seconds: 5,
+            batch_size: 50,
+            max_attempts: 8,
+            initial_backoff_seconds: 30,
+            max_backoff_seconds: 3600,
+        }
+    }
+}
+
+const DEFAULT_SURVEY_URL: &str = "https://forms.gle/dr5tp8CnFM9SjXdm9";
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct MessageTemplateConfig {
+    /// Directory with a `<name>.txt` file for every template, in place of the built-in ones. Emails
+    /// are read from `email/<name>.subject.txt`, `email/<name>.txt` and `email/<name>.html`, along
+    /// with `email/layout.html`.
+    pub directory: Option<PathBuf>,
+    pub survey_url: url::Url,
+}
+
+impl Default for MessageTemplateConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            survey_url: url::Url::parse(DEFAULT_SURVEY_URL).unwrap(),
+        }
+    }
 }
 
 #[derive(Default, Deserialize, Clone, Debug)]
 pub struct MessagingConfig {
     pub biztalk: Option<BiztalkConfig>,
+    pub smtp: Option<SmtpConfig>,
+    #[serde(default)]
+    pub outbox: MessageOutboxConfig,
+    #[serde(default)]
+    pub templates: MessageTemplateConfig,
+}
+
+/// Delivery of the booking events to the webhook subscriptions.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct WebhookSubscriptionConfig {
+    pub timeout_seconds: u64,
+    pub outbox: MessageOutboxConfig,
+}
+
+impl Default for WebhookSubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 10,
+            outbox: Default::default(),
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -308,9 +969,33 @@ pub struct TelemetryConfig {
     pub path: PathBuf,
 }
 
+/// Periodic comparison of recent payments against the payment lookup API of Toss Payments.
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct TossReconciliationConfig {
+    pub enabled: bool,
+    pub interval_minutes: i64,
+    pub lookback_days: i64,
+    /// Time given to the confirmation request from the browser before a payment is repaired.
+    pub grace_minutes: i64,
+}
+
+impl Default for TossReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 30,
+            lookback_days: 7,
+            grace_minutes: 10,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct TossPaymentsConfig {
     pub secret_key: String,
+    #[serde(default)]
+    pub reconciliation: TossReconciliationConfig,
 }
 
 impl dxe_extern::toss_payments::TossPaymentsConfig for TossPaymentsConfig {
@@ -335,8 +1020,242 @@ pub struct Config {
     pub notifications: NotificationConfig,
     #[serde(default)]
     pub messaging: MessagingConfig,
+    #[serde(default)]
+    pub webhooks: WebhookSubscriptionConfig,
+    #[serde(default)]
+    pub bank_statement: BankStatementConfig,
     pub google_apis: Option<GoogleApiConfig>,
     pub toss_payments: TossPaymentsConfig,
     #[serde(flatten)]
     pub telemetry: TelemetryConfig,
 }
+
+impl Config {
+    /// Rejects values which would otherwise break the server at runtime.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.booking.validate()?;
+        self.auth.oidc.validate()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid booking configuration of unit {0}: {1}")]
+    InvalidUnitBookingConfig(UnitId, &'static str),
+    #[error("Invalid hour pack offer {0}: {1}")]
+    InvalidHourPackOffer(String, &'static str),
+    #[error("Invalid payment deadline configuration: {0}")]
+    InvalidPaymentDeadlineConfig(&'static str),
+    #[error("Invalid OIDC provider {provider}: {1}", provider = .0.name())]
+    InvalidOidcProvider(IdentityProvider, &'static str),
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{FixedOffset, NaiveTime, TimeDelta, Weekday};
+    use dxe_types::UnitId;
+
+    use super::{
+        BookingConfig, Error, HourPackOffer, NotificationBackend, NotificationBackendConfig,
+        NotificationConfig, OidcProvidersConfig, OpeningHours, TimeZoneConfig, UnitBookingConfig,
+    };
+
+    /// Single `studio` unit priced 10000 per hour in 30-minute slots.
+    pub(crate) fn booking_config() -> BookingConfig {
+        BookingConfig {
+            lookahead_days: 60,
+            max_booking_hours: 6,
+            buffer_time: (TimeDelta::zero(), TimeDelta::zero()),
+            refund_rates: vec![],
+            units: HashMap::from([(
+                UnitId::from("studio".to_owned()),
+                UnitBookingConfig {
+                    base_price: 0,
+                    price_per_hour: 10000,
+                    slot_minutes: Some(30),
+                    min_booking_minutes: None,
+                    max_booking_minutes: None,
+                    opening_hours: vec![],
+                    pricing: Default::default(),
+                },
+            )]),
+            series: Default::default(),
+            waitlist: Default::default(),
+            holidays: vec![],
+            hour_packs: vec![],
+            payment_deadline: Default::default(),
+            availability: Default::default(),
+        }
+    }
+
+    fn hour_pack_offer(id: &str, hours: i64, price: i64) -> HourPackOffer {
+        HourPackOffer {
+            id: id.to_owned(),
+            name: id.to_owned(),
+            hours,
+            price,
+        }
+    }
+
+    #[test]
+    fn validates_unit_booking_config() {
+        assert!(booking_config().validate().is_ok());
+
+        for slot_minutes in [0, -30] {
+            let mut config = booking_config();
+            for unit_booking_config in config.units.values_mut() {
+                unit_booking_config.slot_minutes = Some(slot_minutes);
+            }
+            assert!(matches!(
+                config.validate(),
+                Err(Error::InvalidUnitBookingConfig(..))
+            ));
+        }
+
+        let mut config = booking_config();
+        for unit_booking_config in config.units.values_mut() {
+            unit_booking_config.min_booking_minutes = Some(8 * 60);
+        }
+        assert!(matches!(
+            config.validate(),
+            Err(Error::InvalidUnitBookingConfig(..))
+        ));
+    }
+
+    #[test]
+    fn validates_hour_pack_offers() {
+        let mut config = booking_config();
+        config.hour_packs = vec![
+            hour_pack_offer("10h", 10, 90000),
+            hour_pack_offer("20h", 20, 170000),
+        ];
+        assert!(config.validate().is_ok());
+
+        for offer in [
+            hour_pack_offer("0h", 0, 90000),
+            hour_pack_offer("free", 10, 0),
+            hour_pack_offer("10h", 5, 40000),
+        ] {
+            let mut config = booking_config();
+            config.hour_packs = vec![hour_pack_offer("10h", 10, 90000), offer];
+            assert!(matches!(
+                config.validate(),
+                Err(Error::InvalidHourPackOffer(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn validates_payment_deadline_config() {
+        for check_interval_minutes in [0, -5] {
+            let mut config = booking_config();
+            config.payment_deadline.hours_before_start = Some(24);
+            config.payment_deadline.check_interval_minutes = check_interval_minutes;
+            assert!(matches!(
+                config.validate(),
+                Err(Error::InvalidPaymentDeadlineConfig(..))
+            ));
+        }
+
+        let mut config = booking_config();
+        config.payment_deadline.grace_minutes = -30;
+        assert!(matches!(
+            config.validate(),
+            Err(Error::InvalidPaymentDeadlineConfig(..))
+        ));
+    }
+
+    #[test]
+    fn rejects_builtin_providers_as_oidc() {
+        let provider = |key: &str| {
+            toml::from_str::<OidcProvidersConfig>(&format!(
+                r#"
+                [{key}]
+                display_name = "Provider"
+                client_id = "client"
+                client_secret = "secret"
+                authorization_endpoint = "https://example.com/authorize"
+                token_endpoint = "https://example.com/token"
+                userinfo_endpoint = "https://example.com/userinfo"
+                "#
+            ))
+            .unwrap()
+        };
+
+        assert!(provider("google").validate().is_ok());
+        assert!(provider("naver").validate().is_ok());
+        for key in ["kakao", "handle"] {
+            assert!(matches!(
+                provider(key).validate(),
+                Err(Error::InvalidOidcProvider(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn opening_hours_to_utc_window() {
+        let timezone_config = TimeZoneConfig {
+            timezone: FixedOffset::east_opt(9 * 3600).unwrap(),
+        };
+        let opening_hours = |weekday, open, close| OpeningHours {
+            weekday,
+            open: NaiveTime::from_hms_opt(open, 0, 0).unwrap(),
+            close: NaiveTime::from_hms_opt(close, 0, 0).unwrap(),
+        };
+
+        // Tuesday 10:00 - 22:00 KST is Tuesday 01:00 - 13:00 UTC
+        assert_eq!(
+            opening_hours(Weekday::Tue, 10, 22).to_utc_window(&timezone_config),
+            (24 * 60 + 60, 12 * 60)
+        );
+        // Closing after midnight
+        assert_eq!(
+            opening_hours(Weekday::Fri, 18, 2).to_utc_window(&timezone_config),
+            (4 * 24 * 60 + 9 * 60, 8 * 60)
+        );
+        // Monday morning in KST falls on Sunday in UTC, before the start of the week
+        assert_eq!(
+            opening_hours(Weekday::Mon, 6, 12).to_utc_window(&timezone_config),
+            (-3 * 60, 6 * 60)
+        );
+    }
+
+    #[test]
+    fn reads_legacy_notification_backend() {
+        let config: NotificationConfig = toml::from_str(
+            r#"
+            backend = "ntfy"
+
+            [ntfy]
+            channels = { major = "major", minor = "minor" }
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.backends.as_slice(),
+            [NotificationBackendConfig {
+                backend: NotificationBackend::Ntfy(_),
+                min_priority: None,
+            }]
+        ));
+
+        let config: NotificationConfig = toml::from_str(r#"backend = "noop""#).unwrap();
+        assert!(config.backends.is_empty());
+
+        assert!(toml::from_str::<NotificationConfig>(r#"backend = "ntfy""#).is_err());
+        assert!(
+            toml::from_str::<NotificationConfig>(
+                r#"
+                [ntfy]
+                channels = { major = "major", minor = "minor" }
+                "#
+            )
+            .is_err()
+        );
+        assert!(toml::from_str::<NotificationConfig>(r#"backedns = []"#).is_err());
+    }
+}
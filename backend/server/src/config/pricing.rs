@@ -0,0 +1,410 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use serde::Deserialize;
+
+use super::{TimeZoneConfig, UnitBookingConfig};
+
+const LABEL_BASE_PRICE: &str = "기본 요금";
+const LABEL_DEFAULT_RATE: &str = "일반 요금";
+const LABEL_WEEKEND_SURCHARGE: &str = "주말 할증";
+const LABEL_HOLIDAY_SURCHARGE: &str = "공휴일 할증";
+
+/// Hourly rate applied to slots starting within the band, in local time.
+#[derive(Clone, Deserialize, Debug)]
+pub struct PricingBand {
+    pub name: String,
+    /// Empty means every day of the week.
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    pub from: NaiveTime,
+    /// On or before `from` means the band runs past midnight.
+    pub to: NaiveTime,
+    pub price_per_hour: i64,
+}
+
+/// Flat price for a booking that fits entirely within the window, in local time.
+#[derive(Clone, Deserialize, Debug)]
+pub struct PricingPackage {
+    pub name: String,
+    /// Weekdays on which the window starts. Empty means every day of the week.
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    pub from: NaiveTime,
+    /// On or before `from` means the window ends on the next day.
+    pub to: NaiveTime,
+    pub price: i64,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct PricingRules {
+    #[serde(default)]
+    pub bands: Vec<PricingBand>,
+    /// Surcharge in percent for slots on Saturdays and Sundays.
+    #[serde(default)]
+    pub weekend_surcharge_rate: i64,
+    /// Surcharge in percent for slots on holidays. Takes precedence over the weekend surcharge.
+    #[serde(default)]
+    pub holiday_surcharge_rate: i64,
+    #[serde(default)]
+    pub packages: Vec<PricingPackage>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PriceItem {
+    pub label: String,
+    pub minutes: i64,
+    pub amount: i64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PriceBreakdown {
+    pub items: Vec<PriceItem>,
+    pub total: i64,
+}
+
+impl PriceBreakdown {
+    fn add(&mut self, label: &str, minutes: i64, amount: i64) {
+        if let Some(item) = self.items.iter_mut().find(|v| v.label == label) {
+            item.minutes += minutes;
+            item.amount += amount;
+        } else {
+            self.items.push(PriceItem {
+                label: label.to_owned(),
+                minutes,
+                amount,
+            });
+        }
+        self.total += amount;
+    }
+
+    /// Appends a discount line, capped so that the total never goes below zero. Returns the
+    /// amount actually discounted.
+    pub fn discount(&mut self, label: &str, amount: i64) -> i64 {
+        let amount = amount.clamp(0, self.total);
+        if amount > 0 {
+            self.add(label, 0, -amount);
+        }
+        amount
+    }
+}
+
+fn matches_weekday(weekdays: &[Weekday], weekday: Weekday) -> bool {
+    weekdays.is_empty() || weekdays.contains(&weekday)
+}
+
+fn window_length(from: NaiveTime, to: NaiveTime) -> TimeDelta {
+    let length = to - from;
+    if length <= TimeDelta::zero() {
+        length + TimeDelta::days(1)
+    } else {
+        length
+    }
+}
+
+impl PricingBand {
+    fn contains(&self, datetime: &DateTime<FixedOffset>) -> bool {
+        // Check the band starting on the same day and the one carried over from the day before
+        [
+            datetime.date_naive(),
+            datetime.date_naive() - TimeDelta::days(1),
+        ]
+        .into_iter()
+        .any(|date| {
+            let start = date.and_time(self.from);
+            let local = datetime.naive_local();
+
+            matches_weekday(&self.weekdays, date.weekday())
+                && local >= start
+                && local < start + window_length(self.from, self.to)
+        })
+    }
+}
+
+impl PricingPackage {
+    fn covers(&self, from: &DateTime<FixedOffset>, to: &DateTime<FixedOffset>) -> bool {
+        [from.date_naive(), from.date_naive() - TimeDelta::days(1)]
+            .into_iter()
+            .any(|date| {
+                let start = date.and_time(self.from);
+
+                matches_weekday(&self.weekdays, date.weekday())
+                    && from.naive_local() >= start
+                    && to.naive_local() <= start + window_length(self.from, self.to)
+            })
+    }
+}
+
+impl PricingRules {
+    /// Prices the time range slot by slot. Base price and packages only apply to whole bookings,
+    /// not to extensions.
+    pub fn calculate(
+        &self,
+        unit_booking_config: &UnitBookingConfig,
+        holidays: &[NaiveDate],
+        timezone_config: &TimeZoneConfig,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        is_extension: bool,
+    ) -> PriceBreakdown {
+        let mut breakdown = PriceBreakdown::default();
+
+        if !is_extension && unit_booking_config.base_price > 0 {
+            breakdown.add(LABEL_BASE_PRICE, 0, unit_booking_config.base_price);
+        }
+
+        let mut time_based = PriceBreakdown::default();
+        let slot_size = unit_booking_config.slot_size();
+        let mut cursor = time_from;
+        while cursor < time_to {
+            let local = timezone_config.convert(cursor);
+            let minutes = std::cmp::min(slot_size, time_to - cursor).num_minutes();
+
+            let (label, price_per_hour) = self
+                .bands
+                .iter()
+                .find(|v| v.contains(&local))
+                .map(|v| (v.name.as_str(), v.price_per_hour))
+                .unwrap_or((LABEL_DEFAULT_RATE, unit_booking_config.price_per_hour));
+
+            // Partial slots are charged as a whole slot
+            let amount = price_per_hour * slot_size.num_minutes() / 60;
+            time_based.add(label, minutes, amount);
+
+            let surcharge = if holidays.contains(&local.date_naive()) {
+                Some((LABEL_HOLIDAY_SURCHARGE, self.holiday_surcharge_rate))
+            } else if matches!(local.weekday(), Weekday::Sat | Weekday::Sun) {
+                Some((LABEL_WEEKEND_SURCHARGE, self.weekend_surcharge_rate))
+            } else {
+                None
+            };
+            if let Some((label, rate)) = surcharge
+                && rate > 0
+            {
+                time_based.add(label, minutes, amount * rate / 100);
+            }
+
+            cursor += slot_size;
+        }
+
+        let local_from = timezone_config.convert(time_from);
+        let local_to = timezone_config.convert(time_to);
+        let package = if is_extension {
+            None
+        } else {
+            self.packages
+                .iter()
+                .filter(|v| v.covers(&local_from, &local_to) && v.price < time_based.total)
+                .min_by_key(|v| v.price)
+        };
+
+        if let Some(package) = package {
+            breakdown.add(
+                &package.name,
+                (time_to - time_from).num_minutes(),
+                package.price,
+            );
+        } else {
+            for item in time_based.items {
+                breakdown.add(&item.label, item.minutes, item.amount);
+            }
+        }
+
+        breakdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveTime, TimeZone, Utc};
+
+    use super::{PriceBreakdown, PricingBand, PricingRules};
+    use crate::config::{TimeZoneConfig, UnitBookingConfig};
+
+    fn timezone_config() -> TimeZoneConfig {
+        TimeZoneConfig {
+            timezone: FixedOffset::east_opt(9 * 3600).unwrap(),
+        }
+    }
+
+    fn unit_booking_config(slot_minutes: i64) -> UnitBookingConfig {
+        UnitBookingConfig {
+            base_price: 0,
+            price_per_hour: 10000,
+            slot_minutes: Some(slot_minutes),
+            min_booking_minutes: None,
+            max_booking_minutes: None,
+            opening_hours: vec![],
+            pricing: PricingRules::default(),
+        }
+    }
+
+    fn band(name: &str, from: u32, to: u32, price_per_hour: i64) -> PricingBand {
+        PricingBand {
+            name: name.to_owned(),
+            weekdays: vec![],
+            from: NaiveTime::from_hms_opt(from, 0, 0).unwrap(),
+            to: NaiveTime::from_hms_opt(to, 0, 0).unwrap(),
+            price_per_hour,
+        }
+    }
+
+    fn kst(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2026-03-02 is a Monday
+        timezone_config()
+            .timezone
+            .with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+            .unwrap()
+            .to_utc()
+    }
+
+    fn items(breakdown: &PriceBreakdown) -> Vec<(&str, i64, i64)> {
+        breakdown
+            .items
+            .iter()
+            .map(|v| (v.label.as_str(), v.minutes, v.amount))
+            .collect()
+    }
+
+    #[test]
+    fn peak_and_off_peak_boundaries() {
+        let rules = PricingRules {
+            bands: vec![band("피크", 18, 23, 15000), band("심야", 23, 2, 5000)],
+            ..Default::default()
+        };
+        let config = unit_booking_config(60);
+
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(2, 17, 0),
+            kst(2, 19, 0),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![("일반 요금", 60, 10000), ("피크", 60, 15000)]
+        );
+        assert_eq!(breakdown.total, 25000);
+
+        // The late night band runs past midnight and ends before 02:00
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(2, 22, 0),
+            kst(3, 3, 0),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![
+                ("피크", 60, 15000),
+                ("심야", 180, 15000),
+                ("일반 요금", 60, 10000)
+            ]
+        );
+        assert_eq!(breakdown.total, 40000);
+    }
+
+    #[test]
+    fn slot_crossing_midnight_is_priced_by_its_start() {
+        let rules = PricingRules {
+            weekend_surcharge_rate: 50,
+            ..Default::default()
+        };
+        let config = unit_booking_config(90);
+
+        // Friday 23:00 - Saturday 02:00, the second slot starting on Saturday 00:30
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(6, 23, 0),
+            kst(7, 2, 0),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![("일반 요금", 180, 30000), ("주말 할증", 90, 7500)]
+        );
+        assert_eq!(breakdown.total, 37500);
+    }
+
+    #[test]
+    fn holiday_surcharge_takes_precedence() {
+        let rules = PricingRules {
+            weekend_surcharge_rate: 50,
+            holiday_surcharge_rate: 100,
+            ..Default::default()
+        };
+        let config = unit_booking_config(60);
+        let holidays = [kst(7, 12, 0)
+            .with_timezone(&timezone_config().timezone)
+            .date_naive()];
+
+        let breakdown = rules.calculate(
+            &config,
+            &holidays,
+            &timezone_config(),
+            kst(7, 12, 0),
+            kst(7, 13, 0),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![("일반 요금", 60, 10000), ("공휴일 할증", 60, 10000)]
+        );
+    }
+
+    #[test]
+    fn partial_slot_and_base_price() {
+        let rules = PricingRules::default();
+        let config = UnitBookingConfig {
+            base_price: 3000,
+            ..unit_booking_config(60)
+        };
+
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(2, 10, 0),
+            kst(2, 11, 30),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![("기본 요금", 0, 3000), ("일반 요금", 90, 20000)]
+        );
+
+        // Extensions are charged without the base price
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(2, 11, 0),
+            kst(2, 12, 0),
+            true,
+        );
+        assert_eq!(breakdown.total, 10000);
+    }
+
+    #[test]
+    fn zero_slot_terminates() {
+        let rules = PricingRules::default();
+        let config = unit_booking_config(0);
+
+        let breakdown = rules.calculate(
+            &config,
+            &[],
+            &timezone_config(),
+            kst(2, 10, 0),
+            kst(2, 11, 0),
+            false,
+        );
+        assert_eq!(
+            items(&breakdown),
+            vec![("일반 요금", 60, 60 * (10000 / 60))]
+        );
+    }
+}
@@ -1,6 +1,9 @@
 mod admin;
 mod auth;
+mod availability;
 mod booking;
+mod calendar_feed;
+mod email_verification;
 mod join_group;
 mod payment;
 mod s2s;
@@ -44,6 +47,9 @@ where
     let scope_with_auth = web::scope("")
         .service(booking::bookings_scope())
         .service(booking::booking_scope())
+        .service(booking::booking_series_scope())
+        .service(booking::waitlist_scope())
+        .service(booking::hour_packs_scope())
         .service(booking::adhoc_reservation_scope())
         .service(payment::scope())
         .service(user::scope())
@@ -63,6 +69,10 @@ where
         .service(web::resource("/timestamp").route(web::get().to(timestamp::get)))
         .service(auth::scope())
         .service(join_group::resource())
+        .service(calendar_feed::resource())
+        .service(email_verification::resource())
+        .service(availability::scope())
+        .service(payment::webhook_resource())
         .service(s2s::scope().wrap(CoordinatorVerifier::new(s2s_public_keys)))
         .service(scope_with_auth)
         .wrap(DateTimeInjector)
@@ -1,10 +1,16 @@
 mod adhoc_parking;
 mod adhoc_reservations;
+mod bank_statement;
 mod booking;
 mod bookings;
+mod coupons;
 mod groups;
+mod hour_packs;
+mod messages;
 mod telemetry;
+mod unit_closures;
 mod users;
+mod webhooks;
 
 use actix_web::web;
 
@@ -29,7 +35,57 @@ pub fn scope() -> actix_web::Scope {
                 .route(web::delete().to(adhoc_reservations::delete)),
         )
         .service(web::resource("/users").route(web::get().to(users::get)))
+        .service(web::resource("/user/{user_id}/merge").route(web::post().to(users::merge)))
         .service(web::resource("/groups").route(web::get().to(groups::get)))
+        .service(
+            web::resource("/unit-closures")
+                .route(web::get().to(unit_closures::get))
+                .route(web::post().to(unit_closures::post)),
+        )
+        .service(
+            web::resource("/unit-closure/{unit_closure_id}")
+                .route(web::delete().to(unit_closures::delete)),
+        )
+        .service(web::resource("/bank-statement").route(web::post().to(bank_statement::post)))
+        .service(web::resource("/hour-packs").route(web::get().to(hour_packs::get)))
+        .service(web::resource("/hour-pack/{hour_pack_id}").route(web::put().to(hour_packs::put)))
+        .service(
+            web::resource("/coupons")
+                .route(web::get().to(coupons::get))
+                .route(web::post().to(coupons::post)),
+        )
+        .service(web::resource("/coupon/{coupon_id}").route(web::delete().to(coupons::delete)))
+        .service(web::resource("/messages").route(web::get().to(messages::get)))
+        .service(web::resource("/message/{message_id}").route(web::get().to(messages::get_message)))
+        .service(
+            web::resource("/message/{message_id}/resend").route(web::post().to(messages::resend)),
+        )
+        .service(
+            web::resource("/message-templates/reload")
+                .route(web::post().to(messages::reload_templates)),
+        )
+        .service(
+            web::resource("/webhooks")
+                .route(web::get().to(webhooks::get))
+                .route(web::post().to(webhooks::post)),
+        )
+        .service(
+            web::resource("/webhook/{subscription_id}")
+                .route(web::put().to(webhooks::put))
+                .route(web::delete().to(webhooks::delete)),
+        )
+        .service(
+            web::resource("/webhook/{subscription_id}/deliveries")
+                .route(web::get().to(webhooks::get_deliveries)),
+        )
+        .service(
+            web::resource("/webhook-delivery/{delivery_id}")
+                .route(web::get().to(webhooks::get_delivery)),
+        )
+        .service(
+            web::resource("/webhook-delivery/{delivery_id}/redeliver")
+                .route(web::post().to(webhooks::redeliver)),
+        )
         .service(
             web::resource("/adhoc-parkings")
                 .route(web::get().to(adhoc_parking::get))
@@ -0,0 +1,177 @@
+use actix_web::web;
+use chrono::TimeDelta;
+use dxe_data::entities::{Booking, Product};
+use dxe_data::queries::booking::{confirm_booking, get_booking, get_product};
+use dxe_data::queries::hour_pack::{confirm_hour_pack, create_hour_balance_entry};
+use dxe_data::queries::payment::{confirm_cash_payment, get_pending_cash_transactions};
+use dxe_types::ProductId;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::config::{BankStatementConfig, TimeZoneConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{BankDeposit, BankDepositMatch, PendingCashTransaction};
+use crate::models::handlers::admin::{ImportBankStatementQuery, ImportBankStatementResponse};
+use crate::models::{Error, IntoView};
+use crate::services::amendment::confirm_cash_amendment;
+use crate::services::bank_statement::{match_deposits, read_bank_statement};
+use crate::services::calendar::CalendarService;
+use crate::services::messaging::MessagingService;
+use crate::services::waitlist::WaitlistService;
+use crate::utils::datetime::is_in_effect;
+
+pub async fn post(
+    now: Now,
+    query: web::Query<ImportBankStatementQuery>,
+    body: web::Bytes,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    bank_statement_config: web::Data<BankStatementConfig>,
+    calendar_service: web::Data<Option<CalendarService>>,
+    messaging_service: web::Data<MessagingService>,
+    waitlist_service: web::Data<WaitlistService>,
+) -> Result<web::Json<ImportBankStatementResponse>, Error> {
+    let format = bank_statement_config
+        .formats
+        .get(&query.format)
+        .ok_or(Error::BankStatementFormatNotFound)?;
+
+    let statement = read_bank_statement(format, &timezone_config, &body).await?;
+
+    let mut tx = database.begin().await?;
+    let mut moved_bookings = vec![];
+
+    let pending = get_pending_cash_transactions(&mut tx, &now).await?;
+    let matches = match_deposits(
+        statement.deposits,
+        pending,
+        TimeDelta::days(bank_statement_config.match_window_days),
+    );
+
+    let mut response = ImportBankStatementResponse {
+        matched: vec![],
+        ambiguous: vec![],
+        unmatched: vec![],
+        invalid_rows: statement.invalid_rows,
+    };
+
+    for deposit_match in matches {
+        let deposit = BankDeposit {
+            row: deposit_match.deposit.row,
+            depositor_name: deposit_match.deposit.depositor_name,
+            amount: deposit_match.deposit.amount,
+            deposited_at: timezone_config.convert(deposit_match.deposit.deposited_at),
+        };
+
+        if deposit_match.candidates.is_empty() {
+            response.unmatched.push(deposit);
+            continue;
+        }
+
+        let is_unique = deposit_match.candidates.len() == 1;
+        let is_applied = if is_unique && query.apply {
+            apply_deposit(
+                &mut tx,
+                &now,
+                &deposit_match.candidates[0].product_id,
+                &messaging_service,
+                &waitlist_service,
+                &mut moved_bookings,
+            )
+            .await?
+        } else {
+            false
+        };
+
+        let result = BankDepositMatch {
+            deposit,
+            candidates: deposit_match
+                .candidates
+                .into_iter()
+                .map(|v| PendingCashTransaction::convert(v, &timezone_config, &now))
+                .collect::<Result<_, _>>()?,
+            is_applied,
+        };
+
+        if is_unique {
+            response.matched.push(result);
+        } else {
+            response.ambiguous.push(result);
+        }
+    }
+
+    tx.commit().await?;
+
+    if let Some(calendar_service) = calendar_service.as_ref() {
+        for booking in moved_bookings {
+            if let Err(e) = calendar_service.update_booking_time(&booking).await {
+                log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+            }
+        }
+    }
+
+    Ok(web::Json(response))
+}
+
+/// Confirms the cash payment along with the product it was made for, as the administrator would.
+/// Bookings moved by a rescheduling which was awaiting this deposit are collected in
+/// `moved_bookings`.
+async fn apply_deposit(
+    connection: &mut SqliteConnection,
+    now: &Now,
+    product_id: &ProductId,
+    messaging_service: &MessagingService,
+    waitlist_service: &WaitlistService,
+    moved_bookings: &mut Vec<Booking>,
+) -> Result<bool, Error> {
+    match get_product(&mut *connection, product_id).await? {
+        Some(Product::Booking(booking)) => {
+            let is_confirmed = confirm_booking(&mut *connection, &booking.id, now).await?;
+            confirm_cash_payment(&mut *connection, now, product_id).await?;
+
+            if is_confirmed {
+                let booking = get_booking(&mut *connection, &booking.id)
+                    .await?
+                    .ok_or(Error::BookingNotFound)?;
+                messaging_service
+                    .send_confirmation(&mut *connection, now, booking)
+                    .await?;
+            }
+        }
+        Some(Product::Amendment(amendment)) if is_in_effect(&amendment.canceled_at, now) => {
+            return Ok(false);
+        }
+        Some(Product::Amendment(amendment)) if !is_in_effect(&amendment.confirmed_at, now) => {
+            if let Some(booking) = confirm_cash_amendment(
+                &mut *connection,
+                now,
+                &amendment,
+                waitlist_service,
+                messaging_service,
+            )
+            .await?
+            {
+                moved_bookings.push(booking);
+            }
+        }
+        Some(Product::Amendment(_)) => {
+            confirm_cash_payment(&mut *connection, now, product_id).await?;
+        }
+        Some(Product::HourPack(hour_pack)) => {
+            if confirm_hour_pack(&mut *connection, now, &hour_pack.id).await? {
+                confirm_cash_payment(&mut *connection, now, product_id).await?;
+                create_hour_balance_entry(
+                    &mut *connection,
+                    now,
+                    &hour_pack.customer_id,
+                    hour_pack.minutes,
+                    Some(&hour_pack.id),
+                    None,
+                )
+                .await?;
+            }
+        }
+        None => return Ok(false),
+    }
+
+    Ok(true)
+}
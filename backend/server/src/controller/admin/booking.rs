@@ -1,10 +1,16 @@
 use actix_web::web;
+use dxe_data::entities::Product;
 use dxe_data::queries::booking::{
-    cancel_booking, confirm_booking, get_audio_recording, get_booking, get_telemetry_files,
+    cancel_booking, confirm_booking, get_audio_recording, get_booking, get_product,
+    get_telemetry_files,
+};
+use dxe_data::queries::coupon::cancel_coupon_redemption;
+use dxe_data::queries::hour_pack::{
+    create_hour_balance_entry, get_hour_balance_usage_by_booking_id,
 };
 use dxe_data::queries::payment::{
-    confirm_cash_payment, get_cash_transaction, get_toss_payments_transaction_by_product_id,
-    refund_cash_payment,
+    confirm_cash_payment, get_cash_transaction, get_cash_transactions_by_booking_amendments,
+    get_toss_payments_transaction_by_product_id, refund_cash_payment,
 };
 use dxe_types::{BookingId, ProductId};
 use sqlx::SqlitePool;
@@ -12,15 +18,18 @@ use sqlx::SqlitePool;
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
 use crate::models::entities::{
-    AudioRecording, Booking, BookingWithPayments, CashTransaction, TelemetryEntry,
-    TossPaymentsTransaction, Transaction,
+    AudioRecording, Booking, BookingWithPayments, CashTransaction, HourBalanceTransaction,
+    TelemetryEntry, TossPaymentsTransaction, Transaction,
 };
 use crate::models::handlers::admin::{
     GetBookingResponse, ModifyAction, ModifyBookingRequest, ModifyBookingResponse,
 };
 use crate::models::{Error, IntoView};
+use crate::services::amendment::confirm_cash_amendment;
 use crate::services::calendar::CalendarService;
 use crate::services::messaging::MessagingService;
+use crate::services::waitlist::WaitlistService;
+use crate::services::webhook::{BookingPayload, WebhookEvent, enqueue_webhook_event};
 use crate::utils::datetime::is_in_effect;
 
 pub async fn get(
@@ -52,6 +61,13 @@ pub async fn get(
             &timezone_config,
             &now,
         )?))
+    } else if let Some((minutes, refunded_minutes)) =
+        get_hour_balance_usage_by_booking_id(&mut tx, &booking_id).await?
+    {
+        Some(Transaction::HourBalance(HourBalanceTransaction {
+            minutes,
+            refunded_minutes,
+        }))
     } else {
         None
     };
@@ -84,6 +100,7 @@ pub async fn put(
     timezone_config: web::Data<TimeZoneConfig>,
     messaging_service: web::Data<MessagingService>,
     calendar_service: web::Data<Option<CalendarService>>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<ModifyBookingResponse>, Error> {
     let mut tx = database.begin().await?;
 
@@ -92,6 +109,7 @@ pub async fn put(
         .ok_or(Error::BookingNotFound)?;
 
     let product_id = ProductId::from(*booking_id);
+    let mut settled_refund_price = 0;
 
     match body.action {
         ModifyAction::Confirm => {
@@ -100,15 +118,95 @@ pub async fn put(
             {
                 confirm_cash_payment(&mut tx, &now, &product_id).await?;
             }
+
+            // Additional payments for rescheduling an already paid booking
+            for cash_tx in get_cash_transactions_by_booking_amendments(&mut tx, &booking_id).await?
+            {
+                if is_in_effect(&cash_tx.confirmed_at, &now) {
+                    continue;
+                }
+
+                match get_product(&mut tx, &cash_tx.product_id).await? {
+                    Some(Product::Amendment(amendment))
+                        if is_in_effect(&amendment.canceled_at, &now) => {}
+                    Some(Product::Amendment(amendment))
+                        if !is_in_effect(&amendment.confirmed_at, &now) =>
+                    {
+                        // The booking is moved only now that the difference has been paid
+                        if let Some(moved_booking) = confirm_cash_amendment(
+                            &mut tx,
+                            &now,
+                            &amendment,
+                            &waitlist_service,
+                            &messaging_service,
+                        )
+                        .await?
+                            && let Some(calendar_service) = calendar_service.as_ref()
+                            && let Err(e) =
+                                calendar_service.update_booking_time(&moved_booking).await
+                        {
+                            log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+                        }
+                    }
+                    _ => {
+                        confirm_cash_payment(&mut tx, &now, &cash_tx.product_id).await?;
+                    }
+                }
+            }
         }
         ModifyAction::Refund => {
-            if is_in_effect(&booking.canceled_at, &now) {
-                refund_cash_payment(&mut tx, &now, &product_id).await?;
+            // Shortened bookings stay active with a partial refund pending
+            let cash_tx = get_cash_transaction(&mut tx, &product_id).await?;
+            let is_refund_requested = cash_tx.as_ref().is_some_and(|v| v.refund_price.is_some());
+
+            if (is_in_effect(&booking.canceled_at, &now) || is_refund_requested)
+                && refund_cash_payment(&mut tx, &now, &product_id).await?
+                && let Some(cash_tx) = cash_tx
+            {
+                // Only the part added since the previous settlement is paid out now
+                settled_refund_price =
+                    cash_tx.refund_price.unwrap_or(0) - cash_tx.refunded_price.unwrap_or(0);
             }
         }
         ModifyAction::Cancel => {
             if !is_in_effect(&booking.canceled_at, &now) {
                 cancel_booking(&mut tx, &now, booking_id.as_ref()).await?;
+                cancel_coupon_redemption(&mut tx, &now, &product_id).await?;
+
+                // Cancellations by the administrator credit back all hours
+                if let Some((debited, credited)) =
+                    get_hour_balance_usage_by_booking_id(&mut tx, &booking.id).await?
+                    && debited > credited
+                {
+                    create_hour_balance_entry(
+                        &mut tx,
+                        &now,
+                        &booking.customer.id(),
+                        debited - credited,
+                        None,
+                        Some(&booking.id),
+                    )
+                    .await?;
+                }
+
+                waitlist_service
+                    .release(
+                        &mut tx,
+                        &now,
+                        &booking.unit_id,
+                        &booking.time_from,
+                        &booking.time_to,
+                    )
+                    .await?;
+                enqueue_webhook_event(
+                    &mut tx,
+                    &now,
+                    WebhookEvent::BookingCanceled {
+                        booking: BookingPayload::canceled(&booking, &now),
+                        refund_rate: None,
+                    },
+                )
+                .await?;
                 if let Some(calendar_service) = calendar_service.as_ref()
                     && let Err(e) = calendar_service.delete_booking(booking_id.as_ref()).await
                 {
@@ -127,15 +225,14 @@ pub async fn put(
     match body.action {
         ModifyAction::Confirm => {
             messaging_service
-                .send_confirmation(&mut tx, booking.clone())
+                .send_confirmation(&mut tx, &now, booking.clone())
                 .await?;
         }
         ModifyAction::Refund => {
-            if let Some(cash_payment_status) = &cash_tx
-                && let Some(refund_price) = cash_payment_status.refund_price
-                && refund_price > 0
-            {
-                messaging_service.send_refund_confirmation(booking.clone(), refund_price);
+            if settled_refund_price > 0 {
+                messaging_service
+                    .send_refund_confirmation(&mut tx, &now, booking.clone(), settled_refund_price)
+                    .await?;
             }
         }
         _ => {}
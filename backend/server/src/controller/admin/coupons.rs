@@ -0,0 +1,113 @@
+use actix_web::web;
+use dxe_data::queries::coupon::{
+    count_coupon_redemptions, create_coupon, delete_coupon, get_coupon, get_coupon_by_code,
+    get_coupon_unit_ids, get_coupons,
+};
+use dxe_data::queries::unit::is_unit_enabled;
+use dxe_types::{CouponDiscountType, CouponId};
+use sqlx::SqlitePool;
+
+use crate::config::TimeZoneConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::Coupon;
+use crate::models::handlers::admin::{
+    CreateCouponRequest, CreateCouponResponse, GetCouponsResponse,
+};
+use crate::models::{Error, IntoView};
+
+pub async fn get(
+    now: Now,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetCouponsResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let mut coupons = vec![];
+    for coupon in get_coupons(&mut connection, &now).await? {
+        let unit_ids = get_coupon_unit_ids(&mut connection, &coupon.id).await?;
+        let redemptions = count_coupon_redemptions(&mut connection, &now, &coupon.id, None).await?;
+
+        coupons.push(Coupon::convert(
+            (coupon, unit_ids, redemptions),
+            &timezone_config,
+            &now,
+        )?);
+    }
+
+    Ok(web::Json(GetCouponsResponse { coupons }))
+}
+
+pub async fn post(
+    now: Now,
+    body: web::Json<CreateCouponRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<CreateCouponResponse>, Error> {
+    let code = body.code.trim();
+    if code.is_empty() {
+        return Err(Error::MissingField("code"));
+    }
+
+    let is_valid_discount = match body.discount_type {
+        CouponDiscountType::Percentage => (1..=100).contains(&body.discount_value),
+        CouponDiscountType::Fixed => body.discount_value > 0,
+    };
+    if !is_valid_discount {
+        return Err(Error::InvalidCouponDiscount);
+    }
+
+    let mut tx = database.begin().await?;
+
+    if get_coupon_by_code(&mut tx, &now, code).await?.is_some() {
+        return Err(Error::CouponCodeAlreadyExists);
+    }
+
+    for unit_id in &body.unit_ids {
+        if is_unit_enabled(&mut tx, unit_id).await?.is_none() {
+            return Err(Error::UnitNotFound);
+        }
+    }
+
+    let id = create_coupon(
+        &mut tx,
+        &now,
+        code,
+        &body.name,
+        body.discount_type,
+        body.discount_value,
+        &body.valid_from.map(|v| v.to_utc()),
+        &body.valid_to.map(|v| v.to_utc()),
+        body.max_redemptions,
+        body.max_redemptions_per_user,
+        body.first_booking_only,
+        &body.unit_ids,
+    )
+    .await?;
+
+    let coupon = get_coupon(&mut tx, &id)
+        .await?
+        .ok_or(Error::CouponNotFound)?;
+    let unit_ids = get_coupon_unit_ids(&mut tx, &id).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(CreateCouponResponse {
+        coupon: Coupon::convert((coupon, unit_ids, 0), &timezone_config, &now)?,
+    }))
+}
+
+pub async fn delete(
+    now: Now,
+    coupon_id: web::Path<CouponId>,
+    database: web::Data<SqlitePool>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut tx = database.begin().await?;
+
+    if !delete_coupon(&mut tx, &now, coupon_id.as_ref()).await? {
+        return Err(Error::CouponNotFound);
+    }
+
+    tx.commit().await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
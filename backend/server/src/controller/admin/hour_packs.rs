@@ -0,0 +1,111 @@
+use actix_web::web;
+use dxe_data::queries::hour_pack::{
+    cancel_hour_pack, confirm_hour_pack, create_hour_balance_entry, get_hour_balance,
+    get_hour_pack, get_pending_hour_packs,
+};
+use dxe_data::queries::payment::{confirm_cash_payment, get_cash_transaction, refund_cash_payment};
+use dxe_types::{HourPackId, ProductId};
+use sqlx::SqlitePool;
+
+use crate::config::TimeZoneConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{CashTransaction, HourPack};
+use crate::models::handlers::admin::{
+    GetHourPacksResponse, ModifyAction, ModifyHourPackRequest, ModifyHourPackResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::utils::datetime::is_in_effect;
+
+pub async fn get(
+    now: Now,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetHourPacksResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let hour_packs = get_pending_hour_packs(&mut connection, &now)
+        .await?
+        .into_iter()
+        .map(|v| HourPack::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetHourPacksResponse { hour_packs }))
+}
+
+pub async fn put(
+    now: Now,
+    hour_pack_id: web::Path<HourPackId>,
+    body: web::Json<ModifyHourPackRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<ModifyHourPackResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    let hour_pack = get_hour_pack(&mut tx, hour_pack_id.as_ref())
+        .await?
+        .ok_or(Error::HourPackNotFound)?;
+
+    let product_id = ProductId::from(hour_pack.id);
+
+    match body.action {
+        ModifyAction::Confirm => {
+            if confirm_hour_pack(&mut tx, &now, &hour_pack.id).await? {
+                confirm_cash_payment(&mut tx, &now, &product_id).await?;
+                create_hour_balance_entry(
+                    &mut tx,
+                    &now,
+                    &hour_pack.customer_id,
+                    hour_pack.minutes,
+                    Some(&hour_pack.id),
+                    None,
+                )
+                .await?;
+            }
+        }
+        ModifyAction::Refund => {
+            if is_in_effect(&hour_pack.canceled_at, &now) {
+                refund_cash_payment(&mut tx, &now, &product_id).await?;
+            }
+        }
+        ModifyAction::Cancel => {
+            if !is_in_effect(&hour_pack.canceled_at, &now) {
+                // Take back the credited hours, unless they have already been used
+                if is_in_effect(&hour_pack.confirmed_at, &now) {
+                    if get_hour_balance(&mut tx, &hour_pack.customer_id).await? < hour_pack.minutes
+                    {
+                        return Err(Error::InsufficientHourBalance);
+                    }
+
+                    create_hour_balance_entry(
+                        &mut tx,
+                        &now,
+                        &hour_pack.customer_id,
+                        -hour_pack.minutes,
+                        Some(&hour_pack.id),
+                        None,
+                    )
+                    .await?;
+                }
+
+                cancel_hour_pack(&mut tx, &now, &hour_pack.id).await?;
+            }
+        }
+    }
+
+    let hour_pack = get_hour_pack(&mut tx, hour_pack_id.as_ref())
+        .await?
+        .ok_or(Error::HourPackNotFound)?;
+
+    let cash_tx = get_cash_transaction(&mut tx, &product_id).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(ModifyHourPackResponse {
+        hour_pack: HourPack::convert(hour_pack, &timezone_config, &now)?,
+        cash_transaction: if let Some(cash_tx) = cash_tx {
+            Some(CashTransaction::convert(cash_tx, &timezone_config, &now)?)
+        } else {
+            None
+        },
+    }))
+}
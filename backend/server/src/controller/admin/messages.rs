@@ -0,0 +1,108 @@
+use actix_web::web;
+use dxe_data::queries::messaging::{
+    get_failed_outbox_messages, get_message_delivery_logs, get_outbox_message,
+    get_outbox_message_fallbacks, requeue_outbox_message,
+};
+use dxe_types::OutboxMessageId;
+use sqlx::SqlitePool;
+
+use crate::config::TimeZoneConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{MessageDeliveryLog, OutboxMessage};
+use crate::models::handlers::admin::{
+    GetFailedMessagesResponse, GetMessageResponse, ReloadMessageTemplatesResponse,
+    ResendMessageResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::services::messaging::templates::{
+    EmailTemplate, MessageTemplate, MessageTemplateRegistry,
+};
+
+pub async fn get(
+    now: Now,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetFailedMessagesResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let messages = get_failed_outbox_messages(&mut connection)
+        .await?
+        .into_iter()
+        .map(|v| OutboxMessage::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetFailedMessagesResponse { messages }))
+}
+
+pub async fn get_message(
+    now: Now,
+    message_id: web::Path<OutboxMessageId>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetMessageResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let message = get_outbox_message(&mut connection, &message_id)
+        .await?
+        .ok_or(Error::MessageNotFound)?;
+
+    let delivery_logs = get_message_delivery_logs(&mut connection, &message_id)
+        .await?
+        .into_iter()
+        .map(|v| MessageDeliveryLog::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fallbacks = get_outbox_message_fallbacks(&mut connection, &message_id)
+        .await?
+        .into_iter()
+        .map(|v| OutboxMessage::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetMessageResponse {
+        message: OutboxMessage::convert(message, &timezone_config, &now)?,
+        delivery_logs,
+        fallbacks,
+    }))
+}
+
+pub async fn resend(
+    now: Now,
+    message_id: web::Path<OutboxMessageId>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<ResendMessageResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    // Messages which are still being retried or already delivered are left as they are
+    requeue_outbox_message(&mut tx, &now, &message_id).await?;
+
+    let message = get_outbox_message(&mut tx, &message_id)
+        .await?
+        .ok_or(Error::MessageNotFound)?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(ResendMessageResponse {
+        message: OutboxMessage::convert(message, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn reload_templates(
+    message_templates: web::Data<MessageTemplateRegistry>,
+) -> Result<web::Json<ReloadMessageTemplatesResponse>, Error> {
+    message_templates
+        .reload()
+        .map_err(|e| Error::InvalidMessageTemplate(e.to_string()))?;
+
+    Ok(web::Json(ReloadMessageTemplatesResponse {
+        templates: MessageTemplate::ALL
+            .iter()
+            .map(|v| v.name().to_owned())
+            .chain(
+                EmailTemplate::ALL
+                    .iter()
+                    .map(|v| format!("email/{}", v.name())),
+            )
+            .collect(),
+    }))
+}
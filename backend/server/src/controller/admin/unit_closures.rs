@@ -0,0 +1,101 @@
+use actix_web::web;
+use dxe_data::queries::booking::get_bookings_by_unit_id;
+use dxe_data::queries::unit::{
+    create_unit_closure, delete_unit_closure, get_unit_closure, get_unit_closures_by_unit_id,
+};
+use dxe_types::UnitClosureId;
+use sqlx::SqlitePool;
+
+use crate::config::TimeZoneConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::UnitClosure;
+use crate::models::handlers::admin::{
+    CreateUnitClosureRequest, CreateUnitClosureResponse, GetUnitClosuresQuery,
+    GetUnitClosuresResponse,
+};
+use crate::models::{Error, IntoView};
+
+pub async fn get(
+    now: Now,
+    query: web::Query<GetUnitClosuresQuery>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetUnitClosuresResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let closures =
+        get_unit_closures_by_unit_id(&mut connection, &now, &query.unit_id, Some(*now)).await?;
+
+    Ok(web::Json(GetUnitClosuresResponse {
+        closures: closures
+            .into_iter()
+            .map(|v| UnitClosure::convert(v, &timezone_config, &now))
+            .collect::<Result<_, _>>()?,
+    }))
+}
+
+pub async fn post(
+    now: Now,
+    body: web::Json<CreateUnitClosureRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<CreateUnitClosureResponse>, Error> {
+    let time_from = body.time_from.to_utc();
+    let time_to = body.time_to.to_utc();
+
+    let mut tx = database.begin().await?;
+
+    // Bookings have to be canceled or moved before the unit can be closed
+    let conflicts = get_bookings_by_unit_id(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &time_from,
+        &time_to,
+        false,
+        true,
+    )
+    .await?
+    .into_iter()
+    .filter(|v| v.time_to > time_from)
+    .count();
+    if conflicts > 0 {
+        return Err(Error::UnitClosureConflict);
+    }
+
+    let id = create_unit_closure(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &time_from,
+        &time_to,
+        &body.reason,
+    )
+    .await?;
+
+    let closure = get_unit_closure(&mut tx, &id)
+        .await?
+        .ok_or(Error::UnitClosureNotFound)?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(CreateUnitClosureResponse {
+        closure: UnitClosure::convert(closure, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn delete(
+    now: Now,
+    unit_closure_id: web::Path<UnitClosureId>,
+    database: web::Data<SqlitePool>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut tx = database.begin().await?;
+
+    if !delete_unit_closure(&mut tx, &now, unit_closure_id.as_ref()).await? {
+        return Err(Error::UnitClosureNotFound);
+    }
+
+    tx.commit().await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
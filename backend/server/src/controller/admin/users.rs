@@ -1,11 +1,12 @@
 use actix_web::web;
-use dxe_data::queries::user::get_users;
+use dxe_data::queries::user::{get_user_by_id, get_users, merge_users};
+use dxe_types::UserId;
 use sqlx::SqlitePool;
 
 use crate::config::TimeZoneConfig;
 use crate::middleware::datetime_injector::Now;
 use crate::models::entities::SelfUser;
-use crate::models::handlers::admin::GetUsersResponse;
+use crate::models::handlers::admin::{GetUsersResponse, MergeUsersRequest, MergeUsersResponse};
 use crate::models::{Error, IntoView};
 
 pub async fn get(
@@ -24,3 +25,36 @@ pub async fn get(
             .collect::<Result<_, _>>()?,
     }))
 }
+
+pub async fn merge(
+    now: Now,
+    user_id: web::Path<UserId>,
+    body: web::Json<MergeUsersRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<MergeUsersResponse>, Error> {
+    if *user_id == body.source_user_id {
+        return Err(Error::CannotMergeSameUser);
+    }
+
+    let mut tx = database.begin().await?;
+
+    get_user_by_id(&mut tx, &user_id, &now)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    get_user_by_id(&mut tx, &body.source_user_id, &now)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    merge_users(&mut tx, &now, &body.source_user_id, &user_id).await?;
+
+    let user = get_user_by_id(&mut tx, &user_id, &now)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(MergeUsersResponse {
+        user: SelfUser::convert(user, &timezone_config, &now)?,
+    }))
+}
@@ -0,0 +1,200 @@
+use actix_web::web;
+use dxe_data::queries::webhook::{
+    create_webhook_subscription, delete_webhook_subscription, get_webhook_deliveries,
+    get_webhook_delivery, get_webhook_delivery_logs, get_webhook_subscription,
+    get_webhook_subscriptions, requeue_webhook_delivery, update_webhook_subscription,
+};
+use dxe_types::{WebhookDeliveryId, WebhookSubscriptionId};
+use sqlx::SqlitePool;
+
+use crate::config::TimeZoneConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{WebhookDelivery, WebhookDeliveryLog, WebhookSubscription};
+use crate::models::handlers::admin::{
+    GetWebhookDeliveriesQuery, GetWebhookDeliveriesResponse, GetWebhookDeliveryResponse,
+    GetWebhookSubscriptionsResponse, WebhookSubscriptionRequest, WebhookSubscriptionResponse,
+};
+use crate::models::{Error, IntoView};
+
+fn validate(body: &WebhookSubscriptionRequest) -> Result<(), Error> {
+    if !matches!(body.url.scheme(), "http" | "https") {
+        return Err(Error::MissingField("url"));
+    }
+    if body.secret.trim().is_empty() {
+        return Err(Error::MissingField("secret"));
+    }
+    if body.event_types.is_empty() {
+        return Err(Error::MissingField("eventTypes"));
+    }
+
+    Ok(())
+}
+
+pub async fn get(
+    now: Now,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetWebhookSubscriptionsResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let subscriptions = get_webhook_subscriptions(&mut connection, &now)
+        .await?
+        .into_iter()
+        .map(|v| WebhookSubscription::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetWebhookSubscriptionsResponse { subscriptions }))
+}
+
+pub async fn post(
+    now: Now,
+    body: web::Json<WebhookSubscriptionRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<WebhookSubscriptionResponse>, Error> {
+    validate(&body)?;
+
+    let mut tx = database.begin().await?;
+
+    let id = create_webhook_subscription(
+        &mut tx,
+        &now,
+        body.url.as_str(),
+        body.secret.trim(),
+        &body.event_types,
+    )
+    .await?;
+
+    let subscription = get_webhook_subscription(&mut tx, &now, &id)
+        .await?
+        .ok_or(Error::WebhookSubscriptionNotFound)?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(WebhookSubscriptionResponse {
+        subscription: WebhookSubscription::convert(subscription, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn put(
+    now: Now,
+    subscription_id: web::Path<WebhookSubscriptionId>,
+    body: web::Json<WebhookSubscriptionRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<WebhookSubscriptionResponse>, Error> {
+    validate(&body)?;
+
+    let mut tx = database.begin().await?;
+
+    if !update_webhook_subscription(
+        &mut tx,
+        &subscription_id,
+        body.url.as_str(),
+        body.secret.trim(),
+        &body.event_types,
+    )
+    .await?
+    {
+        return Err(Error::WebhookSubscriptionNotFound);
+    }
+
+    let subscription = get_webhook_subscription(&mut tx, &now, &subscription_id)
+        .await?
+        .ok_or(Error::WebhookSubscriptionNotFound)?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(WebhookSubscriptionResponse {
+        subscription: WebhookSubscription::convert(subscription, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn delete(
+    now: Now,
+    subscription_id: web::Path<WebhookSubscriptionId>,
+    database: web::Data<SqlitePool>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut tx = database.begin().await?;
+
+    if !delete_webhook_subscription(&mut tx, &now, &subscription_id).await? {
+        return Err(Error::WebhookSubscriptionNotFound);
+    }
+
+    tx.commit().await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
+
+pub async fn get_deliveries(
+    now: Now,
+    subscription_id: web::Path<WebhookSubscriptionId>,
+    query: web::Query<GetWebhookDeliveriesQuery>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetWebhookDeliveriesResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let limit = query.limit.unwrap_or(50);
+
+    let deliveries = get_webhook_deliveries(&mut connection, &subscription_id, limit)
+        .await?
+        .into_iter()
+        .map(|v| WebhookDelivery::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetWebhookDeliveriesResponse { deliveries }))
+}
+
+pub async fn get_delivery(
+    now: Now,
+    delivery_id: web::Path<WebhookDeliveryId>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetWebhookDeliveryResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let delivery = get_webhook_delivery(&mut connection, &delivery_id)
+        .await?
+        .ok_or(Error::WebhookDeliveryNotFound)?;
+
+    let delivery_logs = get_webhook_delivery_logs(&mut connection, &delivery_id)
+        .await?
+        .into_iter()
+        .map(|v| WebhookDeliveryLog::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetWebhookDeliveryResponse {
+        delivery: WebhookDelivery::convert(delivery, &timezone_config, &now)?,
+        delivery_logs,
+    }))
+}
+
+pub async fn redeliver(
+    now: Now,
+    delivery_id: web::Path<WebhookDeliveryId>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetWebhookDeliveryResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    // Deliveries which are still being retried or already delivered are left as they are
+    requeue_webhook_delivery(&mut tx, &now, &delivery_id).await?;
+
+    let delivery = get_webhook_delivery(&mut tx, &delivery_id)
+        .await?
+        .ok_or(Error::WebhookDeliveryNotFound)?;
+
+    let delivery_logs = get_webhook_delivery_logs(&mut tx, &delivery_id)
+        .await?
+        .into_iter()
+        .map(|v| WebhookDeliveryLog::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(GetWebhookDeliveryResponse {
+        delivery: WebhookDelivery::convert(delivery, &timezone_config, &now)?,
+        delivery_logs,
+    }))
+}
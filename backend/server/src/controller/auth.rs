@@ -1,14 +1,33 @@
 mod handle_auth;
+mod handle_signup;
 mod kakao_auth;
 mod kakao_register;
 mod logout;
+mod oidc_auth;
+mod oidc_register;
+mod password_reset;
 
 use actix_web::web;
 
 pub fn scope() -> actix_web::Scope {
     web::scope("/auth")
+        .service(web::resource("/kakao/state").route(web::get().to(kakao_auth::get_state)))
         .service(web::resource("/kakao/redirect").route(web::get().to(kakao_auth::redirect)))
         .service(web::resource("/kakao").route(web::post().to(kakao_register::post)))
+        .service(web::resource("/oidc").route(web::get().to(oidc_auth::get_providers)))
+        .service(
+            web::resource("/oidc/{provider}/authorize").route(web::get().to(oidc_auth::authorize)),
+        )
+        .service(
+            web::resource("/oidc/{provider}/redirect").route(web::get().to(oidc_auth::redirect)),
+        )
+        .service(web::resource("/oidc/{provider}").route(web::post().to(oidc_register::post)))
         .service(web::resource("/logout").route(web::get().to(logout::redirect)))
         .service(web::resource("/login").route(web::post().to(handle_auth::post)))
+        .service(web::resource("/signup").route(web::post().to(handle_signup::post)))
+        .service(web::resource("/password-reset").route(web::post().to(password_reset::post)))
+        .service(
+            web::resource("/password-reset/{token}")
+                .route(web::post().to(password_reset::post_token)),
+        )
 }
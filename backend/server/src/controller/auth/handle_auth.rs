@@ -1,39 +1,62 @@
 use actix_jwt_auth_middleware::TokenSigner;
 use actix_web::body::BoxBody;
-use actix_web::{HttpResponse, web};
-use argon2::Argon2;
-use argon2::password_hash::{PasswordHash, PasswordVerifier};
+use actix_web::{HttpRequest, HttpResponse, web};
 use dxe_data::queries::identity::get_user_plain_credential_with_handle;
 use dxe_data::queries::user::is_administrator;
+use dxe_types::CredentialAttemptKind;
 use jwt_compact::alg::Ed25519;
 use sqlx::SqlitePool;
 
-use crate::config::UrlConfig;
+use crate::config::{CredentialConfig, UrlConfig};
 use crate::middleware::datetime_injector::Now;
 use crate::models::Error;
 use crate::models::handlers::auth::{HandleAuthQuery, HandleAuthRequest, HandleAuthResponse};
+use crate::services::credential::{clear_attempts, login_attempt_key, register_attempt};
 use crate::session::UserSession;
+use crate::utils::client_address;
+use crate::utils::credential::verify_password;
 
 pub async fn post(
     now: Now,
+    request: HttpRequest,
     query: web::Query<HandleAuthQuery>,
     body: web::Json<HandleAuthRequest>,
     database: web::Data<SqlitePool>,
     token_signer: web::Data<TokenSigner<UserSession, Ed25519>>,
     url_config: web::Data<UrlConfig>,
+    credential_config: web::Data<CredentialConfig>,
 ) -> Result<HttpResponse<BoxBody>, Error> {
+    let client_address = client_address(&request, &credential_config.trusted_proxies);
+    let attempt_key = login_attempt_key(&body.handle, &client_address);
+
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::LoginFromAddress,
+        &client_address,
+    )
+    .await?;
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::Login,
+        &attempt_key,
+    )
+    .await?;
+
     let mut tx = database.begin().await?;
 
     let (user, cred) = get_user_plain_credential_with_handle(&mut tx, &now, &body.handle)
         .await?
         .ok_or(Error::AuthFailed)?;
 
-    let argon2 = Argon2::default();
-    let hash = PasswordHash::new(&cred.argon2_password).map_err(|_| Error::AuthFailed)?;
+    if !verify_password(&body.password, &cred.argon2_password) {
+        return Err(Error::AuthFailed);
+    }
 
-    argon2
-        .verify_password(body.password.as_bytes(), &hash)
-        .map_err(|_| Error::AuthFailed)?;
+    clear_attempts(&database, CredentialAttemptKind::Login, &attempt_key).await?;
 
     let session = UserSession {
         user_id: user.id,
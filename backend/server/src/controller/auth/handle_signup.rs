@@ -0,0 +1,104 @@
+use actix_jwt_auth_middleware::TokenSigner;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use dxe_data::queries::identity::create_user_plain_credential;
+use dxe_data::queries::user::{create_user, get_user_by_foreign_id};
+use dxe_types::{CredentialAttemptKind, IdentityProvider};
+use jwt_compact::alg::Ed25519;
+use sqlx::SqlitePool;
+
+use crate::config::{CredentialConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::auth;
+use crate::services::credential::register_attempt;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::session::UserSession;
+use crate::utils::client_address;
+use crate::utils::credential::{hash_password, is_valid_handle, is_valid_password};
+
+pub async fn post(
+    now: Now,
+    request: HttpRequest,
+    body: web::Json<auth::HandleSignupRequest>,
+    database: web::Data<SqlitePool>,
+    token_signer: web::Data<TokenSigner<UserSession, Ed25519>>,
+    notification_sender: web::Data<NotificationSender>,
+    url_config: web::Data<UrlConfig>,
+    credential_config: web::Data<CredentialConfig>,
+) -> Result<impl Responder, Error> {
+    let handle = body.handle.trim();
+    let name = body.name.trim();
+    if !is_valid_handle(handle) {
+        return Err(Error::InvalidHandle);
+    }
+    if !is_valid_password(&body.password) {
+        return Err(Error::InvalidPassword);
+    }
+    if name.is_empty() {
+        return Err(Error::MissingField("name"));
+    }
+
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::Signup,
+        &client_address(&request, &credential_config.trusted_proxies),
+    )
+    .await?;
+
+    let argon2_password =
+        hash_password(&body.password).map_err(|e| Error::Internal(e.to_string().into()))?;
+
+    let mut tx = database.begin().await?;
+
+    if get_user_by_foreign_id(&mut tx, IdentityProvider::Handle, handle, *now)
+        .await?
+        .is_some()
+    {
+        return Err(Error::HandleAlreadyExists);
+    }
+
+    let user_id = create_user(
+        &mut tx,
+        *now,
+        IdentityProvider::Handle,
+        handle,
+        name,
+        body.license_plate_number
+            .as_deref()
+            .filter(|v| !v.is_empty()),
+    )
+    .await?;
+    create_user_plain_credential(&mut tx, &user_id, handle, &argon2_password).await?;
+
+    tx.commit().await?;
+
+    notification_sender.enqueue(
+        Priority::Low,
+        format!("New member joined with handle {handle}: {name}"),
+    );
+
+    let session = UserSession {
+        user_id,
+        is_administrator: false,
+    };
+
+    let mut access_cookie = token_signer.create_access_cookie(&session)?;
+    let mut refresh_cookie = token_signer.create_refresh_cookie(&session)?;
+
+    access_cookie.set_http_only(true);
+    access_cookie.set_path("/");
+    refresh_cookie.set_http_only(true);
+    refresh_cookie.set_path("/");
+
+    if let Some(domain) = url_config.base_url.domain() {
+        access_cookie.set_domain(domain);
+        refresh_cookie.set_domain(domain);
+    }
+
+    Ok(HttpResponse::Ok()
+        .cookie(access_cookie)
+        .cookie(refresh_cookie)
+        .json(serde_json::json!({})))
+}
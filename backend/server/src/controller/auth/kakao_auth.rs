@@ -6,13 +6,12 @@ use actix_web::body::BoxBody;
 use actix_web::cookie::Cookie;
 use actix_web::cookie::time::OffsetDateTime;
 use actix_web::http::header::LOCATION;
-use actix_web::{HttpResponse, ResponseError, web};
-use dxe_data::queries::user::{get_user_by_foreign_id, is_administrator};
+use actix_web::{HttpRequest, HttpResponse, ResponseError, web};
+use dxe_data::queries::user::{get_user_by_foreign_id, is_administrator, update_user_phone_number};
 use dxe_extern::kakao::client as kakao_client;
 use dxe_extern::kakao::models::AccountPropertyKey;
 use dxe_types::IdentityProvider;
 use jwt_compact::alg::Ed25519;
-use serde::Deserialize;
 use sqlx::SqlitePool;
 
 use crate::config::{KakaoAuthConfig, UrlConfig};
@@ -21,17 +20,27 @@ use crate::models::handlers::auth;
 use crate::services::notification::{NotificationSender, Priority};
 use crate::session::UserSession;
 use crate::utils::aes::{AesCrypto, Error as AesError};
+use crate::utils::normalize_phone_number;
+use crate::utils::oauth::{LinkToken, OAuthSession, OAuthState, kakao_redirect_url};
 
-#[derive(Deserialize, Default)]
-#[serde(rename_all = "camelCase")]
-pub struct KakaoAuthState {
-    redirect_to: Option<String>,
-    #[serde(default)]
-    transparent: bool,
+/// Starts signing in with the Kakao SDK, which is given the returned state.
+pub async fn get_state(
+    query: web::Query<auth::KakaoAuthStateQuery>,
+    aes_crypto: web::Data<AesCrypto>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse<BoxBody>, Error> {
+    let session = OAuthSession::new(None);
+
+    Ok(HttpResponse::Ok()
+        .cookie(session.cookie(&aes_crypto, &url_config)?)
+        .json(auth::KakaoAuthStateResponse {
+            state: session.state(query.redirect_to.clone(), query.transparent),
+        }))
 }
 
 pub async fn redirect(
     now: Now,
+    request: HttpRequest,
     query: web::Query<auth::KakaoAuthRedirectQuery>,
     kakao_auth: web::Data<KakaoAuthConfig>,
     database: web::Data<SqlitePool>,
@@ -43,7 +52,7 @@ pub async fn redirect(
     let state = query
         .state
         .as_ref()
-        .and_then(|v| serde_json::from_str::<KakaoAuthState>(v).ok())
+        .and_then(|v| serde_json::from_str::<OAuthState>(v).ok())
         .unwrap_or_default();
 
     let redirect_to = state.redirect_to.clone().unwrap_or("/".to_owned());
@@ -55,16 +64,38 @@ pub async fn redirect(
             .insert_header((LOCATION, redirect_to))
             .finish())
     } else if let Some(code) = &query.code {
-        let mut redirect_url = url_config.base_url.clone();
-        redirect_url.set_path("/api/auth/kakao/redirect");
+        let session = OAuthSession::from_request(&request, &aes_crypto, &state)
+            .ok_or(Error::InvalidSession)?;
 
-        let token =
-            kakao_client::get_oauth_token(kakao_auth.get_ref(), code, redirect_url.as_str())
-                .await?;
+        let token = kakao_client::get_oauth_token(
+            kakao_auth.get_ref(),
+            code,
+            kakao_redirect_url(&url_config).as_str(),
+        )
+        .await?;
+
+        // Kept for `/user/me/identity-link/kakao` of the user who started linking
+        if let Some(user_id) = session.link_user_id {
+            let link_token = LinkToken {
+                user_id,
+                provider: IdentityProvider::Kakao,
+                access_token: token.access_token,
+            };
+
+            return Ok(HttpResponse::Found()
+                .insert_header((LOCATION, redirect_to))
+                .cookie(link_token.cookie(&aes_crypto, &url_config)?)
+                .cookie(OAuthSession::removal_cookie(&url_config))
+                .finish());
+        }
 
         let me = kakao_client::get_me(
             &token,
-            HashSet::from([AccountPropertyKey::Profile, AccountPropertyKey::Email]),
+            HashSet::from([
+                AccountPropertyKey::Profile,
+                AccountPropertyKey::Email,
+                AccountPropertyKey::PhoneNumber,
+            ]),
         )
         .await?;
 
@@ -75,6 +106,11 @@ pub async fn redirect(
         } else {
             String::new()
         };
+        let phone_number = me
+            .kakao_account
+            .as_ref()
+            .and_then(|v| v.phone_number.as_deref())
+            .and_then(normalize_phone_number);
         let foreign_id = me.id.to_string();
 
         let mut tx = database.begin().await.map_err(dxe_data::Error::Sqlx)?;
@@ -88,6 +124,13 @@ pub async fn redirect(
                 is_administrator: is_administrator(&mut tx, &user.id).await?,
             };
 
+            // Kept up to date for sending SMS in place of AlimTalk
+            if let Some(phone_number) = &phone_number {
+                update_user_phone_number(&mut tx, &now, &user.id, phone_number).await?;
+            }
+
+            tx.commit().await.map_err(dxe_data::Error::Sqlx)?;
+
             let mut access_cookie = token_signer
                 .create_access_cookie(&session)
                 .map_err(Error::Jwt)?;
@@ -109,6 +152,7 @@ pub async fn redirect(
                 .insert_header((LOCATION, redirect_to))
                 .cookie(access_cookie)
                 .cookie(refresh_cookie)
+                .cookie(OAuthSession::removal_cookie(&url_config))
                 .finish())
         } else {
             let encrypted_access_token = aes_crypto.encrypt(None, token.access_token.as_bytes())?;
@@ -124,16 +168,16 @@ pub async fn redirect(
 
             notification_sender.enqueue(Priority::Low, format!("Kakao user {name} authenticated."));
 
+            let location = format!(
+                "/register?name={}&redirect_to={}",
+                urlencoding::encode(&name),
+                redirect_to,
+            );
+
             Ok(HttpResponse::Found()
-                .insert_header((
-                    LOCATION,
-                    format!(
-                        "/register?name={}&redirect_to={}",
-                        urlencoding::encode(&name),
-                        redirect_to,
-                    ),
-                ))
+                .insert_header((LOCATION, location))
                 .cookie(cookie_bearer.finish())
+                .cookie(OAuthSession::removal_cookie(&url_config))
                 .finish())
         }
     } else if let Some(error) = &query.error {
@@ -158,6 +202,8 @@ pub async fn redirect(
 pub enum Error {
     #[error("Kakao API error: {0}")]
     Kakao(#[from] kakao_client::Error),
+    #[error("Sign-in was not started from this browser or has expired")]
+    InvalidSession,
     #[error("Data error: {0}")]
     Data(#[from] dxe_data::Error),
     #[error("Error generating/validating token: {0}")]
@@ -1,6 +1,9 @@
+use std::collections::HashSet;
+
 use actix_jwt_auth_middleware::TokenSigner;
 use actix_web::{HttpRequest, HttpResponse, Responder, web};
-use dxe_data::queries::user::create_user;
+use dxe_data::queries::user::{create_user, update_user_phone_number};
+use dxe_extern::kakao::models::AccountPropertyKey;
 use dxe_extern::kakao::{BearerToken, client as kakao_client};
 use dxe_types::IdentityProvider;
 use jwt_compact::alg::Ed25519;
@@ -13,6 +16,7 @@ use crate::models::handlers::auth;
 use crate::services::notification::{NotificationSender, Priority};
 use crate::session::UserSession;
 use crate::utils::aes::AesCrypto;
+use crate::utils::normalize_phone_number;
 
 struct SimpleBearerToken(String);
 
@@ -41,7 +45,11 @@ pub async fn post(
         .map_err(|_| Error::InvalidKakaoAccessToken)?;
     let bearer_token = SimpleBearerToken(token);
 
-    let me = kakao_client::get_me(&bearer_token, Default::default()).await?;
+    let me = kakao_client::get_me(
+        &bearer_token,
+        HashSet::from([AccountPropertyKey::PhoneNumber]),
+    )
+    .await?;
 
     let mut tx = database.begin().await?;
 
@@ -65,6 +73,15 @@ pub async fn post(
     )
     .await?;
 
+    if let Some(phone_number) = me
+        .kakao_account
+        .as_ref()
+        .and_then(|v| v.phone_number.as_deref())
+        .and_then(normalize_phone_number)
+    {
+        update_user_phone_number(&mut tx, &now, &user_id, &phone_number).await?;
+    }
+
     tx.commit().await.unwrap();
 
     notification_sender.enqueue(
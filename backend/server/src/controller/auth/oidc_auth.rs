@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use actix_jwt_auth_middleware::TokenSigner;
+use actix_web::body::BoxBody;
+use actix_web::cookie::Cookie;
+use actix_web::cookie::time::OffsetDateTime;
+use actix_web::http::header::LOCATION;
+use actix_web::{HttpRequest, HttpResponse, ResponseError, web};
+use dxe_data::queries::user::{get_user_by_foreign_id, is_administrator, update_user_phone_number};
+use dxe_extern::oidc;
+use dxe_types::IdentityProvider;
+use jwt_compact::alg::Ed25519;
+use sqlx::SqlitePool;
+
+use crate::config::{OidcProvidersConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::handlers::auth;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::session::UserSession;
+use crate::utils::aes::{AesCrypto, Error as AesError};
+use crate::utils::normalize_phone_number;
+use crate::utils::oauth::{LinkToken, OAuthSession, OAuthState, oidc_redirect_url};
+
+pub async fn get_providers(
+    oidc_providers: web::Data<OidcProvidersConfig>,
+) -> web::Json<auth::GetOidcProvidersResponse> {
+    let mut providers = oidc_providers
+        .0
+        .iter()
+        .map(|(provider, config)| auth::OidcProvider {
+            provider: *provider,
+            display_name: config.display_name.clone(),
+        })
+        .collect::<Vec<_>>();
+    providers.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    web::Json(auth::GetOidcProvidersResponse { providers })
+}
+
+pub async fn authorize(
+    provider: web::Path<IdentityProvider>,
+    query: web::Query<auth::OidcAuthorizeQuery>,
+    oidc_providers: web::Data<OidcProvidersConfig>,
+    aes_crypto: web::Data<AesCrypto>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse<BoxBody>, Error> {
+    let provider = provider.into_inner();
+    let config = oidc_providers
+        .0
+        .get(&provider)
+        .ok_or(Error::NotSupported(provider))?;
+
+    let session = OAuthSession::new(None);
+    let url = oidc::authorization_url(
+        config,
+        oidc_redirect_url(&url_config, provider).as_str(),
+        &session.state(query.redirect_to.clone(), false),
+        &session.code_challenge(),
+    );
+
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, url.to_string()))
+        .cookie(session.cookie(&aes_crypto, &url_config)?)
+        .finish())
+}
+
+pub async fn redirect(
+    now: Now,
+    request: HttpRequest,
+    provider: web::Path<IdentityProvider>,
+    query: web::Query<auth::OidcAuthRedirectQuery>,
+    oidc_providers: web::Data<OidcProvidersConfig>,
+    database: web::Data<SqlitePool>,
+    token_signer: web::Data<TokenSigner<UserSession, Ed25519>>,
+    aes_crypto: web::Data<AesCrypto>,
+    url_config: web::Data<UrlConfig>,
+    notification_sender: web::Data<NotificationSender>,
+) -> Result<HttpResponse<BoxBody>, Error> {
+    let provider = provider.into_inner();
+    let config = oidc_providers
+        .0
+        .get(&provider)
+        .ok_or(Error::NotSupported(provider))?;
+
+    let state = query
+        .state
+        .as_ref()
+        .and_then(|v| serde_json::from_str::<OAuthState>(v).ok())
+        .unwrap_or_default();
+
+    let redirect_to = state.redirect_to.clone().unwrap_or("/".to_owned());
+
+    if let Some(code) = &query.code {
+        let session = OAuthSession::from_request(&request, &aes_crypto, &state)
+            .ok_or(Error::InvalidSession)?;
+
+        let token = oidc::get_oauth_token(
+            config,
+            code,
+            oidc_redirect_url(&url_config, provider).as_str(),
+            &session.code_verifier,
+        )
+        .await?;
+
+        // Kept for `/user/me/identity-link/oidc/{provider}` of the user who started linking
+        if let Some(user_id) = session.link_user_id {
+            let link_token = LinkToken {
+                user_id,
+                provider,
+                access_token: token.access_token,
+            };
+
+            return Ok(HttpResponse::Found()
+                .insert_header((LOCATION, redirect_to))
+                .cookie(link_token.cookie(&aes_crypto, &url_config)?)
+                .cookie(OAuthSession::removal_cookie(&url_config))
+                .finish());
+        }
+
+        let user_info = oidc::get_user_info(config, &token).await?;
+        let name = user_info.name.clone().unwrap_or_default();
+        let phone_number = user_info
+            .phone_number
+            .as_deref()
+            .and_then(normalize_phone_number);
+
+        let mut tx = database.begin().await.map_err(dxe_data::Error::Sqlx)?;
+
+        let user = get_user_by_foreign_id(&mut tx, provider, &user_info.subject, *now).await?;
+
+        if let Some(user) = user {
+            let session = UserSession {
+                user_id: user.id,
+                is_administrator: is_administrator(&mut tx, &user.id).await?,
+            };
+
+            if let Some(phone_number) = &phone_number {
+                update_user_phone_number(&mut tx, &now, &user.id, phone_number).await?;
+            }
+
+            tx.commit().await.map_err(dxe_data::Error::Sqlx)?;
+
+            let mut access_cookie = token_signer
+                .create_access_cookie(&session)
+                .map_err(Error::Jwt)?;
+            let mut refresh_cookie = token_signer
+                .create_refresh_cookie(&session)
+                .map_err(Error::Jwt)?;
+
+            access_cookie.set_http_only(true);
+            access_cookie.set_path("/");
+            refresh_cookie.set_http_only(true);
+            refresh_cookie.set_path("/");
+
+            if let Some(domain) = url_config.base_url.domain() {
+                access_cookie.set_domain(domain);
+                refresh_cookie.set_domain(domain);
+            }
+
+            Ok(HttpResponse::Found()
+                .insert_header((LOCATION, redirect_to))
+                .cookie(access_cookie)
+                .cookie(refresh_cookie)
+                .cookie(OAuthSession::removal_cookie(&url_config))
+                .finish())
+        } else {
+            let encrypted_access_token = aes_crypto.encrypt(None, token.access_token.as_bytes())?;
+            let mut cookie_bearer = Cookie::build("oidc_bearer_token", encrypted_access_token)
+                .path("/")
+                .expires(OffsetDateTime::now_utc() + Duration::from_secs(180))
+                .http_only(true)
+                .secure(true);
+
+            if let Some(domain) = url_config.base_url.domain() {
+                cookie_bearer = cookie_bearer.domain(domain);
+            }
+
+            notification_sender.enqueue(
+                Priority::Low,
+                format!("{} user {name} authenticated.", config.display_name),
+            );
+
+            let location = format!(
+                "/register?provider={}&name={}&redirect_to={}",
+                provider.name(),
+                urlencoding::encode(&name),
+                redirect_to,
+            );
+
+            Ok(HttpResponse::Found()
+                .insert_header((LOCATION, location))
+                .cookie(cookie_bearer.finish())
+                .cookie(OAuthSession::removal_cookie(&url_config))
+                .finish())
+        }
+    } else if let Some(error) = &query.error {
+        let error_message = query.error_description.clone().unwrap_or_default();
+        Ok(HttpResponse::TemporaryRedirect()
+            .insert_header((
+                LOCATION,
+                format!(
+                    "/error/?error_category=oidc_auth&message={}&oidc_error={}",
+                    urlencoding::encode(&error_message),
+                    urlencoding::encode(error),
+                ),
+            ))
+            .finish())
+    } else {
+        Ok(HttpResponse::TemporaryRedirect()
+            .insert_header((LOCATION, "/".to_owned()))
+            .finish())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Identity provider {0:?} is not configured")]
+    NotSupported(IdentityProvider),
+    #[error("Sign-in was not started from this browser or has expired")]
+    InvalidSession,
+    #[error("OAuth2/OIDC error: {0}")]
+    Oidc(#[from] oidc::Error),
+    #[error("Data error: {0}")]
+    Data(#[from] dxe_data::Error),
+    #[error("Error generating/validating token: {0}")]
+    Jwt(actix_jwt_auth_middleware::AuthError),
+    #[error("Error encrypting/decrypting cookie data: {0}")]
+    Aes(#[from] AesError),
+}
+
+impl ResponseError for Error {
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        let message = format!("{self}");
+        let url = format!(
+            "/error/?error_category=oidc_auth&message={}",
+            urlencoding::encode(message.as_str())
+        );
+
+        log::error!("Could not authenticate with OAuth2/OIDC provider: {self}");
+
+        HttpResponse::TemporaryRedirect()
+            .insert_header((LOCATION, url))
+            .finish()
+    }
+}
@@ -0,0 +1,117 @@
+use actix_jwt_auth_middleware::TokenSigner;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use dxe_data::queries::user::{create_user, update_user_phone_number};
+use dxe_extern::oidc::{self, BearerToken};
+use dxe_types::IdentityProvider;
+use jwt_compact::alg::Ed25519;
+use sqlx::SqlitePool;
+
+use crate::config::{OidcProvidersConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::auth;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::session::UserSession;
+use crate::utils::aes::AesCrypto;
+use crate::utils::normalize_phone_number;
+
+struct SimpleBearerToken(String);
+
+impl BearerToken for SimpleBearerToken {
+    fn access_token(&self) -> &str {
+        &self.0
+    }
+}
+
+pub async fn post(
+    now: Now,
+    request: HttpRequest,
+    provider: web::Path<IdentityProvider>,
+    body: web::Json<auth::OidcAuthRegisterRequest>,
+    oidc_providers: web::Data<OidcProvidersConfig>,
+    database: web::Data<SqlitePool>,
+    token_signer: web::Data<TokenSigner<UserSession, Ed25519>>,
+    aes_crypto: web::Data<AesCrypto>,
+    notification_sender: web::Data<NotificationSender>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<impl Responder, Error> {
+    let provider = provider.into_inner();
+    let config = oidc_providers
+        .0
+        .get(&provider)
+        .ok_or(Error::IdentityProviderNotSupported)?;
+
+    let cookie = request
+        .cookie("oidc_bearer_token")
+        .ok_or(Error::InvalidOidcAccessToken)?;
+    let encrypted_token = cookie.value();
+    let token = aes_crypto
+        .decrypt(None, encrypted_token.as_bytes())
+        .map_err(|_| Error::InvalidOidcAccessToken)?;
+    let bearer_token = SimpleBearerToken(token);
+
+    let user_info = oidc::get_user_info(config, &bearer_token).await?;
+
+    let mut tx = database.begin().await?;
+
+    let user_id = create_user(
+        &mut tx,
+        *now,
+        provider,
+        user_info.subject.as_str(),
+        body.name.as_str(),
+        if body
+            .license_plate_number
+            .as_ref()
+            .map(String::len)
+            .unwrap_or_default()
+            > 0
+        {
+            body.license_plate_number.as_deref()
+        } else {
+            None
+        },
+    )
+    .await?;
+
+    if let Some(phone_number) = user_info
+        .phone_number
+        .as_deref()
+        .and_then(normalize_phone_number)
+    {
+        update_user_phone_number(&mut tx, &now, &user_id, &phone_number).await?;
+    }
+
+    tx.commit().await?;
+
+    notification_sender.enqueue(
+        Priority::Low,
+        format!(
+            "New member joined via {}: {}",
+            config.display_name, body.name
+        ),
+    );
+
+    let session = UserSession {
+        user_id,
+        is_administrator: false,
+    };
+
+    let mut access_cookie = token_signer.create_access_cookie(&session)?;
+    let mut refresh_cookie = token_signer.create_refresh_cookie(&session)?;
+
+    access_cookie.set_http_only(true);
+    access_cookie.set_path("/");
+    refresh_cookie.set_http_only(true);
+    refresh_cookie.set_path("/");
+
+    if let Some(domain) = url_config.base_url.domain() {
+        access_cookie.set_domain(domain);
+        refresh_cookie.set_domain(domain);
+    }
+
+    Ok(HttpResponse::Ok()
+        .cookie(access_cookie)
+        .cookie(refresh_cookie)
+        .json(serde_json::json!({})))
+}
@@ -0,0 +1,142 @@
+use actix_web::{HttpRequest, web};
+use chrono::TimeDelta;
+use dxe_data::queries::credential::{consume_password_reset_token, create_password_reset_token};
+use dxe_data::queries::identity::{
+    get_user_plain_credential_with_handle, get_user_plain_credential_with_user_id,
+    update_user_plain_credential_password,
+};
+use dxe_data::queries::user::get_user_email;
+use dxe_types::{CredentialAttemptKind, PasswordResetToken};
+use sqlx::SqlitePool;
+
+use crate::config::{CredentialConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::auth::{RequestPasswordResetRequest, ResetPasswordRequest};
+use crate::services::credential::{clear_attempts, login_attempt_key, register_attempt};
+use crate::services::messaging::MessagingService;
+use crate::utils::client_address;
+use crate::utils::credential::{hash_password, hash_password_reset_token, is_valid_password};
+use crate::utils::datetime::is_in_effect;
+
+/// Sends a one-time reset link to the verified email address of the handle. Responds the same
+/// whether or not the handle exists, so that it can't be used to look up accounts.
+pub async fn post(
+    now: Now,
+    request: HttpRequest,
+    body: web::Json<RequestPasswordResetRequest>,
+    database: web::Data<SqlitePool>,
+    messaging_service: web::Data<MessagingService>,
+    credential_config: web::Data<CredentialConfig>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let email_client = messaging_service
+        .email_client()
+        .ok_or(Error::EmailNotAvailable)?;
+
+    let handle = body.handle.trim();
+    let client_address = client_address(&request, &credential_config.trusted_proxies);
+
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::PasswordResetRequest,
+        &login_attempt_key(handle, &client_address),
+    )
+    .await?;
+
+    let mut tx = database.begin().await?;
+
+    let Some((user, _)) = get_user_plain_credential_with_handle(&mut tx, &now, handle).await?
+    else {
+        return Ok(web::Json(serde_json::json!({})));
+    };
+    let Some(email) = get_user_email(&mut tx, &user.id)
+        .await?
+        .filter(|v| is_in_effect(&v.verified_at, &now))
+    else {
+        return Ok(web::Json(serde_json::json!({})));
+    };
+
+    let token = PasswordResetToken::generate();
+    let expires_at = *now + TimeDelta::minutes(credential_config.password_reset_expires_minutes);
+
+    create_password_reset_token(
+        &mut tx,
+        &now,
+        &user.id,
+        &hash_password_reset_token(&token),
+        &expires_at,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let mut url = url_config.base_url.clone();
+    url.set_path(&format!("password-reset/{token}"));
+
+    // Sent in the background so that the response doesn't tell whether the handle exists
+    let email_client = email_client.clone();
+    let handle = handle.to_owned();
+    tokio::task::spawn(async move {
+        if let Err(e) = email_client
+            .send_password_reset(&email.email, &handle, &url, expires_at)
+            .await
+        {
+            log::warn!("Could not send the password reset link: {e}");
+        }
+    });
+
+    Ok(web::Json(serde_json::json!({})))
+}
+
+pub async fn post_token(
+    now: Now,
+    request: HttpRequest,
+    token: web::Path<PasswordResetToken>,
+    body: web::Json<ResetPasswordRequest>,
+    database: web::Data<SqlitePool>,
+    credential_config: web::Data<CredentialConfig>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    if !is_valid_password(&body.new_password) {
+        return Err(Error::InvalidPassword);
+    }
+
+    let client_address = client_address(&request, &credential_config.trusted_proxies);
+
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::PasswordReset,
+        &client_address,
+    )
+    .await?;
+
+    let argon2_password =
+        hash_password(&body.new_password).map_err(|e| Error::Internal(e.to_string().into()))?;
+
+    let mut tx = database.begin().await?;
+
+    let user_id = consume_password_reset_token(&mut tx, &now, &hash_password_reset_token(&token))
+        .await?
+        .ok_or(Error::PasswordResetTokenNotFound)?;
+    let (_, credential) = get_user_plain_credential_with_user_id(&mut tx, &now, &user_id)
+        .await?
+        .ok_or(Error::PlainCredentialNotFound)?;
+
+    update_user_plain_credential_password(&mut tx, &user_id, &argon2_password).await?;
+
+    tx.commit().await?;
+
+    // Let the user sign in right away even if they have been locked out by failed attempts
+    clear_attempts(
+        &database,
+        CredentialAttemptKind::Login,
+        &login_attempt_key(&credential.handle, &client_address),
+    )
+    .await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
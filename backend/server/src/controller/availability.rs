@@ -0,0 +1,141 @@
+use actix_web::http::header::{CacheControl, CacheDirective};
+use actix_web::{HttpResponse, web};
+use chrono::{DateTime, Utc};
+use dxe_data::queries::unit::is_unit_enabled;
+use dxe_types::UnitId;
+use sqlx::SqlitePool;
+
+use crate::config::{BookingConfig, TimeZoneConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::entities::{AvailabilitySlot, UnitAvailability};
+use crate::models::handlers::booking::{GetAvailabilityQuery, GetAvailabilityResponse};
+use crate::services::availability::AvailabilityService;
+use crate::utils::ical::{Calendar, CalendarEvent};
+
+async fn get(
+    now: Now,
+    query: web::Query<GetAvailabilityQuery>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    booking_config: web::Data<BookingConfig>,
+    availability_service: web::Data<AvailabilityService>,
+) -> Result<HttpResponse, Error> {
+    let unit_ids = if let Some(unit_id) = &query.unit_id {
+        vec![unit_id.clone()]
+    } else {
+        let mut unit_ids = booking_config.units.keys().cloned().collect::<Vec<_>>();
+        unit_ids.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
+        unit_ids
+    };
+
+    let mut connection = database.acquire().await?;
+
+    let to_slots = |ranges: &[(DateTime<Utc>, DateTime<Utc>)]| {
+        ranges
+            .iter()
+            .map(|(from, to)| AvailabilitySlot {
+                start: timezone_config.convert(*from),
+                end: timezone_config.convert(*to),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut units = vec![];
+    for unit_id in unit_ids {
+        let Some(unit_booking_config) = booking_config.units.get(&unit_id) else {
+            return Err(Error::UnitNotFound);
+        };
+
+        if is_unit_enabled(&mut connection, &unit_id).await? != Some(true) {
+            if query.unit_id.is_some() {
+                return Err(Error::UnitNotFound);
+            }
+            continue;
+        }
+
+        let availability = availability_service
+            .get(&mut connection, &now, &unit_id, unit_booking_config)
+            .await?;
+
+        units.push(UnitAvailability {
+            unit_id,
+            start: timezone_config.convert(availability.start),
+            end: timezone_config.convert(availability.end),
+            slot_minutes: unit_booking_config.slot_size().num_minutes(),
+            min_booking_minutes: unit_booking_config.min_length().num_minutes(),
+            occupied: to_slots(&availability.occupied),
+            free: to_slots(&availability.free),
+        });
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header(cache_control(&availability_service))
+        .json(GetAvailabilityResponse {
+            generated_at: timezone_config.convert(*now),
+            units,
+        }))
+}
+
+async fn get_ics(
+    now: Now,
+    unit_id: web::Path<UnitId>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    url_config: web::Data<UrlConfig>,
+    availability_service: web::Data<AvailabilityService>,
+) -> Result<HttpResponse, Error> {
+    let unit_booking_config = booking_config
+        .units
+        .get(unit_id.as_ref())
+        .ok_or(Error::UnitNotFound)?;
+
+    let mut connection = database.acquire().await?;
+
+    if is_unit_enabled(&mut connection, &unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    let availability = availability_service
+        .get(&mut connection, &now, &unit_id, unit_booking_config)
+        .await?;
+
+    let host = url_config.base_url.host_str().unwrap_or("localhost");
+
+    let mut calendar = Calendar::new(
+        &format!("드림하우스 합주실 - {unit_id} 예약 현황"),
+        availability.generated_at,
+    );
+    for (summary, kind, ranges) in [
+        ("예약 마감", "occupied", &availability.occupied),
+        ("예약 가능", "free", &availability.free),
+    ] {
+        for (from, to) in ranges {
+            calendar.add_event(CalendarEvent {
+                uid: format!("{unit_id}-{kind}-{}@{host}", from.timestamp()),
+                summary,
+                time_from: *from,
+                time_to: *to,
+                is_tentative: false,
+            });
+        }
+    }
+
+    Ok(HttpResponse::Ok()
+        .insert_header(cache_control(&availability_service))
+        .content_type("text/calendar; charset=utf-8")
+        .body(calendar.finish()))
+}
+
+fn cache_control(availability_service: &AvailabilityService) -> CacheControl {
+    CacheControl(vec![
+        CacheDirective::Public,
+        CacheDirective::MaxAge(availability_service.max_age().num_seconds().max(0) as u32),
+    ])
+}
+
+pub fn scope() -> actix_web::Scope {
+    web::scope("/availability")
+        .service(web::resource("").route(web::get().to(get)))
+        .service(web::resource("/{unit_id}.ics").route(web::get().to(get_ics)))
+}
@@ -5,8 +5,11 @@ mod cancel;
 mod check;
 mod doorlock;
 mod get;
+mod hour_pack;
 mod recording;
+mod series;
 mod submit;
+mod waitlist;
 
 use actix_web::web;
 
@@ -14,6 +17,8 @@ pub fn bookings_scope() -> actix_web::Scope {
     web::scope("/bookings")
         .service(web::resource("/calendar").route(web::get().to(calendar::get)))
         .service(web::resource("/check").route(web::post().to(check::post)))
+        .service(web::resource("/series/check").route(web::post().to(series::check)))
+        .service(web::resource("/series").route(web::post().to(series::post)))
         .service(web::resource("").route(web::post().to(submit::post)))
 }
 
@@ -29,6 +34,34 @@ pub fn booking_scope() -> actix_web::Scope {
         .service(web::resource("/{booking_id}/recording").route(web::get().to(recording::get)))
 }
 
+pub fn booking_series_scope() -> actix_web::Scope {
+    web::scope("/booking-series").service(
+        web::resource("/{series_id}")
+            .route(web::get().to(series::get))
+            .route(web::delete().to(series::delete)),
+    )
+}
+
+pub fn waitlist_scope() -> actix_web::Scope {
+    web::scope("/waitlist")
+        .service(
+            web::resource("")
+                .route(web::get().to(waitlist::get))
+                .route(web::post().to(waitlist::post)),
+        )
+        .service(web::resource("/{waitlist_entry_id}").route(web::delete().to(waitlist::delete)))
+}
+
+pub fn hour_packs_scope() -> actix_web::Scope {
+    web::scope("/hour-packs")
+        .service(
+            web::resource("")
+                .route(web::get().to(hour_pack::get))
+                .route(web::post().to(hour_pack::post)),
+        )
+        .service(web::resource("/balance").route(web::get().to(hour_pack::get_balance)))
+}
+
 pub fn adhoc_reservation_scope() -> actix_web::Scope {
     web::scope("/adhoc-reservation").service(
         web::resource("/{adhoc_reservation_id}").route(web::delete().to(adhoc_reservation::delete)),
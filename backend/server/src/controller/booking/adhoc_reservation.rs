@@ -5,6 +5,7 @@ use sqlx::SqlitePool;
 
 use crate::middleware::datetime_injector::Now;
 use crate::models::Error;
+use crate::services::waitlist::WaitlistService;
 use crate::session::UserSession;
 
 pub async fn delete(
@@ -12,6 +13,7 @@ pub async fn delete(
     user: UserSession,
     adhoc_reservation_id: web::Path<AdhocReservationId>,
     database: web::Data<SqlitePool>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<serde_json::Value>, Error> {
     let mut tx = database.begin().await?;
 
@@ -25,6 +27,16 @@ pub async fn delete(
 
     expire_adhoc_reservation(&mut tx, &now, adhoc_reservation_id.as_ref()).await?;
 
+    waitlist_service
+        .release(
+            &mut tx,
+            &now,
+            &adhoc_reservation.unit_id,
+            &adhoc_reservation.time_from,
+            &adhoc_reservation.time_to,
+        )
+        .await?;
+
     tx.commit().await?;
 
     Ok(web::Json(serde_json::json!({})))
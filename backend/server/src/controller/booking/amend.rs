@@ -1,24 +1,40 @@
+#![allow(clippy::too_many_arguments)]
+
 use actix_web::web;
-use chrono::TimeDelta;
+use chrono::{DateTime, TimeDelta, Utc};
 use dxe_data::entities::Identity;
 use dxe_data::queries::booking::{
-    create_booking_amendment, get_booking_with_user_id, update_booking_customer,
-    update_booking_time,
+    cancel_pending_booking_amendments, create_booking_amendment, get_booking_with_user_id,
+    reschedule_booking, update_booking_customer, update_booking_time,
+};
+use dxe_data::queries::coupon::get_coupon_redemption_by_product_id;
+use dxe_data::queries::hour_pack::{
+    create_hour_balance_entry, get_hour_balance, get_hour_balance_usage_by_booking_id,
 };
 use dxe_data::queries::identity::is_member_of;
-use dxe_data::queries::payment::create_toss_payments_transaction;
+use dxe_data::queries::payment::{
+    create_cash_transaction, create_toss_payments_transaction, get_cash_transaction,
+    get_toss_payments_transaction_by_product_id, refund_toss_payments,
+    update_cash_refund_information, update_cash_transaction_price,
+};
+use dxe_data::queries::user::update_user_cash_payment_refund_account;
 use dxe_data::utils::is_in_effect;
-use dxe_types::{BookingId, ForeignPaymentId, GroupId, ProductId};
-use sqlx::SqlitePool;
+use dxe_extern::toss_payments::{Error as TossPaymentsError, TossPaymentsClient};
+use dxe_types::{BookingId, ForeignPaymentId, GroupId, ProductId, UnitId};
+use sqlx::{SqliteConnection, SqlitePool};
 
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
-use crate::models::entities::Booking;
+use crate::models::entities::{
+    Booking, CashTransaction, HourBalanceTransaction, TossPaymentsTransaction, Transaction,
+};
 use crate::models::handlers::booking::{AmendBookingRequest, AmendBookingResponse};
 use crate::models::{Error, IntoView};
 use crate::services::calendar::CalendarService;
 use crate::services::messaging::MessagingService;
 use crate::services::notification::{NotificationSender, Priority};
+use crate::services::toss_payments::refund_receive_account;
+use crate::services::waitlist::WaitlistService;
 use crate::session::UserSession;
 
 pub async fn put(
@@ -32,6 +48,8 @@ pub async fn put(
     calendar_service: web::Data<Option<CalendarService>>,
     notification_sender: web::Data<NotificationSender>,
     messaging_service: web::Data<MessagingService>,
+    toss_payments_service: web::Data<TossPaymentsClient>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<AmendBookingResponse>, Error> {
     let mut tx = database.begin().await?;
 
@@ -62,33 +80,165 @@ pub async fn put(
     }
 
     let mut foreign_payment_id = None;
+    let mut transaction = None;
+
+    let current_length = booking.time_to - booking.time_from;
+    let additional_length = body
+        .additional_minutes
+        .map(TimeDelta::minutes)
+        .or(body.additional_hours.map(TimeDelta::hours));
+    let is_rescheduling = body.new_time_from.is_some() || body.new_unit_id.is_some();
 
-    let current_hours = (booking.time_to - booking.time_from).num_hours();
-    if let Some(additional_hours) = body.additional_hours
-        && additional_hours > 0
+    if let Some(additional_length) = additional_length
+        && additional_length < TimeDelta::zero()
+        && !is_rescheduling
     {
         if *now >= booking.time_from {
             return Err(Error::OngoingBookingNotModifiable);
         }
 
-        let total_hours = additional_hours + current_hours;
+        let total_length = current_length + additional_length;
+        if !booking_config
+            .is_valid_length(&booking.unit_id, total_length)
+            .map_err(|_| Error::UnitNotFound)?
+        {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let desired_time_from = booking.time_from;
+        let desired_time_to = desired_time_from + total_length;
+
+        let removed_price = calculate_price_difference(
+            &mut tx,
+            &now,
+            &booking,
+            &booking.unit_id,
+            desired_time_from,
+            desired_time_to,
+            &booking_config,
+            &timezone_config,
+        )
+        .await?
+        .map(|v| -v)
+        .unwrap_or(0);
+
+        let _ = create_booking_amendment(
+            &mut tx,
+            &now,
+            &booking_id,
+            &booking.unit_id,
+            &desired_time_from,
+            &desired_time_to,
+            true,
+        )
+        .await?;
+
+        update_booking_time(
+            &mut tx,
+            &now,
+            &booking_id,
+            &desired_time_from,
+            &desired_time_to,
+        )
+        .await?;
+
+        transaction = refund_partially(
+            &mut tx,
+            &now,
+            &session,
+            &booking,
+            body.refund_account.as_ref(),
+            removed_price,
+            (-additional_length).num_minutes(),
+            &booking_config,
+            &timezone_config,
+            &toss_payments_service,
+            "Booking shortened by user",
+        )
+        .await?;
+
+        waitlist_service
+            .release(
+                &mut tx,
+                &now,
+                &booking.unit_id,
+                &desired_time_to,
+                &booking.time_to,
+            )
+            .await?;
+
+        if let Err(e) = messaging_service
+            .send_amend_notification(
+                &mut tx,
+                &now,
+                booking.clone(),
+                desired_time_from,
+                desired_time_to,
+            )
+            .await
+        {
+            log::warn!("Could not send amend notification to customers: {e}");
+        }
+
+        notification_sender.enqueue(
+            Priority::High,
+            format!(
+                "Booking by {} shortened from {} - {} to {} - {}",
+                booking.customer.name(),
+                timezone_config.convert(booking.time_from),
+                timezone_config.convert(booking.time_to),
+                timezone_config.convert(desired_time_from),
+                timezone_config.convert(desired_time_to),
+            ),
+        );
+
+        if let Some(calendar_service) = calendar_service.as_ref() {
+            let mut updated_booking = booking.clone();
+            updated_booking.time_to = desired_time_to;
+            if let Err(e) = calendar_service.update_booking_time(&updated_booking).await {
+                log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+            }
+        }
+    } else if let Some(additional_length) = additional_length
+        && additional_length > TimeDelta::zero()
+        && !is_rescheduling
+    {
+        if *now >= booking.time_from {
+            return Err(Error::OngoingBookingNotModifiable);
+        }
+
+        if !booking_config
+            .is_slot_aligned(&booking.unit_id, additional_length)
+            .map_err(|_| Error::UnitNotFound)?
+        {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        let total_length = additional_length + current_length;
 
         // NOTE: do not place restrictions on extensions for now
-        // if total_hours > booking_config.max_booking_hours {
+        // if total_length > booking_config.max_booking_length(&booking.unit_id)? {
         //    return Err(Error::InvalidTimeRange);
         // }
 
         let desired_time_from = booking.time_from;
-        let desired_time_to = desired_time_from + TimeDelta::hours(total_hours);
+        let desired_time_to = desired_time_from + total_length;
 
         let price = booking_config
-            .calculate_additive_price(&booking.unit_id, additional_hours)
-            .map_err(|_| Error::UnitNotFound)?;
+            .calculate_additive_price(
+                &timezone_config,
+                &booking.unit_id,
+                booking.time_to,
+                booking.time_to + additional_length,
+            )
+            .map_err(|_| Error::UnitNotFound)?
+            .total;
 
         let booking_amendment_id = create_booking_amendment(
             &mut tx,
             &now,
             &booking_id,
+            &booking.unit_id,
             &desired_time_from,
             &desired_time_to,
             false,
@@ -110,68 +260,268 @@ pub async fn put(
         .await?;
 
         foreign_payment_id = Some(foreign_payment_id_inner);
-    } else if let Some(new_time_from) = body.new_time_from {
+    } else if is_rescheduling {
         if *now >= booking.time_from {
             return Err(Error::OngoingBookingNotModifiable);
         }
 
-        let total_hours = current_hours + body.additional_hours.unwrap_or(0);
-        if total_hours > booking_config.max_booking_hours {
+        let desired_unit_id = body
+            .new_unit_id
+            .clone()
+            .unwrap_or_else(|| booking.unit_id.clone());
+
+        let total_length = current_length + additional_length.unwrap_or(TimeDelta::zero());
+        if !booking_config
+            .is_valid_length(&desired_unit_id, total_length)
+            .map_err(|_| Error::UnitNotFound)?
+        {
             return Err(Error::InvalidTimeRange);
         }
 
-        let desired_time_from = new_time_from.to_utc();
-        let desired_time_to = desired_time_from + TimeDelta::hours(total_hours);
+        let desired_time_from = if let Some(new_time_from) = body.new_time_from {
+            booking_config
+                .truncate_time(&desired_unit_id, new_time_from)
+                .map_err(|_| Error::UnitNotFound)?
+        } else {
+            booking.time_from
+        };
+        let desired_time_to = desired_time_from + total_length;
 
-        let _ = create_booking_amendment(
-            &mut tx,
-            &now,
-            &booking_id,
-            &desired_time_from,
-            &desired_time_to,
-            true,
-        )
-        .await?;
+        if desired_time_from < *now {
+            return Err(Error::InvalidTimeRange);
+        }
 
-        if update_booking_time(
+        let product_id = ProductId::from(*booking_id);
+
+        // A newer request supersedes any rescheduling still awaiting its payment, so that
+        // confirming the older one later can't move the booking again
+        let _ = cancel_pending_booking_amendments(&mut tx, &now, &booking_id).await?;
+
+        let price_difference = calculate_price_difference(
             &mut tx,
             &now,
-            &booking_id,
-            &desired_time_from,
-            &desired_time_to,
+            &booking,
+            &desired_unit_id,
+            desired_time_from,
+            desired_time_to,
+            &booking_config,
+            &timezone_config,
         )
         .await?
+        .unwrap_or(0);
+
+        let toss_tx = get_toss_payments_transaction_by_product_id(&mut tx, &product_id)
+            .await?
+            .filter(|v| v.payment_key.is_some());
+        let paid_cash_tx = get_cash_transaction(&mut tx, &product_id)
+            .await?
+            .filter(|v| is_in_effect(&v.confirmed_at, &now));
+
+        if price_difference > 0 && toss_tx.is_some() {
+            // The booking is moved once the additional payment is confirmed
+            let booking_amendment_id = create_booking_amendment(
+                &mut tx,
+                &now,
+                &booking_id,
+                &desired_unit_id,
+                &desired_time_from,
+                &desired_time_to,
+                false,
+            )
+            .await?;
+
+            let foreign_payment_id_inner = ForeignPaymentId::generate();
+
+            let _ = create_toss_payments_transaction(
+                &mut tx,
+                &now,
+                &foreign_payment_id_inner,
+                &session.user_id,
+                None,
+                Some(&ProductId::from(booking_amendment_id)),
+                price_difference,
+            )
+            .await?;
+
+            foreign_payment_id = Some(foreign_payment_id_inner);
+        } else if price_difference > 0
+            && let Some(paid_cash_tx) = paid_cash_tx
         {
-            if let Err(e) = messaging_service
-                .send_amend_notification(
-                    &mut tx,
-                    booking.clone(),
-                    desired_time_from,
-                    desired_time_to,
-                )
-                .await
-            {
-                log::warn!("Could not send amend notification to customers: {e}");
-            }
+            // The booking is moved once the administrator confirms the deposit for the
+            // difference, and the desired range is held for it until then
+            let booking_amendment_id = create_booking_amendment(
+                &mut tx,
+                &now,
+                &booking_id,
+                &desired_unit_id,
+                &desired_time_from,
+                &desired_time_to,
+                false,
+            )
+            .await?;
+
+            let amendment_product_id = ProductId::from(booking_amendment_id);
+            create_cash_transaction(
+                &mut tx,
+                &now,
+                &amendment_product_id,
+                &paid_cash_tx.depositor_name,
+                price_difference,
+            )
+            .await?;
+
+            let cash_tx = get_cash_transaction(&mut tx, &amendment_product_id)
+                .await?
+                .ok_or(Error::BookingAmendmentNotFound)?;
 
             notification_sender.enqueue(
                 Priority::High,
                 format!(
-                    "Booking by {} moved from {} - {} to {} - {}",
+                    "Booking by {} awaiting deposit of {} to move from {} - {} ({}) to {} - {} ({})",
                     booking.customer.name(),
+                    price_difference,
                     timezone_config.convert(booking.time_from),
                     timezone_config.convert(booking.time_to),
+                    booking.unit_id,
                     timezone_config.convert(desired_time_from),
                     timezone_config.convert(desired_time_to),
+                    desired_unit_id,
                 ),
             );
 
-            if let Some(calendar_service) = calendar_service.as_ref() {
-                let mut updated_booking = booking.clone();
-                updated_booking.time_from = desired_time_from;
-                updated_booking.time_to = desired_time_to;
-                if let Err(e) = calendar_service.update_booking_time(&updated_booking).await {
-                    log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+            transaction = Some(Transaction::Cash(CashTransaction::convert(
+                cash_tx,
+                &timezone_config,
+                &now,
+            )?));
+        } else {
+            let _ = create_booking_amendment(
+                &mut tx,
+                &now,
+                &booking_id,
+                &desired_unit_id,
+                &desired_time_from,
+                &desired_time_to,
+                true,
+            )
+            .await?;
+
+            let additional_minutes = (total_length - current_length).num_minutes();
+
+            transaction = if let Some(mut cash_tx) =
+                get_cash_transaction(&mut tx, &product_id).await?
+                && price_difference > 0
+            {
+                // Not paid yet, so the difference is added to the amount due
+                if update_cash_transaction_price(
+                    &mut tx,
+                    &product_id,
+                    cash_tx.price + price_difference,
+                )
+                .await?
+                {
+                    cash_tx.price += price_difference;
+                }
+
+                Some(Transaction::Cash(CashTransaction::convert(
+                    cash_tx,
+                    &timezone_config,
+                    &now,
+                )?))
+            } else if additional_minutes > 0
+                && let Some((debited, credited)) =
+                    get_hour_balance_usage_by_booking_id(&mut tx, &booking_id).await?
+            {
+                if get_hour_balance(&mut tx, &booking.customer.id()).await? < additional_minutes {
+                    return Err(Error::InsufficientHourBalance);
+                }
+
+                create_hour_balance_entry(
+                    &mut tx,
+                    &now,
+                    &booking.customer.id(),
+                    -additional_minutes,
+                    None,
+                    Some(&booking.id),
+                )
+                .await?;
+
+                Some(Transaction::HourBalance(HourBalanceTransaction {
+                    minutes: debited + additional_minutes,
+                    refunded_minutes: credited,
+                }))
+            } else {
+                refund_partially(
+                    &mut tx,
+                    &now,
+                    &session,
+                    &booking,
+                    body.refund_account.as_ref(),
+                    -price_difference,
+                    -additional_minutes,
+                    &booking_config,
+                    &timezone_config,
+                    &toss_payments_service,
+                    "Booking rescheduled by user",
+                )
+                .await?
+            };
+
+            if reschedule_booking(
+                &mut tx,
+                &now,
+                &booking_id,
+                &desired_unit_id,
+                &desired_time_from,
+                &desired_time_to,
+            )
+            .await?
+            {
+                waitlist_service
+                    .release(
+                        &mut tx,
+                        &now,
+                        &booking.unit_id,
+                        &booking.time_from,
+                        &booking.time_to,
+                    )
+                    .await?;
+
+                if let Err(e) = messaging_service
+                    .send_amend_notification(
+                        &mut tx,
+                        &now,
+                        booking.clone(),
+                        desired_time_from,
+                        desired_time_to,
+                    )
+                    .await
+                {
+                    log::warn!("Could not send amend notification to customers: {e}");
+                }
+
+                notification_sender.enqueue(
+                    Priority::High,
+                    format!(
+                        "Booking by {} moved from {} - {} ({}) to {} - {} ({})",
+                        booking.customer.name(),
+                        timezone_config.convert(booking.time_from),
+                        timezone_config.convert(booking.time_to),
+                        booking.unit_id,
+                        timezone_config.convert(desired_time_from),
+                        timezone_config.convert(desired_time_to),
+                        desired_unit_id,
+                    ),
+                );
+
+                if let Some(calendar_service) = calendar_service.as_ref() {
+                    let mut updated_booking = booking.clone();
+                    updated_booking.unit_id = desired_unit_id.clone();
+                    updated_booking.time_from = desired_time_from;
+                    updated_booking.time_to = desired_time_to;
+                    if let Err(e) = calendar_service.update_booking_time(&updated_booking).await {
+                        log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+                    }
                 }
             }
         }
@@ -186,5 +536,241 @@ pub async fn put(
     Ok(web::Json(AmendBookingResponse {
         booking: Booking::convert(booking, timezone_config.as_ref(), &now)?,
         foreign_payment_id,
+        transaction,
     }))
 }
+
+/// Difference between the price of the booking in the desired range and its current price, with
+/// the coupon discount applied alike. `None` if nothing has been charged for the booking.
+async fn calculate_price_difference(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    booking: &dxe_data::entities::Booking,
+    desired_unit_id: &UnitId,
+    desired_time_from: DateTime<Utc>,
+    desired_time_to: DateTime<Utc>,
+    booking_config: &BookingConfig,
+    timezone_config: &TimeZoneConfig,
+) -> Result<Option<i64>, Error> {
+    // The difference is taken from the full prices, as pricing rules may not be linear
+    let difference = booking_config
+        .calculate_price(
+            timezone_config,
+            desired_unit_id,
+            desired_time_from,
+            desired_time_to,
+        )
+        .map_err(|_| Error::UnitNotFound)?
+        .total
+        - booking_config
+            .calculate_price(
+                timezone_config,
+                &booking.unit_id,
+                booking.time_from,
+                booking.time_to,
+            )
+            .map_err(|_| Error::UnitNotFound)?
+            .total;
+
+    let product_id = ProductId::from(booking.id);
+
+    if get_cash_transaction(&mut *connection, &product_id)
+        .await?
+        .is_none()
+        && get_toss_payments_transaction_by_product_id(&mut *connection, &product_id)
+            .await?
+            .is_none()
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        match get_coupon_redemption_by_product_id(&mut *connection, &product_id).await? {
+            Some(redemption)
+                if !is_in_effect(&redemption.canceled_at, now) && redemption.original_price > 0 =>
+            {
+                difference * (redemption.original_price - redemption.discount_price)
+                    / redemption.original_price
+            }
+            _ => difference,
+        },
+    ))
+}
+
+/// Refunds part of the booking through its original payment method, following the refund
+/// policy. Unpaid cash bookings are charged less by the full removed price instead, and bookings
+/// paid with hour balance get the removed minutes credited back.
+async fn refund_partially(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    session: &UserSession,
+    booking: &dxe_data::entities::Booking,
+    refund_account: Option<&String>,
+    removed_price: i64,
+    removed_minutes: i64,
+    booking_config: &BookingConfig,
+    timezone_config: &TimeZoneConfig,
+    toss_payments_service: &TossPaymentsClient,
+    reason: &str,
+) -> Result<Option<Transaction>, Error> {
+    let product_id = ProductId::from(booking.id);
+    let removed_price = removed_price.max(0);
+
+    if let Some(mut cash_tx) = get_cash_transaction(&mut *connection, &product_id).await? {
+        let prior_refund_price = cash_tx.refund_price.unwrap_or(0);
+        let removed_price = removed_price.min(cash_tx.price - prior_refund_price);
+
+        if !is_in_effect(&cash_tx.confirmed_at, now) {
+            // Nothing has been paid yet, so the removed time is simply not charged. The refund
+            // policy only applies to money already paid.
+            if update_cash_transaction_price(
+                &mut *connection,
+                &product_id,
+                cash_tx.price - removed_price,
+            )
+            .await?
+            {
+                cash_tx.price -= removed_price;
+            }
+
+            return Ok(Some(Transaction::Cash(CashTransaction::convert(
+                cash_tx,
+                timezone_config,
+                now,
+            )?)));
+        }
+
+        let refund_price = booking_config
+            .calculate_refund_price(timezone_config, removed_price, booking.time_from, *now)
+            .map_err(|_| Error::NotRefundable)?;
+
+        if refund_price > 0 {
+            let Some(refund_account) = refund_account else {
+                return Err(Error::RefundAccountRequired);
+            };
+
+            // Refunds accumulate on the transaction until the administrator settles them
+            if !update_cash_refund_information(
+                &mut *connection,
+                &product_id,
+                prior_refund_price + refund_price,
+                Some(refund_account.clone()),
+            )
+            .await?
+            {
+                return Err(Error::NotRefundable);
+            }
+
+            cash_tx.refund_price = Some(prior_refund_price + refund_price);
+            cash_tx.refund_account = Some(refund_account.clone());
+            cash_tx.refunded_at = None;
+
+            let _ = update_user_cash_payment_refund_account(
+                &mut *connection,
+                &session.user_id,
+                Some(refund_account.as_str()),
+            )
+            .await?;
+        }
+
+        Ok(Some(Transaction::Cash(CashTransaction::convert(
+            cash_tx,
+            timezone_config,
+            now,
+        )?)))
+    } else if let Some(mut toss_tx) =
+        get_toss_payments_transaction_by_product_id(&mut *connection, &product_id).await?
+        && let Some(payment_key) = toss_tx.payment_key.clone()
+    {
+        if !is_in_effect(&toss_tx.confirmed_at, now) {
+            return Err(Error::DepositPending);
+        }
+
+        let prior_refund_price = toss_tx.refund_price.unwrap_or(0);
+        let refund_price = booking_config
+            .calculate_refund_price(
+                timezone_config,
+                removed_price.min(toss_tx.price - prior_refund_price),
+                booking.time_from,
+                *now,
+            )
+            .map_err(|_| Error::NotRefundable)?;
+
+        if refund_price > 0 {
+            let refund_receive_account =
+                refund_receive_account(&toss_tx, refund_account.map(String::as_str))?;
+
+            match toss_payments_service
+                .cancel_payment(
+                    &payment_key,
+                    reason,
+                    Some(refund_price),
+                    refund_receive_account.as_ref(),
+                )
+                .await
+            {
+                Ok(_) => {
+                    log::info!(
+                        "Payment {payment_key} partially refunded. Refunded amount: {refund_price}"
+                    );
+                }
+                Err(e) => match e {
+                    TossPaymentsError::Remote { code, message } => {
+                        Err(Error::TossPaymentsFailed { message, code })?
+                    }
+                    TossPaymentsError::RemoteStatus(status) => {
+                        Err(Error::PaymentFailed(status.to_string()))?
+                    }
+                    rest => Err(Error::Internal(Box::new(rest)))?,
+                },
+            }
+
+            if refund_toss_payments(
+                &mut *connection,
+                now,
+                &toss_tx.id,
+                prior_refund_price + refund_price,
+            )
+            .await?
+            {
+                toss_tx.refund_price = Some(prior_refund_price + refund_price);
+                toss_tx.refunded_at = Some(*now);
+            }
+        }
+
+        Ok(Some(Transaction::TossPayments(
+            TossPaymentsTransaction::convert(toss_tx, timezone_config, now)?,
+        )))
+    } else if let Some((debited, credited)) =
+        get_hour_balance_usage_by_booking_id(&mut *connection, &booking.id).await?
+        && debited > 0
+    {
+        let refunded_minutes = booking_config
+            .calculate_refund_price(
+                timezone_config,
+                removed_minutes.max(0).min(debited - credited),
+                booking.time_from,
+                *now,
+            )
+            .map_err(|_| Error::NotRefundable)?;
+
+        if refunded_minutes > 0 {
+            create_hour_balance_entry(
+                &mut *connection,
+                now,
+                &booking.customer.id(),
+                refunded_minutes,
+                None,
+                Some(&booking.id),
+            )
+            .await?;
+        }
+
+        Ok(Some(Transaction::HourBalance(HourBalanceTransaction {
+            minutes: debited,
+            refunded_minutes: credited + refunded_minutes,
+        })))
+    } else {
+        Ok(None)
+    }
+}
@@ -25,6 +25,14 @@ pub async fn get(
         .to_utc();
     let end = start + TimeDelta::days(booking_config.lookahead_days);
 
+    let unit_booking_config = booking_config
+        .units
+        .get(&query.unit_id)
+        .ok_or(Error::UnitNotFound)?;
+    let max_booking_length = booking_config
+        .max_booking_length(&query.unit_id)
+        .map_err(|_| Error::UnitNotFound)?;
+
     let mut connection = database.acquire().await?;
 
     let mut slots = get_occupied_slots(
@@ -42,7 +50,10 @@ pub async fn get(
     Ok(web::Json(CalendarResponse {
         start: timezone_config.convert(start),
         end: timezone_config.convert(end),
-        max_booking_hours: booking_config.max_booking_hours,
+        max_booking_hours: max_booking_length.num_hours(),
+        slot_minutes: unit_booking_config.slot_size().num_minutes(),
+        min_booking_minutes: unit_booking_config.min_length().num_minutes(),
+        max_booking_minutes: max_booking_length.num_minutes(),
         slots: slots
             .into_iter()
             .map(|v| OccupiedSlot::convert(v, &timezone_config, &now))
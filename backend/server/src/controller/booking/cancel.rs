@@ -2,6 +2,10 @@
 
 use actix_web::web;
 use dxe_data::queries::booking::{cancel_booking, get_booking_with_user_id};
+use dxe_data::queries::coupon::cancel_coupon_redemption;
+use dxe_data::queries::hour_pack::{
+    create_hour_balance_entry, get_hour_balance_usage_by_booking_id,
+};
 use dxe_data::queries::payment::{
     get_cash_transaction, get_toss_payments_transaction_by_product_id,
     get_toss_payments_transactions_by_booking_amentments, refund_toss_payments,
@@ -14,13 +18,19 @@ use sqlx::SqlitePool;
 
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
-use crate::models::entities::{CashTransaction, TossPaymentsTransaction, Transaction};
+use crate::models::entities::{
+    CashTransaction, HourBalanceTransaction, TossPaymentsTransaction, Transaction,
+};
 use crate::models::handlers::booking::{CancelBookingRequest, CancelBookingResponse};
 use crate::models::{Error, IntoView};
 use crate::services::calendar::CalendarService;
 use crate::services::messaging::MessagingService;
 use crate::services::notification::{NotificationSender, Priority};
+use crate::services::toss_payments::refund_receive_account;
+use crate::services::waitlist::WaitlistService;
+use crate::services::webhook::{BookingPayload, WebhookEvent, enqueue_webhook_event};
 use crate::session::UserSession;
+use crate::utils::datetime::is_in_effect;
 
 pub async fn delete(
     now: Now,
@@ -34,6 +44,7 @@ pub async fn delete(
     messaging_service: web::Data<MessagingService>,
     calendar_service: web::Data<Option<CalendarService>>,
     toss_payments_service: web::Data<TossPaymentsClient>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<CancelBookingResponse>, Error> {
     let mut tx = database.begin().await?;
 
@@ -41,15 +52,23 @@ pub async fn delete(
         .await?
         .ok_or(Error::BookingNotFound)?;
 
-    cancel_booking(&mut tx, &now, booking_id.as_ref()).await?;
+    // Refunds below build on earlier partial refunds, so they must only be made once
+    if is_in_effect(&booking.canceled_at, &now)
+        || !cancel_booking(&mut tx, &now, booking_id.as_ref()).await?
+    {
+        return Err(Error::BookingAlreadyCanceled);
+    }
 
     let product_id = ProductId::from(*booking_id);
 
     let transaction = if let Some(mut cash_tx) = get_cash_transaction(&mut tx, &product_id).await? {
+        // Shortened bookings may already have been partially refunded
+        let prior_refund_price = cash_tx.refund_price.unwrap_or(0);
+        let remaining_price = cash_tx.price - prior_refund_price;
         let refund_price = booking_config
             .calculate_refund_price(
                 timezone_config.as_ref(),
-                cash_tx.price,
+                remaining_price,
                 booking.time_from,
                 *now,
             )
@@ -59,18 +78,21 @@ pub async fn delete(
             return Err(Error::RefundAccountRequired);
         }
 
-        if update_cash_refund_information(
+        if !update_cash_refund_information(
             &mut tx,
             &product_id,
-            refund_price,
+            prior_refund_price + refund_price,
             query.refund_account.clone(),
         )
         .await?
         {
-            cash_tx.refund_price = Some(refund_price);
-            cash_tx.refund_account = query.refund_account.clone();
+            return Err(Error::NotRefundable);
         }
 
+        cash_tx.refund_price = Some(prior_refund_price + refund_price);
+        cash_tx.refund_account = query.refund_account.clone();
+        cash_tx.refunded_at = None;
+
         if let Some(refund_account) = &query.refund_account {
             let _ = update_user_cash_payment_refund_account(
                 &mut tx,
@@ -80,10 +102,15 @@ pub async fn delete(
             .await?;
         }
 
-        let refund_rate = (refund_price * 100 / cash_tx.price) as i32;
+        // Free bookings (e.g. with a full-discount coupon) have nothing to refund
+        let refund_rate = if remaining_price > 0 {
+            (refund_price * 100 / remaining_price) as i32
+        } else {
+            100
+        };
 
         messaging_service
-            .send_cancellation(&mut tx, booking.clone(), refund_rate)
+            .send_cancellation(&mut tx, &now, booking.clone(), refund_rate)
             .await?;
 
         Some(Transaction::Cash(CashTransaction::convert(
@@ -95,16 +122,42 @@ pub async fn delete(
         get_toss_payments_transaction_by_product_id(&mut tx, &product_id).await?
         && let Some(payment_key) = toss_tx.payment_key.as_ref()
     {
-        let refund_price = booking_config
-            .calculate_refund_price(
-                timezone_config.as_ref(),
-                toss_tx.price,
-                booking.time_from,
-                *now,
-            )
-            .map_err(|_| Error::NotRefundable)?;
+        // Virtual accounts which have not been paid into yet are only closed
+        let is_deposit_pending = !is_in_effect(&toss_tx.confirmed_at, &now);
+
+        let prior_refund_price = toss_tx.refund_price.unwrap_or(0);
+        let remaining_price = toss_tx.price - prior_refund_price;
+        let refund_price = if is_deposit_pending {
+            0
+        } else {
+            booking_config
+                .calculate_refund_price(
+                    timezone_config.as_ref(),
+                    remaining_price,
+                    booking.time_from,
+                    *now,
+                )
+                .map_err(|_| Error::NotRefundable)?
+        };
+
+        if is_deposit_pending || refund_price > 0 {
+            let refund_receive_account = if is_deposit_pending {
+                None
+            } else {
+                refund_receive_account(&toss_tx, query.refund_account.as_deref())?
+            };
+
+            if refund_receive_account.is_some()
+                && let Some(refund_account) = &query.refund_account
+            {
+                let _ = update_user_cash_payment_refund_account(
+                    &mut tx,
+                    &session.user_id,
+                    Some(refund_account.as_str()),
+                )
+                .await?;
+            }
 
-        if refund_price > 0 {
             match toss_payments_service
                 .cancel_payment(
                     payment_key,
@@ -112,7 +165,8 @@ pub async fn delete(
                         .cancel_reason
                         .as_deref()
                         .unwrap_or("Cancellation request by user"),
-                    Some(refund_price),
+                    Some(refund_price).filter(|v| *v > 0),
+                    refund_receive_account.as_ref(),
                 )
                 .await
             {
@@ -133,8 +187,15 @@ pub async fn delete(
             }
         }
 
-        if refund_toss_payments(&mut tx, &now, &toss_tx.id, refund_price).await? {
-            toss_tx.refund_price = Some(refund_price);
+        if refund_toss_payments(
+            &mut tx,
+            &now,
+            &toss_tx.id,
+            prior_refund_price + refund_price,
+        )
+        .await?
+        {
+            toss_tx.refund_price = Some(prior_refund_price + refund_price);
             toss_tx.refunded_at = Some(*now);
         }
 
@@ -163,6 +224,7 @@ pub async fn delete(
                             .as_deref()
                             .unwrap_or("Cancellation request by user"),
                         Some(refund_price),
+                        None,
                     )
                     .await
                 {
@@ -176,9 +238,13 @@ pub async fn delete(
             }
         }
 
-        let refund_rate = (refund_price * 100 / toss_tx.price) as i32;
+        let refund_rate = if remaining_price > 0 && !is_deposit_pending {
+            (refund_price * 100 / remaining_price) as i32
+        } else {
+            100
+        };
         messaging_service
-            .send_cancellation(&mut tx, booking.clone(), refund_rate)
+            .send_cancellation(&mut tx, &now, booking.clone(), refund_rate)
             .await?;
 
         Some(Transaction::TossPayments(TossPaymentsTransaction::convert(
@@ -186,10 +252,66 @@ pub async fn delete(
             &timezone_config,
             &now,
         )?))
+    } else if let Some((debited, credited)) =
+        get_hour_balance_usage_by_booking_id(&mut tx, &booking_id).await?
+        && debited > credited
+    {
+        // Hours are credited back under the same refund policy as payments, excluding those
+        // already credited for shortening the booking
+        let minutes = debited - credited;
+        let refunded_minutes = booking_config
+            .calculate_refund_price(timezone_config.as_ref(), minutes, booking.time_from, *now)
+            .map_err(|_| Error::NotRefundable)?;
+
+        if refunded_minutes > 0 {
+            create_hour_balance_entry(
+                &mut tx,
+                &now,
+                &booking.customer.id(),
+                refunded_minutes,
+                None,
+                Some(&booking.id),
+            )
+            .await?;
+        }
+
+        let refund_rate = (refunded_minutes * 100 / minutes) as i32;
+        messaging_service
+            .send_cancellation(&mut tx, &now, booking.clone(), refund_rate)
+            .await?;
+
+        Some(Transaction::HourBalance(HourBalanceTransaction {
+            minutes: debited,
+            refunded_minutes: credited + refunded_minutes,
+        }))
     } else {
+        enqueue_webhook_event(
+            &mut tx,
+            &now,
+            WebhookEvent::BookingCanceled {
+                booking: BookingPayload::canceled(&booking, &now),
+                refund_rate: None,
+            },
+        )
+        .await?;
+
         None
     };
 
+    // Refunds above are based on the transaction price, which already has the coupon discount
+    // applied. Give the coupon back so that it can be used again.
+    cancel_coupon_redemption(&mut tx, &now, &product_id).await?;
+
+    waitlist_service
+        .release(
+            &mut tx,
+            &now,
+            &booking.unit_id,
+            &booking.time_from,
+            &booking.time_to,
+        )
+        .await?;
+
     tx.commit().await?;
 
     if let Some(calendar_service) = calendar_service.as_ref()
@@ -6,11 +6,12 @@ use sqlx::SqlitePool;
 
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
-use crate::models::entities::Booking;
+use crate::models::entities::{Booking, PriceLineItem};
 use crate::models::handlers::booking::{CheckRequest, CheckResponse};
 use crate::models::{Error, IntoView};
+use crate::services::coupon::apply_coupon;
 use crate::session::UserSession;
-use crate::utils::datetime::truncate_time;
+use crate::utils::datetime::desired_length;
 
 pub async fn post(
     now: Now,
@@ -26,13 +27,26 @@ pub async fn post(
         return Err(Error::UnitNotFound);
     }
 
+    let length = desired_length(body.desired_hours, body.desired_minutes);
+    let additional_length = body
+        .additional_minutes
+        .map(TimeDelta::minutes)
+        .or(body.additional_hours.map(TimeDelta::hours));
+
     // NOTE: do not place restrictions on extensions for now
-    // if body.desired_hours > booking_config.max_booking_hours {
-    //    return Err(Error::InvalidTimeRange);
-    // }
+    let is_valid_length = if additional_length.is_some() {
+        booking_config.is_slot_aligned(&body.unit_id, length)
+    } else {
+        booking_config.is_valid_length(&body.unit_id, length)
+    };
+    if !is_valid_length.map_err(|_| Error::UnitNotFound)? {
+        return Err(Error::InvalidTimeRange);
+    }
 
-    let time_from = truncate_time(body.time_from).to_utc();
-    let time_to = time_from + TimeDelta::hours(body.desired_hours);
+    let time_from = booking_config
+        .truncate_time(&body.unit_id, body.time_from)
+        .map_err(|_| Error::UnitNotFound)?;
+    let time_to = time_from + length;
 
     if is_booking_available(
         &mut connection,
@@ -45,16 +59,36 @@ pub async fn post(
     )
     .await?
     {
-        let total_price = if let Some(additional_hours) = body.additional_hours {
+        let mut price = if let Some(additional_length) = additional_length {
             booking_config
-                .calculate_additive_price(&body.unit_id, additional_hours)
+                .calculate_additive_price(
+                    &timezone_config,
+                    &body.unit_id,
+                    time_to - additional_length,
+                    time_to,
+                )
                 .map_err(|_| Error::UnitNotFound)?
         } else {
             booking_config
-                .calculate_price(&body.unit_id, time_from, time_to)
+                .calculate_price(&timezone_config, &body.unit_id, time_from, time_to)
                 .map_err(|_| Error::UnitNotFound)?
         };
 
+        // Coupons only apply to new bookings
+        if additional_length.is_none()
+            && let Some(coupon_code) = body.coupon_code.as_deref()
+        {
+            apply_coupon(
+                &mut connection,
+                &now,
+                coupon_code,
+                &session.user_id,
+                &body.unit_id,
+                &mut price,
+            )
+            .await?;
+        }
+
         let customer_id = body.customer_id.unwrap_or(session.user_id.into());
 
         let amend_reservation = get_continuous_booking(
@@ -78,7 +112,8 @@ pub async fn post(
 
         Ok(web::Json(CheckResponse {
             amend_reservation,
-            total_price,
+            total_price: price.total,
+            price_breakdown: price.items.into_iter().map(PriceLineItem::from).collect(),
         }))
     } else {
         Err(Error::TimeRangeOccupied)
@@ -1,6 +1,7 @@
 use actix_web::web;
 use chrono::TimeDelta;
 use dxe_data::queries::booking::{get_booking_with_user_id, get_occupied_slots};
+use dxe_data::queries::hour_pack::get_hour_balance_usage_by_booking_id;
 use dxe_data::queries::payment::{
     get_cash_transaction, get_toss_payments_transaction_by_product_id,
     get_toss_payments_transactions_by_booking_amentments,
@@ -10,7 +11,9 @@ use sqlx::SqlitePool;
 
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
-use crate::models::entities::{Booking, CashTransaction, TossPaymentsTransaction, Transaction};
+use crate::models::entities::{
+    Booking, CashTransaction, HourBalanceTransaction, TossPaymentsTransaction, Transaction,
+};
 use crate::models::handlers::booking::GetBookingResponse;
 use crate::models::{Error, IntoView};
 use crate::session::UserSession;
@@ -53,6 +56,13 @@ pub async fn get(
             &timezone_config,
             &now,
         )?))
+    } else if let Some((minutes, refunded_minutes)) =
+        get_hour_balance_usage_by_booking_id(&mut tx, &booking_id).await?
+    {
+        Some(Transaction::HourBalance(HourBalanceTransaction {
+            minutes,
+            refunded_minutes,
+        }))
     } else {
         None
     };
@@ -62,12 +72,14 @@ pub async fn get(
         .map(|v| v != 0)
         .unwrap_or(false);
 
-    let hours = (booking.time_to - booking.time_from).num_hours();
-    let mut extendable_hours = booking_config.max_booking_hours - hours;
+    let max_booking_length = booking_config
+        .max_booking_length(&booking.unit_id)
+        .map_err(|_| Error::UnitNotFound)?;
+    let mut extendable_length = max_booking_length - (booking.time_to - booking.time_from);
 
-    if extendable_hours > 0 {
+    if extendable_length > TimeDelta::zero() {
         let start = booking.time_to;
-        let end = start + TimeDelta::hours(extendable_hours);
+        let end = start + extendable_length;
         let mut slots = get_occupied_slots(
             &mut tx,
             &now,
@@ -81,10 +93,7 @@ pub async fn get(
         slots.sort_by(|a, b| a.time_from.cmp(&b.time_from));
 
         if let Some(first) = slots.first() {
-            extendable_hours = std::cmp::min(
-                extendable_hours,
-                (first.time_from - booking.time_to).num_hours(),
-            );
+            extendable_length = std::cmp::min(extendable_length, first.time_from - booking.time_to);
         }
     }
 
@@ -93,6 +102,7 @@ pub async fn get(
             .finish(booking_config.as_ref(), &now),
         transaction,
         amendable,
-        extendable_hours,
+        extendable_hours: extendable_length.num_hours().max(0),
+        extendable_minutes: extendable_length.num_minutes().max(0),
     }))
 }
@@ -0,0 +1,176 @@
+use actix_web::web;
+use dxe_data::entities::Identity;
+use dxe_data::queries::hour_pack::{
+    create_hour_pack, get_hour_balance, get_hour_balance_entries, get_hour_pack,
+    get_hour_packs_by_holder_id,
+};
+use dxe_data::queries::identity::{get_identity, is_member_of};
+use dxe_data::queries::payment::{create_cash_transaction, get_cash_transaction};
+use dxe_data::queries::user::update_user_cash_payment_depositor_name;
+use dxe_types::ProductId;
+use sqlx::SqlitePool;
+
+use crate::config::{BookingConfig, TimeZoneConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{CashTransaction, HourBalanceEntry, HourPack, HourPackOffer};
+use crate::models::handlers::booking::{
+    GetHourBalanceQuery, GetHourBalanceResponse, GetHourPacksResponse, PurchaseHourPackRequest,
+    PurchaseHourPackResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::services::notification::{NotificationSender, Priority};
+use crate::session::UserSession;
+
+pub async fn get(
+    now: Now,
+    session: UserSession,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetHourPacksResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let hour_packs = get_hour_packs_by_holder_id(&mut connection, &session.user_id)
+        .await?
+        .into_iter()
+        .map(|v| HourPack::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetHourPacksResponse {
+        offers: booking_config
+            .hour_packs
+            .iter()
+            .map(HourPackOffer::from)
+            .collect(),
+        hour_packs,
+    }))
+}
+
+pub async fn post(
+    now: Now,
+    session: UserSession,
+    body: web::Json<PurchaseHourPackRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    notification_sender: web::Data<NotificationSender>,
+) -> Result<web::Json<PurchaseHourPackResponse>, Error> {
+    let offer = booking_config
+        .hour_packs
+        .iter()
+        .find(|v| v.id == body.offer_id)
+        .ok_or(Error::HourPackNotFound)?;
+
+    if body.depositor_name.is_empty() {
+        return Err(Error::MissingField("depositorName"));
+    }
+
+    let mut tx = database.begin().await?;
+
+    let identity = get_identity(&mut tx, &now, &body.identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut tx, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+        }
+    }
+
+    let hour_pack_id = create_hour_pack(
+        &mut tx,
+        &now,
+        &session.user_id,
+        &body.identity_id,
+        &offer.name,
+        offer.hours * 60,
+        offer.price,
+    )
+    .await?;
+
+    update_user_cash_payment_depositor_name(
+        &mut tx,
+        &session.user_id,
+        Some(body.depositor_name.as_str()),
+    )
+    .await?;
+
+    let product_id = ProductId::from(hour_pack_id);
+
+    create_cash_transaction(
+        &mut tx,
+        &now,
+        &product_id,
+        body.depositor_name.as_str(),
+        offer.price,
+    )
+    .await?;
+
+    let hour_pack = get_hour_pack(&mut tx, &hour_pack_id)
+        .await?
+        .ok_or(Error::HourPackNotFound)?;
+
+    let cash_tx = get_cash_transaction(&mut tx, &product_id)
+        .await?
+        .ok_or(Error::HourPackNotFound)?;
+
+    tx.commit().await?;
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "New hour pack request by {}: {} ({} won)",
+            identity.name(),
+            offer.name,
+            offer.price
+        ),
+    );
+
+    Ok(web::Json(PurchaseHourPackResponse {
+        hour_pack: HourPack::convert(hour_pack, &timezone_config, &now)?,
+        cash_transaction: CashTransaction::convert(cash_tx, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn get_balance(
+    now: Now,
+    session: UserSession,
+    query: web::Query<GetHourBalanceQuery>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetHourBalanceResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let identity = get_identity(&mut connection, &now, &query.identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut connection, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+        }
+    }
+
+    let minutes = get_hour_balance(&mut connection, &query.identity_id).await?;
+    let entries = get_hour_balance_entries(&mut connection, &query.identity_id)
+        .await?
+        .into_iter()
+        .map(|v| HourBalanceEntry::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetHourBalanceResponse { minutes, entries }))
+}
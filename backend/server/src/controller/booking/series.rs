@@ -0,0 +1,761 @@
+#![allow(clippy::too_many_arguments)]
+
+use actix_web::web;
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use dxe_data::entities::Identity;
+use dxe_data::queries::booking::{
+    add_booking_to_series, cancel_booking, cancel_booking_series, create_booking,
+    create_booking_series, get_booking_series_with_user_id, get_booking_with_user_id,
+    get_bookings_by_series_id, is_booking_available,
+};
+use dxe_data::queries::identity::{get_group_members, get_identity, is_member_of};
+use dxe_data::queries::payment::{
+    create_cash_transaction, get_cash_transaction, update_cash_refund_information,
+};
+use dxe_data::queries::unit::is_unit_enabled;
+use dxe_data::queries::user::{
+    update_user_cash_payment_depositor_name, update_user_cash_payment_refund_account,
+};
+use dxe_types::{BookingSeriesId, ProductId, UnitId};
+use sqlx::SqlitePool;
+
+use crate::config::{BookingConfig, TimeZoneConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::{
+    Booking, BookingSeries, BookingSeriesOccurrence, BookingWithPayments, CashTransaction,
+    Transaction,
+};
+use crate::models::handlers::booking::{
+    BookingSeriesRecurrence, CancelBookingSeriesRequest, CancelBookingSeriesResponse,
+    CheckBookingSeriesRequest, CheckBookingSeriesResponse, GetBookingSeriesResponse,
+    SubmitBookingSeriesRequest, SubmitBookingSeriesResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::services::calendar::CalendarService;
+use crate::services::messaging::MessagingService;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::services::waitlist::WaitlistService;
+use crate::services::webhook::{BookingPayload, WebhookEvent, enqueue_webhook_event};
+use crate::session::UserSession;
+use crate::utils::datetime::{desired_length, is_in_effect};
+
+fn build_occurrences(
+    booking_config: &BookingConfig,
+    now: &DateTime<Utc>,
+    unit_id: &UnitId,
+    time_from: DateTime<FixedOffset>,
+    length: TimeDelta,
+    recurrence: BookingSeriesRecurrence,
+    until: Option<DateTime<FixedOffset>>,
+    count: Option<i64>,
+) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, Error> {
+    if !booking_config
+        .is_valid_length(unit_id, length)
+        .map_err(|_| Error::UnitNotFound)?
+    {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    if until.is_none() && count.is_none() {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    // A series bounded only by its end date is still capped by the configured maximum and cut
+    // off at the booking horizon, while an explicit count has to fit within both.
+    let bounded_by_count = count.is_some();
+    let count = count.unwrap_or(booking_config.series.max_occurrences);
+    if count <= 0 || count > booking_config.series.max_occurrences {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let time_from = booking_config
+        .truncate_time(unit_id, time_from)
+        .map_err(|_| Error::UnitNotFound)?;
+    if &time_from < now {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let until = until.map(|v| v.to_utc());
+    let horizon = *now + TimeDelta::days(booking_config.lookahead_days);
+    let interval = TimeDelta::weeks(recurrence.interval_weeks());
+
+    let mut occurrences = vec![];
+    let mut start = time_from;
+    while (occurrences.len() as i64) < count {
+        if let Some(until) = until
+            && start > until
+        {
+            break;
+        }
+
+        if start > horizon {
+            if bounded_by_count {
+                return Err(Error::InvalidTimeRange);
+            }
+            break;
+        }
+
+        occurrences.push((start, start + length));
+        start += interval;
+    }
+
+    if occurrences.is_empty() {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    Ok(occurrences)
+}
+
+pub async fn check(
+    now: Now,
+    _session: UserSession,
+    body: web::Json<CheckBookingSeriesRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<CheckBookingSeriesResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    if is_unit_enabled(&mut connection, &body.unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    let occurrences = build_occurrences(
+        &booking_config,
+        &now,
+        &body.unit_id,
+        body.time_from,
+        desired_length(body.desired_hours, body.desired_minutes),
+        body.recurrence,
+        body.until,
+        body.count,
+    )?;
+
+    let mut total_price = 0;
+    let mut result = vec![];
+
+    for (time_from, time_to) in occurrences {
+        let available = is_booking_available(
+            &mut connection,
+            &now,
+            &body.unit_id,
+            &time_from,
+            &time_to,
+            None,
+            None,
+        )
+        .await?;
+
+        let price = booking_config
+            .calculate_price(&timezone_config, &body.unit_id, time_from, time_to)
+            .map_err(|_| Error::UnitNotFound)?
+            .total;
+
+        if available {
+            total_price += price;
+        }
+
+        result.push(BookingSeriesOccurrence {
+            booking_start: timezone_config.convert(time_from),
+            booking_end: timezone_config.convert(time_to),
+            available,
+            price,
+        });
+    }
+
+    Ok(web::Json(CheckBookingSeriesResponse {
+        occurrences: result,
+        total_price,
+    }))
+}
+
+pub async fn post(
+    now: Now,
+    session: UserSession,
+    body: web::Json<SubmitBookingSeriesRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    notification_sender: web::Data<NotificationSender>,
+    calendar_service: web::Data<Option<CalendarService>>,
+) -> Result<web::Json<SubmitBookingSeriesResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    if is_unit_enabled(&mut tx, &body.unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    let occurrences = build_occurrences(
+        &booking_config,
+        &now,
+        &body.unit_id,
+        body.time_from,
+        desired_length(body.desired_hours, body.desired_minutes),
+        body.recurrence,
+        body.until,
+        body.count,
+    )?;
+
+    let identity = get_identity(&mut tx, &now, &body.identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    let customers = match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+            vec![u.clone()]
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut tx, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+            get_group_members(&mut tx, &g.id).await?
+        }
+    };
+
+    let series_id = create_booking_series(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &session.user_id,
+        &body.identity_id,
+        body.recurrence.interval_weeks(),
+    )
+    .await?;
+
+    let mut bookings = vec![];
+    let mut conflicts = vec![];
+
+    for (time_from, time_to) in occurrences {
+        let price = booking_config
+            .calculate_price(&timezone_config, &body.unit_id, time_from, time_to)
+            .map_err(|_| Error::UnitNotFound)?
+            .total;
+
+        if !is_booking_available(
+            &mut tx,
+            &now,
+            &body.unit_id,
+            &time_from,
+            &time_to,
+            None,
+            None,
+        )
+        .await?
+        {
+            conflicts.push(BookingSeriesOccurrence {
+                booking_start: timezone_config.convert(time_from),
+                booking_end: timezone_config.convert(time_to),
+                available: false,
+                price,
+            });
+            continue;
+        }
+
+        let booking_id = create_booking(
+            &mut tx,
+            &now,
+            &body.unit_id,
+            &session.user_id,
+            &body.identity_id,
+            &time_from,
+            &time_to,
+            false,
+        )
+        .await?;
+
+        add_booking_to_series(&mut tx, &series_id, &booking_id).await?;
+
+        let product_id = ProductId::from(booking_id);
+
+        create_cash_transaction(
+            &mut tx,
+            &now,
+            &product_id,
+            body.depositor_name.as_str(),
+            price,
+        )
+        .await?;
+
+        let booking = get_booking_with_user_id(&mut tx, &booking_id, &session.user_id)
+            .await?
+            .ok_or(Error::BookingNotFound)?;
+
+        let cash_tx = get_cash_transaction(&mut tx, &product_id)
+            .await?
+            .ok_or(Error::BookingNotFound)?;
+
+        enqueue_webhook_event(
+            &mut tx,
+            &now,
+            WebhookEvent::BookingCreated {
+                booking: (&booking).into(),
+            },
+        )
+        .await?;
+
+        bookings.push((booking, cash_tx));
+    }
+
+    if bookings.is_empty() {
+        return Err(Error::TimeRangeOccupied);
+    }
+
+    update_user_cash_payment_depositor_name(
+        &mut tx,
+        &session.user_id,
+        Some(body.depositor_name.as_str()),
+    )
+    .await?;
+
+    let series = get_booking_series_with_user_id(&mut tx, &series_id, &session.user_id)
+        .await?
+        .ok_or(Error::BookingSeriesNotFound)?;
+
+    tx.commit().await?;
+
+    if let Some(calendar_service) = calendar_service.as_ref() {
+        for (booking, _) in bookings.iter() {
+            if let Err(e) = calendar_service.register_booking(booking, &customers).await {
+                log::error!("Failed to register event on calendar: {e}");
+            }
+        }
+    }
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "New booking series request by {}: {} ({} minutes, {} occurrences, {} conflicts)",
+            identity.name(),
+            timezone_config.convert(bookings[0].0.time_from),
+            (bookings[0].0.time_to - bookings[0].0.time_from).num_minutes(),
+            bookings.len(),
+            conflicts.len(),
+        ),
+    );
+
+    Ok(web::Json(SubmitBookingSeriesResponse {
+        series: BookingSeries::convert(series, &timezone_config, &now)?,
+        bookings: bookings
+            .into_iter()
+            .map(|(booking, cash_tx)| {
+                Ok(BookingWithPayments {
+                    booking: Booking::convert(booking, &timezone_config, &now)?
+                        .finish(booking_config.as_ref(), &now),
+                    transaction: Some(Transaction::Cash(CashTransaction::convert(
+                        cash_tx,
+                        &timezone_config,
+                        &now,
+                    )?)),
+                })
+            })
+            .collect::<Result<_, Error>>()?,
+        conflicts,
+    }))
+}
+
+pub async fn get(
+    now: Now,
+    session: UserSession,
+    series_id: web::Path<BookingSeriesId>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetBookingSeriesResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let series = get_booking_series_with_user_id(&mut connection, &series_id, &session.user_id)
+        .await?
+        .ok_or(Error::BookingSeriesNotFound)?;
+
+    let mut bookings = vec![];
+    for booking in get_bookings_by_series_id(&mut connection, &series_id).await? {
+        let transaction = if let Some(cash_tx) =
+            get_cash_transaction(&mut connection, &ProductId::from(booking.id)).await?
+        {
+            Some(Transaction::Cash(CashTransaction::convert(
+                cash_tx,
+                &timezone_config,
+                &now,
+            )?))
+        } else {
+            None
+        };
+
+        bookings.push(BookingWithPayments {
+            booking: Booking::convert(booking, &timezone_config, &now)?
+                .finish(booking_config.as_ref(), &now),
+            transaction,
+        });
+    }
+
+    Ok(web::Json(GetBookingSeriesResponse {
+        series: BookingSeries::convert(series, &timezone_config, &now)?,
+        bookings,
+    }))
+}
+
+pub async fn delete(
+    now: Now,
+    session: UserSession,
+    series_id: web::Path<BookingSeriesId>,
+    query: web::Query<CancelBookingSeriesRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    notification_sender: web::Data<NotificationSender>,
+    messaging_service: web::Data<MessagingService>,
+    calendar_service: web::Data<Option<CalendarService>>,
+    waitlist_service: web::Data<WaitlistService>,
+) -> Result<web::Json<CancelBookingSeriesResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    let series = get_booking_series_with_user_id(&mut tx, &series_id, &session.user_id)
+        .await?
+        .ok_or(Error::BookingSeriesNotFound)?;
+
+    let mut canceled_bookings = vec![];
+
+    // Occurrences that have already started are left as they are; every upcoming one is
+    // canceled and refunded on its own schedule.
+    for booking in get_bookings_by_series_id(&mut tx, &series_id).await? {
+        if is_in_effect(&booking.canceled_at, &now) || booking.time_from <= *now {
+            continue;
+        }
+
+        cancel_booking(&mut tx, &now, &booking.id).await?;
+
+        let product_id = ProductId::from(booking.id);
+
+        let transaction =
+            if let Some(mut cash_tx) = get_cash_transaction(&mut tx, &product_id).await? {
+                // Shortened occurrences may already have been partially refunded
+                let prior_refund_price = cash_tx.refund_price.unwrap_or(0);
+                let remaining_price = cash_tx.price - prior_refund_price;
+                let refund_price = booking_config
+                    .calculate_refund_price(
+                        timezone_config.as_ref(),
+                        remaining_price,
+                        booking.time_from,
+                        *now,
+                    )
+                    .map_err(|_| Error::NotRefundable)?;
+
+                if refund_price > 0 && query.refund_account.is_none() {
+                    return Err(Error::RefundAccountRequired);
+                }
+
+                if !update_cash_refund_information(
+                    &mut tx,
+                    &product_id,
+                    prior_refund_price + refund_price,
+                    query.refund_account.clone(),
+                )
+                .await?
+                {
+                    return Err(Error::NotRefundable);
+                }
+
+                cash_tx.refund_price = Some(prior_refund_price + refund_price);
+                cash_tx.refund_account = query.refund_account.clone();
+                cash_tx.refunded_at = None;
+
+                let refund_rate = if remaining_price > 0 {
+                    (refund_price * 100 / remaining_price) as i32
+                } else {
+                    100
+                };
+
+                messaging_service
+                    .send_cancellation(&mut tx, &now, booking.clone(), refund_rate)
+                    .await?;
+
+                Some(Transaction::Cash(CashTransaction::convert(
+                    cash_tx,
+                    &timezone_config,
+                    &now,
+                )?))
+            } else {
+                enqueue_webhook_event(
+                    &mut tx,
+                    &now,
+                    WebhookEvent::BookingCanceled {
+                        booking: BookingPayload::canceled(&booking, &now),
+                        refund_rate: None,
+                    },
+                )
+                .await?;
+
+                None
+            };
+
+        waitlist_service
+            .release(
+                &mut tx,
+                &now,
+                &booking.unit_id,
+                &booking.time_from,
+                &booking.time_to,
+            )
+            .await?;
+
+        canceled_bookings.push((booking, transaction));
+    }
+
+    if let Some(refund_account) = &query.refund_account {
+        let _ = update_user_cash_payment_refund_account(
+            &mut tx,
+            &session.user_id,
+            Some(refund_account.as_str()),
+        )
+        .await?;
+    }
+
+    cancel_booking_series(&mut tx, &now, &series_id).await?;
+
+    let series = get_booking_series_with_user_id(&mut tx, &series_id, &session.user_id)
+        .await?
+        .unwrap_or(series);
+
+    tx.commit().await?;
+
+    if let Some(calendar_service) = calendar_service.as_ref() {
+        for (booking, _) in canceled_bookings.iter() {
+            if let Err(e) = calendar_service.delete_booking(&booking.id).await {
+                log::error!("Failed to delete event on calendar: {e}");
+            }
+        }
+    }
+
+    if let Some((booking, _)) = canceled_bookings.first() {
+        notification_sender.enqueue(
+            Priority::High,
+            format!(
+                "Booking series cancellation by {}: {} ({} occurrences)",
+                booking.customer.name(),
+                timezone_config.convert(booking.time_from),
+                canceled_bookings.len(),
+            ),
+        );
+    }
+
+    Ok(web::Json(CancelBookingSeriesResponse {
+        series: BookingSeries::convert(series, &timezone_config, &now)?,
+        canceled_bookings: canceled_bookings
+            .into_iter()
+            .map(|(booking, transaction)| {
+                Ok(BookingWithPayments {
+                    booking: Booking::convert(booking, &timezone_config, &now)?
+                        .finish(booking_config.as_ref(), &now),
+                    transaction,
+                })
+            })
+            .collect::<Result<_, Error>>()?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, TimeDelta, TimeZone, Utc};
+    use dxe_types::UnitId;
+
+    use super::build_occurrences;
+    use crate::config::tests::booking_config;
+    use crate::models::Error;
+    use crate::models::handlers::booking::BookingSeriesRecurrence;
+
+    fn unit_id() -> UnitId {
+        UnitId::from("studio".to_owned())
+    }
+
+    fn kst(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(9 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        kst(3, 1, 9, 0).to_utc()
+    }
+
+    #[test]
+    fn weekly_series_bounded_by_count() {
+        let occurrences = build_occurrences(
+            &booking_config(),
+            &now(),
+            &unit_id(),
+            kst(3, 2, 19, 0),
+            TimeDelta::hours(2),
+            BookingSeriesRecurrence::Weekly,
+            None,
+            Some(3),
+        )
+        .unwrap();
+
+        assert_eq!(
+            occurrences,
+            vec![
+                (kst(3, 2, 19, 0).to_utc(), kst(3, 2, 21, 0).to_utc()),
+                (kst(3, 9, 19, 0).to_utc(), kst(3, 9, 21, 0).to_utc()),
+                (kst(3, 16, 19, 0).to_utc(), kst(3, 16, 21, 0).to_utc()),
+            ]
+        );
+    }
+
+    #[test]
+    fn biweekly_series_bounded_by_date() {
+        let occurrences = build_occurrences(
+            &booking_config(),
+            &now(),
+            &unit_id(),
+            kst(3, 2, 19, 0),
+            TimeDelta::hours(1),
+            BookingSeriesRecurrence::Biweekly,
+            Some(kst(3, 30, 19, 0)),
+            None,
+        )
+        .unwrap();
+
+        let starts = occurrences.iter().map(|v| v.0).collect::<Vec<_>>();
+        assert_eq!(
+            starts,
+            vec![
+                kst(3, 2, 19, 0).to_utc(),
+                kst(3, 16, 19, 0).to_utc(),
+                kst(3, 30, 19, 0).to_utc(),
+            ]
+        );
+    }
+
+    #[test]
+    fn date_bounded_series_is_capped_by_max_occurrences() {
+        let mut config = booking_config();
+        config.lookahead_days = 365;
+
+        let occurrences = build_occurrences(
+            &config,
+            &now(),
+            &unit_id(),
+            kst(3, 2, 19, 0),
+            TimeDelta::hours(1),
+            BookingSeriesRecurrence::Weekly,
+            Some(kst(12, 31, 0, 0)),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(occurrences.len(), 12);
+    }
+
+    #[test]
+    fn date_bounded_series_is_cut_off_at_horizon() {
+        let occurrences = build_occurrences(
+            &booking_config(),
+            &now(),
+            &unit_id(),
+            kst(3, 2, 19, 0),
+            TimeDelta::hours(1),
+            BookingSeriesRecurrence::Weekly,
+            Some(kst(12, 31, 0, 0)),
+            None,
+        )
+        .unwrap();
+
+        // 60 days after March 1st
+        assert_eq!(occurrences.len(), 9);
+        assert_eq!(occurrences.last().unwrap().0, kst(4, 27, 19, 0).to_utc());
+    }
+
+    #[test]
+    fn start_is_aligned_to_slot() {
+        let occurrences = build_occurrences(
+            &booking_config(),
+            &now(),
+            &unit_id(),
+            kst(3, 2, 19, 40),
+            TimeDelta::minutes(30),
+            BookingSeriesRecurrence::Weekly,
+            None,
+            Some(1),
+        )
+        .unwrap();
+
+        assert_eq!(
+            occurrences,
+            vec![(kst(3, 2, 19, 30).to_utc(), kst(3, 2, 20, 0).to_utc())]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_series() {
+        let config = booking_config();
+        let build = |time_from, length, until, count| {
+            build_occurrences(
+                &config,
+                &now(),
+                &unit_id(),
+                time_from,
+                length,
+                BookingSeriesRecurrence::Weekly,
+                until,
+                count,
+            )
+        };
+
+        // Unbounded
+        assert!(matches!(
+            build(kst(3, 2, 19, 0), TimeDelta::hours(1), None, None),
+            Err(Error::InvalidTimeRange)
+        ));
+        // Too many occurrences
+        assert!(matches!(
+            build(kst(3, 2, 19, 0), TimeDelta::hours(1), None, Some(13)),
+            Err(Error::InvalidTimeRange)
+        ));
+        // Past the horizon
+        assert!(matches!(
+            build(kst(3, 2, 19, 0), TimeDelta::hours(1), None, Some(10)),
+            Err(Error::InvalidTimeRange)
+        ));
+        // Not a whole number of slots
+        assert!(matches!(
+            build(kst(3, 2, 19, 0), TimeDelta::minutes(45), None, Some(2)),
+            Err(Error::InvalidTimeRange)
+        ));
+        // Starts in the past
+        assert!(matches!(
+            build(kst(2, 23, 19, 0), TimeDelta::hours(1), None, Some(2)),
+            Err(Error::InvalidTimeRange)
+        ));
+        // Ends before the first occurrence
+        assert!(matches!(
+            build(
+                kst(3, 2, 19, 0),
+                TimeDelta::hours(1),
+                Some(kst(3, 1, 19, 0)),
+                None
+            ),
+            Err(Error::InvalidTimeRange)
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_unit() {
+        assert!(matches!(
+            build_occurrences(
+                &booking_config(),
+                &now(),
+                &UnitId::from("unknown".to_owned()),
+                kst(3, 2, 19, 0),
+                TimeDelta::hours(1),
+                BookingSeriesRecurrence::Weekly,
+                None,
+                Some(1),
+            ),
+            Err(Error::UnitNotFound)
+        ));
+    }
+}
@@ -1,7 +1,13 @@
+#![allow(clippy::too_many_arguments)]
+
 use actix_web::web;
-use chrono::TimeDelta;
-use dxe_data::entities::Identity;
-use dxe_data::queries::booking::{create_booking, get_booking_with_user_id};
+use chrono::{DateTime, Utc};
+use dxe_data::entities::{Identity, User};
+use dxe_data::queries::booking::{
+    create_booking, expire_adhoc_reservation, get_adhoc_reservation, get_booking_with_user_id,
+};
+use dxe_data::queries::coupon::create_coupon_redemption;
+use dxe_data::queries::hour_pack::{create_hour_balance_entry, get_hour_balance};
 use dxe_data::queries::identity::{get_group_members, get_identity, is_member_of};
 use dxe_data::queries::payment::{create_cash_transaction, get_cash_transaction};
 use dxe_data::queries::unit::is_unit_enabled;
@@ -15,9 +21,11 @@ use crate::models::entities::{Booking, CashTransaction};
 use crate::models::handlers::booking::{SubmitBookingRequest, SubmitBookingResponse};
 use crate::models::{Error, IntoView};
 use crate::services::calendar::CalendarService;
+use crate::services::coupon::apply_coupon;
 use crate::services::notification::{NotificationSender, Priority};
+use crate::services::webhook::{WebhookEvent, enqueue_webhook_event};
 use crate::session::UserSession;
-use crate::utils::datetime::truncate_time;
+use crate::utils::datetime::{desired_length, is_in_effect};
 
 pub async fn post(
     now: Now,
@@ -35,12 +43,18 @@ pub async fn post(
         return Err(Error::UnitNotFound);
     }
 
-    if body.desired_hours > booking_config.max_booking_hours {
+    let length = desired_length(body.desired_hours, body.desired_minutes);
+    if !booking_config
+        .is_valid_length(&body.unit_id, length)
+        .map_err(|_| Error::UnitNotFound)?
+    {
         return Err(Error::InvalidTimeRange);
     }
 
-    let time_from = truncate_time(body.time_from).to_utc();
-    let time_to = time_from + TimeDelta::hours(body.desired_hours);
+    let time_from = booking_config
+        .truncate_time(&body.unit_id, body.time_from)
+        .map_err(|_| Error::UnitNotFound)?;
+    let time_to = time_from + length;
 
     let identity = get_identity(&mut tx, &now, &body.identity_id)
         .await?
@@ -61,6 +75,60 @@ pub async fn post(
         }
     };
 
+    // Consume a temporary reservation held by the user (e.g. a waitlist priority hold)
+    if let Some(temporary_reservation_id) = body.temporary_reservation_id
+        && let Some(temporary_reservation) =
+            get_adhoc_reservation(&mut tx, &temporary_reservation_id).await?
+        && temporary_reservation.holder.id == session.user_id
+        && temporary_reservation.unit_id == body.unit_id
+        && !is_in_effect(&temporary_reservation.deleted_at, &now)
+    {
+        expire_adhoc_reservation(&mut tx, &now, &temporary_reservation.id).await?;
+    }
+
+    if body.use_hour_balance {
+        return submit_with_hour_balance(
+            now,
+            session,
+            body,
+            tx,
+            booking_config,
+            timezone_config,
+            notification_sender,
+            calendar_service,
+            identity,
+            customers,
+            time_from,
+            time_to,
+        )
+        .await;
+    }
+
+    if body.depositor_name.is_empty() {
+        return Err(Error::MissingField("depositorName"));
+    }
+
+    let mut price = booking_config
+        .calculate_price(&timezone_config, &body.unit_id, time_from, time_to)
+        .map_err(|_| Error::UnitNotFound)?;
+
+    // Validate the coupon before the booking exists, so that it does not count as a prior booking
+    let applied_coupon = if let Some(coupon_code) = body.coupon_code.as_deref() {
+        Some(
+            apply_coupon(
+                &mut tx,
+                &now,
+                coupon_code,
+                &session.user_id,
+                &body.unit_id,
+                &mut price,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
     let booking_id = create_booking(
         &mut tx,
         &now,
@@ -73,10 +141,6 @@ pub async fn post(
     )
     .await?;
 
-    let price = booking_config
-        .calculate_price(&body.unit_id, time_from, time_to)
-        .map_err(|_| Error::UnitNotFound)?;
-
     update_user_cash_payment_depositor_name(
         &mut tx,
         &session.user_id,
@@ -91,10 +155,24 @@ pub async fn post(
         &now,
         &product_id,
         body.depositor_name.as_str(),
-        price,
+        price.total,
     )
     .await?;
 
+    if let Some(applied_coupon) = &applied_coupon {
+        create_coupon_redemption(
+            &mut tx,
+            &now,
+            &applied_coupon.coupon.id,
+            &session.user_id,
+            Some(&product_id),
+            None,
+            applied_coupon.original_price,
+            applied_coupon.discount_price,
+        )
+        .await?;
+    }
+
     let booking = get_booking_with_user_id(&mut tx, &booking_id, &session.user_id)
         .await?
         .ok_or(Error::BookingNotFound)?;
@@ -103,6 +181,101 @@ pub async fn post(
         .await?
         .ok_or(Error::BookingNotFound)?;
 
+    enqueue_webhook_event(
+        &mut tx,
+        &now,
+        WebhookEvent::BookingCreated {
+            booking: (&booking).into(),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if let Some(calendar_service) = calendar_service.as_ref()
+        && let Err(e) = calendar_service
+            .register_booking(&booking, &customers)
+            .await
+    {
+        log::error!("Failed to register event on calendar: {e}");
+    }
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "New booking request by {}: {} ({} minutes)",
+            identity.name(),
+            timezone_config.convert(time_from),
+            length.num_minutes()
+        ),
+    );
+
+    Ok(web::Json(SubmitBookingResponse {
+        booking: Booking::convert(booking, &timezone_config, &now)?
+            .finish(booking_config.as_ref(), &now),
+        cash_transaction: Some(CashTransaction::convert(cash_tx, &timezone_config, &now)?),
+    }))
+}
+
+async fn submit_with_hour_balance(
+    now: Now,
+    session: UserSession,
+    body: web::Json<SubmitBookingRequest>,
+    mut tx: sqlx::SqliteTransaction<'_>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    notification_sender: web::Data<NotificationSender>,
+    calendar_service: web::Data<Option<CalendarService>>,
+    identity: Identity,
+    customers: Vec<User>,
+    time_from: DateTime<Utc>,
+    time_to: DateTime<Utc>,
+) -> Result<web::Json<SubmitBookingResponse>, Error> {
+    if body.coupon_code.is_some() {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    let minutes = (time_to - time_from).num_minutes();
+    if get_hour_balance(&mut tx, &body.identity_id).await? < minutes {
+        return Err(Error::InsufficientHourBalance);
+    }
+
+    // Prepaid bookings need no deposit confirmation
+    let booking_id = create_booking(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &session.user_id,
+        &body.identity_id,
+        &time_from,
+        &time_to,
+        true,
+    )
+    .await?;
+
+    create_hour_balance_entry(
+        &mut tx,
+        &now,
+        &body.identity_id,
+        -minutes,
+        None,
+        Some(&booking_id),
+    )
+    .await?;
+
+    let booking = get_booking_with_user_id(&mut tx, &booking_id, &session.user_id)
+        .await?
+        .ok_or(Error::BookingNotFound)?;
+
+    enqueue_webhook_event(
+        &mut tx,
+        &now,
+        WebhookEvent::BookingCreated {
+            booking: (&booking).into(),
+        },
+    )
+    .await?;
+
     tx.commit().await?;
 
     if let Some(calendar_service) = calendar_service.as_ref()
@@ -116,16 +289,16 @@ pub async fn post(
     notification_sender.enqueue(
         Priority::High,
         format!(
-            "New booking request by {}: {} ({} hours)",
+            "New booking by {} with hour balance: {} ({} minutes)",
             identity.name(),
             timezone_config.convert(time_from),
-            body.desired_hours
+            minutes
         ),
     );
 
     Ok(web::Json(SubmitBookingResponse {
         booking: Booking::convert(booking, &timezone_config, &now)?
             .finish(booking_config.as_ref(), &now),
-        cash_transaction: CashTransaction::convert(cash_tx, &timezone_config, &now)?,
+        cash_transaction: None,
     }))
 }
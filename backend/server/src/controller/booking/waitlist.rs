@@ -0,0 +1,180 @@
+use actix_web::web;
+use dxe_data::entities::Identity;
+use dxe_data::queries::booking::{
+    expire_adhoc_reservation, get_adhoc_reservation, is_booking_available,
+};
+use dxe_data::queries::identity::{get_identity, is_member_of};
+use dxe_data::queries::unit::is_unit_enabled;
+use dxe_data::queries::waitlist::{
+    create_waitlist_entry, delete_waitlist_entry, get_waitlist_entries_by_user_id,
+    get_waitlist_entry,
+};
+use dxe_types::WaitlistEntryId;
+use sqlx::SqlitePool;
+
+use crate::config::{BookingConfig, TimeZoneConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::WaitlistEntry;
+use crate::models::handlers::booking::{
+    CreateWaitlistEntryRequest, CreateWaitlistEntryResponse, GetWaitlistEntriesResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::services::notification::{NotificationSender, Priority};
+use crate::services::waitlist::WaitlistService;
+use crate::session::UserSession;
+use crate::utils::datetime::{desired_length, is_in_effect};
+
+pub async fn get(
+    now: Now,
+    session: UserSession,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetWaitlistEntriesResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let entries = get_waitlist_entries_by_user_id(&mut connection, &now, &session.user_id)
+        .await?
+        .into_iter()
+        .map(|v| WaitlistEntry::convert(v, &timezone_config, &now))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(GetWaitlistEntriesResponse { entries }))
+}
+
+pub async fn post(
+    now: Now,
+    session: UserSession,
+    body: web::Json<CreateWaitlistEntryRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    notification_sender: web::Data<NotificationSender>,
+) -> Result<web::Json<CreateWaitlistEntryResponse>, Error> {
+    let mut tx = database.begin().await?;
+
+    if is_unit_enabled(&mut tx, &body.unit_id).await? != Some(true) {
+        return Err(Error::UnitNotFound);
+    }
+
+    let length = desired_length(body.desired_hours, body.desired_minutes);
+    if !booking_config
+        .is_valid_length(&body.unit_id, length)
+        .map_err(|_| Error::UnitNotFound)?
+    {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let time_from = booking_config
+        .truncate_time(&body.unit_id, body.time_from)
+        .map_err(|_| Error::UnitNotFound)?;
+    let time_to = time_from + length;
+
+    if time_from <= *now {
+        return Err(Error::InvalidTimeRange);
+    }
+
+    let identity = get_identity(&mut tx, &now, &body.identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut tx, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+        }
+    }
+
+    if is_booking_available(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &time_from,
+        &time_to,
+        None,
+        None,
+    )
+    .await?
+    {
+        return Err(Error::WaitlistNotRequired);
+    }
+
+    let entry_id = create_waitlist_entry(
+        &mut tx,
+        &now,
+        &body.unit_id,
+        &session.user_id,
+        &body.identity_id,
+        &time_from,
+        &time_to,
+    )
+    .await?;
+
+    let entry = get_waitlist_entry(&mut tx, &entry_id)
+        .await?
+        .ok_or(Error::WaitlistEntryNotFound)?;
+
+    tx.commit().await?;
+
+    notification_sender.enqueue(
+        Priority::Low,
+        format!(
+            "New waitlist entry by {}: {} ({} minutes)",
+            identity.name(),
+            timezone_config.convert(time_from),
+            length.num_minutes()
+        ),
+    );
+
+    Ok(web::Json(CreateWaitlistEntryResponse {
+        entry: WaitlistEntry::convert(entry, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn delete(
+    now: Now,
+    session: UserSession,
+    waitlist_entry_id: web::Path<WaitlistEntryId>,
+    database: web::Data<SqlitePool>,
+    waitlist_service: web::Data<WaitlistService>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut tx = database.begin().await?;
+
+    let entry = get_waitlist_entry(&mut tx, waitlist_entry_id.as_ref())
+        .await?
+        .ok_or(Error::WaitlistEntryNotFound)?;
+
+    if entry.user_id != session.user_id || is_in_effect(&entry.deleted_at, &now) {
+        return Err(Error::WaitlistEntryNotFound);
+    }
+
+    delete_waitlist_entry(&mut tx, &now, &entry.id).await?;
+
+    // Pass the priority hold on to the next waiter if it is still active
+    if let Some(adhoc_reservation_id) = entry.adhoc_reservation_id
+        && let Some(adhoc_reservation) =
+            get_adhoc_reservation(&mut tx, &adhoc_reservation_id).await?
+        && !is_in_effect(&adhoc_reservation.deleted_at, &now)
+    {
+        expire_adhoc_reservation(&mut tx, &now, &adhoc_reservation.id).await?;
+
+        waitlist_service
+            .release(
+                &mut tx,
+                &now,
+                &adhoc_reservation.unit_id,
+                &adhoc_reservation.time_from,
+                &adhoc_reservation.time_to,
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
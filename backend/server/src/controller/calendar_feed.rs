@@ -0,0 +1,72 @@
+use actix_web::{HttpResponse, web};
+use chrono::TimeDelta;
+use dxe_data::entities::Identity;
+use dxe_data::queries::booking::get_bookings_by_user_id;
+use dxe_data::queries::calendar_feed::get_calendar_feed_by_token;
+use dxe_data::queries::identity::get_identity;
+use dxe_types::CalendarFeedToken;
+use sqlx::SqlitePool;
+
+use crate::config::UrlConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::utils::datetime::is_in_effect;
+use crate::utils::ical::{Calendar, CalendarEvent};
+
+const FEED_HISTORY_DAYS: i64 = 90;
+
+async fn get(
+    now: Now,
+    token: web::Path<CalendarFeedToken>,
+    database: web::Data<SqlitePool>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse, Error> {
+    let mut connection = database.acquire().await?;
+
+    let feed = get_calendar_feed_by_token(&mut connection, token.as_ref())
+        .await?
+        .ok_or(Error::CalendarFeedNotFound)?;
+
+    let identity = get_identity(&mut connection, &now, &feed.identity_id)
+        .await?
+        .ok_or(Error::CalendarFeedNotFound)?;
+
+    let range_from = *now - TimeDelta::days(FEED_HISTORY_DAYS);
+
+    // Group feeds are taken from the owner, who is always a member of the group
+    let bookings = match &identity {
+        Identity::User(u) => {
+            get_bookings_by_user_id(&mut connection, &now, &u.id, &range_from, false).await?
+        }
+        Identity::Group(g) => {
+            get_bookings_by_user_id(&mut connection, &now, &g.owner_id, &range_from, false)
+                .await?
+                .into_iter()
+                .filter(|v| v.customer.id() == feed.identity_id)
+                .collect()
+        }
+    };
+
+    let host = url_config.base_url.host_str().unwrap_or("localhost");
+
+    let mut calendar = Calendar::new(&format!("드림하우스 합주실 - {}", identity.name()), *now);
+    for booking in bookings {
+        let summary = format!("{} ({})", booking.customer.name(), booking.unit_id);
+
+        calendar.add_event(CalendarEvent {
+            uid: format!("{}@{host}", booking.id),
+            summary: &summary,
+            time_from: booking.time_from,
+            time_to: booking.time_to,
+            is_tentative: !is_in_effect(&booking.confirmed_at, &now),
+        });
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(calendar.finish()))
+}
+
+pub fn resource() -> actix_web::Resource {
+    web::resource("/calendar-feed/{token}.ics").route(web::get().to(get))
+}
@@ -0,0 +1,33 @@
+use actix_web::http::header;
+use actix_web::{HttpResponse, web};
+use dxe_data::queries::user::verify_user_email;
+use dxe_types::EmailVerificationToken;
+use sqlx::SqlitePool;
+
+use crate::config::UrlConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+
+async fn get(
+    now: Now,
+    token: web::Path<EmailVerificationToken>,
+    database: web::Data<SqlitePool>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse, Error> {
+    let mut connection = database.acquire().await?;
+
+    if !verify_user_email(&mut connection, &now, token.as_ref()).await? {
+        return Err(Error::EmailVerificationNotFound);
+    }
+
+    let mut url = url_config.base_url.clone();
+    url.set_path("my/");
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url.to_string()))
+        .finish())
+}
+
+pub fn resource() -> actix_web::Resource {
+    web::resource("/email-verification/{token}").route(web::get().to(get))
+}
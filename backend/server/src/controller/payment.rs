@@ -5,6 +5,9 @@ use actix_web::web;
 pub fn scope() -> actix_web::Scope {
     web::scope("/payments")
         .service(web::resource("/toss").route(web::post().to(payment_toss::post)))
+        .service(
+            web::resource("/toss/hour-pack").route(web::post().to(payment_toss::post_hour_pack)),
+        )
         .service(
             web::resource("/toss/confirm").route(web::post().to(payment_toss::confirm_payment)),
         )
@@ -14,3 +17,7 @@ pub fn scope() -> actix_web::Scope {
                 .route(web::delete().to(payment_toss::delete)),
         )
 }
+
+pub fn webhook_resource() -> actix_web::Resource {
+    web::resource("/payments/toss/webhook").route(web::post().to(payment_toss::webhook))
+}
@@ -1,38 +1,55 @@
 use actix_web::web;
 use chrono::TimeDelta;
 use dxe_data::entities::{
-    AdhocReservation, BookingAmendment, Identity, Product, TossPaymentsTransaction,
+    AdhocReservation, BookingAmendment, HourPack, Identity, Product, TossPaymentsTransaction,
 };
 use dxe_data::queries::booking::{
-    cancel_booking_amendment, confirm_booking_amendment, create_adhoc_reservation, create_booking,
-    expire_adhoc_reservation, get_adhoc_reservation, get_booking, get_booking_with_user_id,
-    get_product, update_booking_time,
+    cancel_booking_amendment, confirm_booking_amendment, create_adhoc_reservation,
+    expire_adhoc_reservation, get_adhoc_reservation, get_booking, get_product, reschedule_booking,
 };
-use dxe_data::queries::identity::{get_group_members, get_identity, is_member_of};
+use dxe_data::queries::coupon::{
+    create_coupon_redemption, delete_pending_coupon_redemption,
+    is_pending_coupon_redemption_available,
+};
+use dxe_data::queries::hour_pack::{cancel_hour_pack, create_hour_pack};
+use dxe_data::queries::identity::{get_identity, is_member_of};
 use dxe_data::queries::payment::{
-    confirm_toss_payments_transaction, create_toss_payments_transaction,
-    get_toss_payments_transaction_by_id, get_toss_payments_transaction_by_temporary_reservation_id,
+    create_toss_payments_transaction, get_toss_payments_transaction_by_id,
+    get_toss_payments_transaction_by_temporary_reservation_id,
+    update_toss_payments_transaction_price,
 };
 use dxe_data::queries::unit::is_unit_enabled;
+use dxe_extern::toss_payments::types::{Payment, PaymentStatus};
 use dxe_extern::toss_payments::{Error as TossPaymentsError, TossPaymentsClient};
-use dxe_types::{BookingId, ForeignPaymentId, ProductId};
+use dxe_types::{BookingId, ForeignPaymentId, HourPackId, ProductId};
 use sqlx::SqlitePool;
 
 use crate::config::{BookingConfig, TimeZoneConfig};
 use crate::middleware::datetime_injector::Now;
-use crate::models::Error;
-use crate::models::entities::ProductType;
+use crate::models::entities::{
+    HourPack as HourPackView, ProductType, TossPaymentsTransaction as TossPaymentsTransactionView,
+};
 use crate::models::handlers::booking::{
     ConfirmTossPaymentRequest, ConfirmTossPaymentResponse, GetTossPaymentStateResponse,
-    InitiateTossPaymentRequest, InitiateTossPaymentResponse,
+    InitiateTossHourPackPaymentRequest, InitiateTossHourPackPaymentResponse,
+    InitiateTossPaymentRequest, InitiateTossPaymentResponse, TossPaymentsWebhookPayment,
+    TossPaymentsWebhookRequest,
 };
-use crate::services::calendar::CalendarService;
+use crate::models::{Error, IntoView};
+use crate::services::calendar::{CalendarService, CalendarUpdates};
+use crate::services::coupon::apply_coupon;
 use crate::services::messaging::MessagingService;
-use crate::services::notification::{NotificationSender, Priority};
+use crate::services::notification::NotificationSender;
+use crate::services::toss_payments::{
+    TossReconciliationService, complete_amend_payment, complete_booking_payment,
+    complete_hour_pack_payment, hold_booking_for_deposit, hold_hour_pack_for_deposit,
+};
+use crate::services::waitlist::WaitlistService;
 use crate::session::UserSession;
-use crate::utils::datetime::{is_in_effect, truncate_time};
+use crate::utils::datetime::{desired_length, is_in_effect};
 
 const TEMPORARY_RESERVATION_LIFE: TimeDelta = TimeDelta::minutes(5);
+const PAYMENT_STATUS_CHANGED: &str = "PAYMENT_STATUS_CHANGED";
 
 pub async fn post(
     now: Now,
@@ -41,6 +58,7 @@ pub async fn post(
     database: web::Data<SqlitePool>,
     booking_config: web::Data<BookingConfig>,
     timezone_config: web::Data<TimeZoneConfig>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<InitiateTossPaymentResponse>, Error> {
     let mut tx = database.begin().await?;
 
@@ -48,17 +66,58 @@ pub async fn post(
         return Err(Error::UnitNotFound);
     }
 
-    if body.desired_hours > booking_config.max_booking_hours {
+    let length = desired_length(body.desired_hours, body.desired_minutes);
+    if !booking_config
+        .is_valid_length(&body.unit_id, length)
+        .map_err(|_| Error::UnitNotFound)?
+    {
         return Err(Error::InvalidTimeRange);
     }
 
-    let time_from = truncate_time(body.time_from).to_utc();
-    let time_to = time_from + TimeDelta::hours(body.desired_hours);
+    let time_from = booking_config
+        .truncate_time(&body.unit_id, body.time_from)
+        .map_err(|_| Error::UnitNotFound)?;
+    let time_to = time_from + length;
 
-    let price = booking_config
-        .calculate_price(&body.unit_id, time_from, time_to)
+    let mut breakdown = booking_config
+        .calculate_price(&timezone_config, &body.unit_id, time_from, time_to)
         .map_err(|_| Error::UnitNotFound)?;
 
+    // The coupon may have been changed since the payment was initiated. Drop the redemption made
+    // back then so that it does not count against the limits of the coupon.
+    if let Some(temporary_reservation_id) = body.temporary_reservation_id
+        && let Some(toss_tx) = get_toss_payments_transaction_by_temporary_reservation_id(
+            &mut tx,
+            &temporary_reservation_id,
+        )
+        .await?
+        && toss_tx.user_id == session.user_id
+    {
+        delete_pending_coupon_redemption(&mut tx, &toss_tx.id).await?;
+    }
+
+    let applied_coupon = if let Some(coupon_code) = body.coupon_code.as_deref() {
+        Some(
+            apply_coupon(
+                &mut tx,
+                &now,
+                coupon_code,
+                &session.user_id,
+                &body.unit_id,
+                &mut breakdown,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    let price = breakdown.total;
+    if price <= 0 {
+        // Toss Payments cannot process free orders
+        return Err(Error::CouponNotApplicable);
+    }
+
     let identity = get_identity(&mut tx, &now, &body.identity_id)
         .await?
         .ok_or(Error::UserNotFound)?;
@@ -93,6 +152,16 @@ pub async fn post(
             )
             .await?;
 
+            waitlist_service
+                .release(
+                    &mut tx,
+                    &now,
+                    &temporary_reservation.unit_id,
+                    &temporary_reservation.time_from,
+                    &temporary_reservation.time_to,
+                )
+                .await?;
+
             let id = ForeignPaymentId::generate();
             let _ = create_toss_payments_transaction(
                 &mut tx,
@@ -116,6 +185,8 @@ pub async fn post(
             .await?;
 
             if let Some(toss_tx) = toss_tx {
+                update_toss_payments_transaction_price(&mut tx, &toss_tx.id, price).await?;
+
                 (toss_tx.id, temporary_reservation_id)
             } else {
                 let id = ForeignPaymentId::generate();
@@ -165,6 +236,20 @@ pub async fn post(
         (id, temporary_reservation_id)
     };
 
+    if let Some(applied_coupon) = &applied_coupon {
+        create_coupon_redemption(
+            &mut tx,
+            &now,
+            &applied_coupon.coupon.id,
+            &session.user_id,
+            None,
+            Some(&id),
+            applied_coupon.original_price,
+            applied_coupon.discount_price,
+        )
+        .await?;
+    }
+
     tx.commit().await?;
 
     Ok(web::Json(InitiateTossPaymentResponse {
@@ -175,6 +260,70 @@ pub async fn post(
     }))
 }
 
+pub async fn post_hour_pack(
+    now: Now,
+    session: UserSession,
+    body: web::Json<InitiateTossHourPackPaymentRequest>,
+    database: web::Data<SqlitePool>,
+    booking_config: web::Data<BookingConfig>,
+) -> Result<web::Json<InitiateTossHourPackPaymentResponse>, Error> {
+    let offer = booking_config
+        .hour_packs
+        .iter()
+        .find(|v| v.id == body.offer_id)
+        .ok_or(Error::HourPackNotFound)?;
+
+    let mut tx = database.begin().await?;
+
+    let identity = get_identity(&mut tx, &now, &body.identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut tx, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+        }
+    }
+
+    let hour_pack_id = create_hour_pack(
+        &mut tx,
+        &now,
+        &session.user_id,
+        &body.identity_id,
+        &offer.name,
+        offer.hours * 60,
+        offer.price,
+    )
+    .await?;
+
+    let id = ForeignPaymentId::generate();
+    let _ = create_toss_payments_transaction(
+        &mut tx,
+        &now,
+        &id,
+        &session.user_id,
+        None,
+        Some(&ProductId::from(hour_pack_id)),
+        offer.price,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(InitiateTossHourPackPaymentResponse {
+        order_id: id,
+        price: offer.price,
+        hour_pack_id,
+    }))
+}
+
 async fn confirm_booking_payment<'tx>(
     now: &Now,
     body: &ConfirmTossPaymentRequest,
@@ -183,7 +332,7 @@ async fn confirm_booking_payment<'tx>(
     temporary_reservation: AdhocReservation,
     tx: &mut sqlx::SqliteTransaction<'tx>,
     toss_payments_client: &TossPaymentsClient,
-    calendar_service: &Option<CalendarService>,
+    calendar_updates: &mut CalendarUpdates,
     timezone_config: &TimeZoneConfig,
     notification_sender: &NotificationSender,
     messaging_service: &MessagingService,
@@ -202,6 +351,12 @@ async fn confirm_booking_payment<'tx>(
         )));
     }
 
+    // Other payments with the same coupon may have been confirmed since this one was initiated.
+    // The redemption is attached in this transaction, after the payment has been approved.
+    if !is_pending_coupon_redemption_available(tx, now, &toss_tx.id).await? {
+        return Err(Error::CouponNotApplicable);
+    }
+
     let payment = match toss_payments_client
         .confirm_payment(&body.order_id, body.amount, &body.payment_key)
         .await
@@ -226,74 +381,50 @@ async fn confirm_booking_payment<'tx>(
         },
     };
 
-    expire_adhoc_reservation(tx, now, &temporary_reservation.id).await?;
-    if let Some(calendar_service) = calendar_service.as_ref() {
-        let _ = calendar_service
-            .delete_adhoc_reservation(&temporary_reservation.id)
-            .await;
-    }
-
-    let booking_id = create_booking(
-        tx,
-        now,
-        &temporary_reservation.unit_id,
-        &session.user_id,
-        &temporary_reservation.customer.id(),
-        &temporary_reservation.time_from,
-        &temporary_reservation.time_to,
-        true,
-    )
-    .await?;
+    let booking = if matches!(payment.status, PaymentStatus::WaitingForDeposit) {
+        match hold_booking_for_deposit(
+            tx,
+            now,
+            &toss_tx,
+            &temporary_reservation,
+            &payment,
+            calendar_updates,
+            timezone_config,
+            notification_sender,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                // Close the account right away, so that nothing is deposited for an order which
+                // cannot be held
+                cancel_virtual_account(
+                    toss_payments_client,
+                    &toss_tx,
+                    &payment,
+                    "예약을 확정할 수 없어 가상계좌가 취소되었습니다.",
+                )
+                .await;
 
-    let customers = match &temporary_reservation.customer {
-        Identity::User(u) => {
-            if u.id != session.user_id {
-                return Err(Error::UserNotFound);
-            }
-            vec![u.clone()]
-        }
-        Identity::Group(g) => {
-            if !is_member_of(tx, &g.id, &session.user_id).await? {
-                return Err(Error::GroupNotFound);
+                return Err(e);
             }
-            get_group_members(tx, &g.id).await?
         }
-    };
-
-    let booking = get_booking_with_user_id(tx, &booking_id, &session.user_id)
+    } else {
+        complete_booking_payment(
+            tx,
+            now,
+            &toss_tx,
+            &temporary_reservation,
+            &payment.payment_key,
+            calendar_updates,
+            timezone_config,
+            notification_sender,
+            messaging_service,
+        )
         .await?
-        .ok_or(Error::BookingNotFound)?;
-
-    if let Some(calendar_service) = calendar_service.as_ref()
-        && let Err(e) = calendar_service
-            .register_booking(&booking, &customers)
-            .await
-    {
-        log::error!("Failed to register event on calendar: {e}");
-    }
-
-    let product_id = ProductId::from(booking.id);
-
-    confirm_toss_payments_transaction(tx, now, &body.order_id, &product_id, &payment.payment_key)
-        .await?;
-
-    let desired_hours =
-        (temporary_reservation.time_to - temporary_reservation.time_from).num_hours();
-    notification_sender.enqueue(
-        Priority::High,
-        format!(
-            "New booking by {}: {} ({} hours)",
-            temporary_reservation.customer.name(),
-            timezone_config.convert(temporary_reservation.time_from),
-            desired_hours
-        ),
-    );
-
-    messaging_service
-        .send_confirmation(tx, booking.clone())
-        .await?;
+    };
 
-    Ok(booking_id)
+    Ok(booking.id)
 }
 
 async fn confirm_amend_payment<'tx>(
@@ -303,7 +434,7 @@ async fn confirm_amend_payment<'tx>(
     booking_amendment: BookingAmendment,
     tx: &mut sqlx::SqliteTransaction<'tx>,
     toss_payments_client: &TossPaymentsClient,
-    calendar_service: &Option<CalendarService>,
+    calendar_updates: &mut CalendarUpdates,
     timezone_config: &TimeZoneConfig,
     notification_sender: &NotificationSender,
     messaging_service: &MessagingService,
@@ -318,16 +449,25 @@ async fn confirm_amend_payment<'tx>(
         return Err(Error::BookingAlreadyConfirmed)?;
     }
 
+    // Superseded by a newer amendment or abandoned
+    if is_in_effect(&booking_amendment.canceled_at, now) {
+        return Err(Error::BookingAmendmentNotFound);
+    }
+
     if body.amount != toss_tx.price {
         return Err(Error::PaymentFailed(String::from(
             "거래승인된 액수와 결재금액이 다릅니다.",
         )));
     }
 
-    let _ = update_booking_time(
+    let _ = reschedule_booking(
         tx,
         now,
         &booking.id,
+        booking_amendment
+            .desired_unit_id
+            .as_ref()
+            .unwrap_or(&booking.unit_id),
         &booking_amendment.desired_time_from,
         &booking_amendment.desired_time_to,
     )
@@ -359,46 +499,133 @@ async fn confirm_amend_payment<'tx>(
         },
     };
 
-    let product_id = ProductId::from(booking_amendment.id);
-    let _ =
-        confirm_toss_payments_transaction(tx, now, &toss_tx.id, &product_id, &payment.payment_key)
-            .await?;
-
-    if let Err(e) = messaging_service
-        .send_amend_notification(
-            tx,
-            booking.clone(),
-            booking_amendment.desired_time_from,
-            booking_amendment.desired_time_to,
+    if matches!(payment.status, PaymentStatus::WaitingForDeposit) {
+        // Amendments take effect right away, so they cannot wait for the deposit
+        cancel_virtual_account(
+            toss_payments_client,
+            &toss_tx,
+            &payment,
+            "예약 변경은 가상계좌로 결제할 수 없습니다.",
         )
-        .await
+        .await;
+
+        return Err(Error::VirtualAccountNotAllowed);
+    }
+
+    complete_amend_payment(
+        tx,
+        now,
+        &toss_tx,
+        &booking,
+        &booking_amendment,
+        &payment.payment_key,
+        calendar_updates,
+        timezone_config,
+        notification_sender,
+        messaging_service,
+    )
+    .await?;
+
+    Ok(booking.id)
+}
+
+async fn confirm_hour_pack_payment<'tx>(
+    now: &Now,
+    body: &ConfirmTossPaymentRequest,
+    toss_tx: TossPaymentsTransaction,
+    hour_pack: HourPack,
+    tx: &mut sqlx::SqliteTransaction<'tx>,
+    toss_payments_client: &TossPaymentsClient,
+    timezone_config: &TimeZoneConfig,
+    notification_sender: &NotificationSender,
+) -> Result<HourPackId, Error> {
+    if is_in_effect(&toss_tx.confirmed_at, now)
+        || is_in_effect(&hour_pack.confirmed_at, now)
+        || is_in_effect(&hour_pack.canceled_at, now)
     {
-        log::warn!("Could not send amend notification to customers: {e}");
+        return Err(Error::BookingAlreadyConfirmed)?;
+    }
+
+    if body.amount != toss_tx.price {
+        return Err(Error::PaymentFailed(String::from(
+            "거래승인된 액수와 결재금액이 다릅니다.",
+        )));
     }
 
-    if let Some(calendar_service) = calendar_service.as_ref() {
-        let mut updated_booking = booking.clone();
-        updated_booking.time_from = booking_amendment.desired_time_from;
-        updated_booking.time_to = booking_amendment.desired_time_to;
+    let payment = match toss_payments_client
+        .confirm_payment(&body.order_id, body.amount, &body.payment_key)
+        .await
+    {
+        Ok(v) => {
+            log::info!(
+                "Payment {} processed successfully. total amount: {}",
+                v.order_id,
+                v.total_amount,
+            );
 
-        if let Err(e) = calendar_service.update_booking_time(&booking).await {
-            log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+            v
         }
+        Err(e) => match e {
+            TossPaymentsError::Remote { code, message } => {
+                Err(Error::TossPaymentsFailed { message, code })?
+            }
+            TossPaymentsError::RemoteStatus(status) => {
+                Err(Error::PaymentFailed(status.to_string()))?
+            }
+            rest => Err(Error::Internal(Box::new(rest)))?,
+        },
+    };
+
+    if matches!(payment.status, PaymentStatus::WaitingForDeposit) {
+        if let Err(e) = hold_hour_pack_for_deposit(
+            tx,
+            &toss_tx,
+            &hour_pack,
+            &payment,
+            timezone_config,
+            notification_sender,
+        )
+        .await
+        {
+            cancel_virtual_account(
+                toss_payments_client,
+                &toss_tx,
+                &payment,
+                "시간권을 확정할 수 없어 가상계좌가 취소되었습니다.",
+            )
+            .await;
+
+            return Err(e);
+        }
+    } else {
+        complete_hour_pack_payment(
+            tx,
+            now,
+            &toss_tx,
+            &hour_pack,
+            &payment.payment_key,
+            notification_sender,
+        )
+        .await?;
     }
 
-    notification_sender.enqueue(
-        Priority::High,
-        format!(
-            "Booking by {} moved from {} - {} to {} - {}",
-            booking.customer.name(),
-            timezone_config.convert(booking.time_from),
-            timezone_config.convert(booking.time_to),
-            timezone_config.convert(booking_amendment.desired_time_from),
-            timezone_config.convert(booking_amendment.desired_time_to),
-        ),
-    );
+    Ok(hour_pack.id)
+}
 
-    Ok(booking.id)
+/// Closes the virtual account issued for the order. Failures are left to the reconciliation,
+/// which cancels virtual accounts of orders that have not been held.
+async fn cancel_virtual_account(
+    toss_payments_client: &TossPaymentsClient,
+    toss_tx: &TossPaymentsTransaction,
+    payment: &Payment,
+    reason: &str,
+) {
+    if let Err(e) = toss_payments_client
+        .cancel_payment(&payment.payment_key, reason, None, None)
+        .await
+    {
+        log::error!("Could not cancel virtual account of {}: {e}", toss_tx.id);
+    }
 }
 
 pub async fn confirm_payment(
@@ -413,6 +640,7 @@ pub async fn confirm_payment(
     notification_sender: web::Data<NotificationSender>,
 ) -> Result<web::Json<ConfirmTossPaymentResponse>, Error> {
     let mut tx = database.begin().await?;
+    let mut calendar_updates = CalendarUpdates::default();
 
     let toss_tx = get_toss_payments_transaction_by_id(&mut tx, &body.order_id)
         .await?
@@ -426,14 +654,16 @@ pub async fn confirm_payment(
         return Err(Error::BookingAlreadyConfirmed);
     }
 
-    let booking_id = if let Some(temporary_reservation_id) = toss_tx.temporary_reservation_id {
+    let order_id = toss_tx.id.clone();
+
+    let mut response = if let Some(temporary_reservation_id) = toss_tx.temporary_reservation_id {
         let temporary_reservation = get_adhoc_reservation(&mut tx, &temporary_reservation_id)
             .await?
             .ok_or(Error::PaymentFailed(String::from(
                 "임시 예약을 찾을 수 없습니다.",
             )))?;
 
-        confirm_booking_payment(
+        let booking_id = confirm_booking_payment(
             &now,
             &body,
             &session,
@@ -441,45 +671,87 @@ pub async fn confirm_payment(
             temporary_reservation,
             &mut tx,
             toss_payments_client.as_ref(),
-            calendar_service.as_ref(),
+            &mut calendar_updates,
             timezone_config.as_ref(),
             notification_sender.as_ref(),
             messaging_service.as_ref(),
         )
-        .await?
+        .await?;
+
+        ConfirmTossPaymentResponse {
+            booking_id: Some(booking_id),
+            hour_pack_id: None,
+            virtual_account: None,
+        }
     } else if let Some(product_id) = toss_tx.product_id {
-        let Some(Product::Amendment(booking_amendment)) = get_product(&mut tx, &product_id).await?
-        else {
-            return Err(Error::PaymentFailed(String::from(
-                "예약 변경 정보를 찾을 수 없습니다.",
-            )));
-        };
+        match get_product(&mut tx, &product_id).await? {
+            Some(Product::Amendment(booking_amendment)) => {
+                let booking_id = confirm_amend_payment(
+                    &now,
+                    &body,
+                    toss_tx,
+                    booking_amendment,
+                    &mut tx,
+                    toss_payments_client.as_ref(),
+                    &mut calendar_updates,
+                    timezone_config.as_ref(),
+                    notification_sender.as_ref(),
+                    messaging_service.as_ref(),
+                )
+                .await?;
 
-        confirm_amend_payment(
-            &now,
-            &body,
-            toss_tx,
-            booking_amendment,
-            &mut tx,
-            toss_payments_client.as_ref(),
-            calendar_service.as_ref(),
-            timezone_config.as_ref(),
-            notification_sender.as_ref(),
-            messaging_service.as_ref(),
-        )
-        .await?
+                ConfirmTossPaymentResponse {
+                    booking_id: Some(booking_id),
+                    hour_pack_id: None,
+                    virtual_account: None,
+                }
+            }
+            Some(Product::HourPack(hour_pack)) => {
+                let hour_pack_id = confirm_hour_pack_payment(
+                    &now,
+                    &body,
+                    toss_tx,
+                    hour_pack,
+                    &mut tx,
+                    toss_payments_client.as_ref(),
+                    timezone_config.as_ref(),
+                    notification_sender.as_ref(),
+                )
+                .await?;
+
+                ConfirmTossPaymentResponse {
+                    booking_id: None,
+                    hour_pack_id: Some(hour_pack_id),
+                    virtual_account: None,
+                }
+            }
+            _ => {
+                return Err(Error::PaymentFailed(String::from(
+                    "예약 변경 정보를 찾을 수 없습니다.",
+                )));
+            }
+        }
     } else {
         return Err(Error::PaymentFailed(String::from(
             "잘못된 주문 정보입니다.",
         )));
     };
 
+    // Issued virtual accounts are shown to the customer to deposit to
+    if let Some(toss_tx) = get_toss_payments_transaction_by_id(&mut tx, &order_id).await? {
+        response.virtual_account =
+            TossPaymentsTransactionView::convert(toss_tx, &timezone_config, &now)?.virtual_account;
+    }
+
     tx.commit().await?;
 
-    Ok(web::Json(ConfirmTossPaymentResponse { booking_id }))
+    calendar_updates.apply(&calendar_service).await;
+
+    Ok(web::Json(response))
 }
 
 pub async fn get(
+    now: Now,
     session: UserSession,
     foreign_payment_id: web::Path<ForeignPaymentId>,
     timezone_config: web::Data<TimeZoneConfig>,
@@ -502,19 +774,32 @@ pub async fn get(
 
         Ok(web::Json(GetTossPaymentStateResponse {
             r#type: ProductType::Booking,
-            time_from: timezone_config.convert(adhoc_reservation.time_from),
-            time_to: timezone_config.convert(adhoc_reservation.time_to),
+            time_from: Some(timezone_config.convert(adhoc_reservation.time_from)),
+            time_to: Some(timezone_config.convert(adhoc_reservation.time_to)),
+            hour_pack: None,
         }))
     } else if let Some(product_id) = toss_tx.product_id {
-        let Some(Product::Amendment(amendment)) = get_product(&mut tx, &product_id).await? else {
-            return Err(Error::BookingAmendmentNotFound);
-        };
-
-        Ok(web::Json(GetTossPaymentStateResponse {
-            r#type: ProductType::BookingAmendment,
-            time_from: timezone_config.convert(amendment.desired_time_from),
-            time_to: timezone_config.convert(amendment.desired_time_to),
-        }))
+        match get_product(&mut tx, &product_id).await? {
+            Some(Product::Amendment(amendment)) => Ok(web::Json(GetTossPaymentStateResponse {
+                r#type: ProductType::BookingAmendment,
+                time_from: Some(timezone_config.convert(amendment.desired_time_from)),
+                time_to: Some(timezone_config.convert(amendment.desired_time_to)),
+                hour_pack: None,
+            })),
+            Some(Product::HourPack(hour_pack)) => {
+                if hour_pack.holder_id != session.user_id {
+                    return Err(Error::ForeignPaymentNotFound);
+                }
+
+                Ok(web::Json(GetTossPaymentStateResponse {
+                    r#type: ProductType::HourPack,
+                    time_from: None,
+                    time_to: None,
+                    hour_pack: Some(HourPackView::convert(hour_pack, &timezone_config, &now)?),
+                }))
+            }
+            _ => Err(Error::BookingAmendmentNotFound),
+        }
     } else {
         Err(Error::ForeignPaymentNotFound)
     }
@@ -525,6 +810,7 @@ pub async fn delete(
     session: UserSession,
     foreign_payment_id: web::Path<ForeignPaymentId>,
     database: web::Data<SqlitePool>,
+    waitlist_service: web::Data<WaitlistService>,
 ) -> Result<web::Json<serde_json::Value>, Error> {
     let mut tx = database.begin().await?;
 
@@ -544,14 +830,35 @@ pub async fn delete(
 
         if !is_in_effect(&temporary_reservation.deleted_at, &now) {
             let _ = expire_adhoc_reservation(&mut tx, &now, &temporary_reservation.id).await?;
+
+            waitlist_service
+                .release(
+                    &mut tx,
+                    &now,
+                    &temporary_reservation.unit_id,
+                    &temporary_reservation.time_from,
+                    &temporary_reservation.time_to,
+                )
+                .await?;
         }
     } else if let Some(product_id) = toss_tx.product_id {
-        let Some(Product::Amendment(amendment)) = get_product(&mut tx, &product_id).await? else {
-            return Err(Error::BookingAmendmentNotFound);
-        };
-
-        if !is_in_effect(&amendment.canceled_at, &now) {
-            let _ = cancel_booking_amendment(&mut tx, &now, &amendment.id).await?;
+        match get_product(&mut tx, &product_id).await? {
+            Some(Product::Amendment(amendment)) => {
+                if !is_in_effect(&amendment.canceled_at, &now) {
+                    let _ = cancel_booking_amendment(&mut tx, &now, &amendment.id).await?;
+                }
+            }
+            Some(Product::HourPack(hour_pack)) => {
+                if hour_pack.holder_id != session.user_id {
+                    return Err(Error::Forbidden);
+                }
+
+                // Paid hour packs are canceled by an administrator only
+                if !is_in_effect(&hour_pack.confirmed_at, &now) {
+                    let _ = cancel_hour_pack(&mut tx, &now, &hour_pack.id).await?;
+                }
+            }
+            _ => return Err(Error::BookingAmendmentNotFound),
         }
     }
 
@@ -559,3 +866,47 @@ pub async fn delete(
 
     Ok(web::Json(serde_json::json!({})))
 }
+
+/// Receives payment state changes and deposit callbacks of virtual accounts from Toss Payments.
+/// The payload is not signed, so it only tells which order to look up, and the state is taken
+/// from the payment lookup API instead.
+pub async fn webhook(
+    now: Now,
+    body: web::Json<TossPaymentsWebhookRequest>,
+    database: web::Data<SqlitePool>,
+    reconciliation_service: web::Data<TossReconciliationService>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let order_id = if let Some(order_id) = &body.order_id {
+        // Deposit callbacks of virtual accounts come with the secret issued with the account
+        let mut connection = database.acquire().await?;
+        let toss_tx = get_toss_payments_transaction_by_id(&mut connection, order_id)
+            .await?
+            .ok_or(Error::ForeignPaymentNotFound)?;
+
+        if toss_tx.virtual_account_secret.is_some() && toss_tx.virtual_account_secret != body.secret
+        {
+            return Err(Error::Forbidden);
+        }
+
+        order_id.clone()
+    } else if body.event_type == PAYMENT_STATUS_CHANGED {
+        let Ok(payment) = serde_json::from_value::<TossPaymentsWebhookPayment>(body.data.clone())
+        else {
+            log::warn!(
+                "Unrecognized payment in Toss Payments webhook: {}",
+                body.data
+            );
+            return Ok(web::Json(serde_json::json!({})));
+        };
+
+        payment.order_id
+    } else {
+        return Ok(web::Json(serde_json::json!({})));
+    };
+
+    reconciliation_service
+        .reconcile(&database, *now, &order_id)
+        .await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
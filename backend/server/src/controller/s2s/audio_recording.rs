@@ -31,7 +31,7 @@ pub async fn post(
     .await?
         && let Some(audio_recording) = get_audio_recording(&mut tx, &booking_id).await?
         && let Err(e) = messaging_service
-            .send_audio_recording(&mut tx, booking.clone(), audio_recording.clone())
+            .send_audio_recording(&mut tx, &now, booking.clone(), audio_recording.clone())
             .await
     {
         log::warn!("Couldn't send audio recording notification: {e}");
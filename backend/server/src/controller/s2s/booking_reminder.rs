@@ -24,9 +24,14 @@ pub async fn post(
         log::info!("Skipping reminder as the booking is either not confirmed or canceled.");
     } else if *now > booking.time_from {
         log::info!("Skipping reminder as the booking has already started.");
-    } else if let Err(e) = messaging_service.send_reminder(&mut tx, booking).await {
+    } else if let Err(e) = messaging_service
+        .send_reminder(&mut tx, &now, booking)
+        .await
+    {
         log::warn!("Could not send reminder for booking {booking_id}: {e}");
     }
 
+    tx.commit().await?;
+
     Ok(web::Json(serde_json::json!({})))
 }
@@ -13,6 +13,7 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use crate::config::TelemetryConfig;
 use crate::middleware::datetime_injector::Now;
 use crate::models::Error;
+use crate::services::webhook::{WebhookEvent, enqueue_webhook_event};
 
 #[derive(Debug, MultipartForm)]
 pub struct UploadForm {
@@ -75,6 +76,16 @@ pub async fn post(
     )
     .await?;
 
+    enqueue_webhook_event(
+        &mut tx,
+        &now,
+        WebhookEvent::TelemetryUploaded {
+            booking_id: *booking_id,
+            telemetry_type: form.request.r#type,
+        },
+    )
+    .await?;
+
     tx.commit().await?;
 
     Ok(web::Json(serde_json::json!({})))
@@ -1,6 +1,10 @@
+mod calendar_feed;
+mod email;
 mod group;
 mod groups;
+mod identity_links;
 mod me;
+mod password;
 
 use actix_web::web;
 
@@ -11,6 +15,37 @@ pub fn scope() -> actix_web::Scope {
                 .route(web::get().to(me::get))
                 .route(web::post().to(me::post)),
         )
+        .service(
+            web::resource("/me/email")
+                .route(web::put().to(email::put))
+                .route(web::delete().to(email::delete)),
+        )
+        .service(web::resource("/me/password").route(web::put().to(password::put)))
+        .service(web::resource("/me/identity-links").route(web::get().to(identity_links::get)))
+        .service(
+            web::resource("/me/identity-link/kakao")
+                .route(web::post().to(identity_links::post_kakao)),
+        )
+        .service(
+            web::resource("/me/identity-link/kakao/state")
+                .route(web::get().to(identity_links::get_kakao_state)),
+        )
+        .service(
+            web::resource("/me/identity-link/handle")
+                .route(web::post().to(identity_links::post_handle)),
+        )
+        .service(
+            web::resource("/me/identity-link/oidc/{provider}")
+                .route(web::post().to(identity_links::post_oidc)),
+        )
+        .service(
+            web::resource("/me/identity-link/oidc/{provider}/authorize")
+                .route(web::get().to(identity_links::authorize_oidc)),
+        )
+        .service(
+            web::resource("/me/identity-link/{link_id}")
+                .route(web::delete().to(identity_links::delete)),
+        )
         .service(
             web::resource("/group/{group_id}")
                 .route(web::get().to(group::get))
@@ -27,4 +62,9 @@ pub fn scope() -> actix_web::Scope {
                 .route(web::get().to(groups::get))
                 .route(web::post().to(groups::post)),
         )
+        .service(
+            web::resource("/calendar-feed")
+                .route(web::get().to(calendar_feed::get))
+                .route(web::post().to(calendar_feed::post)),
+        )
 }
@@ -0,0 +1,99 @@
+use actix_web::web;
+use dxe_data::entities::Identity;
+use dxe_data::queries::calendar_feed::{create_or_rotate_calendar_feed, get_calendar_feed};
+use dxe_data::queries::identity::{get_identity, is_member_of};
+use dxe_types::{CalendarFeedToken, IdentityId};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::config::UrlConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::user::{
+    CalendarFeedResponse, GetCalendarFeedQuery, RotateCalendarFeedRequest,
+};
+use crate::session::UserSession;
+
+pub async fn get(
+    now: Now,
+    session: UserSession,
+    query: web::Query<GetCalendarFeedQuery>,
+    database: web::Data<SqlitePool>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<web::Json<CalendarFeedResponse>, Error> {
+    let identity_id = query
+        .identity_id
+        .unwrap_or(IdentityId::from(session.user_id));
+
+    let mut tx = database.begin().await?;
+
+    check_identity(&mut tx, &now, &session, &identity_id).await?;
+
+    let token = if let Some(feed) = get_calendar_feed(&mut tx, &identity_id).await? {
+        feed.token
+    } else {
+        create_or_rotate_calendar_feed(&mut tx, &now, &identity_id).await?
+    };
+
+    tx.commit().await?;
+
+    Ok(web::Json(CalendarFeedResponse {
+        url: feed_url(&url_config, &token),
+    }))
+}
+
+pub async fn post(
+    now: Now,
+    session: UserSession,
+    body: web::Json<RotateCalendarFeedRequest>,
+    database: web::Data<SqlitePool>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<web::Json<CalendarFeedResponse>, Error> {
+    let identity_id = body
+        .identity_id
+        .unwrap_or(IdentityId::from(session.user_id));
+
+    let mut tx = database.begin().await?;
+
+    check_identity(&mut tx, &now, &session, &identity_id).await?;
+
+    let token = create_or_rotate_calendar_feed(&mut tx, &now, &identity_id).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(CalendarFeedResponse {
+        url: feed_url(&url_config, &token),
+    }))
+}
+
+async fn check_identity(
+    connection: &mut SqliteConnection,
+    now: &Now,
+    session: &UserSession,
+    identity_id: &IdentityId,
+) -> Result<(), Error> {
+    let identity = get_identity(&mut *connection, now, identity_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+
+    match &identity {
+        Identity::User(u) => {
+            if u.id != session.user_id {
+                return Err(Error::UserNotFound);
+            }
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut *connection, &g.id, &session.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn feed_url(url_config: &UrlConfig, token: &CalendarFeedToken) -> String {
+    let mut url = url_config.base_url.clone();
+    url.set_path(&format!("api/calendar-feed/{token}.ics"));
+
+    url.to_string()
+}
@@ -0,0 +1,63 @@
+use actix_web::web;
+use chrono::TimeDelta;
+use dxe_data::queries::user::{delete_user_email, update_user_email};
+use dxe_types::EmailVerificationToken;
+use sqlx::SqlitePool;
+
+use crate::config::{TimeZoneConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::user::{UpdateEmailRequest, UpdateEmailResponse};
+use crate::services::messaging::MessagingService;
+use crate::session::UserSession;
+
+const VERIFICATION_HOURS: i64 = 24;
+
+pub async fn put(
+    now: Now,
+    session: UserSession,
+    body: web::Json<UpdateEmailRequest>,
+    database: web::Data<SqlitePool>,
+    messaging_service: web::Data<MessagingService>,
+    timezone_config: web::Data<TimeZoneConfig>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<web::Json<UpdateEmailResponse>, Error> {
+    let email = body.email.trim();
+    if !dxe_extern::smtp::is_valid_address(email) {
+        return Err(Error::InvalidEmail);
+    }
+
+    let token = EmailVerificationToken::generate();
+    let expires_at = *now + TimeDelta::hours(VERIFICATION_HOURS);
+
+    let mut url = url_config.base_url.clone();
+    url.set_path(&format!("api/email-verification/{token}"));
+
+    let mut tx = database.begin().await?;
+
+    update_user_email(&mut tx, &now, &session.user_id, email, &token, &expires_at).await?;
+
+    // Delivered through the outbox, which retries failures. The address stays unverified until the
+    // link is followed, so the request can also simply be repeated.
+    messaging_service
+        .send_email_verification(&mut tx, &now, email, url, expires_at)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(UpdateEmailResponse {
+        email: email.to_owned(),
+        verification_expires_at: timezone_config.convert(expires_at),
+    }))
+}
+
+pub async fn delete(
+    session: UserSession,
+    database: web::Data<SqlitePool>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut connection = database.acquire().await?;
+
+    delete_user_email(&mut connection, &session.user_id).await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
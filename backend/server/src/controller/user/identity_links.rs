@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+use actix_web::http::header::LOCATION;
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::{DateTime, Utc};
+use dxe_data::entities::UserIdentityLink as UserIdentityLinkEntity;
+use dxe_data::queries::identity::{
+    create_user_plain_credential, get_user_plain_credential_with_user_id,
+};
+use dxe_data::queries::user::{
+    create_user_identity_link, delete_user_identity_link, get_user_identity_links,
+    is_identity_linked,
+};
+use dxe_extern::kakao::client as kakao_client;
+use dxe_extern::kakao::models::AccountPropertyKey;
+use dxe_extern::oidc;
+use dxe_types::{IdentityProvider, UserIdentityLinkId};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::config::{OidcProvidersConfig, TimeZoneConfig, UrlConfig};
+use crate::middleware::datetime_injector::Now;
+use crate::models::entities::UserIdentityLink;
+use crate::models::handlers::user::{
+    GetIdentityLinksResponse, LinkAuthorizeQuery, LinkHandleRequest, LinkIdentityResponse,
+    LinkKakaoStateResponse,
+};
+use crate::models::{Error, IntoView};
+use crate::session::UserSession;
+use crate::utils::aes::AesCrypto;
+use crate::utils::credential::{hash_password, is_valid_handle, is_valid_password};
+use crate::utils::oauth::{LinkToken, OAuthSession, oidc_redirect_url};
+
+struct SimpleBearerToken(String);
+
+impl dxe_extern::kakao::BearerToken for SimpleBearerToken {
+    fn access_token(&self) -> &str {
+        &self.0
+    }
+}
+
+impl oidc::BearerToken for SimpleBearerToken {
+    fn access_token(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Access token obtained by the flow this user started with [`get_kakao_state`] or
+/// [`authorize_oidc`].
+fn bearer_token(
+    request: &HttpRequest,
+    aes_crypto: &AesCrypto,
+    session: &UserSession,
+    provider: IdentityProvider,
+    error: Error,
+) -> Result<SimpleBearerToken, Error> {
+    LinkToken::from_request(request, aes_crypto, &session.user_id, provider)
+        .map(|v| SimpleBearerToken(v.access_token))
+        .ok_or(error)
+}
+
+async fn link(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    session: &UserSession,
+    provider: IdentityProvider,
+    foreign_id: &str,
+) -> Result<UserIdentityLinkEntity, Error> {
+    // Deactivated users keep their links, so the link table is checked rather than the users
+    if is_identity_linked(&mut *connection, provider, foreign_id).await? {
+        return Err(Error::IdentityAlreadyLinked);
+    }
+
+    let link_id = create_user_identity_link(
+        &mut *connection,
+        now,
+        &session.user_id,
+        provider,
+        foreign_id,
+    )
+    .await?;
+
+    get_user_identity_links(&mut *connection, &session.user_id)
+        .await?
+        .into_iter()
+        .find(|v| v.id == link_id)
+        .ok_or(Error::IdentityLinkNotFound)
+}
+
+pub async fn get(
+    now: Now,
+    session: UserSession,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<GetIdentityLinksResponse>, Error> {
+    let mut connection = database.acquire().await?;
+
+    let identity_links = get_user_identity_links(&mut connection, &session.user_id).await?;
+
+    Ok(web::Json(GetIdentityLinksResponse {
+        identity_links: identity_links
+            .into_iter()
+            .map(|v| UserIdentityLink::convert(v, &timezone_config, &now))
+            .collect::<Result<_, _>>()?,
+    }))
+}
+
+/// Starts linking Kakao, whose SDK is given the returned state.
+pub async fn get_kakao_state(
+    session: UserSession,
+    query: web::Query<LinkAuthorizeQuery>,
+    aes_crypto: web::Data<AesCrypto>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse, Error> {
+    let oauth_session = OAuthSession::new(Some(session.user_id));
+    let cookie = oauth_session
+        .cookie(&aes_crypto, &url_config)
+        .map_err(|e| Error::Internal(Box::new(e)))?;
+
+    Ok(HttpResponse::Ok()
+        .cookie(cookie)
+        .json(LinkKakaoStateResponse {
+            state: oauth_session.state(query.redirect_to.clone(), false),
+        }))
+}
+
+pub async fn post_kakao(
+    now: Now,
+    session: UserSession,
+    request: HttpRequest,
+    database: web::Data<SqlitePool>,
+    aes_crypto: web::Data<AesCrypto>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<LinkIdentityResponse>, Error> {
+    let bearer_token = bearer_token(
+        &request,
+        &aes_crypto,
+        &session,
+        IdentityProvider::Kakao,
+        Error::InvalidKakaoAccessToken,
+    )?;
+
+    let me =
+        kakao_client::get_me(&bearer_token, HashSet::from([AccountPropertyKey::Profile])).await?;
+
+    let mut tx = database.begin().await?;
+
+    let identity_link = link(
+        &mut tx,
+        &now,
+        &session,
+        IdentityProvider::Kakao,
+        &me.id.to_string(),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(LinkIdentityResponse {
+        identity_link: UserIdentityLink::convert(identity_link, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn authorize_oidc(
+    session: UserSession,
+    provider: web::Path<IdentityProvider>,
+    query: web::Query<LinkAuthorizeQuery>,
+    oidc_providers: web::Data<OidcProvidersConfig>,
+    aes_crypto: web::Data<AesCrypto>,
+    url_config: web::Data<UrlConfig>,
+) -> Result<HttpResponse, Error> {
+    let provider = provider.into_inner();
+    let config = oidc_providers
+        .0
+        .get(&provider)
+        .ok_or(Error::IdentityProviderNotSupported)?;
+
+    let oauth_session = OAuthSession::new(Some(session.user_id));
+    let cookie = oauth_session
+        .cookie(&aes_crypto, &url_config)
+        .map_err(|e| Error::Internal(Box::new(e)))?;
+    let url = oidc::authorization_url(
+        config,
+        oidc_redirect_url(&url_config, provider).as_str(),
+        &oauth_session.state(query.redirect_to.clone(), false),
+        &oauth_session.code_challenge(),
+    );
+
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, url.to_string()))
+        .cookie(cookie)
+        .finish())
+}
+
+pub async fn post_oidc(
+    now: Now,
+    session: UserSession,
+    request: HttpRequest,
+    provider: web::Path<IdentityProvider>,
+    oidc_providers: web::Data<OidcProvidersConfig>,
+    database: web::Data<SqlitePool>,
+    aes_crypto: web::Data<AesCrypto>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<LinkIdentityResponse>, Error> {
+    let provider = provider.into_inner();
+    let config = oidc_providers
+        .0
+        .get(&provider)
+        .ok_or(Error::IdentityProviderNotSupported)?;
+
+    let bearer_token = bearer_token(
+        &request,
+        &aes_crypto,
+        &session,
+        provider,
+        Error::InvalidOidcAccessToken,
+    )?;
+
+    let user_info = oidc::get_user_info(config, &bearer_token).await?;
+
+    let mut tx = database.begin().await?;
+
+    let identity_link = link(&mut tx, &now, &session, provider, &user_info.subject).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(LinkIdentityResponse {
+        identity_link: UserIdentityLink::convert(identity_link, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn post_handle(
+    now: Now,
+    session: UserSession,
+    body: web::Json<LinkHandleRequest>,
+    database: web::Data<SqlitePool>,
+    timezone_config: web::Data<TimeZoneConfig>,
+) -> Result<web::Json<LinkIdentityResponse>, Error> {
+    let handle = body.handle.trim();
+    if !is_valid_handle(handle) {
+        return Err(Error::InvalidHandle);
+    }
+    if !is_valid_password(&body.password) {
+        return Err(Error::InvalidPassword);
+    }
+
+    let argon2_password =
+        hash_password(&body.password).map_err(|e| Error::Internal(e.to_string().into()))?;
+
+    let mut tx = database.begin().await?;
+
+    // A user can only have a single handle
+    if get_user_plain_credential_with_user_id(&mut tx, &now, &session.user_id)
+        .await?
+        .is_some()
+    {
+        return Err(Error::IdentityAlreadyLinked);
+    }
+
+    let identity_link = link(&mut tx, &now, &session, IdentityProvider::Handle, handle).await?;
+    create_user_plain_credential(&mut tx, &session.user_id, handle, &argon2_password).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(LinkIdentityResponse {
+        identity_link: UserIdentityLink::convert(identity_link, &timezone_config, &now)?,
+    }))
+}
+
+pub async fn delete(
+    session: UserSession,
+    link_id: web::Path<UserIdentityLinkId>,
+    database: web::Data<SqlitePool>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    let mut tx = database.begin().await?;
+
+    let identity_links = get_user_identity_links(&mut tx, &session.user_id).await?;
+    if !identity_links.iter().any(|v| v.id == *link_id) {
+        return Err(Error::IdentityLinkNotFound);
+    }
+    if identity_links.len() <= 1 {
+        return Err(Error::LastIdentityLink);
+    }
+
+    delete_user_identity_link(&mut tx, &session.user_id, *link_id).await?;
+
+    tx.commit().await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
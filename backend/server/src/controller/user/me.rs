@@ -2,7 +2,10 @@ use std::collections::HashMap;
 
 use actix_web::web;
 use dxe_data::queries::booking::get_bookings_by_user_id;
-use dxe_data::queries::user::{get_user_by_id, get_user_cash_payment_information, update_user};
+use dxe_data::queries::prefs::{create_or_update_notification_config, get_notification_config};
+use dxe_data::queries::user::{
+    get_user_by_id, get_user_cash_payment_information, get_user_email, update_user,
+};
 use dxe_types::UnitId;
 use sqlx::SqlitePool;
 
@@ -11,7 +14,12 @@ use crate::middleware::datetime_injector::Now;
 use crate::models::entities::{Booking, BookingStatus, SelfUser};
 use crate::models::handlers::user::{MeResponse, UpdateMeRequest, UpdateMeResponse};
 use crate::models::{Error, IntoView};
+use crate::services::messaging::MessagingService;
 use crate::session::UserSession;
+use crate::utils::datetime::is_in_effect;
+
+// Reminders can be scheduled up to a week before the booking
+const MAX_REMINDER_LEAD_MINUTES: i64 = 60 * 24 * 7;
 
 pub async fn get(
     now: Now,
@@ -29,6 +37,8 @@ pub async fn get(
 
     let cash_payment_information =
         get_user_cash_payment_information(&mut tx, &session.user_id).await?;
+    let email = get_user_email(&mut tx, &session.user_id).await?;
+    let notification_config = get_notification_config(&mut tx, &session.user_id).await?;
 
     let user = SelfUser {
         id: user.id,
@@ -42,6 +52,11 @@ pub async fn get(
         refund_account: cash_payment_information
             .as_ref()
             .and_then(|v| v.refund_account.clone()),
+        is_email_verified: email
+            .as_ref()
+            .is_some_and(|v| is_in_effect(&v.verified_at, &now)),
+        email: email.map(|v| v.email),
+        notification_preferences: notification_config.map(|v| v.data.0).unwrap_or_default(),
     };
 
     let mut bookings =
@@ -81,7 +96,16 @@ pub async fn post(
     body: web::Json<UpdateMeRequest>,
     database: web::Data<SqlitePool>,
     timezone_config: web::Data<TimeZoneConfig>,
+    messaging_service: web::Data<MessagingService>,
 ) -> Result<web::Json<UpdateMeResponse>, Error> {
+    if let Some(preferences) = &body.new_notification_preferences
+        && preferences
+            .reminder_lead_minutes
+            .is_some_and(|v| !(1..=MAX_REMINDER_LEAD_MINUTES).contains(&v))
+    {
+        return Err(Error::InvalidReminderLeadTime);
+    }
+
     let mut tx = database.begin().await?;
 
     let result = update_user(
@@ -93,8 +117,17 @@ pub async fn post(
     )
     .await?;
 
+    if let Some(preferences) = &body.new_notification_preferences {
+        create_or_update_notification_config(&mut tx, &now, &session.user_id, preferences).await?;
+        messaging_service
+            .reschedule_reminders(&mut tx, &now, &result)
+            .await?;
+    }
+
     let cash_payment_information =
         get_user_cash_payment_information(&mut tx, &session.user_id).await?;
+    let email = get_user_email(&mut tx, &session.user_id).await?;
+    let notification_config = get_notification_config(&mut tx, &session.user_id).await?;
 
     tx.commit().await?;
 
@@ -110,6 +143,11 @@ pub async fn post(
         refund_account: cash_payment_information
             .as_ref()
             .and_then(|v| v.refund_account.clone()),
+        is_email_verified: email
+            .as_ref()
+            .is_some_and(|v| is_in_effect(&v.verified_at, &now)),
+        email: email.map(|v| v.email),
+        notification_preferences: notification_config.map(|v| v.data.0).unwrap_or_default(),
     };
 
     Ok(web::Json(UpdateMeResponse { user }))
@@ -0,0 +1,58 @@
+use actix_web::web;
+use dxe_data::queries::identity::{
+    get_user_plain_credential_with_user_id, update_user_plain_credential_password,
+};
+use dxe_types::{CredentialAttemptKind, IdentityId};
+use sqlx::SqlitePool;
+
+use crate::config::CredentialConfig;
+use crate::middleware::datetime_injector::Now;
+use crate::models::Error;
+use crate::models::handlers::user::ChangePasswordRequest;
+use crate::services::credential::{clear_attempts, register_attempt};
+use crate::session::UserSession;
+use crate::utils::credential::{hash_password, is_valid_password, verify_password};
+
+pub async fn put(
+    now: Now,
+    session: UserSession,
+    body: web::Json<ChangePasswordRequest>,
+    database: web::Data<SqlitePool>,
+    credential_config: web::Data<CredentialConfig>,
+) -> Result<web::Json<serde_json::Value>, Error> {
+    if !is_valid_password(&body.new_password) {
+        return Err(Error::InvalidPassword);
+    }
+
+    let key = IdentityId::from(session.user_id).to_string();
+
+    register_attempt(
+        &database,
+        &credential_config,
+        &now,
+        CredentialAttemptKind::PasswordChange,
+        &key,
+    )
+    .await?;
+
+    let mut tx = database.begin().await?;
+
+    let (_, credential) = get_user_plain_credential_with_user_id(&mut tx, &now, &session.user_id)
+        .await?
+        .ok_or(Error::PlainCredentialNotFound)?;
+
+    if !verify_password(&body.current_password, &credential.argon2_password) {
+        return Err(Error::AuthFailed);
+    }
+
+    let argon2_password =
+        hash_password(&body.new_password).map_err(|e| Error::Internal(e.to_string().into()))?;
+
+    update_user_plain_credential_password(&mut tx, &session.user_id, &argon2_password).await?;
+
+    tx.commit().await?;
+
+    clear_attempts(&database, CredentialAttemptKind::PasswordChange, &key).await?;
+
+    Ok(web::Json(serde_json::json!({})))
+}
@@ -14,15 +14,24 @@ use std::time::Duration;
 use actix_jwt_auth_middleware::{Authority, TokenSigner};
 use actix_web::web::Data;
 use clap::Parser;
+use dxe_data::queries::unit::replace_unit_opening_hours;
 use dxe_extern::toss_payments::TossPaymentsClient;
 use jwt_compact::alg::Ed25519;
 
 use crate::config::Config;
 use crate::middleware::coordinator_verifier::PublicKeyBundle;
+use crate::services::availability::AvailabilityService;
 use crate::services::calendar::CalendarService;
 use crate::services::doorlock::DoorLockService;
-use crate::services::messaging::MessagingService;
+use crate::services::messaging::templates::MessageTemplateRegistry;
+use crate::services::messaging::{
+    MessageOutboxWorker, MessagingService, spawn_message_outbox_task,
+};
 use crate::services::notification::spawn_notification_service_task;
+use crate::services::payment_deadline::{PaymentDeadlineService, spawn_payment_deadline_task};
+use crate::services::toss_payments::{TossReconciliationService, spawn_toss_reconciliation_task};
+use crate::services::waitlist::{WaitlistService, spawn_waitlist_task};
+use crate::services::webhook::{WebhookDeliveryWorker, spawn_webhook_delivery_task};
 use crate::session::UserSession;
 use crate::utils::aes::AesCrypto;
 
@@ -43,6 +52,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     let mut config = toml::from_str::<Config>(&std::fs::read_to_string(&args.config_path)?)?;
     config.booking.sanitize();
+    config.validate()?;
 
     let database = Data::new(sqlx::SqlitePool::connect(config.database.url.as_str()).await?);
 
@@ -55,13 +65,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    {
+        let mut tx = database.begin().await?;
+        for (unit_id, unit_booking_config) in config.booking.units.iter() {
+            let windows = unit_booking_config
+                .opening_hours
+                .iter()
+                .map(|v| v.to_utc_window(&config.timezone))
+                .collect::<Vec<_>>();
+            replace_unit_opening_hours(&mut tx, unit_id, &windows).await?;
+        }
+        tx.commit().await?;
+    }
+
     let aes = Data::new(AesCrypto::new(config.aes_key.as_slice()));
     let kakao_auth_config = Data::new(config.auth.kakao.clone());
+    let oidc_providers_config = Data::new(config.auth.oidc.clone());
+    let credential_config = Data::new(config.auth.credential.clone());
     let booking_config = Data::new(config.booking.clone());
     let timezone_config = Data::new(config.timezone.clone());
     let doorlock_service = Data::new(DoorLockService::new(&config.spaces));
     let url_config = Data::new(config.url.clone());
     let telemetry_config = Data::new(config.telemetry.clone());
+    let bank_statement_config = Data::new(config.bank_statement.clone());
     let s2s_public_keys = Arc::new(PublicKeyBundle::new(&config.spaces));
     let calendar_service = if let Some(google_api_config) = &config.google_apis {
         Some(CalendarService::new(
@@ -74,17 +100,82 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let calendar_service = Data::new(calendar_service);
     let toss_payments_client = Data::new(TossPaymentsClient::new(&config.toss_payments));
 
-    let (messaging_service, messaging_consumers) = MessagingService::new(
+    let message_templates = Data::new(MessageTemplateRegistry::new(&config.messaging.templates)?);
+    let messaging_service = Data::new(MessagingService::new(
         &config.messaging,
+        message_templates.get_ref().clone(),
         config.timezone.clone(),
         config.url.clone(),
+    )?);
+    let message_outbox_task = spawn_message_outbox_task(
+        database.get_ref().clone(),
+        MessageOutboxWorker::new(
+            &config.messaging,
+            message_templates.get_ref().clone(),
+            config.timezone.clone(),
+            config.url.clone(),
+        )?,
+    );
+
+    let webhook_delivery_task = spawn_webhook_delivery_task(
+        database.get_ref().clone(),
+        WebhookDeliveryWorker::new(&config.webhooks)?,
     );
-    let messaging_service = Data::new(messaging_service);
 
     let (notification_task, notification_sender) =
         spawn_notification_service_task(config.notifications.clone());
     let notification_sender = Data::new(notification_sender);
 
+    let waitlist_service = Data::new(WaitlistService::new(
+        &config.booking.waitlist,
+        config.timezone.clone(),
+        messaging_service.get_ref().clone(),
+        notification_sender.get_ref().clone(),
+    ));
+    let waitlist_task = spawn_waitlist_task(
+        database.get_ref().clone(),
+        waitlist_service.get_ref().clone(),
+    );
+
+    let availability_service = Data::new(AvailabilityService::new(
+        &config.booking.availability,
+        config.timezone.clone(),
+    ));
+
+    let toss_reconciliation_service = Data::new(TossReconciliationService::new(
+        &config.toss_payments.reconciliation,
+        toss_payments_client.clone().into_inner(),
+        config.timezone.clone(),
+        messaging_service.get_ref().clone(),
+        notification_sender.get_ref().clone(),
+        waitlist_service.get_ref().clone(),
+        calendar_service.get_ref().clone(),
+    ));
+    let toss_reconciliation_task = if config.toss_payments.reconciliation.enabled {
+        Some(spawn_toss_reconciliation_task(
+            database.get_ref().clone(),
+            toss_reconciliation_service.clone().into_inner(),
+        ))
+    } else {
+        None
+    };
+
+    let payment_deadline_task = if config.booking.payment_deadline.is_enabled() {
+        Some(spawn_payment_deadline_task(
+            database.get_ref().clone(),
+            PaymentDeadlineService::new(
+                &config.booking.payment_deadline,
+                config.timezone.clone(),
+                messaging_service.get_ref().clone(),
+                notification_sender.get_ref().clone(),
+                waitlist_service.get_ref().clone(),
+                calendar_service.get_ref().clone(),
+            ),
+        ))
+    } else {
+        None
+    };
+
     let key_pair = config.jwt.key_pair()?;
 
     let authority = Authority::<UserSession, Ed25519, _, _>::new()
@@ -108,6 +199,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         actix_web::App::new()
             .app_data(token_signer.clone())
             .app_data(kakao_auth_config.clone())
+            .app_data(oidc_providers_config.clone())
+            .app_data(credential_config.clone())
             .app_data(database.clone())
             .app_data(aes.clone())
             .app_data(timezone_config.clone())
@@ -115,9 +208,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .app_data(doorlock_service.clone())
             .app_data(toss_payments_client.clone())
             .app_data(messaging_service.clone())
+            .app_data(message_templates.clone())
             .app_data(notification_sender.clone())
+            .app_data(waitlist_service.clone())
             .app_data(url_config.clone())
             .app_data(calendar_service.clone())
+            .app_data(availability_service.clone())
+            .app_data(bank_statement_config.clone())
+            .app_data(toss_reconciliation_service.clone())
             .app_data(telemetry_config.clone())
             .service(controller::api(authority.clone(), s2s_public_keys.clone()))
     })
@@ -126,9 +224,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .await?;
 
     notification_task.abort();
-    for messaging_consumer in messaging_consumers {
-        messaging_consumer.abort();
+    if let Some(payment_deadline_task) = payment_deadline_task {
+        payment_deadline_task.abort();
+    }
+    if let Some(toss_reconciliation_task) = toss_reconciliation_task {
+        toss_reconciliation_task.abort();
     }
+    message_outbox_task.abort();
+    webhook_delivery_task.abort();
+    waitlist_task.abort();
 
     Ok(())
 }
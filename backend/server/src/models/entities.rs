@@ -1,8 +1,12 @@
 mod converters;
 
 use chrono::{DateTime, FixedOffset};
+use dxe_types::entities::NotificationPreferences;
 use dxe_types::{
-    AdhocParkingId, AdhocReservationId, BookingId, GroupId, SpaceId, TelemetryType, UnitId, UserId,
+    AdhocParkingId, AdhocReservationId, BookingId, BookingSeriesId, CouponDiscountType, CouponId,
+    GroupId, HourPackId, IdentityId, IdentityProvider, MessagingChannel, OutboxMessageId,
+    ProductId, SpaceId, TelemetryType, UnitClosureId, UnitId, UserId, UserIdentityLinkId,
+    WaitlistEntryId, WebhookDeliveryId, WebhookEventType, WebhookSubscriptionId,
 };
 use serde::Serialize;
 
@@ -29,6 +33,21 @@ pub struct SelfUser {
     // Payment information
     pub depositor_name: Option<String>,
     pub refund_account: Option<String>,
+
+    // Messaging
+    pub email: Option<String>,
+    pub is_email_verified: bool,
+    pub notification_preferences: NotificationPreferences,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserIdentityLink {
+    pub id: UserIdentityLinkId,
+    pub provider: IdentityProvider,
+    /// Only set for handle links; foreign IDs of external providers are not exposed.
+    pub handle: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
 }
 
 #[derive(Debug, Serialize)]
@@ -76,6 +95,7 @@ pub enum BookingStatus {
 pub enum ProductType {
     Booking,
     BookingAmendment,
+    HourPack,
 }
 
 #[derive(Debug, Serialize)]
@@ -96,6 +116,46 @@ pub struct Booking {
     pub status: BookingStatus,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingSeries {
+    pub id: BookingSeriesId,
+    pub unit_id: UnitId,
+    pub interval_weeks: i64,
+    pub created_at: DateTime<FixedOffset>,
+    pub canceled_at: Option<DateTime<FixedOffset>>,
+    pub is_canceled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingSeriesOccurrence {
+    pub booking_start: DateTime<FixedOffset>,
+    pub booking_end: DateTime<FixedOffset>,
+    pub available: bool,
+    pub price: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceLineItem {
+    pub label: String,
+    pub minutes: i64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntry {
+    pub id: WaitlistEntryId,
+    pub unit_id: UnitId,
+    pub booking_start: DateTime<FixedOffset>,
+    pub booking_end: DateTime<FixedOffset>,
+    pub created_at: DateTime<FixedOffset>,
+    pub notified_at: Option<DateTime<FixedOffset>>,
+    pub hold_reservation_id: Option<AdhocReservationId>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CashTransaction {
@@ -105,6 +165,7 @@ pub struct CashTransaction {
     pub refund_price: Option<i64>,
     pub refund_account: Option<String>,
     pub refunded_at: Option<DateTime<FixedOffset>>,
+    pub refunded_price: Option<i64>,
     pub is_refund_requested: bool,
     pub is_refunded: bool,
 }
@@ -117,6 +178,15 @@ pub struct TossPaymentsTransaction {
     pub refund_price: Option<i64>,
     pub refunded_at: Option<DateTime<FixedOffset>>,
     pub is_refunded: bool,
+    pub virtual_account: Option<VirtualAccount>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualAccount {
+    pub bank_code: String,
+    pub account_number: String,
+    pub due_at: DateTime<FixedOffset>,
 }
 
 #[derive(Debug, Serialize)]
@@ -124,6 +194,73 @@ pub struct TossPaymentsTransaction {
 pub enum Transaction {
     Cash(CashTransaction),
     TossPayments(TossPaymentsTransaction),
+    HourBalance(HourBalanceTransaction),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourBalanceTransaction {
+    pub minutes: i64,
+    pub refunded_minutes: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingCashTransaction {
+    pub product_id: ProductId,
+    pub depositor_name: String,
+    pub price: i64,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankDeposit {
+    pub row: usize,
+    pub depositor_name: String,
+    pub amount: i64,
+    pub deposited_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankDepositMatch {
+    pub deposit: BankDeposit,
+    pub candidates: Vec<PendingCashTransaction>,
+    pub is_applied: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourPackOffer {
+    pub id: String,
+    pub name: String,
+    pub hours: i64,
+    pub price: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourPack {
+    pub id: HourPackId,
+    pub customer_id: IdentityId,
+    pub name: String,
+    pub minutes: i64,
+    pub price: i64,
+    pub created_at: DateTime<FixedOffset>,
+    pub confirmed_at: Option<DateTime<FixedOffset>>,
+    pub canceled_at: Option<DateTime<FixedOffset>>,
+    pub is_confirmed: bool,
+    pub is_canceled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourBalanceEntry {
+    pub minutes: i64,
+    pub hour_pack_id: Option<HourPackId>,
+    pub booking_id: Option<BookingId>,
+    pub created_at: DateTime<FixedOffset>,
 }
 
 #[derive(Debug, Serialize)]
@@ -145,7 +282,57 @@ pub struct OccupiedSlot {
     pub masked_name: String,
     pub booking_date: DateTime<FixedOffset>,
     pub booking_hours: i64,
+    pub booking_minutes: i64,
     pub confirmed: bool,
+    pub closed: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilitySlot {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitAvailability {
+    pub unit_id: UnitId,
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub slot_minutes: i64,
+    pub min_booking_minutes: i64,
+    pub occupied: Vec<AvailabilitySlot>,
+    pub free: Vec<AvailabilitySlot>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnitClosure {
+    pub id: UnitClosureId,
+    pub unit_id: UnitId,
+    pub closure_start: DateTime<FixedOffset>,
+    pub closure_end: DateTime<FixedOffset>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Coupon {
+    pub id: CouponId,
+    pub code: String,
+    pub name: String,
+    pub discount_type: CouponDiscountType,
+    pub discount_value: i64,
+    pub valid_from: Option<DateTime<FixedOffset>>,
+    pub valid_to: Option<DateTime<FixedOffset>>,
+    pub max_redemptions: Option<i64>,
+    pub max_redemptions_per_user: Option<i64>,
+    pub first_booking_only: bool,
+    pub unit_ids: Vec<UnitId>,
+    pub redemptions: i64,
+    pub created_at: DateTime<FixedOffset>,
 }
 
 #[derive(Debug, Serialize)]
@@ -180,3 +367,60 @@ pub struct AdhocParking {
 pub struct TelemetryEntry {
     pub r#type: TelemetryType,
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxMessage {
+    pub id: OutboxMessageId,
+    pub channel: MessagingChannel,
+    pub recipient: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub attempts: i64,
+    pub created_at: DateTime<FixedOffset>,
+    pub next_attempt_at: DateTime<FixedOffset>,
+    pub delivered_at: Option<DateTime<FixedOffset>>,
+    pub failed_at: Option<DateTime<FixedOffset>>,
+    pub skipped_at: Option<DateTime<FixedOffset>>,
+    pub last_error: Option<String>,
+    pub fallback_of: Option<OutboxMessageId>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDeliveryLog {
+    pub attempted_at: DateTime<FixedOffset>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSubscription {
+    pub id: WebhookSubscriptionId,
+    pub url: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDelivery {
+    pub id: WebhookDeliveryId,
+    pub subscription_id: WebhookSubscriptionId,
+    pub event_type: WebhookEventType,
+    pub payload: serde_json::Value,
+    pub attempts: i64,
+    pub created_at: DateTime<FixedOffset>,
+    pub next_attempt_at: DateTime<FixedOffset>,
+    pub delivered_at: Option<DateTime<FixedOffset>>,
+    pub failed_at: Option<DateTime<FixedOffset>>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryLog {
+    pub attempted_at: DateTime<FixedOffset>,
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+}
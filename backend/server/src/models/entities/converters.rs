@@ -1,12 +1,16 @@
 use chrono::{DateTime, Utc};
 use dxe_data::entities;
+use dxe_types::{IdentityProvider, UnitId};
 
 use super::{
-    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingStatus, CashTransaction, Group,
-    GroupWithUsers, Identity, OccupiedSlot, SelfUser, TelemetryEntry, TelemetryType,
-    TossPaymentsTransaction, User,
+    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingSeries, BookingStatus,
+    CashTransaction, Coupon, Group, GroupWithUsers, HourBalanceEntry, HourPack, HourPackOffer,
+    Identity, MessageDeliveryLog, OccupiedSlot, OutboxMessage, PendingCashTransaction,
+    PriceLineItem, SelfUser, TelemetryEntry, TelemetryType, TossPaymentsTransaction, UnitClosure,
+    User, UserIdentityLink, VirtualAccount, WaitlistEntry, WebhookDelivery, WebhookDeliveryLog,
+    WebhookSubscription,
 };
-use crate::config::{BookingConfig, TimeZoneConfig};
+use crate::config::{self, BookingConfig, PriceItem, TimeZoneConfig};
 use crate::models::Error;
 use crate::utils::datetime::is_in_effect;
 use crate::utils::mask_identity;
@@ -56,6 +60,27 @@ impl IntoView for SelfUser {
             is_administrator: false,
             depositor_name: None,
             refund_account: None,
+            email: None,
+            is_email_verified: false,
+            notification_preferences: Default::default(),
+        })
+    }
+}
+
+impl IntoView for UserIdentityLink {
+    type Entity = entities::UserIdentityLink;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            provider: entity.provider,
+            handle: (entity.provider == IdentityProvider::Handle).then_some(entity.foreign_id),
+            created_at: timezone.convert(entity.created_at),
         })
     }
 }
@@ -184,6 +209,47 @@ impl Booking {
     }
 }
 
+impl IntoView for BookingSeries {
+    type Entity = entities::BookingSeries;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            unit_id: entity.unit_id,
+            interval_weeks: entity.interval_weeks,
+            created_at: timezone.convert(entity.created_at),
+            canceled_at: entity.canceled_at.map(|v| timezone.convert(v)),
+            is_canceled: is_in_effect(&entity.canceled_at, now),
+        })
+    }
+}
+
+impl IntoView for WaitlistEntry {
+    type Entity = entities::WaitlistEntry;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            unit_id: entity.unit_id,
+            booking_start: timezone.convert(entity.time_from),
+            booking_end: timezone.convert(entity.time_to),
+            created_at: timezone.convert(entity.created_at),
+            notified_at: entity.notified_at.map(|v| timezone.convert(v)),
+            hold_reservation_id: entity.adhoc_reservation_id,
+        })
+    }
+}
+
 impl IntoView for OccupiedSlot {
     type Entity = entities::OccupiedSlot;
     type Error = Error;
@@ -196,10 +262,47 @@ impl IntoView for OccupiedSlot {
         let booking_time = entity.time_to - entity.time_from;
 
         Ok(Self {
-            masked_name: mask_identity(entity.name),
+            // Closures carry a reason rather than a customer name
+            masked_name: if entity.closed {
+                entity.name
+            } else {
+                mask_identity(entity.name)
+            },
             booking_date: timezone.convert(entity.time_from),
             booking_hours: booking_time.num_hours(),
+            booking_minutes: booking_time.num_minutes(),
             confirmed: entity.confirmed,
+            closed: entity.closed,
+        })
+    }
+}
+
+impl From<PriceItem> for PriceLineItem {
+    fn from(value: PriceItem) -> Self {
+        Self {
+            label: value.label,
+            minutes: value.minutes,
+            amount: value.amount,
+        }
+    }
+}
+
+impl IntoView for UnitClosure {
+    type Entity = entities::UnitClosure;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            unit_id: entity.unit_id,
+            closure_start: timezone.convert(entity.time_from),
+            closure_end: timezone.convert(entity.time_to),
+            reason: entity.reason,
+            created_at: timezone.convert(entity.created_at),
         })
     }
 }
@@ -221,11 +324,83 @@ impl IntoView for CashTransaction {
             refund_account: entity.refund_account,
             is_refund_requested: entity.refund_price.is_some(),
             refunded_at: entity.refunded_at.map(|v| timezone.convert(v)),
+            refunded_price: entity.refunded_price,
             is_refunded: is_in_effect(&entity.refunded_at, now),
         })
     }
 }
 
+impl IntoView for PendingCashTransaction {
+    type Entity = entities::CashTransaction;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            product_id: entity.product_id,
+            depositor_name: entity.depositor_name,
+            price: entity.price,
+            created_at: timezone.convert(entity.created_at),
+        })
+    }
+}
+
+impl From<&config::HourPackOffer> for HourPackOffer {
+    fn from(value: &config::HourPackOffer) -> Self {
+        Self {
+            id: value.id.clone(),
+            name: value.name.clone(),
+            hours: value.hours,
+            price: value.price,
+        }
+    }
+}
+
+impl IntoView for HourPack {
+    type Entity = entities::HourPack;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            customer_id: entity.customer_id,
+            name: entity.name,
+            minutes: entity.minutes,
+            price: entity.price,
+            created_at: timezone.convert(entity.created_at),
+            confirmed_at: entity.confirmed_at.map(|v| timezone.convert(v)),
+            canceled_at: entity.canceled_at.map(|v| timezone.convert(v)),
+            is_confirmed: is_in_effect(&entity.confirmed_at, now),
+            is_canceled: is_in_effect(&entity.canceled_at, now),
+        })
+    }
+}
+
+impl IntoView for HourBalanceEntry {
+    type Entity = entities::HourBalanceEntry;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            minutes: entity.minutes,
+            hour_pack_id: entity.hour_pack_id,
+            booking_id: entity.booking_id,
+            created_at: timezone.convert(entity.created_at),
+        })
+    }
+}
+
 impl IntoView for TossPaymentsTransaction {
     type Entity = entities::TossPaymentsTransaction;
     type Error = Error;
@@ -241,6 +416,18 @@ impl IntoView for TossPaymentsTransaction {
             refund_price: entity.refund_price,
             refunded_at: entity.refunded_at.map(|v| timezone.convert(v)),
             is_refunded: is_in_effect(&entity.refunded_at, now),
+            virtual_account: match (
+                entity.virtual_account_bank_code,
+                entity.virtual_account_number,
+                entity.virtual_account_due_at,
+            ) {
+                (Some(bank_code), Some(account_number), Some(due_at)) => Some(VirtualAccount {
+                    bank_code,
+                    account_number,
+                    due_at: timezone.convert(due_at),
+                }),
+                _ => None,
+            },
         })
     }
 }
@@ -328,3 +515,137 @@ impl IntoView for TelemetryEntry {
         })
     }
 }
+
+impl IntoView for Coupon {
+    type Entity = (entities::Coupon, Vec<UnitId>, i64);
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        let (coupon, unit_ids, redemptions) = entity;
+
+        Ok(Self {
+            id: coupon.id,
+            code: coupon.code,
+            name: coupon.name,
+            discount_type: coupon.discount_type,
+            discount_value: coupon.discount_value,
+            valid_from: coupon.valid_from.map(|v| timezone.convert(v)),
+            valid_to: coupon.valid_to.map(|v| timezone.convert(v)),
+            max_redemptions: coupon.max_redemptions,
+            max_redemptions_per_user: coupon.max_redemptions_per_user,
+            first_booking_only: coupon.first_booking_only,
+            unit_ids,
+            redemptions,
+            created_at: timezone.convert(coupon.created_at),
+        })
+    }
+}
+
+impl IntoView for OutboxMessage {
+    type Entity = entities::OutboxMessage;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            channel: entity.channel,
+            recipient: entity.recipient,
+            event_type: entity.event_type,
+            payload: serde_json::from_str(&entity.payload).unwrap_or_default(),
+            attempts: entity.attempts,
+            created_at: timezone.convert(entity.created_at),
+            next_attempt_at: timezone.convert(entity.next_attempt_at),
+            delivered_at: entity.delivered_at.map(|v| timezone.convert(v)),
+            failed_at: entity.failed_at.map(|v| timezone.convert(v)),
+            skipped_at: entity.skipped_at.map(|v| timezone.convert(v)),
+            last_error: entity.last_error,
+            fallback_of: entity.fallback_of,
+        })
+    }
+}
+
+impl IntoView for MessageDeliveryLog {
+    type Entity = entities::MessageDeliveryLog;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            attempted_at: timezone.convert(entity.attempted_at),
+            error: entity.error,
+        })
+    }
+}
+
+impl IntoView for WebhookSubscription {
+    type Entity = entities::WebhookSubscription;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        let mut event_types = entity.event_types.0.into_iter().collect::<Vec<_>>();
+        event_types.sort_by_key(|v| v.name());
+
+        Ok(Self {
+            id: entity.id,
+            url: entity.url,
+            event_types,
+            created_at: timezone.convert(entity.created_at),
+        })
+    }
+}
+
+impl IntoView for WebhookDelivery {
+    type Entity = entities::WebhookDelivery;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: entity.id,
+            subscription_id: entity.subscription_id,
+            event_type: entity.event_type,
+            payload: serde_json::from_str(&entity.payload).unwrap_or_default(),
+            attempts: entity.attempts,
+            created_at: timezone.convert(entity.created_at),
+            next_attempt_at: timezone.convert(entity.next_attempt_at),
+            delivered_at: entity.delivered_at.map(|v| timezone.convert(v)),
+            failed_at: entity.failed_at.map(|v| timezone.convert(v)),
+            last_error: entity.last_error,
+        })
+    }
+}
+
+impl IntoView for WebhookDeliveryLog {
+    type Entity = entities::WebhookDeliveryLog;
+    type Error = Error;
+
+    fn convert(
+        entity: Self::Entity,
+        timezone: &TimeZoneConfig,
+        _now: &DateTime<Utc>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            attempted_at: timezone.convert(entity.attempted_at),
+            status_code: entity.status_code,
+            error: entity.error,
+        })
+    }
+}
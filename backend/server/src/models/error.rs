@@ -3,6 +3,7 @@ use actix_web::web::Data;
 use actix_web::{HttpResponse, HttpResponseBuilder, ResponseError};
 use dxe_data::Error as DataError;
 use dxe_extern::kakao::client::Error as KakaoClientError;
+use dxe_extern::oidc::Error as OidcClientError;
 
 use crate::config::UrlConfig;
 use crate::utils::session::log_out;
@@ -15,6 +16,10 @@ pub enum Error {
     Forbidden,
     #[error("첫 화면으로 돌아가서 다시 로그인을 진행해 주십시오.")]
     InvalidKakaoAccessToken,
+    #[error("첫 화면으로 돌아가서 다시 로그인을 진행해 주십시오.")]
+    InvalidOidcAccessToken,
+    #[error("지원하지 않는 로그인 방식입니다.")]
+    IdentityProviderNotSupported,
     #[error("잘못된 날짜 범위입니다.")]
     InvalidTimeRange,
     #[error("현재 진행중인 예약은 변경할 수 없습니다.")]
@@ -33,8 +38,62 @@ pub enum Error {
     BookingNotFound,
     #[error("예약 변경 요청을 찾을 수 없습니다.")]
     BookingAmendmentNotFound,
+    #[error("반복 예약을 찾을 수 없습니다.")]
+    BookingSeriesNotFound,
+    #[error("대기 신청 내역을 찾을 수 없습니다.")]
+    WaitlistEntryNotFound,
+    #[error("휴무 일정을 찾을 수 없습니다.")]
+    UnitClosureNotFound,
+    #[error("해당 기간에 진행될 예약이 있어 휴무를 지정할 수 없습니다.")]
+    UnitClosureConflict,
+    #[error("쿠폰을 찾을 수 없습니다.")]
+    CouponNotFound,
+    #[error("사용할 수 없는 쿠폰입니다.")]
+    CouponNotApplicable,
+    #[error("이미 사용 중인 쿠폰 코드입니다.")]
+    CouponCodeAlreadyExists,
+    #[error("할인 값이 올바르지 않습니다.")]
+    InvalidCouponDiscount,
+    #[error("시간권을 찾을 수 없습니다.")]
+    HourPackNotFound,
+    #[error("잔여 시간이 부족합니다.")]
+    InsufficientHourBalance,
+    #[error("캘린더 피드를 찾을 수 없습니다.")]
+    CalendarFeedNotFound,
+    #[error("거래내역 형식을 찾을 수 없습니다.")]
+    BankStatementFormatNotFound,
+    #[error("거래내역 파일을 읽을 수 없습니다.")]
+    InvalidBankStatement,
+    #[error("해당 시간은 바로 예약할 수 있습니다.")]
+    WaitlistNotRequired,
     #[error("녹음 파일을 찾을 수 없습니다.")]
     AudioRecordingNotFound,
+    #[error("메시지를 찾을 수 없습니다.")]
+    MessageNotFound,
+    #[error("메시지 템플릿이 올바르지 않습니다: {0}")]
+    InvalidMessageTemplate(String),
+    #[error("웹훅 구독을 찾을 수 없습니다.")]
+    WebhookSubscriptionNotFound,
+    #[error("웹훅 전송 내역을 찾을 수 없습니다.")]
+    WebhookDeliveryNotFound,
+    #[error("로그인 수단을 찾을 수 없습니다.")]
+    IdentityLinkNotFound,
+    #[error("이미 다른 계정에 연결된 로그인 수단입니다.")]
+    IdentityAlreadyLinked,
+    #[error("로그인 수단이 하나 이상 연결되어 있어야 합니다.")]
+    LastIdentityLink,
+    #[error("같은 사용자끼리는 병합할 수 없습니다.")]
+    CannotMergeSameUser,
+    #[error("아이디와 비밀번호가 등록되어 있지 않습니다.")]
+    PlainCredentialNotFound,
+    #[error("비밀번호 재설정 기한이 지났거나 이미 사용된 요청입니다.")]
+    PasswordResetTokenNotFound,
+    #[error("이미 사용 중인 아이디입니다.")]
+    HandleAlreadyExists,
+    #[error("시도 횟수를 초과했습니다. 잠시 후 다시 시도해 주십시오.")]
+    TooManyAttempts,
+    #[error("이메일 인증 정보를 찾을 수 없거나 인증 기한이 지났습니다.")]
+    EmailVerificationNotFound,
     #[error("이미 그룹에 속해있기 때문에 그룹으로 전환할 수 없습니다.")]
     BookingNotAssignableToGroup,
     #[error("해당 그룹에 속해있지 않습니다.")]
@@ -49,6 +108,22 @@ pub enum Error {
     CannotTransferGroupOwnership,
     #[error("환불 계좌 정보를 입력해 주세요.")]
     RefundAccountRequired,
+    #[error("환불 계좌를 '은행명 계좌번호 예금주' 형식으로 입력해 주세요.")]
+    InvalidRefundAccount,
+    #[error("올바른 이메일 주소를 입력해 주세요.")]
+    InvalidEmail,
+    #[error("알림 시간은 예약 1분 전부터 7일 전 사이로 설정해 주세요.")]
+    InvalidReminderLeadTime,
+    #[error("아이디는 영문 소문자, 숫자, '.', '_', '-'로 3~40자 이내로 입력해 주세요.")]
+    InvalidHandle,
+    #[error("비밀번호는 8자 이상으로 입력해 주세요.")]
+    InvalidPassword,
+    #[error("이메일 발송이 지원되지 않습니다.")]
+    EmailNotAvailable,
+    #[error("가상계좌 입금이 완료되지 않았습니다.")]
+    DepositPending,
+    #[error("가상계좌로 결제할 수 없는 상품입니다.")]
+    VirtualAccountNotAllowed,
     #[error("환불 시간이 지났습니다.")]
     NotRefundable,
     #[error("현재 이용 시간이 아닙니다.")]
@@ -57,6 +132,8 @@ pub enum Error {
     DoorNotOpened(String),
     #[error("이미 확정된 예약입니다.")]
     BookingAlreadyConfirmed,
+    #[error("이미 취소된 예약입니다.")]
+    BookingAlreadyCanceled,
     #[error("결제 정보를 찾을 수 없습니다.")]
     ForeignPaymentNotFound,
     #[error("잘못된 파일 업로드입니다.")]
@@ -75,6 +152,8 @@ pub enum Error {
     Jwt(actix_jwt_auth_middleware::AuthError),
     #[error("카카오 API 에러가 발생했습니다: {0}")]
     Kakao(dxe_extern::kakao::client::KakaoError),
+    #[error("외부 로그인 API 에러가 발생했습니다: {0}")]
+    Oidc(OidcClientError),
     #[error("외부 API와 통신 중 에러가 발생했습니다.")]
     Http(#[from] reqwest::Error),
     #[error("데이터베이스 오류가 발생했습니다: {0}")]
@@ -95,6 +174,7 @@ impl From<DataError> for Error {
         match value {
             DataError::InvalidTimeRange => Self::InvalidTimeRange,
             DataError::TimeRangeOccupied => Self::TimeRangeOccupied,
+            DataError::IdentityAlreadyLinked => Self::IdentityAlreadyLinked,
             DataError::UnitNotFound => Self::UnitNotFound,
             DataError::UserNotFound => Self::UserNotFound,
             DataError::BookingNotFound => Self::BookingNotFound,
@@ -114,12 +194,23 @@ impl From<KakaoClientError> for Error {
     }
 }
 
+impl From<OidcClientError> for Error {
+    fn from(value: OidcClientError) -> Self {
+        match value {
+            OidcClientError::Http(e) => Self::Http(e),
+            e => Self::Oidc(e),
+        }
+    }
+}
+
 impl ResponseError for Error {
     fn status_code(&self) -> StatusCode {
         match self {
             Self::LoggedOut(_) => StatusCode::UNAUTHORIZED,
             Self::Forbidden => StatusCode::FORBIDDEN,
             Self::InvalidKakaoAccessToken => StatusCode::BAD_REQUEST,
+            Self::InvalidOidcAccessToken => StatusCode::BAD_REQUEST,
+            Self::IdentityProviderNotSupported => StatusCode::NOT_FOUND,
             Self::InvalidTimeRange => StatusCode::BAD_REQUEST,
             Self::OngoingBookingNotModifiable => StatusCode::BAD_REQUEST,
             Self::TimeRangeOccupied => StatusCode::BAD_REQUEST,
@@ -129,7 +220,34 @@ impl ResponseError for Error {
             Self::UserNotFound => StatusCode::NOT_FOUND,
             Self::BookingNotFound => StatusCode::NOT_FOUND,
             Self::BookingAmendmentNotFound => StatusCode::NOT_FOUND,
+            Self::BookingSeriesNotFound => StatusCode::NOT_FOUND,
+            Self::WaitlistEntryNotFound => StatusCode::NOT_FOUND,
+            Self::UnitClosureNotFound => StatusCode::NOT_FOUND,
+            Self::UnitClosureConflict => StatusCode::CONFLICT,
+            Self::CouponNotFound => StatusCode::NOT_FOUND,
+            Self::CouponNotApplicable => StatusCode::BAD_REQUEST,
+            Self::CouponCodeAlreadyExists => StatusCode::CONFLICT,
+            Self::InvalidCouponDiscount => StatusCode::BAD_REQUEST,
+            Self::HourPackNotFound => StatusCode::NOT_FOUND,
+            Self::InsufficientHourBalance => StatusCode::BAD_REQUEST,
+            Self::CalendarFeedNotFound => StatusCode::NOT_FOUND,
+            Self::BankStatementFormatNotFound => StatusCode::NOT_FOUND,
+            Self::InvalidBankStatement => StatusCode::BAD_REQUEST,
+            Self::WaitlistNotRequired => StatusCode::BAD_REQUEST,
             Self::AudioRecordingNotFound => StatusCode::NOT_FOUND,
+            Self::MessageNotFound => StatusCode::NOT_FOUND,
+            Self::InvalidMessageTemplate(_) => StatusCode::BAD_REQUEST,
+            Self::WebhookSubscriptionNotFound => StatusCode::NOT_FOUND,
+            Self::WebhookDeliveryNotFound => StatusCode::NOT_FOUND,
+            Self::IdentityLinkNotFound => StatusCode::NOT_FOUND,
+            Self::IdentityAlreadyLinked => StatusCode::CONFLICT,
+            Self::LastIdentityLink => StatusCode::BAD_REQUEST,
+            Self::CannotMergeSameUser => StatusCode::BAD_REQUEST,
+            Self::PlainCredentialNotFound => StatusCode::NOT_FOUND,
+            Self::PasswordResetTokenNotFound => StatusCode::NOT_FOUND,
+            Self::HandleAlreadyExists => StatusCode::CONFLICT,
+            Self::TooManyAttempts => StatusCode::TOO_MANY_REQUESTS,
+            Self::EmailVerificationNotFound => StatusCode::NOT_FOUND,
             Self::BookingNotAssignableToGroup => StatusCode::BAD_REQUEST,
             Self::UserNotMemberOf => StatusCode::BAD_REQUEST,
             Self::CannotLeaveGroup => StatusCode::BAD_REQUEST,
@@ -137,10 +255,19 @@ impl ResponseError for Error {
             Self::GroupIsNotOpen => StatusCode::BAD_REQUEST,
             Self::CannotTransferGroupOwnership => StatusCode::BAD_REQUEST,
             Self::RefundAccountRequired => StatusCode::BAD_REQUEST,
+            Self::InvalidRefundAccount => StatusCode::BAD_REQUEST,
+            Self::InvalidEmail => StatusCode::BAD_REQUEST,
+            Self::InvalidReminderLeadTime => StatusCode::BAD_REQUEST,
+            Self::InvalidHandle => StatusCode::BAD_REQUEST,
+            Self::InvalidPassword => StatusCode::BAD_REQUEST,
+            Self::EmailNotAvailable => StatusCode::BAD_REQUEST,
+            Self::DepositPending => StatusCode::BAD_REQUEST,
+            Self::VirtualAccountNotAllowed => StatusCode::BAD_REQUEST,
             Self::NotRefundable => StatusCode::BAD_REQUEST,
             Self::BookingNotActive => StatusCode::BAD_REQUEST,
             Self::DoorNotOpened(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::BookingAlreadyConfirmed => StatusCode::BAD_REQUEST,
+            Self::BookingAlreadyCanceled => StatusCode::BAD_REQUEST,
             Self::ForeignPaymentNotFound => StatusCode::NOT_FOUND,
             Self::BadFileUpload => StatusCode::BAD_REQUEST,
             Self::FileNotFound => StatusCode::NOT_FOUND,
@@ -150,6 +277,7 @@ impl ResponseError for Error {
             Self::Csv(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Jwt(_) => StatusCode::UNAUTHORIZED,
             Self::Kakao(_) => StatusCode::UNAUTHORIZED,
+            Self::Oidc(_) => StatusCode::UNAUTHORIZED,
             Self::Http(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Sqlx(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -161,6 +289,8 @@ impl ResponseError for Error {
             Self::LoggedOut(_) => "LoggedOut",
             Self::Forbidden => "Forbidden",
             Self::InvalidKakaoAccessToken => "InvalidKakaoAccessToken",
+            Self::InvalidOidcAccessToken => "InvalidOidcAccessToken",
+            Self::IdentityProviderNotSupported => "IdentityProviderNotSupported",
             Self::InvalidTimeRange => "InvalidTimeRange",
             Self::TimeRangeOccupied => "TimeRangeOccupied",
             Self::OngoingBookingNotModifiable => "OngoingBookingNotModifiable",
@@ -170,7 +300,34 @@ impl ResponseError for Error {
             Self::UserNotFound => "UserNotFound",
             Self::BookingNotFound => "BookingNotFound",
             Self::BookingAmendmentNotFound => "BookingAmendmentNotFound",
+            Self::BookingSeriesNotFound => "BookingSeriesNotFound",
+            Self::WaitlistEntryNotFound => "WaitlistEntryNotFound",
+            Self::UnitClosureNotFound => "UnitClosureNotFound",
+            Self::UnitClosureConflict => "UnitClosureConflict",
+            Self::CouponNotFound => "CouponNotFound",
+            Self::CouponNotApplicable => "CouponNotApplicable",
+            Self::CouponCodeAlreadyExists => "CouponCodeAlreadyExists",
+            Self::InvalidCouponDiscount => "InvalidCouponDiscount",
+            Self::HourPackNotFound => "HourPackNotFound",
+            Self::InsufficientHourBalance => "InsufficientHourBalance",
+            Self::CalendarFeedNotFound => "CalendarFeedNotFound",
+            Self::BankStatementFormatNotFound => "BankStatementFormatNotFound",
+            Self::InvalidBankStatement => "InvalidBankStatement",
+            Self::WaitlistNotRequired => "WaitlistNotRequired",
             Self::AudioRecordingNotFound => "AudioRecordingNotFound",
+            Self::MessageNotFound => "MessageNotFound",
+            Self::InvalidMessageTemplate(_) => "InvalidMessageTemplate",
+            Self::WebhookSubscriptionNotFound => "WebhookSubscriptionNotFound",
+            Self::WebhookDeliveryNotFound => "WebhookDeliveryNotFound",
+            Self::IdentityLinkNotFound => "IdentityLinkNotFound",
+            Self::IdentityAlreadyLinked => "IdentityAlreadyLinked",
+            Self::LastIdentityLink => "LastIdentityLink",
+            Self::CannotMergeSameUser => "CannotMergeSameUser",
+            Self::PlainCredentialNotFound => "PlainCredentialNotFound",
+            Self::PasswordResetTokenNotFound => "PasswordResetTokenNotFound",
+            Self::HandleAlreadyExists => "HandleAlreadyExists",
+            Self::TooManyAttempts => "TooManyAttempts",
+            Self::EmailVerificationNotFound => "EmailVerificationNotFound",
             Self::BookingNotAssignableToGroup => "BookingNotAssignableToGroup",
             Self::UserNotMemberOf => "UserNotMemberOf",
             Self::CannotLeaveGroup => "CannotLeaveGroup",
@@ -178,10 +335,19 @@ impl ResponseError for Error {
             Self::GroupIsNotOpen => "GroupIsNotOpen",
             Self::CannotTransferGroupOwnership => "CannotTransferGroupOwnership",
             Self::RefundAccountRequired => "RefundAccountRequired",
+            Self::InvalidRefundAccount => "InvalidRefundAccount",
+            Self::InvalidEmail => "InvalidEmail",
+            Self::InvalidReminderLeadTime => "InvalidReminderLeadTime",
+            Self::InvalidHandle => "InvalidHandle",
+            Self::InvalidPassword => "InvalidPassword",
+            Self::EmailNotAvailable => "EmailNotAvailable",
+            Self::DepositPending => "DepositPending",
+            Self::VirtualAccountNotAllowed => "VirtualAccountNotAllowed",
             Self::NotRefundable => "NotRefundable",
             Self::BookingNotActive => "BookingNotActive",
             Self::DoorNotOpened(_) => "DoorNotOpened",
             Self::BookingAlreadyConfirmed => "BookingAlreadyConfirmed",
+            Self::BookingAlreadyCanceled => "BookingAlreadyCanceled",
             Self::ForeignPaymentNotFound => "ForeignPaymentNotFound",
             Self::BadFileUpload => "BadFileUpload",
             Self::FileNotFound => "FileNotFound",
@@ -192,6 +358,7 @@ impl ResponseError for Error {
             Self::Jwt(_) => "AuthError",
             Self::Http(_) => "HttpError",
             Self::Kakao(_) => "KakaoApiError",
+            Self::Oidc(_) => "OidcApiError",
             Self::Sqlx(_) => "DatabaseError",
             Self::Internal(_) => "InternalError",
         };
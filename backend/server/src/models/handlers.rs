@@ -1,15 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, FixedOffset};
+use dxe_types::entities::NotificationPreferences;
 use dxe_types::{
-    AdhocReservationId, BookingId, ForeignPaymentId, IdentityId, SpaceId, TelemetryType, UnitId,
-    UserId,
+    AdhocReservationId, BookingId, BookingSeriesId, CouponDiscountType, ForeignPaymentId,
+    HourPackId, IdentityId, IdentityProvider, SpaceId, TelemetryType, UnitId, UserId,
+    WebhookEventType,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::models::entities::{
-    AdhocParking, AdhocReservation, AudioRecording, Booking, BookingWithPayments, CashTransaction,
-    Group, GroupWithUsers, OccupiedSlot, ProductType, SelfUser, TelemetryEntry, Transaction,
+    AdhocParking, AdhocReservation, AudioRecording, BankDeposit, BankDepositMatch, Booking,
+    BookingSeries, BookingSeriesOccurrence, BookingWithPayments, CashTransaction, Coupon, Group,
+    GroupWithUsers, HourBalanceEntry, HourPack, HourPackOffer, MessageDeliveryLog, OccupiedSlot,
+    OutboxMessage, PriceLineItem, ProductType, SelfUser, TelemetryEntry, Transaction,
+    UnitAvailability, UnitClosure, UserIdentityLink, VirtualAccount, WaitlistEntry,
+    WebhookDelivery, WebhookDeliveryLog, WebhookSubscription,
 };
 
 pub mod admin {
@@ -75,6 +81,42 @@ pub mod admin {
         pub cash_transaction: Option<CashTransaction>,
     }
 
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetHourPacksResponse {
+        pub hour_packs: Vec<HourPack>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ModifyHourPackRequest {
+        pub action: ModifyAction,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ModifyHourPackResponse {
+        pub hour_pack: HourPack,
+        pub cash_transaction: Option<CashTransaction>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImportBankStatementQuery {
+        pub format: String,
+        #[serde(default)]
+        pub apply: bool,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImportBankStatementResponse {
+        pub matched: Vec<BankDepositMatch>,
+        pub ambiguous: Vec<BankDepositMatch>,
+        pub unmatched: Vec<BankDeposit>,
+        pub invalid_rows: Vec<usize>,
+    }
+
     #[derive(Debug, Deserialize)]
     pub struct GetAdhocReservationsQuery {
         pub unit_id: UnitId,
@@ -115,6 +157,18 @@ pub mod admin {
         pub users: Vec<SelfUser>,
     }
 
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MergeUsersRequest {
+        pub source_user_id: UserId,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MergeUsersResponse {
+        pub user: SelfUser,
+    }
+
     #[derive(Debug, Deserialize)]
     pub struct GetAdhocParkingsQuery {
         pub space_id: SpaceId,
@@ -134,6 +188,126 @@ pub mod admin {
         pub desired_hours: i64,
         pub license_plate_number: String,
     }
+
+    #[derive(Debug, Deserialize)]
+    pub struct GetUnitClosuresQuery {
+        pub unit_id: UnitId,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetUnitClosuresResponse {
+        pub closures: Vec<UnitClosure>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateUnitClosureRequest {
+        pub unit_id: UnitId,
+        pub time_from: DateTime<FixedOffset>,
+        pub time_to: DateTime<FixedOffset>,
+        pub reason: Option<String>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateUnitClosureResponse {
+        pub closure: UnitClosure,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetCouponsResponse {
+        pub coupons: Vec<Coupon>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateCouponRequest {
+        pub code: String,
+        pub name: String,
+        pub discount_type: CouponDiscountType,
+        pub discount_value: i64,
+        pub valid_from: Option<DateTime<FixedOffset>>,
+        pub valid_to: Option<DateTime<FixedOffset>>,
+        pub max_redemptions: Option<i64>,
+        pub max_redemptions_per_user: Option<i64>,
+        #[serde(default)]
+        pub first_booking_only: bool,
+        #[serde(default)]
+        pub unit_ids: Vec<UnitId>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateCouponResponse {
+        pub coupon: Coupon,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetFailedMessagesResponse {
+        pub messages: Vec<OutboxMessage>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetMessageResponse {
+        pub message: OutboxMessage,
+        pub delivery_logs: Vec<MessageDeliveryLog>,
+        pub fallbacks: Vec<OutboxMessage>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ResendMessageResponse {
+        pub message: OutboxMessage,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReloadMessageTemplatesResponse {
+        pub templates: Vec<String>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetWebhookSubscriptionsResponse {
+        pub subscriptions: Vec<WebhookSubscription>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct WebhookSubscriptionRequest {
+        pub url: url::Url,
+        pub secret: String,
+        pub event_types: HashSet<WebhookEventType>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct WebhookSubscriptionResponse {
+        pub subscription: WebhookSubscription,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetWebhookDeliveriesQuery {
+        pub limit: Option<i64>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetWebhookDeliveriesResponse {
+        pub deliveries: Vec<WebhookDelivery>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetWebhookDeliveryResponse {
+        pub delivery: WebhookDelivery,
+        pub delivery_logs: Vec<WebhookDeliveryLog>,
+    }
 }
 
 pub mod auth {
@@ -147,6 +321,19 @@ pub mod auth {
         pub error_description: Option<String>,
     }
 
+    #[derive(Clone, Debug, Deserialize)]
+    pub struct KakaoAuthStateQuery {
+        pub redirect_to: Option<String>,
+        #[serde(default)]
+        pub transparent: bool,
+    }
+
+    #[derive(Clone, Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct KakaoAuthStateResponse {
+        pub state: String,
+    }
+
     #[derive(Clone, Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct KakaoAuthRegisterRequest {
@@ -154,6 +341,39 @@ pub mod auth {
         pub license_plate_number: Option<String>,
     }
 
+    #[derive(Clone, Debug, Deserialize)]
+    pub struct OidcAuthorizeQuery {
+        pub redirect_to: Option<String>,
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    pub struct OidcAuthRedirectQuery {
+        pub code: Option<String>,
+        pub state: Option<String>,
+        pub error: Option<String>,
+        pub error_description: Option<String>,
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OidcAuthRegisterRequest {
+        pub name: String,
+        pub license_plate_number: Option<String>,
+    }
+
+    #[derive(Clone, Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OidcProvider {
+        pub provider: IdentityProvider,
+        pub display_name: String,
+    }
+
+    #[derive(Clone, Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetOidcProvidersResponse {
+        pub providers: Vec<OidcProvider>,
+    }
+
     #[derive(Clone, Debug, Deserialize)]
     pub struct HandleAuthQuery {
         pub redirect_to: Option<String>,
@@ -171,6 +391,27 @@ pub mod auth {
     pub struct HandleAuthResponse {
         pub redirect_to: String,
     }
+
+    #[derive(Clone, Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct HandleSignupRequest {
+        pub handle: String,
+        pub password: String,
+        pub name: String,
+        pub license_plate_number: Option<String>,
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RequestPasswordResetRequest {
+        pub handle: String,
+    }
+
+    #[derive(Clone, Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ResetPasswordRequest {
+        pub new_password: String,
+    }
 }
 
 pub mod booking {
@@ -181,6 +422,7 @@ pub mod booking {
         pub unit_id: UnitId,
         pub exclude_booking_id: Option<BookingId>,
         pub exclude_adhoc_reservation_id: Option<AdhocReservationId>,
+        pub coupon_code: Option<String>,
     }
 
     #[derive(Debug, Serialize)]
@@ -189,17 +431,54 @@ pub mod booking {
         pub start: DateTime<FixedOffset>,
         pub end: DateTime<FixedOffset>,
         pub max_booking_hours: i64,
+        pub slot_minutes: i64,
+        pub min_booking_minutes: i64,
+        pub max_booking_minutes: i64,
         pub slots: Vec<OccupiedSlot>,
     }
 
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TossPaymentsWebhookRequest {
+        #[serde(default)]
+        pub event_type: String,
+        #[serde(default)]
+        pub data: serde_json::Value,
+        // Deposit callbacks of virtual accounts carry the payment at the top level instead
+        pub order_id: Option<ForeignPaymentId>,
+        pub secret: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TossPaymentsWebhookPayment {
+        pub order_id: ForeignPaymentId,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetAvailabilityQuery {
+        pub unit_id: Option<UnitId>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetAvailabilityResponse {
+        pub generated_at: DateTime<FixedOffset>,
+        pub units: Vec<UnitAvailability>,
+    }
+
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CheckRequest {
         pub unit_id: UnitId,
         pub time_from: DateTime<FixedOffset>,
         pub customer_id: Option<IdentityId>,
+        #[serde(default)]
         pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
         pub additional_hours: Option<i64>,
+        pub additional_minutes: Option<i64>,
         pub exclude_booking_id: Option<BookingId>,
         pub exclude_adhoc_reservation_id: Option<AdhocReservationId>,
     }
@@ -209,25 +488,69 @@ pub mod booking {
     pub struct CheckResponse {
         pub amend_reservation: Option<Booking>,
         pub total_price: i64,
+        pub price_breakdown: Vec<PriceLineItem>,
     }
 
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SubmitBookingRequest {
+        pub temporary_reservation_id: Option<AdhocReservationId>,
         pub unit_id: UnitId,
         pub time_from: DateTime<FixedOffset>,
+        #[serde(default)]
         pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
         pub identity_id: IdentityId,
+        #[serde(default)]
         pub depositor_name: String,
+        pub coupon_code: Option<String>,
+        /// Pays by debiting the identity's prepaid hour balance instead of a cash transfer.
+        #[serde(default)]
+        pub use_hour_balance: bool,
     }
 
     #[derive(Debug, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct SubmitBookingResponse {
         pub booking: Booking,
+        pub cash_transaction: Option<CashTransaction>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetHourPacksResponse {
+        pub offers: Vec<HourPackOffer>,
+        pub hour_packs: Vec<HourPack>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PurchaseHourPackRequest {
+        pub offer_id: String,
+        pub identity_id: IdentityId,
+        pub depositor_name: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PurchaseHourPackResponse {
+        pub hour_pack: HourPack,
         pub cash_transaction: CashTransaction,
     }
 
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetHourBalanceQuery {
+        pub identity_id: IdentityId,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetHourBalanceResponse {
+        pub minutes: i64,
+        pub entries: Vec<HourBalanceEntry>,
+    }
+
     #[derive(Debug, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct GetBookingResponse {
@@ -235,6 +558,7 @@ pub mod booking {
         pub transaction: Option<Transaction>,
         pub amendable: bool,
         pub extendable_hours: i64,
+        pub extendable_minutes: i64,
     }
 
     #[derive(Debug, Deserialize)]
@@ -254,7 +578,10 @@ pub mod booking {
     pub struct AmendBookingRequest {
         pub new_identity_id: Option<IdentityId>,
         pub new_time_from: Option<DateTime<FixedOffset>>,
+        pub new_unit_id: Option<UnitId>,
         pub additional_hours: Option<i64>,
+        pub additional_minutes: Option<i64>,
+        pub refund_account: Option<String>,
     }
 
     #[derive(Debug, Serialize)]
@@ -262,6 +589,108 @@ pub mod booking {
     pub struct AmendBookingResponse {
         pub booking: Booking,
         pub foreign_payment_id: Option<ForeignPaymentId>,
+        pub transaction: Option<Transaction>,
+    }
+
+    #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum BookingSeriesRecurrence {
+        Weekly,
+        Biweekly,
+    }
+
+    impl BookingSeriesRecurrence {
+        pub fn interval_weeks(&self) -> i64 {
+            match self {
+                Self::Weekly => 1,
+                Self::Biweekly => 2,
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CheckBookingSeriesRequest {
+        pub unit_id: UnitId,
+        pub time_from: DateTime<FixedOffset>,
+        #[serde(default)]
+        pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
+        pub recurrence: BookingSeriesRecurrence,
+        pub until: Option<DateTime<FixedOffset>>,
+        pub count: Option<i64>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CheckBookingSeriesResponse {
+        pub occurrences: Vec<BookingSeriesOccurrence>,
+        pub total_price: i64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SubmitBookingSeriesRequest {
+        pub unit_id: UnitId,
+        pub time_from: DateTime<FixedOffset>,
+        #[serde(default)]
+        pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
+        pub recurrence: BookingSeriesRecurrence,
+        pub until: Option<DateTime<FixedOffset>>,
+        pub count: Option<i64>,
+        pub identity_id: IdentityId,
+        pub depositor_name: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SubmitBookingSeriesResponse {
+        pub series: BookingSeries,
+        pub bookings: Vec<BookingWithPayments>,
+        pub conflicts: Vec<BookingSeriesOccurrence>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetBookingSeriesResponse {
+        pub series: BookingSeries,
+        pub bookings: Vec<BookingWithPayments>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct CancelBookingSeriesRequest {
+        pub refund_account: Option<String>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CancelBookingSeriesResponse {
+        pub series: BookingSeries,
+        pub canceled_bookings: Vec<BookingWithPayments>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateWaitlistEntryRequest {
+        pub unit_id: UnitId,
+        pub time_from: DateTime<FixedOffset>,
+        #[serde(default)]
+        pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
+        pub identity_id: IdentityId,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateWaitlistEntryResponse {
+        pub entry: WaitlistEntry,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetWaitlistEntriesResponse {
+        pub entries: Vec<WaitlistEntry>,
     }
 
     #[derive(Debug, Serialize)]
@@ -276,8 +705,11 @@ pub mod booking {
         pub temporary_reservation_id: Option<AdhocReservationId>,
         pub unit_id: UnitId,
         pub time_from: DateTime<FixedOffset>,
+        #[serde(default)]
         pub desired_hours: i64,
+        pub desired_minutes: Option<i64>,
         pub identity_id: IdentityId,
+        pub coupon_code: Option<String>,
     }
 
     #[derive(Debug, Serialize)]
@@ -289,6 +721,21 @@ pub mod booking {
         pub expires_in: DateTime<FixedOffset>,
     }
 
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InitiateTossHourPackPaymentRequest {
+        pub offer_id: String,
+        pub identity_id: IdentityId,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InitiateTossHourPackPaymentResponse {
+        pub order_id: ForeignPaymentId,
+        pub price: i64,
+        pub hour_pack_id: HourPackId,
+    }
+
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ConfirmTossPaymentRequest {
@@ -300,15 +747,18 @@ pub mod booking {
     #[derive(Debug, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ConfirmTossPaymentResponse {
-        pub booking_id: BookingId,
+        pub booking_id: Option<BookingId>,
+        pub hour_pack_id: Option<HourPackId>,
+        pub virtual_account: Option<VirtualAccount>,
     }
 
     #[derive(Debug, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct GetTossPaymentStateResponse {
         pub r#type: ProductType,
-        pub time_from: DateTime<FixedOffset>,
-        pub time_to: DateTime<FixedOffset>,
+        pub time_from: Option<DateTime<FixedOffset>>,
+        pub time_to: Option<DateTime<FixedOffset>>,
+        pub hour_pack: Option<HourPack>,
     }
 }
 
@@ -366,6 +816,7 @@ pub mod user {
     pub struct UpdateMeRequest {
         pub new_name: Option<String>,
         pub new_license_plate_number: Option<String>,
+        pub new_notification_preferences: Option<NotificationPreferences>,
     }
 
     #[derive(Debug, Serialize)]
@@ -373,4 +824,72 @@ pub mod user {
     pub struct UpdateMeResponse {
         pub user: SelfUser,
     }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct UpdateEmailRequest {
+        pub email: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct UpdateEmailResponse {
+        pub email: String,
+        pub verification_expires_at: DateTime<FixedOffset>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetCalendarFeedQuery {
+        pub identity_id: Option<IdentityId>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RotateCalendarFeedRequest {
+        pub identity_id: Option<IdentityId>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CalendarFeedResponse {
+        pub url: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetIdentityLinksResponse {
+        pub identity_links: Vec<UserIdentityLink>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ChangePasswordRequest {
+        pub current_password: String,
+        pub new_password: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LinkAuthorizeQuery {
+        pub redirect_to: Option<String>,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LinkKakaoStateResponse {
+        pub state: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LinkHandleRequest {
+        pub handle: String,
+        pub password: String,
+    }
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LinkIdentityResponse {
+        pub identity_link: UserIdentityLink,
+    }
 }
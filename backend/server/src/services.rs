@@ -1,4 +1,13 @@
+pub mod amendment;
+pub mod availability;
+pub mod bank_statement;
 pub mod calendar;
+pub mod coupon;
+pub mod credential;
 pub mod doorlock;
 pub mod messaging;
 pub mod notification;
+pub mod payment_deadline;
+pub mod toss_payments;
+pub mod waitlist;
+pub mod webhook;
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use dxe_data::entities::{Booking, BookingAmendment};
+use dxe_data::queries::booking::{confirm_booking_amendment, get_booking};
+use dxe_data::queries::payment::confirm_cash_payment;
+use dxe_types::ProductId;
+use sqlx::SqliteConnection;
+
+use crate::models::Error;
+use crate::services::messaging::MessagingService;
+use crate::services::waitlist::WaitlistService;
+
+/// Applies a rescheduling which has been held until the cash deposit for its price difference
+/// arrived. Returns the booking at its new time, or `None` if the amendment is no longer
+/// pending.
+pub async fn confirm_cash_amendment(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    booking_amendment: &BookingAmendment,
+    waitlist_service: &WaitlistService,
+    messaging_service: &MessagingService,
+) -> Result<Option<Booking>, Error> {
+    let booking = get_booking(&mut *connection, &booking_amendment.booking_id)
+        .await?
+        .ok_or(Error::BookingNotFound)?;
+
+    if !confirm_booking_amendment(&mut *connection, now, &booking_amendment.id).await? {
+        return Ok(None);
+    }
+
+    confirm_cash_payment(
+        &mut *connection,
+        now,
+        &ProductId::from(booking_amendment.id),
+    )
+    .await?;
+
+    waitlist_service
+        .release(
+            &mut *connection,
+            now,
+            &booking.unit_id,
+            &booking.time_from,
+            &booking.time_to,
+        )
+        .await?;
+
+    if let Err(e) = messaging_service
+        .send_amend_notification(
+            &mut *connection,
+            now,
+            booking.clone(),
+            booking_amendment.desired_time_from,
+            booking_amendment.desired_time_to,
+        )
+        .await
+    {
+        log::warn!("Could not send amend notification to customers: {e}");
+    }
+
+    Ok(get_booking(&mut *connection, &booking.id).await?)
+}
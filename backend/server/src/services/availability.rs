@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
+use dxe_data::queries::booking::get_occupied_slots;
+use dxe_types::UnitId;
+use sqlx::SqliteConnection;
+
+use crate::config::{AvailabilityConfig, TimeZoneConfig, UnitBookingConfig};
+use crate::models::Error;
+use crate::utils::datetime::truncate_time_to_slot;
+
+/// Anonymized occupancy of a unit, with the gaps long enough to be booked.
+pub struct Availability {
+    pub generated_at: DateTime<Utc>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub occupied: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    pub free: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+pub struct AvailabilityService {
+    horizon: TimeDelta,
+    max_age: TimeDelta,
+    timezone_config: TimeZoneConfig,
+    cache: Mutex<HashMap<UnitId, Arc<Availability>>>,
+}
+
+impl AvailabilityService {
+    pub fn new(config: &AvailabilityConfig, timezone_config: TimeZoneConfig) -> Self {
+        Self {
+            horizon: TimeDelta::days(config.horizon_days),
+            max_age: TimeDelta::seconds(config.cache_seconds),
+            timezone_config,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn max_age(&self) -> TimeDelta {
+        self.max_age
+    }
+
+    pub async fn get(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        unit_id: &UnitId,
+        unit_booking_config: &UnitBookingConfig,
+    ) -> Result<Arc<Availability>, Error> {
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .get(unit_id)
+            .filter(|v| v.generated_at + self.max_age > *now)
+            .cloned();
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+
+        let start = self
+            .timezone_config
+            .convert(*now)
+            .with_time(NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+            .unwrap()
+            .to_utc();
+        let end = start + self.horizon;
+
+        let mut slots =
+            get_occupied_slots(&mut *connection, now, unit_id, &start, &end, None, None).await?;
+        slots.sort_by(|a, b| a.time_from.cmp(&b.time_from));
+
+        // Customer names and closure reasons are dropped, leaving merged time ranges only
+        let mut occupied: Vec<(DateTime<Utc>, DateTime<Utc>)> = vec![];
+        for slot in slots {
+            let from = slot.time_from.max(start);
+            let to = slot.time_to.min(end);
+            if from >= to {
+                continue;
+            }
+
+            match occupied.last_mut() {
+                Some(last) if last.1 >= from => last.1 = last.1.max(to),
+                _ => occupied.push((from, to)),
+            }
+        }
+
+        let slot_size = unit_booking_config.slot_size();
+        let min_length = unit_booking_config.min_length();
+
+        let mut cursor =
+            truncate_time_to_slot(self.timezone_config.convert(*now), slot_size).to_utc();
+        if cursor < *now {
+            cursor += slot_size;
+        }
+
+        let mut free = vec![];
+        for (from, to) in &occupied {
+            if *from - cursor >= min_length {
+                free.push((cursor, *from));
+            }
+            cursor = cursor.max(*to);
+        }
+        if end - cursor >= min_length {
+            free.push((cursor, end));
+        }
+
+        let availability = Arc::new(Availability {
+            generated_at: *now,
+            start,
+            end,
+            occupied,
+            free,
+        });
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(unit_id.clone(), availability.clone());
+
+        Ok(availability)
+    }
+}
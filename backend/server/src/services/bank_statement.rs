@@ -0,0 +1,315 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use dxe_data::entities::CashTransaction;
+use encoding_rs::EUC_KR;
+use futures::StreamExt;
+
+use crate::config::{BankStatementFormat, TimeZoneConfig};
+use crate::models::Error;
+
+pub struct BankDeposit {
+    /// Row number in the uploaded file, starting from 1.
+    pub row: usize,
+    pub depositor_name: String,
+    pub amount: i64,
+    pub deposited_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct BankStatement {
+    pub deposits: Vec<BankDeposit>,
+    /// Rows that could not be parsed with the configured format.
+    pub invalid_rows: Vec<usize>,
+}
+
+pub struct DepositMatch {
+    pub deposit: BankDeposit,
+    pub candidates: Vec<CashTransaction>,
+}
+
+pub async fn read_bank_statement(
+    format: &BankStatementFormat,
+    timezone_config: &TimeZoneConfig,
+    data: &[u8],
+) -> Result<BankStatement, Error> {
+    let data = decode_bank_statement(data)?;
+    let mut reader = csv_async::AsyncReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .create_reader(data.as_bytes());
+    let mut records = reader.records();
+
+    let mut statement = BankStatement::default();
+    let mut columns = None;
+    let mut row = 0;
+
+    while let Some(record) = records.next().await {
+        let record = record.map_err(|_| Error::InvalidBankStatement)?;
+        row += 1;
+
+        if row <= format.skip_rows {
+            continue;
+        }
+
+        let Some(column_indices) = columns else {
+            let find = |name: &str| {
+                record
+                    .iter()
+                    .position(|v| v.trim_start_matches('\u{feff}').trim() == name)
+                    .ok_or(Error::InvalidBankStatement)
+            };
+            columns = Some((
+                find(&format.date_column)?,
+                find(&format.depositor_column)?,
+                find(&format.amount_column)?,
+            ));
+            continue;
+        };
+
+        let deposit = parse_deposit(
+            row,
+            &record,
+            column_indices,
+            &format.date_format,
+            timezone_config,
+        );
+
+        match deposit {
+            // Withdrawals leave the deposit column empty
+            Some(deposit) if deposit.amount <= 0 => {}
+            Some(deposit) => statement.deposits.push(deposit),
+            None => statement.invalid_rows.push(row),
+        }
+    }
+
+    if columns.is_none() {
+        return Err(Error::InvalidBankStatement);
+    }
+
+    Ok(statement)
+}
+
+/// Pairs each deposit with the pending cash transactions of the same amount and depositor name,
+/// created within the window before the deposit. Deposits are processed in chronological order,
+/// and a transaction is matched to a single deposit at most.
+pub fn match_deposits(
+    mut deposits: Vec<BankDeposit>,
+    pending: Vec<CashTransaction>,
+    window: TimeDelta,
+) -> Vec<DepositMatch> {
+    deposits.sort_by(|a, b| a.deposited_at.cmp(&b.deposited_at));
+
+    let mut claimed = HashSet::new();
+    let mut result = vec![];
+
+    for deposit in deposits {
+        let depositor_name = normalize_name(&deposit.depositor_name);
+
+        let candidates = pending
+            .iter()
+            .filter(|v| {
+                !claimed.contains(&v.product_id)
+                    && v.price == deposit.amount
+                    && normalize_name(&v.depositor_name) == depositor_name
+                    // Statements without the time of day put deposits at midnight
+                    && deposit.deposited_at + TimeDelta::days(1) > v.created_at
+                    && deposit.deposited_at <= v.created_at + window
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if let [candidate] = candidates.as_slice() {
+            claimed.insert(candidate.product_id);
+        }
+
+        result.push(DepositMatch {
+            deposit,
+            candidates,
+        });
+    }
+
+    result
+}
+
+/// Statements exported by Korean banks are often encoded in CP949 rather than UTF-8.
+fn decode_bank_statement(data: &[u8]) -> Result<Cow<'_, str>, Error> {
+    if let Ok(data) = std::str::from_utf8(data) {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    // The EUC-KR decoder of the Encoding Standard covers the CP949 extensions
+    let (data, had_errors) = EUC_KR.decode_without_bom_handling(data);
+    if had_errors {
+        return Err(Error::InvalidBankStatement);
+    }
+
+    Ok(data)
+}
+
+fn parse_deposit(
+    row: usize,
+    record: &csv_async::StringRecord,
+    (date_index, depositor_index, amount_index): (usize, usize, usize),
+    date_format: &str,
+    timezone_config: &TimeZoneConfig,
+) -> Option<BankDeposit> {
+    let deposited_at = parse_date(record.get(date_index)?.trim(), date_format)?
+        .and_local_timezone(timezone_config.timezone)
+        .single()?
+        .to_utc();
+
+    Some(BankDeposit {
+        row,
+        depositor_name: record.get(depositor_index)?.trim().to_owned(),
+        amount: parse_amount(record.get(amount_index)?)?,
+        deposited_at,
+    })
+}
+
+fn parse_date(value: &str, format: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, format)
+                .ok()
+                .and_then(|v| v.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Parses amounts such as `50,000`, `50000.00` or `₩50,000`. Fractions of a won are rejected.
+fn parse_amount(value: &str) -> Option<i64> {
+    let digits = value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '-' || *c == '.')
+        .collect::<String>();
+
+    if digits.is_empty() {
+        return Some(0);
+    }
+
+    let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+    if !fraction.chars().all(|c| c == '0') {
+        return None;
+    }
+
+    integer.parse().ok()
+}
+
+fn normalize_name(value: &str) -> String {
+    value.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeDelta, TimeZone, Utc};
+    use dxe_data::entities::CashTransaction;
+    use dxe_types::{BookingId, ProductId};
+    use encoding_rs::EUC_KR;
+
+    use super::{BankDeposit, match_deposits, parse_amount, read_bank_statement};
+    use crate::config::{BankStatementFormat, TimeZoneConfig};
+
+    fn format() -> BankStatementFormat {
+        BankStatementFormat {
+            skip_rows: 0,
+            date_column: "거래일시".to_owned(),
+            date_format: "%Y-%m-%d %H:%M:%S".to_owned(),
+            depositor_column: "내용".to_owned(),
+            amount_column: "입금액".to_owned(),
+        }
+    }
+
+    fn timezone_config() -> TimeZoneConfig {
+        TimeZoneConfig {
+            timezone: FixedOffset::east_opt(9 * 3600).unwrap(),
+        }
+    }
+
+    fn cash_transaction(depositor_name: &str, price: i64) -> CashTransaction {
+        CashTransaction {
+            product_id: ProductId::from(BookingId::generate()),
+            depositor_name: depositor_name.to_owned(),
+            price,
+            created_at: Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap(),
+            confirmed_at: None,
+            refund_price: None,
+            refund_account: None,
+            refunded_at: None,
+            refunded_price: None,
+        }
+    }
+
+    #[test]
+    fn parses_amounts() {
+        assert_eq!(parse_amount("50000"), Some(50000));
+        assert_eq!(parse_amount("50,000"), Some(50000));
+        assert_eq!(parse_amount(" 50000.00 "), Some(50000));
+        assert_eq!(parse_amount("₩50,000원"), Some(50000));
+        assert_eq!(parse_amount(""), Some(0));
+        assert_eq!(parse_amount("50000.50"), None);
+    }
+
+    #[tokio::test]
+    async fn reads_cp949_statement() {
+        let csv = "거래일시,내용,입금액,출금액\n\
+                   2026-03-02 10:15:00,홍길동,\"50,000.00\",\n\
+                   2026-03-02 11:00:00,카드대금,,30000\n\
+                   2026-03-02 12:00:00,김철수,1000.5,\n";
+        let (data, _, had_errors) = EUC_KR.encode(csv);
+        assert!(!had_errors);
+
+        let statement = read_bank_statement(&format(), &timezone_config(), &data)
+            .await
+            .unwrap();
+
+        assert_eq!(statement.deposits.len(), 1);
+        assert_eq!(statement.deposits[0].row, 2);
+        assert_eq!(statement.deposits[0].depositor_name, "홍길동");
+        assert_eq!(statement.deposits[0].amount, 50000);
+        assert_eq!(
+            statement.deposits[0].deposited_at,
+            Utc.with_ymd_and_hms(2026, 3, 2, 1, 15, 0).unwrap()
+        );
+        assert_eq!(statement.invalid_rows, vec![4]);
+    }
+
+    #[tokio::test]
+    async fn reads_utf8_statement_with_bom() {
+        let csv = "\u{feff}거래일시,내용,입금액\n2026-03-02 10:15:00,홍길동,50000\n";
+
+        let statement = read_bank_statement(&format(), &timezone_config(), csv.as_bytes())
+            .await
+            .unwrap();
+
+        assert_eq!(statement.deposits.len(), 1);
+        assert_eq!(statement.deposits[0].depositor_name, "홍길동");
+    }
+
+    #[test]
+    fn matches_deposits_once() {
+        let pending = vec![
+            cash_transaction("홍 길동", 50000),
+            cash_transaction("홍길동", 30000),
+        ];
+        let deposit = |row, amount| BankDeposit {
+            row,
+            depositor_name: "홍길동".to_owned(),
+            amount,
+            deposited_at: Utc.with_ymd_and_hms(2026, 3, 2, 1, 0, 0).unwrap(),
+        };
+
+        let matches = match_deposits(
+            vec![deposit(1, 50000), deposit(2, 50000), deposit(3, 10000)],
+            pending.clone(),
+            TimeDelta::days(3),
+        );
+
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].candidates.len(), 1);
+        assert_eq!(matches[0].candidates[0].product_id, pending[0].product_id);
+        assert!(matches[1].candidates.is_empty());
+        assert!(matches[2].candidates.is_empty());
+    }
+}
@@ -145,6 +145,74 @@ impl CalendarService {
     }
 }
 
+/// Calendar changes made along with a database transaction. They are applied once the
+/// transaction has been committed, so that rolled back changes leave no events behind.
+#[derive(Default)]
+pub struct CalendarUpdates {
+    expired_adhoc_reservations: Vec<AdhocReservationId>,
+    registered_bookings: Vec<(Booking, Vec<User>)>,
+    moved_bookings: Vec<Booking>,
+    deleted_bookings: Vec<BookingId>,
+}
+
+impl CalendarUpdates {
+    pub fn delete_adhoc_reservation(&mut self, adhoc_reservation_id: AdhocReservationId) {
+        self.expired_adhoc_reservations.push(adhoc_reservation_id);
+    }
+
+    pub fn register_booking(&mut self, booking: Booking, users: Vec<User>) {
+        self.registered_bookings.push((booking, users));
+    }
+
+    pub fn update_booking_time(&mut self, booking: Booking) {
+        self.moved_bookings.push(booking);
+    }
+
+    pub fn delete_booking(&mut self, booking_id: BookingId) {
+        self.deleted_bookings.push(booking_id);
+    }
+
+    /// Takes over the changes of a nested transaction which has been committed.
+    pub fn append(&mut self, mut other: Self) {
+        self.expired_adhoc_reservations
+            .append(&mut other.expired_adhoc_reservations);
+        self.registered_bookings
+            .append(&mut other.registered_bookings);
+        self.moved_bookings.append(&mut other.moved_bookings);
+        self.deleted_bookings.append(&mut other.deleted_bookings);
+    }
+
+    pub async fn apply(self, calendar_service: &Option<CalendarService>) {
+        let Some(calendar_service) = calendar_service.as_ref() else {
+            return;
+        };
+
+        for adhoc_reservation_id in self.expired_adhoc_reservations {
+            let _ = calendar_service
+                .delete_adhoc_reservation(&adhoc_reservation_id)
+                .await;
+        }
+
+        for (booking, users) in self.registered_bookings {
+            if let Err(e) = calendar_service.register_booking(&booking, &users).await {
+                log::error!("Failed to register event on calendar: {e}");
+            }
+        }
+
+        for booking in self.moved_bookings {
+            if let Err(e) = calendar_service.update_booking_time(&booking).await {
+                log::warn!("Could not update booking {} to calendar: {e}", booking.id);
+            }
+        }
+
+        for booking_id in self.deleted_bookings {
+            if let Err(e) = calendar_service.delete_booking(&booking_id).await {
+                log::error!("Failed to delete event on calendar: {e}");
+            }
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("GCP authentication error: {0}")]
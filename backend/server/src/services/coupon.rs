@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use dxe_data::entities::Coupon;
+use dxe_data::queries::coupon::{
+    count_coupon_redemptions, get_coupon_by_code, get_coupon_unit_ids, has_booking_history,
+};
+use dxe_types::{CouponDiscountType, UnitId, UserId};
+use sqlx::SqliteConnection;
+
+use crate::config::PriceBreakdown;
+use crate::models::Error;
+
+pub struct AppliedCoupon {
+    pub coupon: Coupon,
+    pub original_price: i64,
+    pub discount_price: i64,
+}
+
+/// Validates the coupon code for a booking of the unit by the user, and adds its discount to the
+/// price breakdown.
+pub async fn apply_coupon(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    code: &str,
+    user_id: &UserId,
+    unit_id: &UnitId,
+    breakdown: &mut PriceBreakdown,
+) -> Result<AppliedCoupon, Error> {
+    let coupon = get_coupon_by_code(&mut *connection, now, code.trim())
+        .await?
+        .ok_or(Error::CouponNotFound)?;
+
+    if coupon.valid_from.is_some_and(|v| v > *now) || coupon.valid_to.is_some_and(|v| v <= *now) {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    let unit_ids = get_coupon_unit_ids(&mut *connection, &coupon.id).await?;
+    if !unit_ids.is_empty() && !unit_ids.contains(unit_id) {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    if let Some(max_redemptions) = coupon.max_redemptions
+        && count_coupon_redemptions(&mut *connection, now, &coupon.id, None).await?
+            >= max_redemptions
+    {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    if let Some(max_redemptions_per_user) = coupon.max_redemptions_per_user
+        && count_coupon_redemptions(&mut *connection, now, &coupon.id, Some(user_id)).await?
+            >= max_redemptions_per_user
+    {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    if coupon.first_booking_only && has_booking_history(&mut *connection, now, user_id).await? {
+        return Err(Error::CouponNotApplicable);
+    }
+
+    let original_price = breakdown.total;
+    let amount = match coupon.discount_type {
+        CouponDiscountType::Percentage => original_price * coupon.discount_value / 100,
+        CouponDiscountType::Fixed => coupon.discount_value,
+    };
+    let discount_price = breakdown.discount(&coupon.name, amount);
+
+    Ok(AppliedCoupon {
+        coupon,
+        original_price,
+        discount_price,
+    })
+}
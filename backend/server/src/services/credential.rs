@@ -0,0 +1,66 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use dxe_data::queries::credential::{
+    create_credential_attempt_within_limit, delete_credential_attempts,
+    delete_expired_credential_attempts,
+};
+use dxe_types::CredentialAttemptKind;
+use sqlx::SqlitePool;
+
+use crate::config::CredentialConfig;
+use crate::models::Error;
+
+/// Records an attempt of the credential operation, failing with [`Error::TooManyAttempts`] once
+/// the key has used up its attempts within the window.
+///
+/// Attempts are written outside of the transaction of the caller so that they stay recorded
+/// when the operation itself fails.
+pub async fn register_attempt(
+    database: &SqlitePool,
+    config: &CredentialConfig,
+    now: &DateTime<Utc>,
+    kind: CredentialAttemptKind,
+    key: &str,
+) -> Result<(), Error> {
+    let since = *now - TimeDelta::minutes(config.attempt_window_minutes);
+    let max_attempts = match kind {
+        CredentialAttemptKind::LoginFromAddress => config.max_login_attempts_per_address,
+        _ => config.max_attempts,
+    };
+
+    let mut connection = database.acquire().await?;
+
+    delete_expired_credential_attempts(&mut connection, &since).await?;
+
+    if !create_credential_attempt_within_limit(
+        &mut connection,
+        now,
+        kind,
+        key,
+        &since,
+        max_attempts,
+    )
+    .await?
+    {
+        return Err(Error::TooManyAttempts);
+    }
+
+    Ok(())
+}
+
+/// Key of the login attempts of the handle from the client address.
+pub fn login_attempt_key(handle: &str, client_address: &str) -> String {
+    format!("{client_address} {handle}")
+}
+
+/// Forgets the attempts of the key after the operation has succeeded.
+pub async fn clear_attempts(
+    database: &SqlitePool,
+    kind: CredentialAttemptKind,
+    key: &str,
+) -> Result<(), Error> {
+    let mut connection = database.acquire().await?;
+
+    delete_credential_attempts(&mut connection, kind, key).await?;
+
+    Ok(())
+}
@@ -1,57 +1,114 @@
 pub mod biztalk;
+pub mod email;
+pub mod templates;
 
-use chrono::{DateTime, Utc};
-use dxe_data::entities::{AudioRecording, Booking, Identity};
+use chrono::{DateTime, TimeDelta, Utc};
+use dxe_data::entities::{AudioRecording, Booking, Identity, OutboxMessage, User};
+use dxe_data::queries::booking::{get_booking, get_bookings_by_user_id};
 use dxe_data::queries::identity::get_group_members;
-use dxe_types::IdentityProvider;
-use sqlx::SqliteConnection;
-use tokio::{sync::mpsc, task::JoinHandle};
+use dxe_data::queries::messaging::{
+    create_message_delivery_log, create_outbox_message, get_due_outbox_messages,
+    get_outbox_message, has_booking_outbox_message, mark_outbox_message_attempt_failed,
+    mark_outbox_message_bounced, mark_outbox_message_delivered, mark_outbox_message_skipped,
+    skip_scheduled_outbox_messages,
+};
+use dxe_data::queries::prefs::get_notification_config;
+use dxe_data::queries::user::{
+    get_user_by_foreign_id, get_user_email, get_user_identity_links, get_user_phone_number,
+};
+use dxe_types::entities::{NotificationEventType, NotificationPreferences};
+use dxe_types::{IdentityProvider, MessagingChannel, OutboxMessageId, UnitId};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
 
-use crate::config::{MessagingConfig, TimeZoneConfig, UrlConfig};
+use crate::config::{MessageOutboxConfig, MessagingConfig, TimeZoneConfig, UrlConfig};
 use crate::models::Error;
-use crate::services::messaging::biztalk::{BiztalkClient, BiztalkSender};
+use crate::services::messaging::biztalk::BiztalkClient;
+use crate::services::messaging::email::EmailClient;
+use crate::services::messaging::templates::MessageTemplateRegistry;
+use crate::services::webhook::{BookingPayload, WebhookEvent, enqueue_webhook_event};
+use crate::utils::datetime::is_in_effect;
 
-#[derive(Debug)]
-pub enum MessagingEvent<R> {
+/// Message to be delivered to a single recipient, stored in the outbox as JSON.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MessagingEvent {
     BookingConfirmation {
-        recipients: Vec<R>,
         booking: Booking,
     },
     BookingReminder {
-        recipients: Vec<R>,
         booking: Booking,
     },
     AmendNotification {
-        recipients: Vec<R>,
         booking: Booking,
         new_time_from: DateTime<Utc>,
         new_time_to: DateTime<Utc>,
     },
     CancelNotification {
-        recipients: Vec<R>,
         booking: Booking,
         refund_rate: i32,
     },
     RefundNotification {
-        recipient: R,
         booking: Booking,
         refunded_price: i64,
     },
     AudioRecording {
-        recipients: Vec<R>,
         booking: Booking,
         audio_recording: AudioRecording,
     },
+    WaitlistOffer {
+        customer_name: String,
+        unit_id: UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    },
+    PaymentDeadlineWarning {
+        booking: Booking,
+        deadline: DateTime<Utc>,
+    },
+    PaymentExpiredNotification {
+        booking: Booking,
+    },
+    /// Sent by email only, to the address which has not been verified yet.
+    EmailVerification {
+        url: url::Url,
+        expires_at: DateTime<Utc>,
+    },
 }
 
-#[derive(Clone, Debug)]
-pub struct MessagingSender<R> {
-    tx: mpsc::UnboundedSender<MessagingEvent<R>>,
-}
+impl MessagingEvent {
+    pub const BOOKING_REMINDER: &str = "bookingReminder";
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation { .. } => "bookingConfirmation",
+            Self::BookingReminder { .. } => Self::BOOKING_REMINDER,
+            Self::AmendNotification { .. } => "amendNotification",
+            Self::CancelNotification { .. } => "cancelNotification",
+            Self::RefundNotification { .. } => "refundNotification",
+            Self::AudioRecording { .. } => "audioRecording",
+            Self::WaitlistOffer { .. } => "waitlistOffer",
+            Self::PaymentDeadlineWarning { .. } => "paymentDeadlineWarning",
+            Self::PaymentExpiredNotification { .. } => "paymentExpiredNotification",
+            Self::EmailVerification { .. } => "emailVerification",
+        }
+    }
 
-impl<R> MessagingSender<R> {
-    pub fn send(&self, event: MessagingEvent<R>) {
-        let _ = self.tx.send(event);
+    /// Type of the event members can opt out of, if it's not always sent.
+    pub fn notification_event_type(&self) -> Option<NotificationEventType> {
+        match self {
+            Self::BookingConfirmation { .. } => Some(NotificationEventType::Confirmation),
+            Self::BookingReminder { .. } => Some(NotificationEventType::Reminder),
+            Self::AmendNotification { .. } => Some(NotificationEventType::Amendment),
+            Self::CancelNotification { .. } => Some(NotificationEventType::Cancellation),
+            Self::AudioRecording { .. } => Some(NotificationEventType::RecordingReady),
+            Self::RefundNotification { .. }
+            | Self::WaitlistOffer { .. }
+            | Self::PaymentDeadlineWarning { .. }
+            | Self::PaymentExpiredNotification { .. }
+            | Self::EmailVerification { .. } => None,
+        }
     }
 }
 
@@ -95,210 +152,841 @@ pub trait MessagingBackend {
         booking: Booking,
         audio_recording: AudioRecording,
     ) -> Result<(), Self::Error>;
+    async fn send_waitlist_offer(
+        &self,
+        recipient: Self::Recipient,
+        customer_name: String,
+        unit_id: UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error>;
+    async fn send_payment_deadline_warning(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), Self::Error>;
+    async fn send_payment_expired_notification(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error>;
+    async fn send_email_verification(
+        &self,
+        recipient: Self::Recipient,
+        url: url::Url,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error>;
 }
 
-pub fn spawn_messaging_backend<B, R>(
-    backend: B,
-) -> (tokio::task::JoinHandle<()>, MessagingSender<R>)
+async fn dispatch<B>(
+    backend: &B,
+    recipient: B::Recipient,
+    event: MessagingEvent,
+) -> Result<(), B::Error>
 where
-    B: MessagingBackend<Recipient = R> + Send + 'static,
-    R: Send + 'static,
+    B: MessagingBackend + Sync,
+    B::Recipient: Send,
 {
-    let (tx, mut rx) = mpsc::unbounded_channel();
-
-    let task = tokio::task::spawn(async move {
-        while let Some(command) = rx.recv().await {
-            match command {
-                MessagingEvent::BookingConfirmation {
-                    recipients,
-                    booking,
-                } => {
-                    if let Err(e) = backend.send_booking_confirmation(recipients, booking).await {
-                        log::warn!("Could not send booking confirmation: {e}");
-                    }
-                }
-                MessagingEvent::BookingReminder {
-                    recipients,
-                    booking,
-                } => {
-                    if let Err(e) = backend.send_booking_reminder(recipients, booking).await {
-                        log::warn!("Could not send booking confirmation: {e}");
-                    }
+    match event {
+        MessagingEvent::BookingConfirmation { booking } => {
+            backend
+                .send_booking_confirmation(vec![recipient], booking)
+                .await
+        }
+        MessagingEvent::BookingReminder { booking } => {
+            backend
+                .send_booking_reminder(vec![recipient], booking)
+                .await
+        }
+        MessagingEvent::AmendNotification {
+            booking,
+            new_time_from,
+            new_time_to,
+        } => {
+            backend
+                .send_amend_notification(vec![recipient], booking, new_time_from, new_time_to)
+                .await
+        }
+        MessagingEvent::CancelNotification {
+            booking,
+            refund_rate,
+        } => {
+            backend
+                .send_cancel_notification(vec![recipient], booking, refund_rate)
+                .await
+        }
+        MessagingEvent::RefundNotification {
+            booking,
+            refunded_price,
+        } => {
+            backend
+                .send_refund_confirmation(recipient, booking, refunded_price)
+                .await
+        }
+        MessagingEvent::AudioRecording {
+            booking,
+            audio_recording,
+        } => {
+            backend
+                .send_audio_recording(vec![recipient], booking, audio_recording)
+                .await
+        }
+        MessagingEvent::WaitlistOffer {
+            customer_name,
+            unit_id,
+            time_from,
+            time_to,
+            expires_at,
+        } => {
+            backend
+                .send_waitlist_offer(
+                    recipient,
+                    customer_name,
+                    unit_id,
+                    time_from,
+                    time_to,
+                    expires_at,
+                )
+                .await
+        }
+        MessagingEvent::PaymentDeadlineWarning { booking, deadline } => {
+            backend
+                .send_payment_deadline_warning(vec![recipient], booking, deadline)
+                .await
+        }
+        MessagingEvent::PaymentExpiredNotification { booking } => {
+            backend
+                .send_payment_expired_notification(vec![recipient], booking)
+                .await
+        }
+        MessagingEvent::EmailVerification { url, expires_at } => {
+            backend
+                .send_email_verification(recipient, url, expires_at)
+                .await
+        }
+    }
+}
+
+struct DeliveryError {
+    message: String,
+    /// The recipient can't be reached through the channel, so retrying won't help.
+    is_rejected: bool,
+}
+
+impl DeliveryError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            is_rejected: false,
+        }
+    }
+}
+
+impl From<biztalk::Error> for DeliveryError {
+    fn from(value: biztalk::Error) -> Self {
+        Self {
+            message: value.to_string(),
+            is_rejected: value.is_rejected(),
+        }
+    }
+}
+
+impl From<email::Error> for DeliveryError {
+    fn from(value: email::Error) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+/// Index of the AlimTalk message sent for the attempt, which Biztalk reports its result with.
+fn alimtalk_message_index(message: &OutboxMessage) -> String {
+    format!("{}-{}", message.id, message.attempts + 1)
+}
+
+fn parse_alimtalk_message_index(message_index: &str) -> Option<OutboxMessageId> {
+    let (id, attempt) = message_index.split_once('-')?;
+    attempt.parse::<i64>().ok()?;
+
+    id.parse::<i64>().ok().map(OutboxMessageId::from)
+}
+
+/// Delivers the messages in the outbox through the configured backends.
+pub struct MessageOutboxWorker {
+    config: MessageOutboxConfig,
+    result_poll_interval_seconds: Option<u64>,
+    biztalk_client: Option<BiztalkClient>,
+    sms_client: Option<BiztalkClient>,
+    email_client: Option<EmailClient>,
+}
+
+impl MessageOutboxWorker {
+    pub fn new(
+        config: &MessagingConfig,
+        templates: MessageTemplateRegistry,
+        timezone_config: TimeZoneConfig,
+        url_config: UrlConfig,
+    ) -> Result<Self, email::Error> {
+        let biztalk_client = config.biztalk.as_ref().map(|v| {
+            BiztalkClient::new(
+                v,
+                &config.templates,
+                templates.clone(),
+                timezone_config.clone(),
+                url_config.clone(),
+            )
+        });
+
+        Ok(Self {
+            config: config.outbox.clone(),
+            result_poll_interval_seconds: config
+                .biztalk
+                .as_ref()
+                .map(|v| v.result_poll_interval_seconds),
+            sms_client: biztalk_client.as_ref().and_then(BiztalkClient::sms),
+            biztalk_client,
+            email_client: config
+                .smtp
+                .as_ref()
+                .map(|v| EmailClient::new(v, templates, timezone_config, url_config))
+                .transpose()?,
+        })
+    }
+
+    async fn deliver(&self, message: &OutboxMessage) -> Result<(), DeliveryError> {
+        let event = serde_json::from_str::<MessagingEvent>(&message.payload)
+            .map_err(|e| DeliveryError::new(format!("Malformed payload: {e}")))?;
+
+        match message.channel {
+            MessagingChannel::Biztalk => {
+                let Some(biztalk_client) = &self.biztalk_client else {
+                    return Err(DeliveryError::new("Biztalk is not configured"));
+                };
+                let biztalk_client =
+                    biztalk_client.with_message_index(alimtalk_message_index(message));
+                Ok(dispatch(&biztalk_client, message.recipient.clone(), event).await?)
+            }
+            MessagingChannel::Sms => {
+                let Some(sms_client) = &self.sms_client else {
+                    return Err(DeliveryError::new("SMS is not configured"));
+                };
+                Ok(dispatch(sms_client, message.recipient.clone(), event).await?)
+            }
+            MessagingChannel::Email => {
+                let Some(email_client) = &self.email_client else {
+                    return Err(DeliveryError::new("SMTP is not configured"));
+                };
+                Ok(dispatch(email_client, message.recipient.clone(), event).await?)
+            }
+        }
+    }
+
+    /// Queues the message undelivered through AlimTalk as SMS to the phone number of the recipient.
+    async fn enqueue_fallback(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        message: &OutboxMessage,
+    ) -> Result<bool, Error> {
+        if message.channel != MessagingChannel::Biztalk || self.sms_client.is_none() {
+            return Ok(false);
+        }
+
+        let Some(user) = get_user_by_foreign_id(
+            &mut *connection,
+            IdentityProvider::Kakao,
+            &message.recipient,
+            *now,
+        )
+        .await?
+        else {
+            return Ok(false);
+        };
+        let Some(phone_number) = get_user_phone_number(&mut *connection, &user.id).await? else {
+            return Ok(false);
+        };
+
+        create_outbox_message(
+            &mut *connection,
+            now,
+            MessagingChannel::Sms,
+            &phone_number.phone_number,
+            &message.event_type,
+            &message.payload,
+            Some(&message.id),
+            None,
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Reminders may be scheduled well in advance, so they're dropped if the booking has been
+    /// canceled or moved since.
+    async fn is_outdated(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        message: &OutboxMessage,
+    ) -> Result<bool, Error> {
+        let Ok(MessagingEvent::BookingReminder { booking }) =
+            serde_json::from_str::<MessagingEvent>(&message.payload)
+        else {
+            return Ok(false);
+        };
+
+        Ok(get_booking(&mut *connection, &booking.id)
+            .await?
+            .is_none_or(|v| {
+                !is_in_effect(&v.confirmed_at, now)
+                    || is_in_effect(&v.canceled_at, now)
+                    || v.time_from != booking.time_from
+            }))
+    }
+
+    pub async fn run(&self, database: &SqlitePool) -> Result<(), Error> {
+        let messages = {
+            let mut connection = database.acquire().await?;
+            get_due_outbox_messages(&mut connection, &Utc::now(), self.config.batch_size).await?
+        };
+
+        for message in messages {
+            {
+                let now = Utc::now();
+                let mut tx = database.begin().await?;
+
+                if self.is_outdated(&mut tx, &now, &message).await? {
+                    let reason = "Booking has been changed since the message was scheduled";
+                    mark_outbox_message_skipped(&mut tx, &now, &message.id, reason).await?;
+                    tx.commit().await?;
+
+                    continue;
                 }
-                MessagingEvent::AmendNotification {
-                    recipients,
-                    booking,
-                    new_time_from,
-                    new_time_to,
-                } => {
-                    if let Err(e) = backend
-                        .send_amend_notification(recipients, booking, new_time_from, new_time_to)
-                        .await
-                    {
-                        log::warn!("Could not send booking confirmation: {e}");
-                    }
+            }
+
+            let result = self.deliver(&message).await;
+
+            let now = Utc::now();
+            let mut tx = database.begin().await?;
+
+            match result {
+                Ok(()) => {
+                    mark_outbox_message_delivered(&mut tx, &now, &message.id).await?;
+                    create_message_delivery_log(&mut tx, &now, &message.id, None).await?;
                 }
-                MessagingEvent::CancelNotification {
-                    recipients,
-                    booking,
-                    refund_rate,
-                } => {
-                    if let Err(e) = backend
-                        .send_cancel_notification(recipients, booking, refund_rate)
-                        .await
-                    {
-                        log::warn!("Could not send cancel notification: {e}");
-                    }
+                Err(e) => {
+                    let attempts = message.attempts + 1;
+                    let next_attempt_at =
+                        if e.is_rejected && self.enqueue_fallback(&mut tx, &now, &message).await? {
+                            log::info!(
+                                "Sending {} message {} as SMS instead: {}",
+                                message.event_type,
+                                message.id,
+                                e.message
+                            );
+                            None
+                        } else if attempts < self.config.max_attempts {
+                            Some(now + self.config.backoff(attempts))
+                        } else {
+                            log::error!(
+                                "Giving up on {} message {} after {attempts} attempts: {}",
+                                message.event_type,
+                                message.id,
+                                e.message
+                            );
+                            None
+                        };
+
+                    mark_outbox_message_attempt_failed(
+                        &mut tx,
+                        &now,
+                        &message.id,
+                        &e.message,
+                        next_attempt_at.as_ref(),
+                    )
+                    .await?;
+                    create_message_delivery_log(&mut tx, &now, &message.id, Some(&e.message))
+                        .await?;
                 }
-                MessagingEvent::RefundNotification {
-                    recipient,
-                    booking,
-                    refunded_price,
-                } => {
-                    if let Err(e) = backend
-                        .send_refund_confirmation(recipient, booking, refunded_price)
-                        .await
-                    {
-                        log::warn!("Could not send refund confirmation: {e}");
+            }
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends the AlimTalk messages which have been accepted but couldn't reach the recipients as
+    /// SMS instead.
+    pub async fn poll_alimtalk_results(&self, database: &SqlitePool) -> Result<(), Error> {
+        let Some(biztalk_client) = &self.biztalk_client else {
+            return Ok(());
+        };
+
+        let results = match biztalk_client.get_alimtalk_results().await {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Could not fetch the results of AlimTalk messages: {e}");
+                return Ok(());
+            }
+        };
+
+        for result in results {
+            if !result.is_recipient_failure() {
+                continue;
+            }
+            let Some(id) = parse_alimtalk_message_index(&result.msg_idx) else {
+                continue;
+            };
+
+            let now = Utc::now();
+            let mut tx = database.begin().await?;
+
+            let Some(message) = get_outbox_message(&mut tx, &id).await? else {
+                continue;
+            };
+            if message.channel != MessagingChannel::Biztalk {
+                continue;
+            }
+
+            let error = format!("AlimTalk was not delivered ({})", result.result_code);
+            if !mark_outbox_message_bounced(&mut tx, &now, &message.id, &error).await? {
+                continue;
+            }
+            create_message_delivery_log(&mut tx, &now, &message.id, Some(&error)).await?;
+
+            if self.enqueue_fallback(&mut tx, &now, &message).await? {
+                log::info!(
+                    "Sending {} message {} as SMS instead: {error}",
+                    message.event_type,
+                    message.id,
+                );
+            } else {
+                log::error!(
+                    "Could not deliver {} message {}: {error}",
+                    message.event_type,
+                    message.id,
+                );
+            }
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn spawn_message_outbox_task(
+    database: SqlitePool,
+    worker: MessageOutboxWorker,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            worker.config.poll_interval_seconds.max(1),
+        ));
+        // Without Biztalk, there are no results to poll and the interval is never ticked
+        let mut result_interval = tokio::time::interval(std::time::Duration::from_secs(
+            worker
+                .result_poll_interval_seconds
+                .unwrap_or_default()
+                .max(1),
+        ));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = worker.run(&database).await {
+                        log::error!("Could not deliver messages in the outbox: {e}");
                     }
                 }
-                MessagingEvent::AudioRecording {
-                    recipients,
-                    booking,
-                    audio_recording,
-                } => {
-                    if let Err(e) = backend
-                        .send_audio_recording(recipients, booking, audio_recording)
-                        .await
-                    {
-                        log::warn!("Could not send audio recording: {e}")
+                _ = result_interval.tick(), if worker.result_poll_interval_seconds.is_some() => {
+                    if let Err(e) = worker.poll_alimtalk_results(&database).await {
+                        log::error!("Could not process the results of AlimTalk messages: {e}");
                     }
                 }
             }
         }
-    });
-
-    (task, MessagingSender { tx })
+    })
 }
 
+/// Queues messages in the outbox, within the transaction of the caller. Events of the booking are
+/// also queued for the webhook subscriptions.
+#[derive(Clone)]
 pub struct MessagingService {
-    biztalk_sender: Option<BiztalkSender>,
+    channels: Vec<MessagingChannel>,
+    email_client: Option<EmailClient>,
 }
 
 impl MessagingService {
     pub fn new(
         config: &MessagingConfig,
+        templates: MessageTemplateRegistry,
         timezone_config: TimeZoneConfig,
         url_config: UrlConfig,
-    ) -> (Self, Vec<JoinHandle<()>>) {
-        let mut tasks = vec![];
+    ) -> Result<Self, email::Error> {
+        let mut channels = vec![];
 
-        let biztalk_sender = if let Some(biztalk_config) = &config.biztalk {
-            let biztalk_client = BiztalkClient::new(biztalk_config, timezone_config, url_config);
-            let (biztalk_task, biztalk_sender) = spawn_messaging_backend(biztalk_client);
+        if config.biztalk.is_some() {
+            channels.push(MessagingChannel::Biztalk);
+        }
+        if config.smtp.is_some() {
+            channels.push(MessagingChannel::Email);
+        }
 
-            tasks.push(biztalk_task);
+        Ok(Self {
+            channels,
+            email_client: config
+                .smtp
+                .as_ref()
+                .map(|v| EmailClient::new(v, templates, timezone_config, url_config))
+                .transpose()?,
+        })
+    }
 
-            Some(biztalk_sender)
-        } else {
-            None
-        };
+    pub fn email_client(&self) -> Option<&EmailClient> {
+        self.email_client.as_ref()
+    }
 
-        (Self { biztalk_sender }, tasks)
+    async fn recipient(
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        channel: MessagingChannel,
+        user: &User,
+    ) -> Result<Option<String>, Error> {
+        Ok(match channel {
+            // AlimTalk reaches whichever Kakao account is linked, not only the one registered with
+            MessagingChannel::Biztalk => get_user_identity_links(&mut *database, &user.id)
+                .await?
+                .into_iter()
+                .find(|v| v.provider == IdentityProvider::Kakao)
+                .map(|v| v.foreign_id),
+            MessagingChannel::Email => get_user_email(&mut *database, &user.id)
+                .await?
+                .filter(|v| is_in_effect(&v.verified_at, now))
+                .map(|v| v.email),
+            // Only used in place of the rejected AlimTalk messages
+            MessagingChannel::Sms => None,
+        })
     }
 
-    pub async fn send_confirmation(
+    async fn preferences(
+        database: &mut SqliteConnection,
+        user: &User,
+    ) -> Result<NotificationPreferences, Error> {
+        Ok(get_notification_config(&mut *database, &user.id)
+            .await?
+            .map(|v| v.data.0)
+            .unwrap_or_default())
+    }
+
+    async fn enqueue_for_user(
         &self,
         database: &mut SqliteConnection,
-        booking: Booking,
+        now: &DateTime<Utc>,
+        user: &User,
+        preferences: &NotificationPreferences,
+        event: &MessagingEvent,
+        payload: &str,
+        scheduled_at: Option<&DateTime<Utc>>,
     ) -> Result<(), Error> {
-        let recipients = match &booking.customer {
-            Identity::Group(g) => get_group_members(&mut *database, &g.id).await?,
-            Identity::User(u) => vec![u.clone()],
-        };
+        // Each recipient is reached through every channel they have and haven't opted out of
+        for channel in &self.channels {
+            if let Some(event_type) = event.notification_event_type()
+                && !preferences.is_enabled(event_type, *channel)
+            {
+                continue;
+            }
 
-        if let Some(biztalk_sender) = &self.biztalk_sender {
-            let biztalk_recipients: Vec<_> = recipients
-                .iter()
-                .filter_map(|v| {
-                    if v.provider == IdentityProvider::Kakao {
-                        Some(v.foreign_id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+            let Some(recipient) = Self::recipient(&mut *database, now, *channel, user).await?
+            else {
+                continue;
+            };
 
-            biztalk_sender.send(MessagingEvent::BookingConfirmation {
-                recipients: biztalk_recipients,
-                booking,
-            });
+            create_outbox_message(
+                &mut *database,
+                now,
+                *channel,
+                &recipient,
+                event.event_type(),
+                payload,
+                None,
+                scheduled_at,
+            )
+            .await?;
         }
 
         Ok(())
     }
 
-    pub async fn send_reminder(
+    async fn enqueue(
         &self,
         database: &mut SqliteConnection,
-        booking: Booking,
+        now: &DateTime<Utc>,
+        recipients: &[User],
+        event: MessagingEvent,
     ) -> Result<(), Error> {
-        let recipients = match &booking.customer {
+        let payload = serde_json::to_string(&event).map_err(|e| Error::Internal(Box::new(e)))?;
+
+        for user in recipients {
+            let preferences = Self::preferences(&mut *database, user).await?;
+
+            // Reminders for those who have chosen when to be reminded are usually scheduled, unless
+            // the lead time had already passed when the booking was confirmed or the lead time set
+            if let MessagingEvent::BookingReminder { booking } = &event
+                && preferences.reminder_lead_minutes.is_some()
+                && self
+                    .has_scheduled_reminder(&mut *database, now, user, booking)
+                    .await?
+            {
+                continue;
+            }
+
+            self.enqueue_for_user(
+                &mut *database,
+                now,
+                user,
+                &preferences,
+                &event,
+                &payload,
+                None,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn customer_users(
+        database: &mut SqliteConnection,
+        booking: &Booking,
+    ) -> Result<Vec<User>, Error> {
+        Ok(match &booking.customer {
             Identity::Group(g) => get_group_members(&mut *database, &g.id).await?,
             Identity::User(u) => vec![u.clone()],
+        })
+    }
+
+    async fn enqueue_for_customer(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: &Booking,
+        event: MessagingEvent,
+    ) -> Result<(), Error> {
+        let recipients = Self::customer_users(&mut *database, booking).await?;
+
+        self.enqueue(database, now, &recipients, event).await
+    }
+
+    async fn has_scheduled_reminder(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        user: &User,
+        booking: &Booking,
+    ) -> Result<bool, Error> {
+        for channel in &self.channels {
+            let Some(recipient) = Self::recipient(&mut *database, now, *channel, user).await?
+            else {
+                continue;
+            };
+
+            if has_booking_outbox_message(
+                &mut *database,
+                *channel,
+                &recipient,
+                MessagingEvent::BOOKING_REMINDER,
+                &booking.id,
+            )
+            .await?
+            {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Schedules the reminder for the customer who has set their own lead time, unless the time to
+    /// send it has already passed.
+    async fn schedule_reminder(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        user: &User,
+        preferences: &NotificationPreferences,
+        booking: &Booking,
+    ) -> Result<(), Error> {
+        let Some(lead_minutes) = preferences.reminder_lead_minutes else {
+            return Ok(());
         };
 
-        if let Some(biztalk_sender) = &self.biztalk_sender {
-            let biztalk_recipients: Vec<_> = recipients
-                .iter()
-                .filter_map(|v| {
-                    if v.provider == IdentityProvider::Kakao {
-                        Some(v.foreign_id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        let scheduled_at = booking.time_from - TimeDelta::minutes(lead_minutes);
+        if scheduled_at <= *now {
+            return Ok(());
+        }
 
-            biztalk_sender.send(MessagingEvent::BookingReminder {
-                recipients: biztalk_recipients,
-                booking,
-            });
+        let event = MessagingEvent::BookingReminder {
+            booking: booking.clone(),
+        };
+        let payload = serde_json::to_string(&event).map_err(|e| Error::Internal(Box::new(e)))?;
+
+        self.enqueue_for_user(
+            &mut *database,
+            now,
+            user,
+            preferences,
+            &event,
+            &payload,
+            Some(&scheduled_at),
+        )
+        .await
+    }
+
+    /// Schedules reminders for the customers who have set their own lead time.
+    async fn schedule_reminders(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+    ) -> Result<(), Error> {
+        let recipients = Self::customer_users(&mut *database, &booking).await?;
+
+        for user in &recipients {
+            let preferences = Self::preferences(&mut *database, user).await?;
+
+            self.schedule_reminder(&mut *database, now, user, &preferences, &booking)
+                .await?;
         }
 
         Ok(())
     }
 
+    /// Replaces the reminders scheduled for the user with the ones following the current
+    /// preferences, for all of the upcoming bookings.
+    pub async fn reschedule_reminders(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        user: &User,
+    ) -> Result<(), Error> {
+        for channel in &self.channels {
+            let Some(recipient) = Self::recipient(&mut *database, now, *channel, user).await?
+            else {
+                continue;
+            };
+
+            skip_scheduled_outbox_messages(
+                &mut *database,
+                now,
+                *channel,
+                &recipient,
+                MessagingEvent::BOOKING_REMINDER,
+                "Notification preferences have been changed",
+            )
+            .await?;
+        }
+
+        let preferences = Self::preferences(&mut *database, user).await?;
+        if preferences.reminder_lead_minutes.is_none() {
+            return Ok(());
+        }
+
+        let bookings = get_bookings_by_user_id(&mut *database, now, &user.id, now, false).await?;
+        for booking in bookings {
+            if !is_in_effect(&booking.confirmed_at, now) || booking.time_from <= *now {
+                continue;
+            }
+
+            self.schedule_reminder(&mut *database, now, user, &preferences, &booking)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn send_confirmation(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+    ) -> Result<(), Error> {
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::BookingConfirmed {
+                booking: (&booking).into(),
+            },
+        )
+        .await?;
+
+        self.enqueue_for_customer(
+            &mut *database,
+            now,
+            &booking.clone(),
+            MessagingEvent::BookingConfirmation {
+                booking: booking.clone(),
+            },
+        )
+        .await?;
+
+        self.schedule_reminders(database, now, booking).await
+    }
+
+    pub async fn send_reminder(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+    ) -> Result<(), Error> {
+        self.enqueue_for_customer(
+            database,
+            now,
+            &booking.clone(),
+            MessagingEvent::BookingReminder { booking },
+        )
+        .await
+    }
+
     pub async fn send_amend_notification(
         &self,
         database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
         booking: Booking,
         new_time_from: DateTime<Utc>,
         new_time_to: DateTime<Utc>,
     ) -> Result<(), Error> {
-        let recipients = match &booking.customer {
-            Identity::Group(g) => get_group_members(&mut *database, &g.id).await?,
-            Identity::User(u) => vec![u.clone()],
-        };
-
-        if let Some(biztalk_sender) = &self.biztalk_sender {
-            let biztalk_recipients: Vec<_> = recipients
-                .iter()
-                .filter_map(|v| {
-                    if v.provider == IdentityProvider::Kakao {
-                        Some(v.foreign_id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::BookingAmended {
+                booking: (&booking).into(),
+                new_time_from,
+                new_time_to,
+            },
+        )
+        .await?;
 
-            biztalk_sender.send(MessagingEvent::AmendNotification {
-                recipients: biztalk_recipients,
-                booking,
+        self.enqueue_for_customer(
+            &mut *database,
+            now,
+            &booking.clone(),
+            MessagingEvent::AmendNotification {
+                booking: booking.clone(),
                 new_time_from,
                 new_time_to,
-            });
+            },
+        )
+        .await?;
+
+        // Reminders scheduled for the previous time are dropped when they're due
+        if new_time_from != booking.time_from {
+            let booking = Booking {
+                time_from: new_time_from,
+                time_to: new_time_to,
+                ..booking
+            };
+            self.schedule_reminders(database, now, booking).await?;
         }
 
         Ok(())
@@ -307,82 +995,206 @@ impl MessagingService {
     pub async fn send_cancellation(
         &self,
         database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
         booking: Booking,
         refund_rate: i32,
     ) -> Result<(), Error> {
-        let recipients = match &booking.customer {
-            Identity::Group(g) => get_group_members(&mut *database, &g.id).await?,
-            Identity::User(u) => vec![u.clone()],
-        };
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::BookingCanceled {
+                booking: BookingPayload::canceled(&booking, now),
+                refund_rate: Some(refund_rate),
+            },
+        )
+        .await?;
 
-        if let Some(biztalk_sender) = &self.biztalk_sender {
-            let biztalk_recipients: Vec<_> = recipients
-                .iter()
-                .filter_map(|v| {
-                    if v.provider == IdentityProvider::Kakao {
-                        Some(v.foreign_id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-            biztalk_sender.send(MessagingEvent::CancelNotification {
-                recipients: biztalk_recipients,
+        self.enqueue_for_customer(
+            database,
+            now,
+            &booking.clone(),
+            MessagingEvent::CancelNotification {
                 booking,
                 refund_rate,
-            });
-        }
+            },
+        )
+        .await
+    }
 
-        Ok(())
+    pub async fn send_payment_deadline_warning(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.enqueue_for_customer(
+            database,
+            now,
+            &booking.clone(),
+            MessagingEvent::PaymentDeadlineWarning { booking, deadline },
+        )
+        .await
     }
 
-    pub fn send_refund_confirmation(&self, booking: Booking, refunded_price: i64) {
-        #[allow(clippy::single_match)]
-        match booking.holder.provider {
-            IdentityProvider::Kakao => {
-                if let Some(biztalk_sender) = &self.biztalk_sender {
-                    biztalk_sender.send(MessagingEvent::RefundNotification {
-                        recipient: booking.holder.foreign_id.clone(),
-                        booking,
-                        refunded_price,
-                    });
-                }
-            }
-            _ => {}
-        }
+    pub async fn send_payment_expired_notification(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+    ) -> Result<(), Error> {
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::BookingCanceled {
+                booking: BookingPayload::canceled(&booking, now),
+                refund_rate: None,
+            },
+        )
+        .await?;
+
+        self.enqueue_for_customer(
+            database,
+            now,
+            &booking.clone(),
+            MessagingEvent::PaymentExpiredNotification { booking },
+        )
+        .await
+    }
+
+    pub async fn send_refund_confirmation(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: Booking,
+        refunded_price: i64,
+    ) -> Result<(), Error> {
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::BookingRefunded {
+                booking: (&booking).into(),
+                refunded_price,
+            },
+        )
+        .await?;
+
+        let recipients = vec![booking.holder.clone()];
+
+        self.enqueue(
+            database,
+            now,
+            &recipients,
+            MessagingEvent::RefundNotification {
+                booking,
+                refunded_price,
+            },
+        )
+        .await
+    }
+
+    pub async fn send_waitlist_offer(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        recipient: &User,
+        customer_name: String,
+        unit_id: UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.enqueue(
+            database,
+            now,
+            std::slice::from_ref(recipient),
+            MessagingEvent::WaitlistOffer {
+                customer_name,
+                unit_id,
+                time_from,
+                time_to,
+                expires_at,
+            },
+        )
+        .await
     }
 
     pub async fn send_audio_recording(
         &self,
         database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
         booking: Booking,
         audio_recording: AudioRecording,
     ) -> Result<(), Error> {
-        let recipients = match &booking.customer {
-            Identity::Group(g) => get_group_members(&mut *database, &g.id).await?,
-            Identity::User(u) => vec![u.clone()],
-        };
-
-        if let Some(biztalk_sender) = &self.biztalk_sender {
-            let biztalk_recipients: Vec<_> = recipients
-                .iter()
-                .filter_map(|v| {
-                    if v.provider == IdentityProvider::Kakao {
-                        Some(v.foreign_id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+        enqueue_webhook_event(
+            &mut *database,
+            now,
+            WebhookEvent::RecordingUploaded {
+                booking: (&booking).into(),
+                audio_recording: (&audio_recording).into(),
+            },
+        )
+        .await?;
 
-            biztalk_sender.send(MessagingEvent::AudioRecording {
-                recipients: biztalk_recipients,
+        self.enqueue_for_customer(
+            database,
+            now,
+            &booking.clone(),
+            MessagingEvent::AudioRecording {
                 booking,
                 audio_recording,
-            });
+            },
+        )
+        .await
+    }
+
+    /// Queues the verification link to the email address, which is not verified yet and so is
+    /// not among the recipients of other messages.
+    pub async fn send_email_verification(
+        &self,
+        database: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        email: &str,
+        url: url::Url,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        if !self.channels.contains(&MessagingChannel::Email) {
+            return Err(Error::EmailNotAvailable);
         }
 
+        let event = MessagingEvent::EmailVerification { url, expires_at };
+        let payload = serde_json::to_string(&event).map_err(|e| Error::Internal(Box::new(e)))?;
+
+        create_outbox_message(
+            &mut *database,
+            now,
+            MessagingChannel::Email,
+            email,
+            event.event_type(),
+            &payload,
+            None,
+            None,
+        )
+        .await?;
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_alimtalk_message_index() {
+        assert_eq!(
+            parse_alimtalk_message_index("42-3"),
+            Some(OutboxMessageId::from(42))
+        );
+        assert_eq!(
+            parse_alimtalk_message_index("1f0e8a52-6c1b-4d7e-9b36-2f5a1c0d9e47"),
+            None
+        );
+        assert_eq!(parse_alimtalk_message_index("42"), None);
+    }
+}
@@ -1,43 +1,33 @@
 use chrono::{DateTime, Utc};
 use dxe_data::entities::{AudioRecording, Booking};
-use dxe_extern::biztalk::models::AlimTalkButtonAttachment;
+use dxe_extern::biztalk::models::{AlimTalkButtonAttachment, AlimTalkResult};
+use dxe_types::UnitId;
+use minijinja::context;
 
 use super::MessagingBackend;
-use crate::config::{BiztalkConfig, TimeZoneConfig, UrlConfig};
-
-const MESSAGE_AUDIO_READY: &str = include_str!("biztalk/AUDIO_READY_02.txt").trim_ascii();
-const MESSAGE_RESERVATION_CANCEL_CONFIRM: &str =
-    include_str!("biztalk/RESERVATION_CANCEL_CONFIRM_01.txt").trim_ascii();
-const MESSAGE_RESERVATION_CANCEL_HRF: &str =
-    include_str!("biztalk/RESERVATION_CANCEL_HRF_01.txt").trim_ascii();
-const MESSAGE_RESERVATION_CANCEL_NRF: &str =
-    include_str!("biztalk/RESERVATION_CANCEL_NRF_01.txt").trim_ascii();
-const MESSAGE_RESERVATION_CANCEL_RF: &str =
-    include_str!("biztalk/RESERVATION_CANCEL_RF_01.txt").trim_ascii();
-const MESSAGE_RESERVATION_CONFIRMATION: &str =
-    include_str!("biztalk/RESERVATION_CONFIRMATION_02.txt").trim_ascii();
-const MESSAGE_RESERVATION_REMINDER: &str =
-    include_str!("biztalk/RESERVATION_REMINDER_02.txt").trim_ascii();
-const MESSAGE_RESERVATION_AMEND: &str =
-    include_str!("biztalk/RESERVATION_AMEND_01.txt").trim_ascii();
-
-const TEMPLATE_AUDIO_READY: &str = "AUDIO_READY _02";
-const TEMPLATE_RESERVATION_CANCEL_CONFIRM: &str = "RESERVATION_CONFIRM_01";
-const TEMPLATE_RESERVATION_CANCEL_NO_REFUND: &str = "RESERVATION_CANCEL_NRF_01";
-const TEMPLATE_RESERVATION_CANCEL_HALF_REFUND: &str = "RESERVATION_CANCEL_HRF_01";
-const TEMPLATE_RESERVATION_CANCEL_FULL_REFUND: &str = "RESERVATION_CANCEL_RF_01";
-const TEMPLATE_RESERVATION_CONFIRMATION: &str = "RESERVATION_CONFIRMATION_02";
-const TEMPLATE_RESERVATION_REMINDER: &str = "RESERVATION_REMINDER_02";
-const TEMPLATE_RESERVATION_AMEND: &str = "RESERVATION_AMEND_01";
+use super::templates::{self, MessageTemplate, MessageTemplateRegistry};
+use crate::config::{BiztalkConfig, MessageTemplateConfig, TimeZoneConfig, UrlConfig};
 
 pub type BiztalkRecipient = String;
-pub type BiztalkSender = super::MessagingSender<BiztalkRecipient>;
 
-const SURVEY_URL: &str = "https://forms.gle/dr5tp8CnFM9SjXdm9";
+const SMS_SUBJECT: &str = "[드림하우스]";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Transport {
+    AlimTalk,
+    /// Sends the same message as SMS/LMS to the phone number, with buttons written out as links.
+    Sms,
+}
 
 #[derive(Debug, Clone)]
 pub(super) struct BiztalkClient {
     client: dxe_extern::biztalk::BiztalkClient,
+    config: BiztalkConfig,
+    transport: Transport,
+    /// Identifies the AlimTalk message in the results reported by Biztalk.
+    message_index: Option<String>,
+    templates: MessageTemplateRegistry,
+    survey_url: url::Url,
     timezone_config: TimeZoneConfig,
     url_config: UrlConfig,
 }
@@ -45,60 +35,99 @@ pub(super) struct BiztalkClient {
 impl BiztalkClient {
     pub fn new(
         config: &BiztalkConfig,
+        template_config: &MessageTemplateConfig,
+        templates: MessageTemplateRegistry,
         timezone_config: TimeZoneConfig,
         url_config: UrlConfig,
     ) -> Self {
         Self {
             client: dxe_extern::biztalk::BiztalkClient::new(config),
+            config: config.clone(),
+            transport: Transport::AlimTalk,
+            message_index: None,
+            templates,
+            survey_url: template_config.survey_url.clone(),
             timezone_config,
             url_config,
         }
     }
-}
 
-#[async_trait::async_trait]
-impl MessagingBackend for BiztalkClient {
-    type Recipient = BiztalkRecipient;
-    type Error = Error;
+    /// Client sending the messages to phone numbers, if sending SMS is configured.
+    pub fn sms(&self) -> Option<Self> {
+        if self.client.is_sms_enabled() {
+            Some(Self {
+                transport: Transport::Sms,
+                ..self.clone()
+            })
+        } else {
+            None
+        }
+    }
 
-    async fn send_booking_confirmation(
-        &self,
-        recipients: Vec<Self::Recipient>,
-        booking: Booking,
-    ) -> Result<(), Self::Error> {
-        let start = self.timezone_config.convert(booking.time_from);
-        let end = self.timezone_config.convert(booking.time_to);
+    /// Client sending the AlimTalk message with the given index, to match it with its result.
+    pub fn with_message_index(&self, message_index: String) -> Self {
+        Self {
+            message_index: Some(message_index),
+            ..self.clone()
+        }
+    }
 
-        let time_str = format!(
-            "{} - {} ({} 시간)",
-            start.format("%Y-%m-%d %H:%M"),
-            end.format("%Y-%m-%d %H:%M"),
-            (end - start).num_hours()
-        );
+    pub async fn get_alimtalk_results(&self) -> Result<Vec<AlimTalkResult>, Error> {
+        Ok(self.client.get_alimtalk_results().await?)
+    }
 
-        let message = MESSAGE_RESERVATION_CONFIRMATION
-            .replace("#{customer}", booking.customer.name())
-            .replace("#{reservation_dt}", &time_str);
+    fn format_time_range(&self, time_from: DateTime<Utc>, time_to: DateTime<Utc>) -> String {
+        templates::format_time_range(&self.timezone_config, time_from, time_to)
+    }
 
+    fn booking_url(&self, booking: &Booking) -> url::Url {
         let mut url = self.url_config.base_url.clone();
         url.set_path(&format!("reservation/{}", booking.id));
+        url
+    }
+
+    async fn send(
+        &self,
+        recipient: &str,
+        template: MessageTemplate,
+        message: String,
+        buttons: Option<Vec<AlimTalkButtonAttachment>>,
+    ) -> Result<(), dxe_extern::biztalk::Error> {
+        match self.transport {
+            Transport::AlimTalk => {
+                self.client
+                    .send_alimtalk(
+                        self.message_index.as_deref(),
+                        recipient,
+                        self.config.template_code(template),
+                        message,
+                        buttons,
+                    )
+                    .await
+            }
+            Transport::Sms => {
+                let mut message = message;
+                for button in buttons.unwrap_or_default() {
+                    message.push_str(&format!("\n\n{}: {}", button.name, button.url_mobile));
+                }
+
+                self.client.send_sms(recipient, SMS_SUBJECT, message).await
+            }
+        }
+    }
 
+    async fn send_all(
+        &self,
+        recipients: Vec<BiztalkRecipient>,
+        template: MessageTemplate,
+        message: String,
+        buttons: Option<Vec<AlimTalkButtonAttachment>>,
+    ) -> Result<(), Error> {
         let mut error = None;
 
         for recipient in recipients {
             if let Err(e) = self
-                .client
-                .send_alimtalk(
-                    &recipient,
-                    TEMPLATE_RESERVATION_CONFIRMATION,
-                    message.clone(),
-                    Some(vec![AlimTalkButtonAttachment {
-                        name: "이용 안내".to_owned(),
-                        r#type: Default::default(),
-                        url_mobile: url.to_string(),
-                        url_pc: Some(url.to_string()),
-                    }]),
-                )
+                .send(&recipient, template, message.clone(), buttons.clone())
                 .await
             {
                 error = Some(e);
@@ -111,75 +140,73 @@ impl MessagingBackend for BiztalkClient {
             Ok(())
         }
     }
+}
 
-    async fn send_booking_reminder(
+fn link_button(name: &str, url: &url::Url) -> AlimTalkButtonAttachment {
+    AlimTalkButtonAttachment {
+        name: name.to_owned(),
+        r#type: Default::default(),
+        url_mobile: url.to_string(),
+        url_pc: Some(url.to_string()),
+    }
+}
+
+#[async_trait::async_trait]
+impl MessagingBackend for BiztalkClient {
+    type Recipient = BiztalkRecipient;
+    type Error = Error;
+
+    async fn send_booking_confirmation(
         &self,
         recipients: Vec<Self::Recipient>,
         booking: Booking,
     ) -> Result<(), Self::Error> {
-        let start = self.timezone_config.convert(booking.time_from);
-        let end = self.timezone_config.convert(booking.time_to);
-
-        let relative_time_str = {
-            let mut dt = String::new();
-
-            let now = Utc::now();
-            let remaining = booking.time_from - now;
-            let hours = remaining.num_hours();
-            let minutes = remaining.num_minutes() - (hours * 60);
-
-            if hours > 0 {
-                dt.push_str(&format!("{hours}시간 "));
-            }
-            if minutes > 0 {
-                dt.push_str(&format!("{minutes}분 "));
-            }
-
-            dt
-        };
-
-        let time_str = format!(
-            "{} - {} ({} 시간)",
-            start.format("%Y-%m-%d %H:%M"),
-            end.format("%Y-%m-%d %H:%M"),
-            (end - start).num_hours()
-        );
-
-        let message = MESSAGE_RESERVATION_REMINDER
-            .replace("#{customer}", booking.customer.name())
-            .replace("#{reservation_relative_time}", relative_time_str.trim())
-            .replace("#{reservation_dt}", &time_str);
-
-        let mut url = self.url_config.base_url.clone();
-        url.set_path(&format!("reservation/{}", booking.id));
-
-        let mut error = None;
-
-        for recipient in recipients {
-            if let Err(e) = self
-                .client
-                .send_alimtalk(
-                    &recipient,
-                    TEMPLATE_RESERVATION_REMINDER,
-                    message.clone(),
-                    Some(vec![AlimTalkButtonAttachment {
-                        name: "예약 확인".to_owned(),
-                        r#type: Default::default(),
-                        url_mobile: url.to_string(),
-                        url_pc: Some(url.to_string()),
-                    }]),
-                )
-                .await
-            {
-                error = Some(e);
-            }
-        }
+        let url = self.booking_url(&booking);
+
+        let message = self.templates.render(
+            MessageTemplate::BookingConfirmation,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                booking_url => url.as_str(),
+            },
+        )?;
+
+        self.send_all(
+            recipients,
+            MessageTemplate::BookingConfirmation,
+            message,
+            Some(vec![link_button("이용 안내", &url)]),
+        )
+        .await
+    }
 
-        if let Some(error) = error {
-            Err(error.into())
-        } else {
-            Ok(())
-        }
+    async fn send_booking_reminder(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error> {
+        let relative_time_str = templates::format_remaining_time(booking.time_from - Utc::now());
+
+        let url = self.booking_url(&booking);
+
+        let message = self.templates.render(
+            MessageTemplate::BookingReminder,
+            context! {
+                customer => booking.customer.name(),
+                reservation_relative_time => relative_time_str,
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                booking_url => url.as_str(),
+            },
+        )?;
+
+        self.send_all(
+            recipients,
+            MessageTemplate::BookingReminder,
+            message,
+            Some(vec![link_button("예약 확인", &url)]),
+        )
+        .await
     }
 
     async fn send_amend_notification(
@@ -189,61 +216,25 @@ impl MessagingBackend for BiztalkClient {
         new_time_from: DateTime<Utc>,
         new_time_to: DateTime<Utc>,
     ) -> Result<(), Self::Error> {
-        let prev_start = self.timezone_config.convert(booking.time_from);
-        let prev_end = self.timezone_config.convert(booking.time_to);
-
-        let prev_time_str = format!(
-            "{} - {} ({} 시간)",
-            prev_start.format("%Y-%m-%d %H:%M"),
-            prev_end.format("%Y-%m-%d %H:%M"),
-            (prev_end - prev_start).num_hours()
-        );
-
-        let new_start = self.timezone_config.convert(new_time_from);
-        let new_end = self.timezone_config.convert(new_time_to);
-
-        let new_time_str = format!(
-            "{} - {} ({} 시간)",
-            new_start.format("%Y-%m-%d %H:%M"),
-            new_end.format("%Y-%m-%d %H:%M"),
-            (new_end - new_start).num_hours()
-        );
-
-        let message = MESSAGE_RESERVATION_AMEND
-            .replace("#{customer}", booking.customer.name())
-            .replace("#{old_reservation_dt}", &prev_time_str)
-            .replace("#{new_reservation_dt}", &new_time_str);
-
-        let mut url = self.url_config.base_url.clone();
-        url.set_path(&format!("reservation/{}", booking.id));
-
-        let mut error = None;
-
-        for recipient in recipients {
-            if let Err(e) = self
-                .client
-                .send_alimtalk(
-                    &recipient,
-                    TEMPLATE_RESERVATION_AMEND,
-                    message.clone(),
-                    Some(vec![AlimTalkButtonAttachment {
-                        name: "예약 확인".to_owned(),
-                        r#type: Default::default(),
-                        url_mobile: url.to_string(),
-                        url_pc: Some(url.to_string()),
-                    }]),
-                )
-                .await
-            {
-                error = Some(e);
-            }
-        }
-
-        if let Some(error) = error {
-            Err(error.into())
-        } else {
-            Ok(())
-        }
+        let url = self.booking_url(&booking);
+
+        let message = self.templates.render(
+            MessageTemplate::AmendNotification,
+            context! {
+                customer => booking.customer.name(),
+                old_reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                new_reservation_dt => self.format_time_range(new_time_from, new_time_to),
+                booking_url => url.as_str(),
+            },
+        )?;
+
+        self.send_all(
+            recipients,
+            MessageTemplate::AmendNotification,
+            message,
+            Some(vec![link_button("예약 확인", &url)]),
+        )
+        .await
     }
 
     async fn send_cancel_notification(
@@ -252,57 +243,24 @@ impl MessagingBackend for BiztalkClient {
         booking: Booking,
         refund_rate: i32,
     ) -> Result<(), Self::Error> {
-        let start = self.timezone_config.convert(booking.time_from);
-        let end = self.timezone_config.convert(booking.time_to);
-
-        let time_str = format!(
-            "{} - {} ({} 시간)",
-            start.format("%Y-%m-%d %H:%M"),
-            end.format("%Y-%m-%d %H:%M"),
-            (end - start).num_hours()
-        );
-
-        let (template, message) = match refund_rate {
-            0 => (
-                TEMPLATE_RESERVATION_CANCEL_NO_REFUND,
-                MESSAGE_RESERVATION_CANCEL_NRF
-                    .replace("#{customer}", booking.customer.name())
-                    .replace("#{reservation_dt}", &time_str),
-            ),
-            50 => (
-                TEMPLATE_RESERVATION_CANCEL_HALF_REFUND,
-                MESSAGE_RESERVATION_CANCEL_HRF
-                    .replace("#{customer}", booking.customer.name())
-                    .replace("#{reservation_dt}", &time_str),
-            ),
-            100 => (
-                TEMPLATE_RESERVATION_CANCEL_FULL_REFUND,
-                MESSAGE_RESERVATION_CANCEL_RF
-                    .replace("#{customer}", booking.customer.name())
-                    .replace("#{reservation_dt}", &time_str),
-            ),
+        let template = match refund_rate {
+            0 => MessageTemplate::CancelNoRefund,
+            50 => MessageTemplate::CancelHalfRefund,
+            100 => MessageTemplate::CancelFullRefund,
             _ => {
                 return Err(Error::NoTemplateForRefundRate(refund_rate));
             }
         };
 
-        let mut error = None;
+        let message = self.templates.render(
+            template,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+            },
+        )?;
 
-        for recipient in recipients {
-            if let Err(e) = self
-                .client
-                .send_alimtalk(&recipient, template, message.clone(), None)
-                .await
-            {
-                error = Some(e);
-            }
-        }
-
-        if let Some(error) = error {
-            Err(error.into())
-        } else {
-            Ok(())
-        }
+        self.send_all(recipients, template, message, None).await
     }
 
     async fn send_refund_confirmation(
@@ -311,29 +269,22 @@ impl MessagingBackend for BiztalkClient {
         booking: Booking,
         refunded_price: i64,
     ) -> Result<(), Self::Error> {
-        let start = self.timezone_config.convert(booking.time_from);
-        let end = self.timezone_config.convert(booking.time_to);
-
-        let time_str = format!(
-            "{} - {} ({} 시간)",
-            start.format("%Y-%m-%d %H:%M"),
-            end.format("%Y-%m-%d %H:%M"),
-            (end - start).num_hours()
-        );
-
-        let message = MESSAGE_RESERVATION_CANCEL_CONFIRM
-            .replace("#{customer}", booking.customer.name())
-            .replace("#{reservation_dt}", &time_str)
-            .replace("#{refund_price}", &refunded_price.to_string());
-
-        self.client
-            .send_alimtalk(
-                &recipient,
-                TEMPLATE_RESERVATION_CANCEL_CONFIRM,
-                message.clone(),
-                None,
-            )
-            .await?;
+        let message = self.templates.render(
+            MessageTemplate::RefundConfirmation,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                refund_price => refunded_price,
+            },
+        )?;
+
+        self.send(
+            &recipient,
+            MessageTemplate::RefundConfirmation,
+            message,
+            None,
+        )
+        .await?;
 
         Ok(())
     }
@@ -344,71 +295,141 @@ impl MessagingBackend for BiztalkClient {
         booking: Booking,
         audio_recording: AudioRecording,
     ) -> Result<(), Self::Error> {
-        let start = self.timezone_config.convert(booking.time_from);
-        let end = self.timezone_config.convert(booking.time_to);
-
-        let time_str = format!(
-            "{} - {} ({} 시간)",
-            start.format("%Y-%m-%d %H:%M"),
-            end.format("%Y-%m-%d %H:%M"),
-            (end - start).num_hours()
-        );
-
         let expires_in = audio_recording
             .expires_in
             .map(|v| v.format("%Y-%m-%d %H:%M:%S").to_string())
             .unwrap_or(String::from("-"));
 
-        let message = MESSAGE_AUDIO_READY
-            .replace("#{customer}", booking.customer.name())
-            .replace("#{reservation_dt}", &time_str)
-            .replace("#{expires_dt}", &expires_in);
-
         let mut url = self.url_config.base_url.clone();
         url.set_path(&format!("booking/{}/recording", booking.id));
 
-        let mut error = None;
+        let message = self.templates.render(
+            MessageTemplate::AudioRecording,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                expires_dt => expires_in,
+                download_url => url.as_str(),
+                survey_url => self.survey_url.as_str(),
+            },
+        )?;
+
+        self.send_all(
+            recipients,
+            MessageTemplate::AudioRecording,
+            message,
+            Some(vec![
+                link_button("음원 다운로드", &url),
+                link_button("설문조사 참여", &self.survey_url),
+            ]),
+        )
+        .await
+    }
 
-        for recipient in recipients {
-            if let Err(e) = self
-                .client
-                .send_alimtalk(
-                    &recipient,
-                    TEMPLATE_AUDIO_READY,
-                    message.clone(),
-                    Some(vec![
-                        AlimTalkButtonAttachment {
-                            name: "음원 다운로드".to_owned(),
-                            r#type: Default::default(),
-                            url_mobile: url.to_string(),
-                            url_pc: Some(url.to_string()),
-                        },
-                        AlimTalkButtonAttachment {
-                            name: "설문조사 참여".to_owned(),
-                            r#type: Default::default(),
-                            url_mobile: SURVEY_URL.to_owned(),
-                            url_pc: Some(SURVEY_URL.to_owned()),
-                        },
-                    ]),
-                )
-                .await
-            {
-                error = Some(e);
-            }
-        }
+    async fn send_waitlist_offer(
+        &self,
+        recipient: Self::Recipient,
+        customer_name: String,
+        _unit_id: UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        let mut url = self.url_config.base_url.clone();
+        url.set_path("reservation/");
+
+        let message = self.templates.render(
+            MessageTemplate::WaitlistOffer,
+            context! {
+                customer => customer_name,
+                reservation_dt => self.format_time_range(time_from, time_to),
+                expires_dt => templates::format_time(&self.timezone_config, expires_at),
+                reservation_url => url.as_str(),
+            },
+        )?;
+
+        self.send(
+            &recipient,
+            MessageTemplate::WaitlistOffer,
+            message,
+            Some(vec![link_button("예약하기", &url)]),
+        )
+        .await?;
 
-        if let Some(error) = error {
-            Err(error.into())
-        } else {
-            Ok(())
-        }
+        Ok(())
+    }
+
+    async fn send_payment_deadline_warning(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        let message = self.templates.render(
+            MessageTemplate::PaymentDeadlineWarning,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                deadline_dt => templates::format_time(&self.timezone_config, deadline),
+            },
+        )?;
+
+        self.send_all(
+            recipients,
+            MessageTemplate::PaymentDeadlineWarning,
+            message,
+            None,
+        )
+        .await
+    }
+
+    async fn send_payment_expired_notification(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error> {
+        let message = self.templates.render(
+            MessageTemplate::PaymentExpired,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+            },
+        )?;
+
+        self.send_all(recipients, MessageTemplate::PaymentExpired, message, None)
+            .await
+    }
+
+    async fn send_email_verification(
+        &self,
+        _recipient: Self::Recipient,
+        _url: url::Url,
+        _expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        Err(Error::Unsupported("email verification"))
     }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("Not sent through AlimTalk: {0}")]
+    Unsupported(&'static str),
     #[error("No template for refund rate: {0}")]
     NoTemplateForRefundRate(i32),
+    #[error("Template error: {0}")]
+    Template(#[from] templates::Error),
     #[error("Biztalk Error: {0}")]
     Biztalk(#[from] dxe_extern::biztalk::Error),
 }
+
+impl Error {
+    /// Whether Biztalk has refused the message because the recipient can't receive AlimTalk.
+    /// Other errors from Biztalk, such as a mismatching template, may be fixed before retrying.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            Self::Biztalk(dxe_extern::biztalk::Error::Biztalk(code, _))
+                if dxe_extern::biztalk::is_recipient_failure(code)
+        )
+    }
+}
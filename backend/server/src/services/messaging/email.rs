@@ -0,0 +1,312 @@
+use chrono::{DateTime, Utc};
+use dxe_data::entities::{AudioRecording, Booking};
+use dxe_types::UnitId;
+use minijinja::context;
+
+use super::MessagingBackend;
+use super::templates::{self, EmailTemplate, MessageTemplateRegistry};
+use crate::config::{SmtpConfig, TimeZoneConfig, UrlConfig};
+
+pub type EmailRecipient = String;
+
+#[derive(Clone)]
+pub struct EmailClient {
+    client: dxe_extern::smtp::SmtpClient,
+    templates: MessageTemplateRegistry,
+    timezone_config: TimeZoneConfig,
+    url_config: UrlConfig,
+}
+
+impl EmailClient {
+    pub fn new(
+        config: &SmtpConfig,
+        templates: MessageTemplateRegistry,
+        timezone_config: TimeZoneConfig,
+        url_config: UrlConfig,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            client: dxe_extern::smtp::SmtpClient::new(config)?,
+            templates,
+            timezone_config,
+            url_config,
+        })
+    }
+
+    fn format_time_range(&self, time_from: DateTime<Utc>, time_to: DateTime<Utc>) -> String {
+        templates::format_time_range(&self.timezone_config, time_from, time_to)
+    }
+
+    fn format_time(&self, time: DateTime<Utc>) -> String {
+        templates::format_time(&self.timezone_config, time)
+    }
+
+    fn url(&self, path: &str) -> String {
+        let mut url = self.url_config.base_url.clone();
+        url.set_path(path);
+        url.to_string()
+    }
+
+    async fn send(
+        &self,
+        recipients: Vec<EmailRecipient>,
+        template: EmailTemplate,
+        context: minijinja::Value,
+    ) -> Result<(), Error> {
+        let email = self.templates.render_email(template, context)?;
+
+        let mut error = None;
+
+        for recipient in recipients {
+            if let Err(e) = self
+                .client
+                .send(
+                    &recipient,
+                    &email.subject,
+                    email.text.clone(),
+                    email.html.clone(),
+                )
+                .await
+            {
+                error = Some(e);
+            }
+        }
+
+        if let Some(error) = error {
+            Err(error.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn send_password_reset(
+        &self,
+        recipient: &str,
+        handle: &str,
+        url: &url::Url,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.send(
+            vec![recipient.to_owned()],
+            EmailTemplate::PasswordReset,
+            context! {
+                handle => handle,
+                expires_dt => self.format_time(expires_at),
+                url => url.as_str(),
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl MessagingBackend for EmailClient {
+    type Recipient = EmailRecipient;
+    type Error = Error;
+
+    async fn send_booking_confirmation(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            recipients,
+            EmailTemplate::BookingConfirmation,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                url => self.url(&format!("reservation/{}", booking.id)),
+            },
+        )
+        .await
+    }
+
+    async fn send_booking_reminder(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error> {
+        let relative_time_str = templates::format_remaining_time(booking.time_from - Utc::now());
+
+        self.send(
+            recipients,
+            EmailTemplate::BookingReminder,
+            context! {
+                customer => booking.customer.name(),
+                reservation_relative_time => relative_time_str,
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                url => self.url(&format!("reservation/{}", booking.id)),
+            },
+        )
+        .await
+    }
+
+    async fn send_amend_notification(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        new_time_from: DateTime<Utc>,
+        new_time_to: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            recipients,
+            EmailTemplate::AmendNotification,
+            context! {
+                customer => booking.customer.name(),
+                old_reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                new_reservation_dt => self.format_time_range(new_time_from, new_time_to),
+                url => self.url(&format!("reservation/{}", booking.id)),
+            },
+        )
+        .await
+    }
+
+    async fn send_cancel_notification(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        refund_rate: i32,
+    ) -> Result<(), Self::Error> {
+        let refund_notice = match refund_rate {
+            0 => "예약 당일 취소는 환불이 불가능하다는 점을 알려드립니다.".to_owned(),
+            100 => "영업일 1~2일 내로 이용금액은 전액 환불됩니다.".to_owned(),
+            _ => {
+                format!("영업일 1~2일 내로 위약금을 제외한 이용금액의 {refund_rate}%가 환불됩니다.")
+            }
+        };
+
+        self.send(
+            recipients,
+            EmailTemplate::CancelNotification,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                refund_notice => refund_notice,
+            },
+        )
+        .await
+    }
+
+    async fn send_refund_confirmation(
+        &self,
+        recipient: Self::Recipient,
+        booking: Booking,
+        refunded_price: i64,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            vec![recipient],
+            EmailTemplate::RefundNotification,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                refund_price => format!("{refunded_price}원"),
+            },
+        )
+        .await
+    }
+
+    async fn send_audio_recording(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        audio_recording: AudioRecording,
+    ) -> Result<(), Self::Error> {
+        let expires_in = audio_recording
+            .expires_in
+            .map(|v| self.format_time(v))
+            .unwrap_or(String::from("-"));
+
+        self.send(
+            recipients,
+            EmailTemplate::AudioRecording,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                expires_dt => expires_in,
+                url => self.url(&format!("booking/{}/recording", booking.id)),
+            },
+        )
+        .await
+    }
+
+    async fn send_waitlist_offer(
+        &self,
+        recipient: Self::Recipient,
+        customer_name: String,
+        _unit_id: UnitId,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            vec![recipient],
+            EmailTemplate::WaitlistOffer,
+            context! {
+                customer => customer_name,
+                reservation_dt => self.format_time_range(time_from, time_to),
+                expires_dt => self.format_time(expires_at),
+                url => self.url("reservation/"),
+            },
+        )
+        .await
+    }
+
+    async fn send_payment_deadline_warning(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            recipients,
+            EmailTemplate::PaymentDeadlineWarning,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+                deadline_dt => self.format_time(deadline),
+            },
+        )
+        .await
+    }
+
+    async fn send_payment_expired_notification(
+        &self,
+        recipients: Vec<Self::Recipient>,
+        booking: Booking,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            recipients,
+            EmailTemplate::PaymentExpiredNotification,
+            context! {
+                customer => booking.customer.name(),
+                reservation_dt => self.format_time_range(booking.time_from, booking.time_to),
+            },
+        )
+        .await
+    }
+
+    async fn send_email_verification(
+        &self,
+        recipient: Self::Recipient,
+        url: url::Url,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), Self::Error> {
+        self.send(
+            vec![recipient.clone()],
+            EmailTemplate::EmailVerification,
+            context! {
+                email => recipient,
+                expires_dt => self.format_time(expires_at),
+                url => url.as_str(),
+            },
+        )
+        .await
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Template error: {0}")]
+    Template(#[from] templates::Error),
+    #[error("SMTP Error: {0}")]
+    Smtp(#[from] dxe_extern::smtp::Error),
+}
@@ -0,0 +1,542 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use minijinja::{Environment, UndefinedBehavior, Value, context};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{MessageTemplateConfig, TimeZoneConfig};
+
+const EMAIL_LAYOUT: &str = "email/layout.html";
+
+/// Texts of the messages, each of which has to match the AlimTalk template registered on Biztalk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageTemplate {
+    BookingConfirmation,
+    BookingReminder,
+    AmendNotification,
+    CancelNoRefund,
+    CancelHalfRefund,
+    CancelFullRefund,
+    RefundConfirmation,
+    AudioRecording,
+    WaitlistOffer,
+    PaymentDeadlineWarning,
+    PaymentExpired,
+}
+
+impl MessageTemplate {
+    pub const ALL: [Self; 11] = [
+        Self::BookingConfirmation,
+        Self::BookingReminder,
+        Self::AmendNotification,
+        Self::CancelNoRefund,
+        Self::CancelHalfRefund,
+        Self::CancelFullRefund,
+        Self::RefundConfirmation,
+        Self::AudioRecording,
+        Self::WaitlistOffer,
+        Self::PaymentDeadlineWarning,
+        Self::PaymentExpired,
+    ];
+
+    /// File name of the template without the `.txt` extension, also the key in the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => "booking_confirmation",
+            Self::BookingReminder => "booking_reminder",
+            Self::AmendNotification => "amend_notification",
+            Self::CancelNoRefund => "cancel_no_refund",
+            Self::CancelHalfRefund => "cancel_half_refund",
+            Self::CancelFullRefund => "cancel_full_refund",
+            Self::RefundConfirmation => "refund_confirmation",
+            Self::AudioRecording => "audio_recording",
+            Self::WaitlistOffer => "waitlist_offer",
+            Self::PaymentDeadlineWarning => "payment_deadline_warning",
+            Self::PaymentExpired => "payment_expired",
+        }
+    }
+
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            Self::BookingConfirmation => &["customer", "reservation_dt", "booking_url"],
+            Self::BookingReminder => &[
+                "customer",
+                "reservation_dt",
+                "reservation_relative_time",
+                "booking_url",
+            ],
+            Self::AmendNotification => &[
+                "customer",
+                "old_reservation_dt",
+                "new_reservation_dt",
+                "booking_url",
+            ],
+            Self::CancelNoRefund | Self::CancelHalfRefund | Self::CancelFullRefund => {
+                &["customer", "reservation_dt"]
+            }
+            Self::RefundConfirmation => &["customer", "reservation_dt", "refund_price"],
+            Self::AudioRecording => &[
+                "customer",
+                "reservation_dt",
+                "expires_dt",
+                "download_url",
+                "survey_url",
+            ],
+            Self::WaitlistOffer => &[
+                "customer",
+                "reservation_dt",
+                "expires_dt",
+                "reservation_url",
+            ],
+            Self::PaymentDeadlineWarning => &["customer", "reservation_dt", "deadline_dt"],
+            Self::PaymentExpired => &["customer", "reservation_dt"],
+        }
+    }
+
+    /// Template code on Biztalk, unless overridden in the config.
+    pub fn default_biztalk_code(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => "RESERVATION_CONFIRMATION_02",
+            Self::BookingReminder => "RESERVATION_REMINDER_02",
+            Self::AmendNotification => "RESERVATION_AMEND_01",
+            Self::CancelNoRefund => "RESERVATION_CANCEL_NRF_01",
+            Self::CancelHalfRefund => "RESERVATION_CANCEL_HRF_01",
+            Self::CancelFullRefund => "RESERVATION_CANCEL_RF_01",
+            Self::RefundConfirmation => "RESERVATION_CONFIRM_01",
+            Self::AudioRecording => "AUDIO_READY _02",
+            Self::WaitlistOffer => "WAITLIST_OFFER_01",
+            Self::PaymentDeadlineWarning => "PAYMENT_DEADLINE_WARNING_01",
+            Self::PaymentExpired => "RESERVATION_CANCEL_UNPAID_01",
+        }
+    }
+
+    fn builtin_source(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => include_str!("templates/booking_confirmation.txt"),
+            Self::BookingReminder => include_str!("templates/booking_reminder.txt"),
+            Self::AmendNotification => include_str!("templates/amend_notification.txt"),
+            Self::CancelNoRefund => include_str!("templates/cancel_no_refund.txt"),
+            Self::CancelHalfRefund => include_str!("templates/cancel_half_refund.txt"),
+            Self::CancelFullRefund => include_str!("templates/cancel_full_refund.txt"),
+            Self::RefundConfirmation => include_str!("templates/refund_confirmation.txt"),
+            Self::AudioRecording => include_str!("templates/audio_recording.txt"),
+            Self::WaitlistOffer => include_str!("templates/waitlist_offer.txt"),
+            Self::PaymentDeadlineWarning => include_str!("templates/payment_deadline_warning.txt"),
+            Self::PaymentExpired => include_str!("templates/payment_expired.txt"),
+        }
+    }
+}
+
+/// Emails sent in both plain text and HTML, the latter of which is wrapped in the layout.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EmailTemplate {
+    BookingConfirmation,
+    BookingReminder,
+    AmendNotification,
+    CancelNotification,
+    RefundNotification,
+    AudioRecording,
+    WaitlistOffer,
+    PaymentDeadlineWarning,
+    PaymentExpiredNotification,
+    EmailVerification,
+    PasswordReset,
+}
+
+impl EmailTemplate {
+    pub const ALL: [Self; 11] = [
+        Self::BookingConfirmation,
+        Self::BookingReminder,
+        Self::AmendNotification,
+        Self::CancelNotification,
+        Self::RefundNotification,
+        Self::AudioRecording,
+        Self::WaitlistOffer,
+        Self::PaymentDeadlineWarning,
+        Self::PaymentExpiredNotification,
+        Self::EmailVerification,
+        Self::PasswordReset,
+    ];
+
+    /// File name of the templates in the `email` directory, followed by `.subject.txt`, `.txt` and
+    /// `.html`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => "booking_confirmation",
+            Self::BookingReminder => "booking_reminder",
+            Self::AmendNotification => "amend_notification",
+            Self::CancelNotification => "cancel_notification",
+            Self::RefundNotification => "refund_notification",
+            Self::AudioRecording => "audio_recording",
+            Self::WaitlistOffer => "waitlist_offer",
+            Self::PaymentDeadlineWarning => "payment_deadline_warning",
+            Self::PaymentExpiredNotification => "payment_expired_notification",
+            Self::EmailVerification => "email_verification",
+            Self::PasswordReset => "password_reset",
+        }
+    }
+
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            Self::BookingConfirmation => &["customer", "reservation_dt", "url"],
+            Self::BookingReminder => &[
+                "customer",
+                "reservation_dt",
+                "reservation_relative_time",
+                "url",
+            ],
+            Self::AmendNotification => &[
+                "customer",
+                "old_reservation_dt",
+                "new_reservation_dt",
+                "url",
+            ],
+            Self::CancelNotification => &["customer", "reservation_dt", "refund_notice"],
+            Self::RefundNotification => &["customer", "reservation_dt", "refund_price"],
+            Self::AudioRecording | Self::WaitlistOffer => {
+                &["customer", "reservation_dt", "expires_dt", "url"]
+            }
+            Self::PaymentDeadlineWarning => &["customer", "reservation_dt", "deadline_dt"],
+            Self::PaymentExpiredNotification => &["customer", "reservation_dt"],
+            Self::EmailVerification => &["email", "expires_dt", "url"],
+            Self::PasswordReset => &["handle", "expires_dt", "url"],
+        }
+    }
+
+    fn subject_name(&self) -> String {
+        format!("email/{}.subject.txt", self.name())
+    }
+
+    fn text_name(&self) -> String {
+        format!("email/{}.txt", self.name())
+    }
+
+    /// Values in the HTML template are escaped, as the name ends with `.html`.
+    fn html_name(&self) -> String {
+        format!("email/{}.html", self.name())
+    }
+
+    fn builtin_subject(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => "[드림하우스] 예약이 확정되었습니다",
+            Self::BookingReminder => "[드림하우스] 예약 시간이 다가오고 있습니다",
+            Self::AmendNotification => "[드림하우스] 예약 일시가 변경되었습니다",
+            Self::CancelNotification => "[드림하우스] 예약이 취소되었습니다",
+            Self::RefundNotification => "[드림하우스] 이용금액이 환불되었습니다",
+            Self::AudioRecording => "[드림하우스] 레코딩 파일이 준비되었습니다",
+            Self::WaitlistOffer => "[드림하우스] 대기 신청하신 시간에 자리가 났습니다",
+            Self::PaymentDeadlineWarning => "[드림하우스] 입금 기한이 다가오고 있습니다",
+            Self::PaymentExpiredNotification => {
+                "[드림하우스] 입금 기한이 지나 예약이 취소되었습니다"
+            }
+            Self::EmailVerification => "[드림하우스] 이메일 주소를 인증해 주세요",
+            Self::PasswordReset => "[드림하우스] 비밀번호를 재설정해 주세요",
+        }
+    }
+
+    fn builtin_text(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => include_str!("email/booking_confirmation.txt"),
+            Self::BookingReminder => include_str!("email/booking_reminder.txt"),
+            Self::AmendNotification => include_str!("email/amend_notification.txt"),
+            Self::CancelNotification => include_str!("email/cancel_notification.txt"),
+            Self::RefundNotification => include_str!("email/refund_notification.txt"),
+            Self::AudioRecording => include_str!("email/audio_recording.txt"),
+            Self::WaitlistOffer => include_str!("email/waitlist_offer.txt"),
+            Self::PaymentDeadlineWarning => include_str!("email/payment_deadline_warning.txt"),
+            Self::PaymentExpiredNotification => {
+                include_str!("email/payment_expired_notification.txt")
+            }
+            Self::EmailVerification => include_str!("email/email_verification.txt"),
+            Self::PasswordReset => include_str!("email/password_reset.txt"),
+        }
+    }
+
+    fn builtin_html(&self) -> &'static str {
+        match self {
+            Self::BookingConfirmation => include_str!("email/booking_confirmation.html"),
+            Self::BookingReminder => include_str!("email/booking_reminder.html"),
+            Self::AmendNotification => include_str!("email/amend_notification.html"),
+            Self::CancelNotification => include_str!("email/cancel_notification.html"),
+            Self::RefundNotification => include_str!("email/refund_notification.html"),
+            Self::AudioRecording => include_str!("email/audio_recording.html"),
+            Self::WaitlistOffer => include_str!("email/waitlist_offer.html"),
+            Self::PaymentDeadlineWarning => include_str!("email/payment_deadline_warning.html"),
+            Self::PaymentExpiredNotification => {
+                include_str!("email/payment_expired_notification.html")
+            }
+            Self::EmailVerification => include_str!("email/email_verification.html"),
+            Self::PasswordReset => include_str!("email/password_reset.html"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Templates read from the configured directory, or the built-in ones if there's none.
+#[derive(Clone, Debug)]
+pub struct MessageTemplateRegistry {
+    directory: Option<PathBuf>,
+    environment: Arc<RwLock<Environment<'static>>>,
+}
+
+impl MessageTemplateRegistry {
+    pub fn new(config: &MessageTemplateConfig) -> Result<Self, Error> {
+        let environment = Self::load(config.directory.as_deref())?;
+
+        Ok(Self {
+            directory: config.directory.clone(),
+            environment: Arc::new(RwLock::new(environment)),
+        })
+    }
+
+    fn load(directory: Option<&Path>) -> Result<Environment<'static>, Error> {
+        let mut environment = Environment::new();
+        environment.set_undefined_behavior(UndefinedBehavior::Strict);
+
+        for template in MessageTemplate::ALL {
+            Self::add(
+                &mut environment,
+                directory,
+                template.name().to_owned(),
+                format!("{}.txt", template.name()),
+                template.builtin_source(),
+                template.variables(),
+            )?;
+        }
+
+        for template in EmailTemplate::ALL {
+            for (name, builtin_source) in [
+                (template.subject_name(), template.builtin_subject()),
+                (template.text_name(), template.builtin_text()),
+                (template.html_name(), template.builtin_html()),
+            ] {
+                Self::add(
+                    &mut environment,
+                    directory,
+                    name.clone(),
+                    name,
+                    builtin_source,
+                    template.variables(),
+                )?;
+            }
+        }
+
+        Self::add(
+            &mut environment,
+            directory,
+            EMAIL_LAYOUT.to_owned(),
+            EMAIL_LAYOUT.to_owned(),
+            include_str!("email/layout.html"),
+            &["subject", "content"],
+        )?;
+
+        Ok(environment)
+    }
+
+    fn add(
+        environment: &mut Environment<'static>,
+        directory: Option<&Path>,
+        name: String,
+        file_name: String,
+        builtin_source: &'static str,
+        variables: &[&str],
+    ) -> Result<(), Error> {
+        let source = if let Some(directory) = directory {
+            let path = directory.join(file_name);
+            std::fs::read_to_string(&path).map_err(|e| Error::Read(path, e))?
+        } else {
+            builtin_source.to_owned()
+        };
+
+        environment
+            .add_template_owned(name.clone(), source.trim_ascii().to_owned())
+            .map_err(|e| Error::Template(name.clone(), e))?;
+
+        let undeclared_variables = environment
+            .get_template(&name)
+            .map_err(|e| Error::Template(name.clone(), e))?
+            .undeclared_variables(false);
+
+        if let Some(variable) = undeclared_variables
+            .into_iter()
+            .find(|v| !variables.contains(&v.as_str()))
+        {
+            return Err(Error::UnknownVariable(name, variable));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the templates again. Current ones are kept if any of the new ones is invalid.
+    pub fn reload(&self) -> Result<(), Error> {
+        let environment = Self::load(self.directory.as_deref())?;
+
+        *self.environment.write().unwrap() = environment;
+
+        Ok(())
+    }
+
+    pub fn render<S: Serialize>(
+        &self,
+        template: MessageTemplate,
+        context: S,
+    ) -> Result<String, Error> {
+        let environment = self.environment.read().unwrap();
+
+        environment
+            .get_template(template.name())
+            .and_then(|v| v.render(context))
+            .map_err(|e| Error::Template(template.name().to_owned(), e))
+    }
+
+    pub fn render_email<S: Serialize>(
+        &self,
+        template: EmailTemplate,
+        context: S,
+    ) -> Result<RenderedEmail, Error> {
+        let environment = self.environment.read().unwrap();
+        let context = Value::from_serialize(&context);
+        let render = |name: String, context: &Value| {
+            environment
+                .get_template(&name)
+                .and_then(|v| v.render(context))
+                .map_err(|e| Error::Template(name, e))
+        };
+
+        let subject = render(template.subject_name(), &context)?;
+        let text = render(template.text_name(), &context)?;
+        let content = render(template.html_name(), &context)?;
+        let html = render(
+            EMAIL_LAYOUT.to_owned(),
+            &context! {
+                subject => &subject,
+                content => Value::from_safe_string(content),
+            },
+        )?;
+
+        Ok(RenderedEmail {
+            subject,
+            text,
+            html,
+        })
+    }
+}
+
+/// Time range of the booking as shown in the messages, with its length in hours.
+pub fn format_time_range(
+    timezone_config: &TimeZoneConfig,
+    time_from: DateTime<Utc>,
+    time_to: DateTime<Utc>,
+) -> String {
+    let start = timezone_config.convert(time_from);
+    let end = timezone_config.convert(time_to);
+
+    format!(
+        "{} - {} ({} 시간)",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format("%Y-%m-%d %H:%M"),
+        (end - start).num_hours()
+    )
+}
+
+pub fn format_time(timezone_config: &TimeZoneConfig, time: DateTime<Utc>) -> String {
+    timezone_config
+        .convert(time)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Time left until the booking starts, e.g. "1시간 30분".
+pub fn format_remaining_time(remaining: TimeDelta) -> String {
+    let mut dt = String::new();
+
+    let hours = remaining.num_hours();
+    let minutes = remaining.num_minutes() - (hours * 60);
+
+    if hours > 0 {
+        dt.push_str(&format!("{hours}시간 "));
+    }
+    if minutes > 0 {
+        dt.push_str(&format!("{minutes}분 "));
+    }
+
+    dt.trim_end().to_owned()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Could not read template {path}: {1}", path = .0.display())]
+    Read(PathBuf, std::io::Error),
+    #[error("Error in template {0}: {1}")]
+    Template(String, minijinja::Error),
+    #[error("Unknown variable in template {0}: {1}")]
+    UnknownVariable(String, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> MessageTemplateRegistry {
+        MessageTemplateRegistry::new(&MessageTemplateConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn renders_email_in_layout() {
+        let email = registry()
+            .render_email(
+                EmailTemplate::PasswordReset,
+                context! {
+                    handle => "<b>Tom & \"Jerry\"</b>",
+                    expires_dt => "2026-03-02 18:00",
+                    url => "https://example.com/reset?token={{ url }}",
+                },
+            )
+            .unwrap();
+
+        assert_eq!(email.subject, "[드림하우스] 비밀번호를 재설정해 주세요");
+        assert!(email.text.contains("아이디: <b>Tom & \"Jerry\"</b>"));
+        assert!(
+            email
+                .text
+                .contains("https://example.com/reset?token={{ url }}")
+        );
+        assert!(
+            email
+                .html
+                .contains("<title>[드림하우스] 비밀번호를 재설정해 주세요</title>")
+        );
+        assert!(
+            email
+                .html
+                .contains("<li>아이디: &lt;b&gt;Tom &amp; &quot;Jerry&quot;&lt;&#x2f;b&gt;</li>")
+        );
+    }
+
+    #[test]
+    fn rejects_missing_variables() {
+        assert!(
+            registry()
+                .render_email(
+                    EmailTemplate::EmailVerification,
+                    context! { email => "a@b.c" }
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn formats_remaining_time() {
+        assert_eq!(format_remaining_time(TimeDelta::minutes(150)), "2시간 30분");
+        assert_eq!(format_remaining_time(TimeDelta::minutes(60)), "1시간");
+        assert_eq!(format_remaining_time(TimeDelta::minutes(30)), "30분");
+    }
+}
@@ -1,15 +1,32 @@
 use dxe_extern::ntfy::NtfyClient;
+use dxe_extern::webhook::WebhookClient;
+use serde::Deserialize;
 use tokio::sync::mpsc;
 
-use crate::config::{NotificationBackend, NotificationConfig, NtfyConfig};
+use crate::config::{NotificationBackend, NotificationConfig, NtfyConfig, WebhookConfig};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Priority {
     High,
     Default,
     Low,
 }
 
+impl Priority {
+    fn level(&self) -> u8 {
+        match self {
+            Self::High => 2,
+            Self::Default => 1,
+            Self::Low => 0,
+        }
+    }
+
+    pub fn is_at_least(&self, other: Priority) -> bool {
+        self.level() >= other.level()
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 struct NoopService;
 
@@ -51,21 +68,62 @@ impl NtfyService {
     }
 }
 
+#[derive(Clone, Debug)]
+struct WebhookService {
+    client: WebhookClient,
+}
+
+impl WebhookService {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self {
+            client: WebhookClient::new(&config),
+        }
+    }
+
+    pub async fn notify(&self, priority: Priority, message: String) -> Result<(), Error> {
+        let priority = match priority {
+            Priority::High => dxe_extern::webhook::Priority::High,
+            Priority::Default => dxe_extern::webhook::Priority::Default,
+            Priority::Low => dxe_extern::webhook::Priority::Low,
+        };
+
+        self.client.send(priority, message).await?;
+
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 enum Backend {
     Noop(NoopService),
     Ntfy(NtfyService),
+    Webhook(WebhookService),
+}
+
+impl Backend {
+    async fn notify(&self, priority: Priority, message: String) -> Result<(), Error> {
+        match self {
+            Self::Noop(service) => service.notify(priority, message),
+            Self::Ntfy(service) => service.notify(priority, message).await,
+            Self::Webhook(service) => service.notify(priority, message).await,
+        }
+    }
 }
 
 pub struct NotificationService {
-    backend: Backend,
+    backends: Vec<(Option<Priority>, Backend)>,
 }
 
 impl NotificationService {
-    async fn notify(&self, priority: Priority, message: String) -> Result<(), Error> {
-        match &self.backend {
-            Backend::Noop(service) => service.notify(priority, message),
-            Backend::Ntfy(service) => service.notify(priority, message).await,
+    async fn notify(&self, priority: Priority, message: String) {
+        for (min_priority, backend) in &self.backends {
+            if min_priority.is_some_and(|v| !priority.is_at_least(v)) {
+                continue;
+            }
+
+            if let Err(e) = backend.notify(priority, message.clone()).await {
+                log::warn!("Cannot send notification: {e}");
+            }
         }
     }
 }
@@ -83,12 +141,27 @@ impl NotificationSender {
 
 impl NotificationService {
     pub fn new(config: NotificationConfig) -> Self {
-        let backend = match config.backend {
-            NotificationBackend::Noop => Backend::Noop(Default::default()),
-            NotificationBackend::Ntfy => Backend::Ntfy(NtfyService::new(config.ntfy.unwrap())),
-        };
+        let mut backends = config
+            .backends
+            .into_iter()
+            .map(|v| {
+                let backend = match v.backend {
+                    NotificationBackend::Noop => Backend::Noop(Default::default()),
+                    NotificationBackend::Ntfy(config) => Backend::Ntfy(NtfyService::new(config)),
+                    NotificationBackend::Webhook(config) => {
+                        Backend::Webhook(WebhookService::new(config))
+                    }
+                };
+
+                (v.min_priority, backend)
+            })
+            .collect::<Vec<_>>();
+
+        if backends.is_empty() {
+            backends.push((None, Backend::Noop(Default::default())));
+        }
 
-        Self { backend }
+        Self { backends }
     }
 }
 
@@ -101,9 +174,7 @@ pub fn spawn_notification_service_task(
         let service = NotificationService::new(config);
 
         while let Some((priority, message)) = rx.recv().await {
-            if let Err(e) = service.notify(priority, message).await {
-                log::warn!("Cannot send notification: {e}");
-            }
+            service.notify(priority, message).await;
         }
     });
 
@@ -114,4 +185,6 @@ pub fn spawn_notification_service_task(
 pub enum Error {
     #[error("{0}")]
     Ntfy(#[from] dxe_extern::ntfy::Error),
+    #[error("{0}")]
+    Webhook(#[from] dxe_extern::webhook::Error),
 }
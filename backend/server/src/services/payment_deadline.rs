@@ -0,0 +1,168 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use dxe_data::entities::{Booking, BookingAmendment};
+use dxe_data::queries::booking::{
+    cancel_booking, cancel_booking_amendment, get_booking,
+    get_booking_amendments_with_unpaid_cash_transaction,
+    get_booking_ids_with_unpaid_cash_transaction,
+};
+use dxe_data::queries::coupon::cancel_coupon_redemption;
+use dxe_data::queries::payment::create_cash_payment_deadline_warning;
+use dxe_types::ProductId;
+use sqlx::SqlitePool;
+
+use crate::config::{PaymentDeadlineConfig, TimeZoneConfig};
+use crate::models::Error;
+use crate::services::calendar::CalendarService;
+use crate::services::messaging::MessagingService;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::services::waitlist::WaitlistService;
+
+/// Cancels cash bookings which have not been paid by the deadline, warning the customers
+/// beforehand. Reschedulings awaiting a deposit for the price difference are dropped by the
+/// same deadline, leaving the booking where it was.
+pub struct PaymentDeadlineService {
+    config: PaymentDeadlineConfig,
+    timezone_config: TimeZoneConfig,
+    messaging_service: MessagingService,
+    notification_sender: NotificationSender,
+    waitlist_service: WaitlistService,
+    calendar_service: Option<CalendarService>,
+}
+
+impl PaymentDeadlineService {
+    pub fn new(
+        config: &PaymentDeadlineConfig,
+        timezone_config: TimeZoneConfig,
+        messaging_service: MessagingService,
+        notification_sender: NotificationSender,
+        waitlist_service: WaitlistService,
+        calendar_service: Option<CalendarService>,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            timezone_config,
+            messaging_service,
+            notification_sender,
+            waitlist_service,
+            calendar_service,
+        }
+    }
+
+    pub async fn run(&self, database: &SqlitePool, now: DateTime<Utc>) -> Result<(), Error> {
+        let mut tx = database.begin().await?;
+
+        let mut expired_bookings: Vec<Booking> = vec![];
+
+        for booking_id in get_booking_ids_with_unpaid_cash_transaction(&mut tx, &now).await? {
+            let Some(booking) = get_booking(&mut tx, &booking_id).await? else {
+                continue;
+            };
+            let Some(deadline) = self.config.deadline(booking.created_at, booking.time_from) else {
+                continue;
+            };
+
+            let product_id = ProductId::from(booking.id);
+
+            if deadline <= now {
+                if !cancel_booking(&mut tx, &now, &booking.id).await? {
+                    continue;
+                }
+
+                cancel_coupon_redemption(&mut tx, &now, &product_id).await?;
+
+                self.waitlist_service
+                    .release(
+                        &mut tx,
+                        &now,
+                        &booking.unit_id,
+                        &booking.time_from,
+                        &booking.time_to,
+                    )
+                    .await?;
+
+                self.messaging_service
+                    .send_payment_expired_notification(&mut tx, &now, booking.clone())
+                    .await?;
+
+                expired_bookings.push(booking);
+            } else if deadline - TimeDelta::hours(self.config.warning_hours) <= now
+                && create_cash_payment_deadline_warning(&mut tx, &now, &product_id).await?
+            {
+                self.messaging_service
+                    .send_payment_deadline_warning(&mut tx, &now, booking, deadline)
+                    .await?;
+            }
+        }
+
+        let mut expired_amendments: Vec<BookingAmendment> = vec![];
+
+        for amendment in get_booking_amendments_with_unpaid_cash_transaction(&mut tx, &now).await? {
+            let Some(deadline) = self
+                .config
+                .deadline(amendment.created_at, amendment.desired_time_from)
+            else {
+                continue;
+            };
+
+            if deadline.min(amendment.original_time_from) <= now
+                && cancel_booking_amendment(&mut tx, &now, &amendment.id).await?
+            {
+                expired_amendments.push(amendment);
+            }
+        }
+
+        tx.commit().await?;
+
+        for amendment in expired_amendments {
+            self.notification_sender.enqueue(
+                Priority::High,
+                format!(
+                    "Unpaid rescheduling of booking {} to {} - {} dropped after the payment deadline",
+                    amendment.booking_id,
+                    self.timezone_config.convert(amendment.desired_time_from),
+                    self.timezone_config.convert(amendment.desired_time_to),
+                ),
+            );
+        }
+
+        for booking in expired_bookings {
+            if let Some(calendar_service) = self.calendar_service.as_ref()
+                && let Err(e) = calendar_service.delete_booking(&booking.id).await
+            {
+                log::error!("Failed to delete event on calendar: {e}");
+            }
+
+            self.notification_sender.enqueue(
+                Priority::High,
+                format!(
+                    "Unpaid booking by {} canceled after the payment deadline: {}",
+                    booking.customer.name(),
+                    self.timezone_config.convert(booking.time_from),
+                ),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+pub fn spawn_payment_deadline_task(
+    database: SqlitePool,
+    service: PaymentDeadlineService,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(
+            TimeDelta::minutes(service.config.check_interval_minutes.max(1))
+                .to_std()
+                .unwrap_or(std::time::Duration::from_secs(300)),
+        );
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = service.run(&database, Utc::now()).await {
+                log::error!("Could not process cash payment deadlines: {e}");
+            }
+        }
+    })
+}
@@ -0,0 +1,1130 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use dxe_data::entities::{
+    AdhocReservation, Booking, BookingAmendment, HourPack, Identity, Product,
+    TossPaymentsTransaction,
+};
+use dxe_data::queries::booking::{
+    cancel_booking, confirm_booking, confirm_booking_amendment, create_booking,
+    expire_adhoc_reservation, get_adhoc_reservation, get_booking, get_booking_with_user_id,
+    get_product, reschedule_booking,
+};
+use dxe_data::queries::coupon::{
+    attach_coupon_redemption, cancel_coupon_redemption, delete_pending_coupon_redemption,
+};
+use dxe_data::queries::hour_pack::{
+    cancel_hour_pack, confirm_hour_pack, create_hour_balance_entry, get_hour_balance,
+};
+use dxe_data::queries::identity::{get_group_members, is_member_of};
+use dxe_data::queries::payment::{
+    confirm_toss_payments_transaction, get_overdue_toss_virtual_accounts,
+    get_toss_payments_transaction_by_id, get_toss_payments_transactions_created_since,
+    issue_toss_payments_virtual_account, refund_toss_payments,
+};
+use dxe_extern::toss_payments::types::{Payment, PaymentStatus, RefundReceiveAccount};
+use dxe_extern::toss_payments::{Error as TossPaymentsError, TossPaymentsClient};
+use dxe_types::{ForeignPaymentId, ProductId};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+
+use crate::config::{TimeZoneConfig, TossReconciliationConfig};
+use crate::models::Error;
+use crate::services::calendar::{CalendarService, CalendarUpdates};
+use crate::services::messaging::MessagingService;
+use crate::services::notification::{NotificationSender, Priority};
+use crate::services::waitlist::WaitlistService;
+use crate::services::webhook::{WebhookEvent, enqueue_webhook_event};
+use crate::utils::datetime::is_in_effect;
+
+/// Toss Payments bank codes by the names customers write their banks with.
+const BANK_CODES: &[(&str, &str)] = &[
+    ("단위농협", "12"),
+    ("지역농협", "12"),
+    ("농협", "11"),
+    ("NH", "11"),
+    ("국민", "06"),
+    ("KB", "06"),
+    ("기업", "03"),
+    ("IBK", "03"),
+    ("신한", "88"),
+    ("우리", "20"),
+    ("하나", "81"),
+    ("SC", "23"),
+    ("제일", "23"),
+    ("씨티", "27"),
+    ("산업", "02"),
+    ("수협", "07"),
+    ("대구", "31"),
+    ("IM", "31"),
+    ("부산", "32"),
+    ("광주", "34"),
+    ("제주", "35"),
+    ("전북", "37"),
+    ("경남", "39"),
+    ("새마을", "45"),
+    ("신협", "48"),
+    ("저축", "50"),
+    ("HSBC", "54"),
+    ("산림", "64"),
+    ("우체국", "71"),
+    ("케이", "89"),
+    ("카카오", "90"),
+    ("토스", "92"),
+];
+
+/// Reads the bank account from a refund account in the form of `<bank> <account number>
+/// <holder name>`.
+fn parse_refund_account(value: &str) -> Option<RefundReceiveAccount> {
+    let tokens = value.split_whitespace().collect::<Vec<_>>();
+    let [bank, number @ .., holder_name] = tokens.as_slice() else {
+        return None;
+    };
+
+    let bank = bank.to_uppercase();
+    let (_, bank_code) = BANK_CODES.iter().find(|(name, _)| bank.starts_with(name))?;
+
+    if number
+        .iter()
+        .any(|v| v.chars().any(|c| !c.is_ascii_digit() && c != '-'))
+    {
+        return None;
+    }
+    let account_number = number
+        .iter()
+        .flat_map(|v| v.chars())
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    if account_number.is_empty() {
+        return None;
+    }
+
+    Some(RefundReceiveAccount {
+        bank: (*bank_code).to_owned(),
+        account_number,
+        holder_name: (*holder_name).to_owned(),
+    })
+}
+
+/// Bank account to send the refund of a virtual account payment to, taken from the refund
+/// account customers enter for cash payments. Other payment methods are refunded to where they
+/// have been paid from.
+pub fn refund_receive_account(
+    toss_tx: &TossPaymentsTransaction,
+    refund_account: Option<&str>,
+) -> Result<Option<RefundReceiveAccount>, Error> {
+    if toss_tx.virtual_account_due_at.is_none() {
+        return Ok(None);
+    }
+
+    let refund_account = refund_account.ok_or(Error::RefundAccountRequired)?;
+
+    parse_refund_account(refund_account)
+        .map(Some)
+        .ok_or(Error::InvalidRefundAccount)
+}
+
+/// Due date of the virtual account issued for the payment.
+pub fn virtual_account_due_at(
+    payment: &Payment,
+    timezone_config: &TimeZoneConfig,
+) -> Result<DateTime<Utc>, Error> {
+    // Due dates are given in the local time of Korea
+    payment
+        .virtual_account
+        .as_ref()
+        .and_then(|v| {
+            v.due_date
+                .and_local_timezone(timezone_config.timezone)
+                .single()
+        })
+        .map(|v| v.to_utc())
+        .ok_or(Error::PaymentFailed(String::from(
+            "가상계좌 정보를 찾을 수 없습니다.",
+        )))
+}
+
+async fn record_virtual_account(
+    connection: &mut SqliteConnection,
+    toss_tx: &TossPaymentsTransaction,
+    product_id: &ProductId,
+    payment: &Payment,
+    due_at: &DateTime<Utc>,
+) -> Result<(), Error> {
+    let Some(virtual_account) = payment.virtual_account.as_ref() else {
+        return Err(Error::PaymentFailed(String::from(
+            "가상계좌 정보를 찾을 수 없습니다.",
+        )));
+    };
+
+    issue_toss_payments_virtual_account(
+        &mut *connection,
+        &toss_tx.id,
+        product_id,
+        &payment.payment_key,
+        &virtual_account.bank_code,
+        &virtual_account.account_number,
+        due_at,
+        payment.secret.as_deref(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn book_temporary_reservation(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_tx: &TossPaymentsTransaction,
+    temporary_reservation: &AdhocReservation,
+    is_confirmed: bool,
+    calendar_updates: &mut CalendarUpdates,
+) -> Result<Booking, Error> {
+    expire_adhoc_reservation(&mut *connection, now, &temporary_reservation.id).await?;
+    calendar_updates.delete_adhoc_reservation(temporary_reservation.id);
+
+    let booking_id = create_booking(
+        &mut *connection,
+        now,
+        &temporary_reservation.unit_id,
+        &toss_tx.user_id,
+        &temporary_reservation.customer.id(),
+        &temporary_reservation.time_from,
+        &temporary_reservation.time_to,
+        is_confirmed,
+    )
+    .await?;
+
+    let customers = match &temporary_reservation.customer {
+        Identity::User(u) => {
+            if u.id != toss_tx.user_id {
+                return Err(Error::UserNotFound);
+            }
+            vec![u.clone()]
+        }
+        Identity::Group(g) => {
+            if !is_member_of(&mut *connection, &g.id, &toss_tx.user_id).await? {
+                return Err(Error::GroupNotFound);
+            }
+            get_group_members(&mut *connection, &g.id).await?
+        }
+    };
+
+    let booking = get_booking_with_user_id(&mut *connection, &booking_id, &toss_tx.user_id)
+        .await?
+        .ok_or(Error::BookingNotFound)?;
+
+    enqueue_webhook_event(
+        &mut *connection,
+        now,
+        WebhookEvent::BookingCreated {
+            booking: (&booking).into(),
+        },
+    )
+    .await?;
+
+    calendar_updates.register_booking(booking.clone(), customers);
+
+    Ok(booking)
+}
+
+/// Turns the temporary reservation into a booking once its payment has been approved.
+pub async fn complete_booking_payment(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_tx: &TossPaymentsTransaction,
+    temporary_reservation: &AdhocReservation,
+    payment_key: &str,
+    calendar_updates: &mut CalendarUpdates,
+    timezone_config: &TimeZoneConfig,
+    notification_sender: &NotificationSender,
+    messaging_service: &MessagingService,
+) -> Result<Booking, Error> {
+    let booking = book_temporary_reservation(
+        &mut *connection,
+        now,
+        toss_tx,
+        temporary_reservation,
+        true,
+        calendar_updates,
+    )
+    .await?;
+
+    let product_id = ProductId::from(booking.id);
+
+    confirm_toss_payments_transaction(&mut *connection, now, &toss_tx.id, &product_id, payment_key)
+        .await?;
+    attach_coupon_redemption(&mut *connection, &toss_tx.id, &product_id).await?;
+
+    let desired_minutes =
+        (temporary_reservation.time_to - temporary_reservation.time_from).num_minutes();
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "New booking by {}: {} ({} minutes)",
+            temporary_reservation.customer.name(),
+            timezone_config.convert(temporary_reservation.time_from),
+            desired_minutes
+        ),
+    );
+
+    messaging_service
+        .send_confirmation(&mut *connection, now, booking.clone())
+        .await?;
+
+    Ok(booking)
+}
+
+/// Holds the slot of the temporary reservation with an unconfirmed booking while the virtual
+/// account issued for the payment waits for the deposit.
+pub async fn hold_booking_for_deposit(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_tx: &TossPaymentsTransaction,
+    temporary_reservation: &AdhocReservation,
+    payment: &Payment,
+    calendar_updates: &mut CalendarUpdates,
+    timezone_config: &TimeZoneConfig,
+    notification_sender: &NotificationSender,
+) -> Result<Booking, Error> {
+    let due_at = virtual_account_due_at(payment, timezone_config)?;
+    if due_at > temporary_reservation.time_from {
+        return Err(Error::VirtualAccountNotAllowed);
+    }
+
+    let booking = book_temporary_reservation(
+        &mut *connection,
+        now,
+        toss_tx,
+        temporary_reservation,
+        false,
+        calendar_updates,
+    )
+    .await?;
+
+    let product_id = ProductId::from(booking.id);
+
+    record_virtual_account(&mut *connection, toss_tx, &product_id, payment, &due_at).await?;
+    attach_coupon_redemption(&mut *connection, &toss_tx.id, &product_id).await?;
+
+    let desired_minutes =
+        (temporary_reservation.time_to - temporary_reservation.time_from).num_minutes();
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "New booking by {} awaiting deposit until {}: {} ({} minutes)",
+            temporary_reservation.customer.name(),
+            timezone_config.convert(due_at),
+            timezone_config.convert(temporary_reservation.time_from),
+            desired_minutes
+        ),
+    );
+
+    Ok(booking)
+}
+
+/// Records the approved payment of a booking amendment which has already been applied to the
+/// booking.
+pub async fn complete_amend_payment(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_tx: &TossPaymentsTransaction,
+    booking: &Booking,
+    booking_amendment: &BookingAmendment,
+    payment_key: &str,
+    calendar_updates: &mut CalendarUpdates,
+    timezone_config: &TimeZoneConfig,
+    notification_sender: &NotificationSender,
+    messaging_service: &MessagingService,
+) -> Result<(), Error> {
+    let product_id = ProductId::from(booking_amendment.id);
+    let _ = confirm_toss_payments_transaction(
+        &mut *connection,
+        now,
+        &toss_tx.id,
+        &product_id,
+        payment_key,
+    )
+    .await?;
+
+    if let Err(e) = messaging_service
+        .send_amend_notification(
+            &mut *connection,
+            now,
+            booking.clone(),
+            booking_amendment.desired_time_from,
+            booking_amendment.desired_time_to,
+        )
+        .await
+    {
+        log::warn!("Could not send amend notification to customers: {e}");
+    }
+
+    let mut updated_booking = booking.clone();
+    updated_booking.time_from = booking_amendment.desired_time_from;
+    updated_booking.time_to = booking_amendment.desired_time_to;
+    calendar_updates.update_booking_time(updated_booking);
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "Booking by {} moved from {} - {} to {} - {}",
+            booking.customer.name(),
+            timezone_config.convert(booking.time_from),
+            timezone_config.convert(booking.time_to),
+            timezone_config.convert(booking_amendment.desired_time_from),
+            timezone_config.convert(booking_amendment.desired_time_to),
+        ),
+    );
+
+    Ok(())
+}
+
+/// Credits the hours of the hour pack once its payment has been approved.
+pub async fn complete_hour_pack_payment(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    toss_tx: &TossPaymentsTransaction,
+    hour_pack: &HourPack,
+    payment_key: &str,
+    notification_sender: &NotificationSender,
+) -> Result<(), Error> {
+    let _ = confirm_hour_pack(&mut *connection, now, &hour_pack.id).await?;
+    create_hour_balance_entry(
+        &mut *connection,
+        now,
+        &hour_pack.customer_id,
+        hour_pack.minutes,
+        Some(&hour_pack.id),
+        None,
+    )
+    .await?;
+
+    let product_id = ProductId::from(hour_pack.id);
+    let _ = confirm_toss_payments_transaction(
+        &mut *connection,
+        now,
+        &toss_tx.id,
+        &product_id,
+        payment_key,
+    )
+    .await?;
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "Hour pack purchased: {} ({} won)",
+            hour_pack.name, hour_pack.price
+        ),
+    );
+
+    Ok(())
+}
+
+/// Keeps the hour pack pending while the virtual account issued for the payment waits for the
+/// deposit.
+pub async fn hold_hour_pack_for_deposit(
+    connection: &mut SqliteConnection,
+    toss_tx: &TossPaymentsTransaction,
+    hour_pack: &HourPack,
+    payment: &Payment,
+    timezone_config: &TimeZoneConfig,
+    notification_sender: &NotificationSender,
+) -> Result<(), Error> {
+    let due_at = virtual_account_due_at(payment, timezone_config)?;
+
+    record_virtual_account(
+        &mut *connection,
+        toss_tx,
+        &ProductId::from(hour_pack.id),
+        payment,
+        &due_at,
+    )
+    .await?;
+
+    notification_sender.enqueue(
+        Priority::High,
+        format!(
+            "Hour pack awaiting deposit until {}: {} ({} won)",
+            timezone_config.convert(due_at),
+            hour_pack.name,
+            hour_pack.price
+        ),
+    );
+
+    Ok(())
+}
+
+/// Brings local Toss Payments transactions in line with the payments on Toss Payments. This
+/// covers approvals whose confirmation request never completed on our side, and cancellations
+/// made from the Toss Payments console.
+pub struct TossReconciliationService {
+    config: TossReconciliationConfig,
+    toss_payments_client: Arc<TossPaymentsClient>,
+    timezone_config: TimeZoneConfig,
+    messaging_service: MessagingService,
+    notification_sender: NotificationSender,
+    waitlist_service: WaitlistService,
+    calendar_service: Option<CalendarService>,
+}
+
+impl TossReconciliationService {
+    pub fn new(
+        config: &TossReconciliationConfig,
+        toss_payments_client: Arc<TossPaymentsClient>,
+        timezone_config: TimeZoneConfig,
+        messaging_service: MessagingService,
+        notification_sender: NotificationSender,
+        waitlist_service: WaitlistService,
+        calendar_service: Option<CalendarService>,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            toss_payments_client,
+            timezone_config,
+            messaging_service,
+            notification_sender,
+            waitlist_service,
+            calendar_service,
+        }
+    }
+
+    pub async fn run(&self, database: &SqlitePool, now: DateTime<Utc>) -> Result<(), Error> {
+        let since = now - TimeDelta::days(self.config.lookback_days);
+
+        let toss_txs = {
+            let mut connection = database.acquire().await?;
+            let mut toss_txs =
+                get_toss_payments_transactions_created_since(&mut connection, &since).await?;
+
+            // Virtual accounts may be due long after the order has been created
+            toss_txs.extend(
+                get_overdue_toss_virtual_accounts(&mut connection, &now)
+                    .await?
+                    .into_iter()
+                    .filter(|v| v.created_at < since),
+            );
+
+            toss_txs
+        };
+
+        for toss_tx in toss_txs {
+            // Fully refunded payments cannot change any more, and neither can closed virtual
+            // accounts
+            if toss_tx.refund_price.is_some_and(|v| v >= toss_tx.price)
+                || (toss_tx.refunded_at.is_some() && toss_tx.confirmed_at.is_none())
+            {
+                continue;
+            }
+
+            if let Err(e) = self.reconcile(database, now, &toss_tx.id).await {
+                log::error!("Could not reconcile Toss payment {}: {e}", toss_tx.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Repairs the local transaction of the order after the payment state on Toss Payments.
+    /// Repairs only depend on the current states on both sides, so that repeated calls are
+    /// harmless.
+    pub async fn reconcile(
+        &self,
+        database: &SqlitePool,
+        now: DateTime<Utc>,
+        order_id: &ForeignPaymentId,
+    ) -> Result<(), Error> {
+        let payment = match self
+            .toss_payments_client
+            .get_payment_by_order_id(order_id)
+            .await
+        {
+            Ok(v) => v,
+            // Orders abandoned before reaching the payment window are unknown to Toss Payments
+            Err(TossPaymentsError::Remote { code, .. }) if code == "NOT_FOUND_PAYMENT" => {
+                return Ok(());
+            }
+            Err(e) => return Err(Error::Internal(Box::new(e))),
+        };
+
+        let mut tx = database.begin().await?;
+        let mut calendar_updates = CalendarUpdates::default();
+
+        let Some(toss_tx) = get_toss_payments_transaction_by_id(&mut tx, order_id).await? else {
+            return Ok(());
+        };
+
+        // Leave recent payments to the confirmation request from the browser
+        let is_settled = toss_tx.created_at + TimeDelta::minutes(self.config.grace_minutes) <= now;
+
+        // The customer has authorized the payment, but the confirmation request never arrived.
+        // Authorizations for orders which can no longer be used lapse without being charged.
+        let payment = if matches!(payment.status, PaymentStatus::InProgress)
+            && is_settled
+            && !is_in_effect(&toss_tx.confirmed_at, &now)
+        {
+            if self.has_order_started(&mut tx, &now, &toss_tx).await? {
+                return Ok(());
+            }
+
+            let payment = self
+                .toss_payments_client
+                .confirm_payment(order_id, toss_tx.price, &payment.payment_key)
+                .await
+                .map_err(|e| Error::Internal(Box::new(e)))?;
+            log::info!("Confirmed authorized Toss payment {}", toss_tx.id);
+
+            payment
+        } else {
+            payment
+        };
+
+        let is_approved = matches!(
+            payment.status,
+            PaymentStatus::Done | PaymentStatus::PartialCanceled
+        );
+
+        if toss_tx.virtual_account_due_at.is_some() {
+            // Closed virtual accounts have been released without any deposit
+            if !is_in_effect(&toss_tx.confirmed_at, &now) && toss_tx.refunded_at.is_none() {
+                self.reconcile_virtual_account(
+                    &mut tx,
+                    &now,
+                    &toss_tx,
+                    &payment,
+                    &mut calendar_updates,
+                )
+                .await?;
+            }
+        } else if is_approved && !is_in_effect(&toss_tx.confirmed_at, &now) {
+            if !is_settled {
+                return Ok(());
+            }
+
+            self.complete_missed_payment(&mut tx, &now, &toss_tx, &payment, &mut calendar_updates)
+                .await?;
+        } else if matches!(payment.status, PaymentStatus::WaitingForDeposit) && is_settled {
+            // The virtual account has been issued, but the order could not be held for it
+            self.toss_payments_client
+                .cancel_payment(
+                    &payment.payment_key,
+                    "예약을 확정할 수 없어 가상계좌가 취소되었습니다.",
+                    None,
+                    None,
+                )
+                .await
+                .map_err(|e| Error::Internal(Box::new(e)))?;
+
+            refund_toss_payments(&mut tx, &now, &toss_tx.id, 0).await?;
+            delete_pending_coupon_redemption(&mut tx, &toss_tx.id).await?;
+
+            self.notification_sender.enqueue(
+                Priority::High,
+                format!(
+                    "Virtual account of Toss payment {} could not be held and has been canceled",
+                    toss_tx.id
+                ),
+            );
+        }
+
+        let Some(toss_tx) = get_toss_payments_transaction_by_id(&mut tx, order_id).await? else {
+            return Ok(());
+        };
+
+        let canceled_amount = payment.total_amount - payment.balance_amount;
+        if is_in_effect(&toss_tx.confirmed_at, &now)
+            && canceled_amount > toss_tx.refund_price.unwrap_or(0)
+        {
+            self.record_cancellation(
+                &mut tx,
+                &now,
+                &toss_tx,
+                canceled_amount,
+                matches!(payment.status, PaymentStatus::Canceled),
+                &mut calendar_updates,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        calendar_updates.apply(&self.calendar_service).await;
+
+        Ok(())
+    }
+
+    /// Whether the booking paid for by the order has already begun, so that it is of no use to
+    /// the customer any more.
+    async fn has_order_started(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+    ) -> Result<bool, Error> {
+        if let Some(temporary_reservation_id) = toss_tx.temporary_reservation_id {
+            return Ok(
+                get_adhoc_reservation(&mut *connection, &temporary_reservation_id)
+                    .await?
+                    .is_none_or(|v| v.time_from <= *now),
+            );
+        }
+
+        let Some(product_id) = toss_tx.product_id.as_ref() else {
+            return Ok(false);
+        };
+
+        Ok(match get_product(&mut *connection, product_id).await? {
+            Some(Product::Amendment(booking_amendment)) => {
+                booking_amendment.original_time_from <= *now
+                    || booking_amendment.desired_time_from <= *now
+            }
+            _ => false,
+        })
+    }
+
+    async fn complete_missed_payment(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        payment: &Payment,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        let mut savepoint = connection.begin().await?;
+        // Kept apart until the savepoint is committed, as the payment may be given back instead
+        let mut savepoint_calendar_updates = CalendarUpdates::default();
+
+        let result = if let Some(temporary_reservation_id) = toss_tx.temporary_reservation_id {
+            match get_adhoc_reservation(&mut savepoint, &temporary_reservation_id).await? {
+                // Bookings are not made after they would have started
+                Some(temporary_reservation) if temporary_reservation.time_from <= *now => {
+                    Err(Error::OngoingBookingNotModifiable)
+                }
+                Some(temporary_reservation) => complete_booking_payment(
+                    &mut savepoint,
+                    now,
+                    toss_tx,
+                    &temporary_reservation,
+                    &payment.payment_key,
+                    &mut savepoint_calendar_updates,
+                    &self.timezone_config,
+                    &self.notification_sender,
+                    &self.messaging_service,
+                )
+                .await
+                .map(|_| ()),
+                None => Err(Error::TimeRangeOccupied),
+            }
+        } else if let Some(product_id) = toss_tx.product_id {
+            match get_product(&mut savepoint, &product_id).await? {
+                Some(Product::Amendment(booking_amendment)) => {
+                    self.complete_missed_amend_payment(
+                        &mut savepoint,
+                        now,
+                        toss_tx,
+                        &booking_amendment,
+                        &payment.payment_key,
+                        &mut savepoint_calendar_updates,
+                    )
+                    .await
+                }
+                Some(Product::HourPack(hour_pack))
+                    if !is_in_effect(&hour_pack.canceled_at, now) =>
+                {
+                    complete_hour_pack_payment(
+                        &mut savepoint,
+                        now,
+                        toss_tx,
+                        &hour_pack,
+                        &payment.payment_key,
+                        &self.notification_sender,
+                    )
+                    .await
+                }
+                _ => Err(Error::ForeignPaymentNotFound),
+            }
+        } else {
+            Err(Error::ForeignPaymentNotFound)
+        };
+
+        let reason = match result {
+            Ok(()) => {
+                savepoint.commit().await?;
+                calendar_updates.append(savepoint_calendar_updates);
+                log::info!("Recovered confirmation of Toss payment {}", toss_tx.id);
+
+                return Ok(());
+            }
+            Err(e @ (Error::Sqlx(_) | Error::Internal(_))) => return Err(e),
+            Err(e) => e.to_string(),
+        };
+
+        // The order cannot be fulfilled any more, e.g. the slot has been taken after the temporary
+        // reservation expired. Give the money back instead.
+        drop(savepoint);
+
+        self.toss_payments_client
+            .cancel_payment(
+                &payment.payment_key,
+                "예약을 확정할 수 없어 결제가 취소되었습니다.",
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| Error::Internal(Box::new(e)))?;
+
+        refund_toss_payments(&mut *connection, now, &toss_tx.id, payment.total_amount).await?;
+        delete_pending_coupon_redemption(&mut *connection, &toss_tx.id).await?;
+
+        self.notification_sender.enqueue(
+            Priority::High,
+            format!(
+                "Toss payment {} could not be fulfilled and has been canceled: {reason}",
+                toss_tx.id
+            ),
+        );
+
+        Ok(())
+    }
+
+    async fn complete_missed_amend_payment(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        booking_amendment: &BookingAmendment,
+        payment_key: &str,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        let booking = get_booking(&mut *connection, &booking_amendment.booking_id)
+            .await?
+            .ok_or(Error::BookingNotFound)?;
+
+        if is_in_effect(&booking.canceled_at, now)
+            || is_in_effect(&booking_amendment.confirmed_at, now)
+            || is_in_effect(&booking_amendment.canceled_at, now)
+        {
+            return Err(Error::BookingAmendmentNotFound);
+        }
+
+        if booking.time_from <= *now || booking_amendment.desired_time_from <= *now {
+            return Err(Error::OngoingBookingNotModifiable);
+        }
+
+        reschedule_booking(
+            &mut *connection,
+            now,
+            &booking.id,
+            booking_amendment
+                .desired_unit_id
+                .as_ref()
+                .unwrap_or(&booking.unit_id),
+            &booking_amendment.desired_time_from,
+            &booking_amendment.desired_time_to,
+        )
+        .await?;
+        confirm_booking_amendment(&mut *connection, now, &booking_amendment.id).await?;
+
+        complete_amend_payment(
+            &mut *connection,
+            now,
+            toss_tx,
+            &booking,
+            booking_amendment,
+            payment_key,
+            calendar_updates,
+            &self.timezone_config,
+            &self.notification_sender,
+            &self.messaging_service,
+        )
+        .await
+    }
+
+    /// Follows the virtual account of the order until it is paid into or closed.
+    async fn reconcile_virtual_account(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        payment: &Payment,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        match payment.status {
+            PaymentStatus::Done | PaymentStatus::PartialCanceled => {
+                self.complete_deposit(connection, now, toss_tx, payment)
+                    .await
+            }
+            PaymentStatus::WaitingForDeposit => {
+                let is_expired = payment.virtual_account.as_ref().is_some_and(|v| v.expired)
+                    || toss_tx.virtual_account_due_at.is_some_and(|v| v <= *now);
+                if !is_expired {
+                    return Ok(());
+                }
+
+                // Close the account so that late deposits are turned down by the bank
+                self.toss_payments_client
+                    .cancel_payment(
+                        &payment.payment_key,
+                        "입금 기한이 지나 가상계좌가 취소되었습니다.",
+                        None,
+                        None,
+                    )
+                    .await
+                    .map_err(|e| Error::Internal(Box::new(e)))?;
+
+                self.release_deposit_hold(connection, now, toss_tx, calendar_updates)
+                    .await
+            }
+            _ => {
+                self.release_deposit_hold(connection, now, toss_tx, calendar_updates)
+                    .await
+            }
+        }
+    }
+
+    async fn complete_deposit(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        payment: &Payment,
+    ) -> Result<(), Error> {
+        let Some(product_id) = toss_tx.product_id.as_ref() else {
+            return Err(Error::ForeignPaymentNotFound);
+        };
+
+        match get_product(&mut *connection, product_id).await? {
+            Some(Product::Booking(booking)) => {
+                confirm_toss_payments_transaction(
+                    &mut *connection,
+                    now,
+                    &toss_tx.id,
+                    product_id,
+                    &payment.payment_key,
+                )
+                .await?;
+
+                if is_in_effect(&booking.canceled_at, now) {
+                    // Refunds of deposits need the bank account of the customer
+                    self.notification_sender.enqueue(
+                        Priority::High,
+                        format!(
+                            "Deposit of {} won received for canceled booking by {}: {}. Refund it manually.",
+                            toss_tx.price,
+                            booking.customer.name(),
+                            self.timezone_config.convert(booking.time_from),
+                        ),
+                    );
+
+                    return Ok(());
+                }
+
+                if confirm_booking(&mut *connection, &booking.id, now).await? {
+                    self.messaging_service
+                        .send_confirmation(&mut *connection, now, booking.clone())
+                        .await?;
+                }
+
+                self.notification_sender.enqueue(
+                    Priority::High,
+                    format!(
+                        "Deposit received for booking by {}: {}",
+                        booking.customer.name(),
+                        self.timezone_config.convert(booking.time_from),
+                    ),
+                );
+
+                Ok(())
+            }
+            Some(Product::HourPack(hour_pack)) => {
+                complete_hour_pack_payment(
+                    &mut *connection,
+                    now,
+                    toss_tx,
+                    &hour_pack,
+                    &payment.payment_key,
+                    &self.notification_sender,
+                )
+                .await
+            }
+            _ => Err(Error::ForeignPaymentNotFound),
+        }
+    }
+
+    /// Gives up the booking or the hour pack held for a virtual account which has not been paid
+    /// into.
+    async fn release_deposit_hold(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        // Nothing has been paid, so the transaction is closed without a refund
+        refund_toss_payments(&mut *connection, now, &toss_tx.id, 0).await?;
+
+        let product = if let Some(product_id) = &toss_tx.product_id {
+            get_product(&mut *connection, product_id).await?
+        } else {
+            None
+        };
+
+        match product {
+            Some(Product::Booking(booking)) => {
+                if !cancel_booking(&mut *connection, now, &booking.id).await? {
+                    return Ok(());
+                }
+
+                cancel_coupon_redemption(&mut *connection, now, &ProductId::from(booking.id))
+                    .await?;
+
+                self.waitlist_service
+                    .release(
+                        &mut *connection,
+                        now,
+                        &booking.unit_id,
+                        &booking.time_from,
+                        &booking.time_to,
+                    )
+                    .await?;
+
+                self.messaging_service
+                    .send_payment_expired_notification(&mut *connection, now, booking.clone())
+                    .await?;
+
+                calendar_updates.delete_booking(booking.id);
+
+                self.notification_sender.enqueue(
+                    Priority::High,
+                    format!(
+                        "Booking by {} released without deposit: {}",
+                        booking.customer.name(),
+                        self.timezone_config.convert(booking.time_from),
+                    ),
+                );
+            }
+            Some(Product::HourPack(hour_pack)) => {
+                cancel_hour_pack(&mut *connection, now, &hour_pack.id).await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    async fn record_cancellation(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        toss_tx: &TossPaymentsTransaction,
+        canceled_amount: i64,
+        is_fully_canceled: bool,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        refund_toss_payments(&mut *connection, now, &toss_tx.id, canceled_amount).await?;
+
+        let product = if let Some(product_id) = &toss_tx.product_id {
+            get_product(&mut *connection, product_id).await?
+        } else {
+            None
+        };
+
+        if is_fully_canceled {
+            match &product {
+                Some(Product::Booking(booking)) if !is_in_effect(&booking.canceled_at, now) => {
+                    self.cancel_refunded_booking(&mut *connection, now, booking, calendar_updates)
+                        .await?;
+                }
+                Some(Product::HourPack(hour_pack))
+                    if !is_in_effect(&hour_pack.canceled_at, now) =>
+                {
+                    // Take back the credited hours, unless they have already been used
+                    if get_hour_balance(&mut *connection, &hour_pack.customer_id).await?
+                        >= hour_pack.minutes
+                    {
+                        create_hour_balance_entry(
+                            &mut *connection,
+                            now,
+                            &hour_pack.customer_id,
+                            -hour_pack.minutes,
+                            Some(&hour_pack.id),
+                            None,
+                        )
+                        .await?;
+                    }
+
+                    cancel_hour_pack(&mut *connection, now, &hour_pack.id).await?;
+                }
+                _ => {}
+            }
+        }
+
+        self.notification_sender.enqueue(
+            Priority::High,
+            format!(
+                "Toss payment {} has been {} outside of the service ({} won)",
+                toss_tx.id,
+                if is_fully_canceled {
+                    "canceled"
+                } else {
+                    "partially canceled"
+                },
+                canceled_amount,
+            ),
+        );
+
+        Ok(())
+    }
+
+    async fn cancel_refunded_booking(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        booking: &Booking,
+        calendar_updates: &mut CalendarUpdates,
+    ) -> Result<(), Error> {
+        if !cancel_booking(&mut *connection, now, &booking.id).await? {
+            return Ok(());
+        }
+
+        cancel_coupon_redemption(&mut *connection, now, &ProductId::from(booking.id)).await?;
+
+        self.waitlist_service
+            .release(
+                &mut *connection,
+                now,
+                &booking.unit_id,
+                &booking.time_from,
+                &booking.time_to,
+            )
+            .await?;
+
+        self.messaging_service
+            .send_cancellation(&mut *connection, now, booking.clone(), 100)
+            .await?;
+
+        calendar_updates.delete_booking(booking.id);
+
+        Ok(())
+    }
+}
+
+pub fn spawn_toss_reconciliation_task(
+    database: SqlitePool,
+    service: Arc<TossReconciliationService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(
+            TimeDelta::minutes(service.config.interval_minutes.max(1))
+                .to_std()
+                .unwrap_or(std::time::Duration::from_secs(1800)),
+        );
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = service.run(&database, Utc::now()).await {
+                log::error!("Could not reconcile Toss payments: {e}");
+            }
+        }
+    })
+}
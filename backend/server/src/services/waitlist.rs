@@ -0,0 +1,175 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use dxe_data::entities::WaitlistEntry;
+use dxe_data::queries::booking::{create_adhoc_reservation, is_booking_available};
+use dxe_data::queries::identity::get_identity;
+use dxe_data::queries::user::get_user_by_id;
+use dxe_data::queries::waitlist::{
+    delete_waitlist_entry, get_finished_waitlist_holds, get_pending_waitlist_entries,
+    update_waitlist_entry_notified,
+};
+use dxe_types::UnitId;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::config::{TimeZoneConfig, WaitlistConfig};
+use crate::models::Error;
+use crate::services::messaging::MessagingService;
+use crate::services::notification::{NotificationSender, Priority};
+
+#[derive(Clone)]
+pub struct WaitlistService {
+    hold_time: TimeDelta,
+    check_interval: TimeDelta,
+    timezone_config: TimeZoneConfig,
+    messaging_service: MessagingService,
+    notification_sender: NotificationSender,
+}
+
+impl WaitlistService {
+    pub fn new(
+        config: &WaitlistConfig,
+        timezone_config: TimeZoneConfig,
+        messaging_service: MessagingService,
+        notification_sender: NotificationSender,
+    ) -> Self {
+        Self {
+            hold_time: TimeDelta::minutes(config.hold_minutes),
+            check_interval: TimeDelta::minutes(config.check_interval_minutes.max(1)),
+            timezone_config,
+            messaging_service,
+            notification_sender,
+        }
+    }
+
+    /// Offers a slot that has just been freed to the earliest waiter whose desired time range
+    /// overlaps it and is now entirely free. The waiter gets a temporary adhoc reservation as a
+    /// priority hold.
+    pub async fn release(
+        &self,
+        connection: &mut SqliteConnection,
+        now: &DateTime<Utc>,
+        unit_id: &UnitId,
+        time_from: &DateTime<Utc>,
+        time_to: &DateTime<Utc>,
+    ) -> Result<Option<WaitlistEntry>, Error> {
+        let entries =
+            get_pending_waitlist_entries(&mut *connection, now, unit_id, time_from, time_to)
+                .await?;
+
+        for entry in entries {
+            if !is_booking_available(
+                &mut *connection,
+                now,
+                &entry.unit_id,
+                &entry.time_from,
+                &entry.time_to,
+                None,
+                None,
+            )
+            .await?
+            {
+                continue;
+            }
+
+            let Some(user) = get_user_by_id(&mut *connection, &entry.user_id, now).await? else {
+                continue;
+            };
+            let Some(customer) = get_identity(&mut *connection, now, &entry.customer_id).await?
+            else {
+                continue;
+            };
+
+            let expires_at = std::cmp::min(*now + self.hold_time, entry.time_from);
+
+            let adhoc_reservation_id = create_adhoc_reservation(
+                &mut *connection,
+                now,
+                &entry.unit_id,
+                &entry.customer_id,
+                &entry.user_id,
+                &entry.time_from,
+                &entry.time_to,
+                &Some(String::from("Priority hold for waitlist")),
+                &Some(expires_at),
+            )
+            .await?;
+
+            update_waitlist_entry_notified(&mut *connection, now, &entry.id, &adhoc_reservation_id)
+                .await?;
+
+            self.messaging_service
+                .send_waitlist_offer(
+                    &mut *connection,
+                    now,
+                    &user,
+                    customer.name().to_owned(),
+                    entry.unit_id.clone(),
+                    entry.time_from,
+                    entry.time_to,
+                    expires_at,
+                )
+                .await?;
+
+            self.notification_sender.enqueue(
+                Priority::Default,
+                format!(
+                    "Freed slot offered to waitlisted {}: {} ({} minutes)",
+                    customer.name(),
+                    self.timezone_config.convert(entry.time_from),
+                    (entry.time_to - entry.time_from).num_minutes(),
+                ),
+            );
+
+            return Ok(Some(entry));
+        }
+
+        Ok(None)
+    }
+
+    /// Closes the entries whose priority hold is over and offers the held range to the next
+    /// waiter, so that the queue keeps moving when a hold lapses unclaimed.
+    pub async fn advance(&self, database: &SqlitePool, now: DateTime<Utc>) -> Result<(), Error> {
+        let mut tx = database.begin().await?;
+
+        for entry in get_finished_waitlist_holds(&mut tx, &now).await? {
+            if !delete_waitlist_entry(&mut tx, &now, &entry.id).await? {
+                continue;
+            }
+
+            // A claimed hold has turned into a booking and is no longer available to others
+            self.release(
+                &mut tx,
+                &now,
+                &entry.unit_id,
+                &entry.time_from,
+                &entry.time_to,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+pub fn spawn_waitlist_task(
+    database: SqlitePool,
+    service: WaitlistService,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(
+            service
+                .check_interval
+                .to_std()
+                .unwrap_or(std::time::Duration::from_secs(60)),
+        );
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = service.advance(&database, Utc::now()).await {
+                log::error!("Could not advance the waitlist: {e}");
+            }
+        }
+    })
+}
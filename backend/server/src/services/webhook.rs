@@ -0,0 +1,328 @@
+use chrono::{DateTime, Utc};
+use dxe_data::entities::{AudioRecording, Booking, WebhookDelivery};
+use dxe_data::queries::webhook::{
+    create_webhook_delivery, create_webhook_delivery_log, get_due_webhook_deliveries,
+    get_webhook_subscription, get_webhook_subscriptions, mark_webhook_delivery_attempt_failed,
+    mark_webhook_delivery_delivered,
+};
+use dxe_types::{BookingId, IdentityId, TelemetryType, UnitId, UserId, WebhookEventType};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::config::WebhookSubscriptionConfig;
+use crate::models::Error;
+
+const EVENT_HEADER: &str = "X-Dxe-Event";
+const DELIVERY_HEADER: &str = "X-Dxe-Delivery";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookingPayload {
+    pub id: BookingId,
+    pub unit_id: UnitId,
+    pub holder_id: UserId,
+    pub customer_id: IdentityId,
+    pub customer_name: String,
+    pub time_from: DateTime<Utc>,
+    pub time_to: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub canceled_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Booking> for BookingPayload {
+    fn from(value: &Booking) -> Self {
+        Self {
+            id: value.id,
+            unit_id: value.unit_id.clone(),
+            holder_id: value.holder.id,
+            customer_id: value.customer.id(),
+            customer_name: value.customer.name().to_owned(),
+            time_from: value.time_from,
+            time_to: value.time_to,
+            created_at: value.created_at,
+            confirmed_at: value.confirmed_at,
+            canceled_at: value.canceled_at,
+        }
+    }
+}
+
+impl BookingPayload {
+    /// Booking which is being canceled, as it may have been read before that.
+    pub fn canceled(booking: &Booking, now: &DateTime<Utc>) -> Self {
+        Self {
+            canceled_at: Some(booking.canceled_at.unwrap_or(*now)),
+            ..booking.into()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioRecordingPayload {
+    pub url: String,
+    pub expires_in: Option<DateTime<Utc>>,
+}
+
+impl From<&AudioRecording> for AudioRecordingPayload {
+    fn from(value: &AudioRecording) -> Self {
+        Self {
+            url: value.url.clone(),
+            expires_in: value.expires_in,
+        }
+    }
+}
+
+/// Booking lifecycle event, sent to the subscriptions as `{"type", "data", "occurredAt"}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum WebhookEvent {
+    #[serde(rename_all = "camelCase")]
+    BookingCreated { booking: BookingPayload },
+    #[serde(rename_all = "camelCase")]
+    BookingConfirmed { booking: BookingPayload },
+    #[serde(rename_all = "camelCase")]
+    BookingAmended {
+        booking: BookingPayload,
+        new_time_from: DateTime<Utc>,
+        new_time_to: DateTime<Utc>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BookingCanceled {
+        booking: BookingPayload,
+        refund_rate: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    BookingRefunded {
+        booking: BookingPayload,
+        refunded_price: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    RecordingUploaded {
+        booking: BookingPayload,
+        audio_recording: AudioRecordingPayload,
+    },
+    #[serde(rename_all = "camelCase")]
+    TelemetryUploaded {
+        booking_id: BookingId,
+        telemetry_type: TelemetryType,
+    },
+}
+
+impl WebhookEvent {
+    pub fn event_type(&self) -> WebhookEventType {
+        match self {
+            Self::BookingCreated { .. } => WebhookEventType::BookingCreated,
+            Self::BookingConfirmed { .. } => WebhookEventType::BookingConfirmed,
+            Self::BookingAmended { .. } => WebhookEventType::BookingAmended,
+            Self::BookingCanceled { .. } => WebhookEventType::BookingCanceled,
+            Self::BookingRefunded { .. } => WebhookEventType::BookingRefunded,
+            Self::RecordingUploaded { .. } => WebhookEventType::RecordingUploaded,
+            Self::TelemetryUploaded { .. } => WebhookEventType::TelemetryUploaded,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload<'a> {
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+    occurred_at: DateTime<Utc>,
+}
+
+/// Queues the event for every subscription to its type, within the transaction of the caller.
+pub async fn enqueue_webhook_event(
+    connection: &mut SqliteConnection,
+    now: &DateTime<Utc>,
+    event: WebhookEvent,
+) -> Result<(), Error> {
+    let event_type = event.event_type();
+
+    let subscriptions = get_webhook_subscriptions(&mut *connection, now)
+        .await?
+        .into_iter()
+        .filter(|v| v.event_types.contains(&event_type))
+        .collect::<Vec<_>>();
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_string(&WebhookPayload {
+        event: &event,
+        occurred_at: *now,
+    })
+    .map_err(|e| Error::Internal(Box::new(e)))?;
+
+    for subscription in subscriptions {
+        create_webhook_delivery(
+            &mut *connection,
+            now,
+            &subscription.id,
+            event_type,
+            &payload,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+struct DeliveryError {
+    message: String,
+    /// Status of the response, if the subscriber has responded.
+    status_code: Option<i64>,
+}
+
+impl DeliveryError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            status_code: None,
+        }
+    }
+}
+
+/// Delivers the queued events to the subscriptions, retried with exponential backoff.
+pub struct WebhookDeliveryWorker {
+    config: WebhookSubscriptionConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookDeliveryWorker {
+    pub fn new(config: &WebhookSubscriptionConfig) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(config.timeout_seconds))
+                .build()?,
+        })
+    }
+
+    async fn deliver(
+        &self,
+        database: &SqlitePool,
+        delivery: &WebhookDelivery,
+    ) -> Result<i64, DeliveryError> {
+        let subscription = {
+            let mut connection = database
+                .acquire()
+                .await
+                .map_err(|e| DeliveryError::new(e.to_string()))?;
+            get_webhook_subscription(&mut connection, &Utc::now(), &delivery.subscription_id)
+                .await
+                .map_err(|e| DeliveryError::new(e.to_string()))?
+        };
+        let Some(subscription) = subscription else {
+            return Err(DeliveryError::new("Subscription has been deleted"));
+        };
+
+        let url = url::Url::parse(&subscription.url)
+            .map_err(|e| DeliveryError::new(format!("Invalid URL: {e}")))?;
+
+        let status = dxe_extern::webhook::post(
+            &self.client,
+            &url,
+            Some(subscription.secret.as_str()),
+            vec![
+                (EVENT_HEADER, delivery.event_type.name().to_owned()),
+                (DELIVERY_HEADER, delivery.id.to_string()),
+            ],
+            delivery.payload.clone().into_bytes(),
+        )
+        .await
+        .map_err(|e| DeliveryError::new(e.to_string()))?;
+
+        let status_code = status.as_u16() as i64;
+        if status.is_success() {
+            Ok(status_code)
+        } else {
+            Err(DeliveryError {
+                message: format!("Unexpected status: {status}"),
+                status_code: Some(status_code),
+            })
+        }
+    }
+
+    pub async fn run(&self, database: &SqlitePool) -> Result<(), Error> {
+        let deliveries = {
+            let mut connection = database.acquire().await?;
+            get_due_webhook_deliveries(&mut connection, &Utc::now(), self.config.outbox.batch_size)
+                .await?
+        };
+
+        for delivery in deliveries {
+            let result = self.deliver(database, &delivery).await;
+
+            let now = Utc::now();
+            let mut tx = database.begin().await?;
+
+            match result {
+                Ok(status_code) => {
+                    mark_webhook_delivery_delivered(&mut tx, &now, &delivery.id).await?;
+                    create_webhook_delivery_log(
+                        &mut tx,
+                        &now,
+                        &delivery.id,
+                        Some(status_code),
+                        None,
+                    )
+                    .await?;
+                }
+                Err(e) => {
+                    let attempts = delivery.attempts + 1;
+                    let next_attempt_at = if attempts < self.config.outbox.max_attempts {
+                        Some(now + self.config.outbox.backoff(attempts))
+                    } else {
+                        log::error!(
+                            "Giving up on webhook delivery {} after {attempts} attempts: {}",
+                            delivery.id,
+                            e.message
+                        );
+                        None
+                    };
+
+                    mark_webhook_delivery_attempt_failed(
+                        &mut tx,
+                        &now,
+                        &delivery.id,
+                        &e.message,
+                        next_attempt_at.as_ref(),
+                    )
+                    .await?;
+                    create_webhook_delivery_log(
+                        &mut tx,
+                        &now,
+                        &delivery.id,
+                        e.status_code,
+                        Some(&e.message),
+                    )
+                    .await?;
+                }
+            }
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn spawn_webhook_delivery_task(
+    database: SqlitePool,
+    worker: WebhookDeliveryWorker,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            worker.config.outbox.poll_interval_seconds.max(1),
+        ));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = worker.run(&database).await {
+                log::error!("Could not deliver webhook events: {e}");
+            }
+        }
+    })
+}
@@ -1,6 +1,11 @@
+use std::net::IpAddr;
+
 pub mod aes;
+pub mod credential;
 pub mod csv;
 pub mod datetime;
+pub mod ical;
+pub mod oauth;
 pub mod session;
 
 pub fn mask_identity(identity: String) -> String {
@@ -9,3 +14,129 @@ pub fn mask_identity(identity: String) -> String {
         .map(|(index, char)| if index == 0 { char } else { '·' })
         .collect()
 }
+
+/// Converts the phone number in either international (`+82 10-1234-5678`) or domestic format into
+/// digits only, as in `01012345678`.
+pub fn normalize_phone_number(phone_number: &str) -> Option<String> {
+    let digits = phone_number
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+
+    let digits = if phone_number.trim_start().starts_with('+') {
+        format!("0{}", digits.strip_prefix("82")?)
+    } else {
+        digits
+    };
+
+    if digits.starts_with('0') && (10..=11).contains(&digits.len()) {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+/// Address of the client. `X-Forwarded-For` is only honored when the peer is one of the trusted
+/// reverse proxies, in which case the right-most address not belonging to them is taken.
+pub fn client_address(request: &actix_web::HttpRequest, trusted_proxies: &[IpAddr]) -> String {
+    let forwarded_for = request
+        .headers()
+        .get_all("x-forwarded-for")
+        .filter_map(|v| v.to_str().ok());
+
+    resolve_client_address(
+        request.peer_addr().map(|v| v.ip()),
+        forwarded_for,
+        trusted_proxies,
+    )
+    .map(|v| v.to_string())
+    .unwrap_or_default()
+}
+
+fn resolve_client_address<'a>(
+    peer_address: Option<IpAddr>,
+    forwarded_for: impl Iterator<Item = &'a str>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut address = peer_address?.to_canonical();
+    let hops = forwarded_for
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    for hop in hops.into_iter().rev() {
+        if !trusted_proxies.contains(&address) {
+            break;
+        }
+        // Anything the proxies can't vouch for ends the chain at the last trusted one
+        let Ok(hop) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        address = hop.to_canonical();
+    }
+
+    Some(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::resolve_client_address;
+
+    fn resolve(peer_address: &str, forwarded_for: &[&str], trusted_proxies: &[&str]) -> String {
+        let trusted_proxies = trusted_proxies
+            .iter()
+            .map(|v| v.parse().unwrap())
+            .collect::<Vec<IpAddr>>();
+
+        resolve_client_address(
+            Some(peer_address.parse().unwrap()),
+            forwarded_for.iter().copied(),
+            &trusted_proxies,
+        )
+        .unwrap()
+        .to_string()
+    }
+
+    #[test]
+    fn ignores_forwarded_for_from_untrusted_peers() {
+        assert_eq!(
+            resolve("203.0.113.7", &["198.51.100.1"], &[]),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            resolve("203.0.113.7", &["198.51.100.1"], &["10.0.0.1"]),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn takes_rightmost_untrusted_forwarded_address() {
+        assert_eq!(
+            resolve("10.0.0.1", &["1.1.1.1, 203.0.113.7"], &["10.0.0.1"]),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            resolve(
+                "10.0.0.1",
+                &["1.1.1.1", "203.0.113.7, 10.0.0.2"],
+                &["10.0.0.1", "10.0.0.2"]
+            ),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            resolve("::ffff:10.0.0.1", &["203.0.113.7"], &["10.0.0.1"]),
+            "203.0.113.7"
+        );
+    }
+
+    #[test]
+    fn stops_at_malformed_forwarded_address() {
+        assert_eq!(
+            resolve("10.0.0.1", &["203.0.113.7, unknown"], &["10.0.0.1"]),
+            "10.0.0.1"
+        );
+        assert_eq!(resolve("10.0.0.1", &[], &["10.0.0.1"]), "10.0.0.1");
+    }
+}
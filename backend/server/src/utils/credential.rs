@@ -0,0 +1,96 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{Error, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use dxe_types::PasswordResetToken;
+use sha2::{Digest, Sha256};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Handles are 3 to 40 characters of lowercase letters, digits, `.`, `_` and `-`.
+pub fn is_valid_handle(handle: &str) -> bool {
+    (3..=40).contains(&handle.len())
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c))
+}
+
+pub fn is_valid_password(password: &str) -> bool {
+    password.chars().count() >= MIN_PASSWORD_LENGTH
+}
+
+/// Hashes the password into a PHC string, as stored in `user_plain_credential.argon2_password`.
+pub fn hash_password(password: &str) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+pub fn verify_password(password: &str, argon2_password: &str) -> bool {
+    PasswordHash::new(argon2_password).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// SHA-256 digest of the reset token, as stored in `password_reset_token.token_hash`.
+pub fn hash_password_reset_token(token: &PasswordResetToken) -> Vec<u8> {
+    Sha256::digest(token.to_string().as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use dxe_types::PasswordResetToken;
+
+    use super::{
+        hash_password, hash_password_reset_token, is_valid_handle, is_valid_password,
+        verify_password,
+    };
+
+    #[test]
+    fn validates_handles() {
+        assert!(is_valid_handle("dreamhouse"));
+        assert!(is_valid_handle("dream.house_01-a"));
+
+        assert!(!is_valid_handle("dh"));
+        assert!(!is_valid_handle(&"a".repeat(41)));
+        assert!(!is_valid_handle("DreamHouse"));
+        assert!(!is_valid_handle("dream house"));
+        assert!(!is_valid_handle("드림하우스"));
+    }
+
+    #[test]
+    fn validates_passwords() {
+        assert!(is_valid_password("12345678"));
+        assert!(is_valid_password("비밀번호비밀번호"));
+
+        assert!(!is_valid_password("1234567"));
+        assert!(!is_valid_password("비밀번호"));
+    }
+
+    #[test]
+    fn verifies_hashed_passwords() {
+        let argon2_password = hash_password("correct horse").unwrap();
+
+        assert!(verify_password("correct horse", &argon2_password));
+        assert!(!verify_password("wrong horse", &argon2_password));
+        assert!(!verify_password("correct horse", "not a hash"));
+        assert_ne!(argon2_password, hash_password("correct horse").unwrap());
+    }
+
+    #[test]
+    fn hashes_password_reset_tokens() {
+        let token = PasswordResetToken::generate();
+        let token_hash = hash_password_reset_token(&token);
+
+        assert_eq!(token_hash.len(), 32);
+        assert_eq!(token_hash, hash_password_reset_token(&token));
+        assert_ne!(
+            token_hash,
+            hash_password_reset_token(&PasswordResetToken::generate())
+        );
+        assert_ne!(token_hash, token.to_string().into_bytes());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, NaiveTime, TimeZone, Timelike};
+use chrono::{DateTime, NaiveTime, TimeDelta, TimeZone, Timelike};
 
 pub use dxe_data::utils::is_in_effect;
 
@@ -7,3 +7,26 @@ pub fn truncate_time<Tz: TimeZone>(datetime: DateTime<Tz>) -> DateTime<Tz> {
         .with_time(NaiveTime::from_hms_opt(datetime.hour(), 0, 0).unwrap())
         .unwrap()
 }
+
+pub fn truncate_time_to_slot<Tz: TimeZone>(
+    datetime: DateTime<Tz>,
+    slot: TimeDelta,
+) -> DateTime<Tz> {
+    let slot_minutes = slot.num_minutes().max(1);
+    let minutes = (datetime.hour() * 60 + datetime.minute()) as i64;
+    let truncated = minutes - minutes % slot_minutes;
+
+    datetime
+        .with_time(
+            NaiveTime::from_hms_opt((truncated / 60) as u32, (truncated % 60) as u32, 0).unwrap(),
+        )
+        .unwrap()
+}
+
+/// Requested booking length. Minute precision takes precedence over the hour field, which is
+/// kept for clients that only book whole hours.
+pub fn desired_length(desired_hours: i64, desired_minutes: Option<i64>) -> TimeDelta {
+    desired_minutes
+        .map(TimeDelta::minutes)
+        .unwrap_or(TimeDelta::hours(desired_hours))
+}
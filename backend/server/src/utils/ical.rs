@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+
+const PRODUCT_ID: &str = "-//Dream House//Booking Feed//KO";
+const MAX_LINE_OCTETS: usize = 75;
+
+pub struct CalendarEvent<'a> {
+    pub uid: String,
+    pub summary: &'a str,
+    pub time_from: DateTime<Utc>,
+    pub time_to: DateTime<Utc>,
+    pub is_tentative: bool,
+}
+
+/// Minimal iCalendar (RFC 5545) writer for read-only feeds.
+pub struct Calendar {
+    lines: Vec<String>,
+    now: DateTime<Utc>,
+}
+
+impl Calendar {
+    pub fn new(name: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            lines: vec![
+                "BEGIN:VCALENDAR".to_owned(),
+                "VERSION:2.0".to_owned(),
+                format!("PRODID:{PRODUCT_ID}"),
+                "CALSCALE:GREGORIAN".to_owned(),
+                "METHOD:PUBLISH".to_owned(),
+                format!("X-WR-CALNAME:{}", escape_text(name)),
+            ],
+            now,
+        }
+    }
+
+    pub fn add_event(&mut self, event: CalendarEvent<'_>) {
+        self.lines.push("BEGIN:VEVENT".to_owned());
+        self.lines.push(format!("UID:{}", event.uid));
+        self.lines
+            .push(format!("DTSTAMP:{}", format_date_time(self.now)));
+        self.lines
+            .push(format!("DTSTART:{}", format_date_time(event.time_from)));
+        self.lines
+            .push(format!("DTEND:{}", format_date_time(event.time_to)));
+        self.lines
+            .push(format!("SUMMARY:{}", escape_text(event.summary)));
+        self.lines.push(format!(
+            "STATUS:{}",
+            if event.is_tentative {
+                "TENTATIVE"
+            } else {
+                "CONFIRMED"
+            }
+        ));
+        self.lines.push("END:VEVENT".to_owned());
+    }
+
+    pub fn finish(mut self) -> String {
+        self.lines.push("END:VCALENDAR".to_owned());
+
+        let mut result = String::new();
+        for line in self.lines {
+            fold_line(&mut result, &line);
+        }
+
+        result
+    }
+}
+
+fn format_date_time(value: DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits the content line into lines of at most 75 octets, without breaking characters.
+fn fold_line(output: &mut String, line: &str) {
+    let mut octets = 0;
+    for char in line.chars() {
+        if octets + char.len_utf8() > MAX_LINE_OCTETS {
+            output.push_str("\r\n ");
+            octets = 1;
+        }
+        output.push(char);
+        octets += char.len_utf8();
+    }
+    output.push_str("\r\n");
+}
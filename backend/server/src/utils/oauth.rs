@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use actix_web::HttpRequest;
+use actix_web::cookie::time::OffsetDateTime;
+use actix_web::cookie::{Cookie, SameSite};
+use base64::Engine;
+use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+use dxe_types::{IdentityProvider, UserId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::config::UrlConfig;
+use crate::utils::aes::{AesCrypto, Error as AesError};
+
+const SESSION_COOKIE_NAME: &str = "_dxe_oauth_session";
+const SESSION_EXPIRES_IN: Duration = Duration::from_secs(600);
+const LINK_TOKEN_EXPIRES_IN: Duration = Duration::from_secs(180);
+
+/// `state` parameter sent to the provider and echoed back on redirect.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthState {
+    pub nonce: String,
+    pub redirect_to: Option<String>,
+    /// Set for the silent sign-in inside the KakaoTalk in-app browser.
+    #[serde(default)]
+    pub transparent: bool,
+}
+
+/// Authorization request in progress, kept in an encrypted HttpOnly cookie so that only the
+/// browser which started it can finish it.
+#[derive(Deserialize, Serialize)]
+pub struct OAuthSession {
+    nonce: String,
+    /// PKCE code verifier, sent along with the authorization code.
+    pub code_verifier: String,
+    /// User attaching the provider to their account, or `None` when signing in.
+    pub link_user_id: Option<UserId>,
+}
+
+impl OAuthSession {
+    pub fn new(link_user_id: Option<UserId>) -> Self {
+        Self {
+            nonce: Uuid::new_v4().simple().to_string(),
+            code_verifier: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+            link_user_id,
+        }
+    }
+
+    pub fn state(&self, redirect_to: Option<String>, transparent: bool) -> String {
+        serde_json::to_string(&OAuthState {
+            nonce: self.nonce.clone(),
+            redirect_to,
+            transparent,
+        })
+        .unwrap_or_default()
+    }
+
+    /// `S256` PKCE challenge of the code verifier.
+    pub fn code_challenge(&self) -> String {
+        BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+
+    pub fn cookie(
+        &self,
+        aes_crypto: &AesCrypto,
+        url_config: &UrlConfig,
+    ) -> Result<Cookie<'static>, AesError> {
+        Ok(cookie(
+            SESSION_COOKIE_NAME,
+            seal(aes_crypto, self)?,
+            SESSION_EXPIRES_IN,
+            url_config,
+        ))
+    }
+
+    /// Session started by this browser for the given state, if any.
+    pub fn from_request(
+        request: &HttpRequest,
+        aes_crypto: &AesCrypto,
+        state: &OAuthState,
+    ) -> Option<Self> {
+        let cookie = request.cookie(SESSION_COOKIE_NAME)?;
+
+        open::<Self>(aes_crypto, cookie.value()).filter(|v| v.nonce == state.nonce)
+    }
+
+    pub fn removal_cookie(url_config: &UrlConfig) -> Cookie<'static> {
+        let mut cookie = cookie(
+            SESSION_COOKIE_NAME,
+            String::new(),
+            Duration::ZERO,
+            url_config,
+        );
+        cookie.make_removal();
+
+        cookie
+    }
+}
+
+/// Access token of the provider being linked, usable only by the user who started linking.
+#[derive(Deserialize, Serialize)]
+pub struct LinkToken {
+    pub user_id: UserId,
+    pub provider: IdentityProvider,
+    pub access_token: String,
+}
+
+impl LinkToken {
+    fn cookie_name(provider: IdentityProvider) -> String {
+        format!("{}_link_token", provider.name())
+    }
+
+    pub fn cookie(
+        &self,
+        aes_crypto: &AesCrypto,
+        url_config: &UrlConfig,
+    ) -> Result<Cookie<'static>, AesError> {
+        Ok(cookie(
+            Self::cookie_name(self.provider),
+            seal(aes_crypto, self)?,
+            LINK_TOKEN_EXPIRES_IN,
+            url_config,
+        ))
+    }
+
+    pub fn from_request(
+        request: &HttpRequest,
+        aes_crypto: &AesCrypto,
+        user_id: &UserId,
+        provider: IdentityProvider,
+    ) -> Option<Self> {
+        let cookie = request.cookie(&Self::cookie_name(provider))?;
+
+        open::<Self>(aes_crypto, cookie.value())
+            .filter(|v| v.user_id == *user_id && v.provider == provider)
+    }
+}
+
+pub fn kakao_redirect_url(url_config: &UrlConfig) -> url::Url {
+    let mut redirect_url = url_config.base_url.clone();
+    redirect_url.set_path("/api/auth/kakao/redirect");
+
+    redirect_url
+}
+
+pub fn oidc_redirect_url(url_config: &UrlConfig, provider: IdentityProvider) -> url::Url {
+    let mut redirect_url = url_config.base_url.clone();
+    redirect_url.set_path(&format!("/api/auth/oidc/{}/redirect", provider.name()));
+
+    redirect_url
+}
+
+fn cookie(
+    name: impl Into<String>,
+    value: String,
+    expires_in: Duration,
+    url_config: &UrlConfig,
+) -> Cookie<'static> {
+    // Lax so that the cookie comes along with the top-level redirect from the provider
+    let mut cookie = Cookie::build(name.into(), value)
+        .path("/")
+        .expires(OffsetDateTime::now_utc() + expires_in)
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax);
+
+    if let Some(domain) = url_config.base_url.domain() {
+        cookie = cookie.domain(domain.to_owned());
+    }
+
+    cookie.finish()
+}
+
+/// Encrypts the value with a fresh nonce, which is prepended to the ciphertext.
+fn seal(aes_crypto: &AesCrypto, value: &impl Serialize) -> Result<String, AesError> {
+    let mut nonce = [0; 12];
+    nonce.copy_from_slice(&Uuid::new_v4().as_bytes()[..12]);
+
+    let plaintext = serde_json::to_vec(value).unwrap_or_default();
+    let ciphertext = aes_crypto.encrypt(Some(nonce), &plaintext)?;
+
+    Ok(format!("{}.{ciphertext}", BASE64_STANDARD.encode(nonce)))
+}
+
+fn open<T: DeserializeOwned>(aes_crypto: &AesCrypto, value: &str) -> Option<T> {
+    let (nonce, ciphertext) = value.split_once('.')?;
+    let nonce = BASE64_STANDARD.decode(nonce).ok()?.try_into().ok()?;
+    let plaintext = aes_crypto
+        .decrypt(Some(nonce), ciphertext.as_bytes())
+        .ok()?;
+
+    serde_json::from_str(&plaintext).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AesCrypto, OAuthSession, open, seal};
+
+    #[test]
+    fn computes_pkce_challenge() {
+        let session = OAuthSession {
+            nonce: String::new(),
+            code_verifier: String::from("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            link_user_id: None,
+        };
+
+        // RFC 7636, Appendix B
+        assert_eq!(
+            session.code_challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn seals_sessions() {
+        let aes_crypto = AesCrypto::new(&[7; 32]);
+        let session = OAuthSession::new(None);
+
+        let sealed = seal(&aes_crypto, &session).unwrap();
+        let opened = open::<OAuthSession>(&aes_crypto, &sealed).unwrap();
+
+        assert_eq!(opened.nonce, session.nonce);
+        assert_eq!(opened.code_verifier, session.code_verifier);
+        assert_ne!(sealed, seal(&aes_crypto, &session).unwrap());
+        assert!(open::<OAuthSession>(&AesCrypto::new(&[8; 32]), &sealed).is_none());
+    }
+}
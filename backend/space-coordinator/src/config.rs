@@ -67,15 +67,23 @@ pub struct MqttConfig {
 #[serde(rename_all = "snake_case", tag = "backend")]
 pub enum NotificationBackend {
     Ntfy(NtfyConfig),
+    Webhook(WebhookConfig),
 }
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct NtfyConfig {
+    base_url: Option<url::Url>,
     token: Option<String>,
     channels: HashMap<String, String>,
+    title: Option<String>,
+    click_url: Option<url::Url>,
 }
 
 impl dxe_extern::ntfy::NtfyConfig for NtfyConfig {
+    fn base_url(&self) -> Option<&url::Url> {
+        self.base_url.as_ref()
+    }
+
     fn access_token(&self) -> Option<&str> {
         self.token.as_deref()
     }
@@ -87,6 +95,49 @@ impl dxe_extern::ntfy::NtfyConfig for NtfyConfig {
             dxe_extern::ntfy::Channel::Minor => self.channels.get("minor").unwrap(),
         }
     }
+
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn click_url(&self) -> Option<&url::Url> {
+        self.click_url.as_ref()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct WebhookConfig {
+    url: url::Url,
+    #[serde(default)]
+    format: dxe_extern::webhook::PayloadFormat,
+    title: Option<String>,
+    secret: Option<String>,
+}
+
+impl dxe_extern::webhook::WebhookConfig for WebhookConfig {
+    fn url(&self) -> &url::Url {
+        &self.url
+    }
+
+    fn format(&self) -> dxe_extern::webhook::PayloadFormat {
+        self.format
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn secret(&self) -> Option<&str> {
+        self.secret.as_deref()
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct NotificationBackendConfig {
+    #[serde(flatten)]
+    pub backend: NotificationBackend,
+    /// Notifications less important than this are not sent to the backend.
+    pub min_priority: Option<NotificationPriority>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize, Default)]
@@ -98,6 +149,20 @@ pub enum NotificationPriority {
     Low,
 }
 
+impl NotificationPriority {
+    fn level(&self) -> u8 {
+        match self {
+            Self::High => 2,
+            Self::Default => 1,
+            Self::Low => 0,
+        }
+    }
+
+    pub fn is_at_least(&self, other: NotificationPriority) -> bool {
+        self.level() >= other.level()
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct NotificationAlert {
     pub event_ids: HashSet<EventId>,
@@ -105,13 +170,74 @@ pub struct NotificationAlert {
     pub priority: NotificationPriority,
 }
 
+/// The former single ntfy backend, with `backend = "ntfy"`, `token` and `channels` right in the
+/// `[notifications]` table, is still read as one of the backends.
 #[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "RawNotificationConfig")]
 pub struct NotificationConfig {
-    #[serde(flatten)]
-    pub backend: NotificationBackend,
+    pub backends: Vec<NotificationBackendConfig>,
     pub alerts: Vec<NotificationAlert>,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum LegacyNotificationBackend {
+    Ntfy,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct RawNotificationConfig {
+    #[serde(default)]
+    backends: Vec<NotificationBackendConfig>,
+    alerts: Vec<NotificationAlert>,
+    backend: Option<LegacyNotificationBackend>,
+    token: Option<String>,
+    channels: Option<HashMap<String, String>>,
+}
+
+impl TryFrom<RawNotificationConfig> for NotificationConfig {
+    type Error = &'static str;
+
+    fn try_from(value: RawNotificationConfig) -> Result<Self, Self::Error> {
+        let mut backends = value.backends;
+
+        match (value.backend, value.channels) {
+            (Some(LegacyNotificationBackend::Ntfy), Some(channels)) => {
+                backends.push(NotificationBackendConfig {
+                    backend: NotificationBackend::Ntfy(NtfyConfig {
+                        base_url: None,
+                        token: value.token,
+                        channels,
+                        title: None,
+                        click_url: None,
+                    }),
+                    min_priority: None,
+                });
+            }
+            (Some(LegacyNotificationBackend::Ntfy), None) => {
+                return Err("`backend = \"ntfy\"` requires `channels`");
+            }
+            (None, _) if value.token.is_some() => {
+                return Err("`token` is only read with `backend = \"ntfy\"`, use `backends`");
+            }
+            (None, Some(_)) => {
+                return Err("`channels` is only read with `backend = \"ntfy\"`, use `backends`");
+            }
+            (None, None) => {}
+        }
+
+        if backends.is_empty() {
+            return Err("No notification backend is configured");
+        }
+
+        Ok(Self {
+            backends,
+            alerts: value.alerts,
+        })
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct GoogleDriveConfig {
     pub parent: String,
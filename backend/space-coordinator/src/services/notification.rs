@@ -1,4 +1,5 @@
 use dxe_extern::ntfy::{Channel, NtfyClient};
+use dxe_extern::webhook::WebhookClient;
 
 use crate::config::{NotificationBackend, NotificationConfig, NotificationPriority};
 
@@ -12,25 +13,79 @@ impl From<NotificationPriority> for Channel {
     }
 }
 
+impl From<NotificationPriority> for dxe_extern::webhook::Priority {
+    fn from(value: NotificationPriority) -> Self {
+        match value {
+            NotificationPriority::Default => Self::Default,
+            NotificationPriority::High => Self::High,
+            NotificationPriority::Low => Self::Low,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
-pub enum NotificationService {
+enum Backend {
     Ntfy(NtfyClient),
+    Webhook(WebhookClient),
+}
+
+impl Backend {
+    async fn notify(&self, priority: NotificationPriority, message: String) -> Result<(), Error> {
+        match self {
+            Self::Ntfy(client) => Ok(client.send(priority.into(), message).await?),
+            Self::Webhook(client) => Ok(client.send(priority.into(), message).await?),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NotificationService {
+    backends: Vec<(Option<NotificationPriority>, Backend)>,
 }
 
 impl NotificationService {
     pub fn new(config: &NotificationConfig) -> Self {
-        match &config.backend {
-            NotificationBackend::Ntfy(config) => Self::Ntfy(NtfyClient::new(config)),
+        Self {
+            backends: config
+                .backends
+                .iter()
+                .map(|v| {
+                    let backend = match &v.backend {
+                        NotificationBackend::Ntfy(config) => Backend::Ntfy(NtfyClient::new(config)),
+                        NotificationBackend::Webhook(config) => {
+                            Backend::Webhook(WebhookClient::new(config))
+                        }
+                    };
+
+                    (v.min_priority, backend)
+                })
+                .collect(),
         }
     }
 
+    /// Sends the notification to all of the backends accepting the priority, returning the last
+    /// error if any of them has failed.
     pub async fn notify(
         &self,
         priority: NotificationPriority,
         message: String,
     ) -> Result<(), Error> {
-        match &self {
-            Self::Ntfy(client) => Ok(client.send(priority.into(), message).await?),
+        let mut error = None;
+
+        for (min_priority, backend) in &self.backends {
+            if min_priority.is_some_and(|v| !priority.is_at_least(v)) {
+                continue;
+            }
+
+            if let Err(e) = backend.notify(priority, message.clone()).await {
+                error = Some(e);
+            }
+        }
+
+        if let Some(error) = error {
+            Err(error)
+        } else {
+            Ok(())
         }
     }
 }
@@ -39,4 +94,6 @@ impl NotificationService {
 pub enum Error {
     #[error("{0}")]
     Ntfy(#[from] dxe_extern::ntfy::Error),
+    #[error("{0}")]
+    Webhook(#[from] dxe_extern::webhook::Error),
 }
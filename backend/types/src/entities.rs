@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
-use crate::MixerChannelId;
+use crate::{MessagingChannel, MixerChannelId};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct MixerChannelData {
@@ -36,3 +36,33 @@ pub struct MixerPreferences {
     pub default: MixerPresets,
     pub scenes: HashMap<String, MixerPresets>,
 }
+
+/// Messages which members can opt out of. Others are always sent.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationEventType {
+    Confirmation,
+    Reminder,
+    Amendment,
+    Cancellation,
+    RecordingReady,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferences {
+    /// Channels each event is sent through. Events not listed are sent through every channel.
+    #[serde(default)]
+    pub channels: HashMap<NotificationEventType, HashSet<MessagingChannel>>,
+    /// Minutes before the booking to send the reminder, in place of the default timing.
+    #[serde(default)]
+    pub reminder_lead_minutes: Option<i64>,
+}
+
+impl NotificationPreferences {
+    pub fn is_enabled(&self, event_type: NotificationEventType, channel: MessagingChannel) -> bool {
+        self.channels
+            .get(&event_type)
+            .is_none_or(|v| v.contains(&channel))
+    }
+}
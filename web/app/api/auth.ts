@@ -3,8 +3,15 @@ import type {
   HandleAuthRequest,
   HandleAuthResponse,
   KakaoAuthRegisterRequest,
+  KakaoAuthStateResponse,
 } from "../types/handlers/auth";
 
+const kakaoState = (redirectTo: string | null, transparent: boolean) => {
+  return API.get<KakaoAuthStateResponse>("/auth/kakao/state", {
+    params: { redirect_to: redirectTo ?? undefined, transparent },
+  });
+};
+
 const kakaoRegister = (data: KakaoAuthRegisterRequest) => {
   return API.post("/auth/kakao", data);
 };
@@ -21,6 +28,7 @@ const handleAuth = (redirectTo: string | null, data: HandleAuthRequest) => {
 };
 
 const AuthService = {
+  kakaoState,
   kakaoRegister,
   handleAuth,
 };
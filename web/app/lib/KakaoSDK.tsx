@@ -1,5 +1,6 @@
 import { useEffect } from "react";
 
+import AuthService from "../api/auth";
 import type { Environment } from "../context/EnvContext";
 import { useEnv } from "../context/EnvContext";
 import generateRandomString from "../lib/random";
@@ -31,21 +32,29 @@ export function isKakaoWebView() {
   return window.navigator.userAgent.indexOf("KAKAOTALK") >= 0;
 }
 
-export function kakaoInAppLogin(env: Environment, redirectTo: string | null) {
+export async function kakaoInAppLogin(
+  env: Environment,
+  redirectTo: string | null,
+) {
+  const { data } = await AuthService.kakaoState(redirectTo, true);
   window.Kakao.Auth.authorize({
     redirectUri: `${env.urlBase}/api/auth/kakao/redirect`,
     scope: "profile_nickname,talk_calendar",
-    state: JSON.stringify({ redirectTo, transparent: true }),
+    state: data.state,
     nonce: generateRandomString(16),
     prompt: "none",
   });
 }
 
-export function kakaoLogin(env: Environment, redirectTo: string | null) {
+export async function kakaoLogin(
+  env: Environment,
+  redirectTo: string | null,
+) {
+  const { data } = await AuthService.kakaoState(redirectTo, false);
   window.Kakao.Auth.authorize({
     redirectUri: `${env.urlBase}/api/auth/kakao/redirect`,
     scope: "profile_nickname,talk_calendar",
-    state: JSON.stringify({ redirectTo }),
+    state: data.state,
     nonce: generateRandomString(16),
     throughTalk: true,
   });
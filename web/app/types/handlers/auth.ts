@@ -1,3 +1,7 @@
+export interface KakaoAuthStateResponse {
+  state: string;
+}
+
 export interface KakaoAuthRegisterRequest {
   name: string;
   licensePlateNumber?: string;
@@ -7,6 +7,7 @@ export interface CashTransaction {
   refundPrice: number | null;
   refundAccount: string | null;
   refundedAt: DateTime | null;
+  refundedPrice: number | null;
   isRefundRequested: boolean;
   isRefunded: boolean;
 }